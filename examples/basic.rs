@@ -3,7 +3,7 @@
 //! This example demonstrates how to use the library for common
 //! tensor canonicalization tasks in theoretical physics.
 
-use butler_portugal::*;
+use butler_portugal::prelude::*;
 
 fn main() -> Result<()> {
     println!("Butler-Portugal Tensor Canonicalization Examples");
@@ -275,6 +275,66 @@ static void test_zero_tensor(void) {
   printf("  PASSED\n");
 }
 
+static void test_context(void) {
+  printf("Testing context canonicalization...\n");
+
+  BPConfig config = bp_config_default();
+  assert(config.struct_size == sizeof(BPConfig));
+  BPContextHandle ctx = bp_context_new_with_config(&config);
+  assert(ctx != NULL);
+  assert(bp_context_set_ordering_version(ctx, 1) == BP_SUCCESS);
+
+  BPTensorIndexHandle idx_b = bp_index_new("b", 0);
+  BPTensorIndexHandle idx_a = bp_index_new("a", 1);
+  BPTensorIndexHandle indices[] = {idx_b, idx_a};
+  BPTensorHandle tensor = bp_tensor_new("F", indices, 2);
+
+  size_t asym_indices[] = {0, 1};
+  BPSymmetryHandle asym = bp_symmetry_antisymmetric(asym_indices, 2);
+  bp_tensor_add_symmetry(tensor, asym);
+  bp_symmetry_free(asym);
+
+  /* The second call is answered from the context's cache */
+  for (int i = 0; i < 2; i++) {
+    BPResult error;
+    BPTensorHandle canonical = bp_context_canonicalize(ctx, tensor, &error);
+    assert(error == BP_SUCCESS);
+    assert(bp_tensor_coefficient(canonical) == -1);
+    bp_tensor_free(canonical);
+  }
+  assert(bp_context_cache_len(ctx) == 1);
+  assert(bp_context_last_error(ctx) == NULL);
+
+  bp_tensor_free(tensor);
+  bp_index_free(idx_b);
+  bp_index_free(idx_a);
+  bp_context_free(ctx);
+
+  printf("  PASSED\n");
+}
+
+static void test_arena(void) {
+  printf("Testing arena allocation...\n");
+
+  const char *names[] = {"b", "a"};
+  BPArenaHandle arena = bp_arena_new();
+  BPTensorIndexHandle indices[2];
+  assert(bp_arena_indices_new(arena, names, NULL, 2, indices) == BP_SUCCESS);
+
+  BPTensorHandle tensors[100];
+  for (int i = 0; i < 100; i++) {
+    tensors[i] = bp_arena_tensor_new(arena, "S", indices, 2);
+    assert(tensors[i] != NULL);
+  }
+  assert(bp_tensor_rank(tensors[99]) == 2);
+  assert(bp_arena_len(arena) == 102);
+
+  /* Everything is released at once */
+  bp_arena_free(arena);
+
+  printf("  PASSED\n");
+}
+
 int main(void) {
   printf("=== Butler-Portugal C FFI Tests ===\n\n");
 
@@ -286,6 +346,8 @@ int main(void) {
   test_symmetry_addition();
   test_canonicalization();
   test_zero_tensor();
+  test_context();
+  test_arena();
 
   printf("\n=== All tests passed! ===\n");
   return 0;
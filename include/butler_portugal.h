@@ -19,8 +19,10 @@ extern "C" {
 typedef void *BPTensorHandle;
 typedef void *BPTensorIndexHandle;
 typedef void *BPSymmetryHandle;
+typedef void *BPContextHandle;
+typedef void *BPArenaHandle;
 
-/* Result codes */
+/* Result codes. Values are fixed across releases; new codes are appended. */
 typedef enum {
   BP_SUCCESS = 0,
   BP_NULL_POINTER = 1,
@@ -29,6 +31,16 @@ typedef enum {
   BP_ALLOCATION_ERROR = 4,
 } BPResult;
 
+/*
+ * Canonicalization configuration. The layout is fixed across releases:
+ * fields are only ever appended, and struct_size must be set to
+ * sizeof(BPConfig). Initialize with bp_config_default().
+ */
+typedef struct {
+  uint32_t struct_size;
+  uint32_t ordering_version;
+} BPConfig;
+
 /* -------------------------------------------------------------------------- */
 /* TensorIndex Functions */
 /* -------------------------------------------------------------------------- */
@@ -72,6 +84,29 @@ void bp_index_free(BPTensorIndexHandle index);
  */
 BPTensorIndexHandle bp_index_clone(BPTensorIndexHandle index);
 
+/**
+ * Create several tensor indices in one call, the i-th at position i.
+ *
+ * @param names          Array of count null-terminated index names
+ * @param contravariant  Array of count variance flags, or NULL for all
+ *                       covariant
+ * @param count          Number of indices to create
+ * @param out            Array receiving count handles (untouched on failure)
+ * @return               BP_SUCCESS on success, error code otherwise
+ *
+ * Every returned handle must be freed, e.g. with bp_index_free_all().
+ */
+BPResult bp_indices_new(const char *const *names, const bool *contravariant,
+                        size_t count, BPTensorIndexHandle *out);
+
+/**
+ * Free several tensor indices in one call.
+ *
+ * @param handles  Array of count handles (entries may be NULL)
+ * @param count    Number of handles
+ */
+void bp_index_free_all(const BPTensorIndexHandle *handles, size_t count);
+
 /* -------------------------------------------------------------------------- */
 /* Symmetry Functions */
 /* -------------------------------------------------------------------------- */
@@ -161,6 +196,19 @@ BPTensorHandle bp_tensor_new(const char *name,
                              const BPTensorIndexHandle *indices,
                              size_t num_indices);
 
+/**
+ * Parse a tensor written in LaTeX index notation.
+ *
+ * @param input      Null-terminated string such as "R_{mu nu}^{rho sigma}"
+ * @param error_out  Optional pointer to receive error code (may be NULL)
+ * @return           Handle to the new tensor, or NULL if the text is not a
+ *                   single tensor
+ *
+ * The metric g, the Riemann and Ricci tensors R and the Weyl tensor C get
+ * their symmetries. The returned handle must be freed with bp_tensor_free().
+ */
+BPTensorHandle bp_tensor_parse(const char *input, BPResult *error_out);
+
 /**
  * Create a new tensor with a coefficient.
  *
@@ -184,6 +232,14 @@ BPTensorHandle bp_tensor_with_coefficient(const char *name,
  */
 void bp_tensor_free(BPTensorHandle tensor);
 
+/**
+ * Free several tensors in one call.
+ *
+ * @param handles  Array of count handles (entries may be NULL)
+ * @param count    Number of handles
+ */
+void bp_tensor_free_all(const BPTensorHandle *handles, size_t count);
+
 /**
  * Clone a tensor.
  *
@@ -262,6 +318,230 @@ void bp_string_free(char *s);
  */
 BPTensorHandle bp_canonicalize(BPTensorHandle tensor, BPResult *error_out);
 
+/* -------------------------------------------------------------------------- */
+/* Arena Functions */
+/* -------------------------------------------------------------------------- */
+
+/*
+ * An arena owns many indices and tensors and frees them all at once, without
+ * a separate allocation per object. Arena handles work with every function
+ * taking an index or tensor handle, except bp_index_free(), bp_tensor_free()
+ * and the *_free_all() functions. An arena is not thread-safe.
+ */
+
+/**
+ * Create an empty arena.
+ *
+ * @return  Handle to the new arena
+ *
+ * The returned handle must be freed with bp_arena_free().
+ */
+BPArenaHandle bp_arena_new(void);
+
+/**
+ * Free an arena together with every index and tensor allocated in it.
+ *
+ * @param arena  Handle to free (may be NULL, which is a no-op)
+ */
+void bp_arena_free(BPArenaHandle arena);
+
+/**
+ * Create several tensor indices owned by an arena, the i-th at position i.
+ *
+ * @param arena          Handle to the arena
+ * @param names          Array of count null-terminated index names
+ * @param contravariant  Array of count variance flags, or NULL for all
+ *                       covariant
+ * @param count          Number of indices to create
+ * @param out            Array receiving count handles (untouched on failure)
+ * @return               BP_SUCCESS on success, error code otherwise
+ */
+BPResult bp_arena_indices_new(BPArenaHandle arena, const char *const *names,
+                              const bool *contravariant, size_t count,
+                              BPTensorIndexHandle *out);
+
+/**
+ * Create a tensor owned by an arena.
+ *
+ * @param arena        Handle to the arena
+ * @param name         Null-terminated string for the tensor name
+ * @param indices      Array of TensorIndexHandle pointers (cloned)
+ * @param num_indices  Number of indices
+ * @return             Handle to the new tensor, or NULL on failure
+ */
+BPTensorHandle bp_arena_tensor_new(BPArenaHandle arena, const char *name,
+                                   const BPTensorIndexHandle *indices,
+                                   size_t num_indices);
+
+/**
+ * Get the number of indices and tensors owned by an arena.
+ *
+ * @param arena  Handle to the arena
+ * @return       Number of owned objects, or 0 if arena is NULL
+ */
+size_t bp_arena_len(BPArenaHandle arena);
+
+/* -------------------------------------------------------------------------- */
+/* Context Functions */
+/* -------------------------------------------------------------------------- */
+
+/*
+ * A context owns the configuration, the canonicalization cache and the last
+ * error message, so no hidden global state is involved. A context may be
+ * shared between threads: calls on the same context are serialized, calls on
+ * different contexts run concurrently.
+ */
+
+/**
+ * Create a new context with the default configuration and an empty cache.
+ *
+ * @return  Handle to the new context
+ *
+ * The returned handle must be freed with bp_context_free().
+ */
+BPContextHandle bp_context_new(void);
+
+/**
+ * Create a new context with the given configuration and an empty cache.
+ *
+ * @param config  Configuration to copy
+ * @return        Handle to the new context, or NULL if config is NULL or
+ *                holds an unknown value
+ *
+ * The returned handle must be freed with bp_context_free().
+ */
+BPContextHandle bp_context_new_with_config(const BPConfig *config);
+
+/**
+ * Return the default configuration, with struct_size filled in.
+ */
+BPConfig bp_config_default(void);
+
+/**
+ * Read the configuration of a context.
+ *
+ * @param context  Handle to the context
+ * @param out      Receives the configuration
+ * @return         BP_SUCCESS, or BP_NULL_POINTER
+ */
+BPResult bp_context_config(BPContextHandle context, BPConfig *out);
+
+/**
+ * Replace the configuration of a context. The cache is cleared if the
+ * ordering version changes.
+ *
+ * @param context  Handle to the context
+ * @param config   Configuration to copy
+ * @return         BP_SUCCESS, or BP_INVALID_ARGUMENT for an unknown value
+ */
+BPResult bp_context_set_config(BPContextHandle context, const BPConfig *config);
+
+/**
+ * Free a context.
+ *
+ * @param context  Handle to free (may be NULL, which is a no-op). No other
+ *                 thread may be using it.
+ */
+void bp_context_free(BPContextHandle context);
+
+/**
+ * Select the ordering version used by canonicalizations through a context.
+ *
+ * @param context  Handle to the context
+ * @param version  Ordering version; canonical forms for a version never
+ *                 change across releases. Versions 1 and 2 are defined.
+ * @return         BP_SUCCESS, or BP_INVALID_ARGUMENT for an unknown version
+ */
+BPResult bp_context_set_ordering_version(BPContextHandle context,
+                                         uint32_t version);
+
+/**
+ * Canonicalize a tensor through a context, reusing and filling its cache.
+ *
+ * @param context    Handle to the context
+ * @param tensor     Handle to the tensor to canonicalize
+ * @param error_out  Optional pointer to receive error code (may be NULL)
+ * @return           Handle to the canonical form tensor, or NULL on failure
+ *
+ * On failure the error message is available from bp_context_last_error().
+ * The returned handle must be freed with bp_tensor_free().
+ */
+BPTensorHandle bp_context_canonicalize(BPContextHandle context,
+                                       BPTensorHandle tensor,
+                                       BPResult *error_out);
+
+/**
+ * Register the name an integer index identifier stands for in a context.
+ *
+ * @param context  Handle to the context
+ * @param id       Identifier used in place of the name
+ * @param name     Null-terminated index name, validated once here
+ * @return         BP_SUCCESS, or BP_INVALID_ARGUMENT if the identifier or the
+ *                 name is already bound to something else
+ */
+BPResult bp_context_register_name(BPContextHandle context, uint32_t id,
+                                  const char *name);
+
+/**
+ * Create a tensor whose indices are given by registered identifiers, the
+ * i-th at position i.
+ *
+ * @param context        Handle to the context
+ * @param name           Null-terminated string for the tensor name
+ * @param ids            Array of count registered identifiers
+ * @param contravariant  Array of count variance flags, or NULL for all
+ *                       covariant
+ * @param count          Number of indices
+ * @return               Handle to the new tensor, or NULL on failure
+ *
+ * The returned handle must be freed with bp_tensor_free().
+ */
+BPTensorHandle bp_context_tensor_from_ids(BPContextHandle context,
+                                          const char *name, const uint32_t *ids,
+                                          const bool *contravariant,
+                                          size_t count);
+
+/**
+ * Write the registered identifiers of a tensor's indices, slot by slot.
+ *
+ * @param context            Handle to the context
+ * @param tensor             Handle to the tensor
+ * @param out_ids            Array receiving one identifier per slot
+ * @param out_contravariant  Array receiving one variance flag per slot, or
+ *                           NULL
+ * @param count              Capacity of the arrays, at least the tensor rank
+ * @return                   BP_SUCCESS on success, error code otherwise
+ */
+BPResult bp_context_tensor_ids(BPContextHandle context, BPTensorHandle tensor,
+                               uint32_t *out_ids, bool *out_contravariant,
+                               size_t count);
+
+/**
+ * Get the message of the last failed call on a context.
+ *
+ * @param context  Handle to the context
+ * @return         Newly allocated C string, or NULL if the last call succeeded
+ *
+ * The returned string must be freed with bp_string_free().
+ */
+char *bp_context_last_error(BPContextHandle context);
+
+/**
+ * Get the number of canonical forms cached by a context.
+ *
+ * @param context  Handle to the context
+ * @return         Number of cached forms, or 0 if context is NULL
+ */
+size_t bp_context_cache_len(BPContextHandle context);
+
+/**
+ * Remove all cached data from a context.
+ *
+ * @param context  Handle to the context
+ * @return         BP_SUCCESS on success, error code otherwise
+ */
+BPResult bp_context_clear_cache(BPContextHandle context);
+
 /* -------------------------------------------------------------------------- */
 /* Version Information */
 /* -------------------------------------------------------------------------- */
@@ -0,0 +1,9 @@
+"""Python bindings for the Butler-Portugal tensor canonicalization library.
+
+The shared library is built with ``cargo build --release`` and located under
+``target/`` or through the ``BUTLER_PORTUGAL_LIB`` environment variable.
+"""
+
+from ._ffi import lib
+
+__version__ = lib.bp_version().decode()
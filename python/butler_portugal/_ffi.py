@@ -0,0 +1,91 @@
+"""ctypes bindings for the C interface declared in include/butler_portugal.h."""
+
+import ctypes
+import ctypes.util
+import os
+import sys
+from pathlib import Path
+
+BP_SUCCESS = 0
+
+
+def _library_path():
+    """Locates the shared library, preferring $BUTLER_PORTUGAL_LIB."""
+    override = os.environ.get("BUTLER_PORTUGAL_LIB")
+    if override:
+        return override
+    if sys.platform == "darwin":
+        filename = "libbutler_portugal.dylib"
+    elif sys.platform == "win32":
+        filename = "butler_portugal.dll"
+    else:
+        filename = "libbutler_portugal.so"
+    root = Path(__file__).resolve().parents[2]
+    for profile in ("release", "debug"):
+        candidate = root / "target" / profile / filename
+        if candidate.exists():
+            return str(candidate)
+    return ctypes.util.find_library("butler_portugal") or filename
+
+
+lib = ctypes.CDLL(_library_path())
+
+_handle = ctypes.c_void_p
+_size = ctypes.c_size_t
+
+
+class BPConfig(ctypes.Structure):
+    """Mirror of the fixed-layout BPConfig struct."""
+
+    _fields_ = [("struct_size", ctypes.c_uint32), ("ordering_version", ctypes.c_uint32)]
+
+
+_signatures = {
+    "bp_symmetry_symmetric": (_handle, [ctypes.POINTER(_size), _size]),
+    "bp_symmetry_antisymmetric": (_handle, [ctypes.POINTER(_size), _size]),
+    "bp_symmetry_symmetric_pairs": (_handle, [ctypes.POINTER(_size), _size]),
+    "bp_symmetry_cyclic": (_handle, [ctypes.POINTER(_size), _size]),
+    "bp_symmetry_free": (None, [_handle]),
+    "bp_tensor_free": (None, [_handle]),
+    "bp_tensor_add_symmetry": (ctypes.c_int, [_handle, _handle]),
+    "bp_tensor_coefficient": (ctypes.c_int32, [_handle]),
+    "bp_tensor_rank": (_size, [_handle]),
+    "bp_string_free": (None, [ctypes.c_void_p]),
+    "bp_context_new": (_handle, []),
+    "bp_context_new_with_config": (_handle, [ctypes.POINTER(BPConfig)]),
+    "bp_config_default": (BPConfig, []),
+    "bp_context_free": (None, [_handle]),
+    "bp_context_register_name": (ctypes.c_int, [_handle, ctypes.c_uint32, ctypes.c_char_p]),
+    "bp_context_tensor_from_ids": (
+        _handle,
+        [_handle, ctypes.c_char_p, ctypes.POINTER(ctypes.c_uint32), ctypes.POINTER(ctypes.c_bool), _size],
+    ),
+    "bp_context_tensor_ids": (
+        ctypes.c_int,
+        [_handle, _handle, ctypes.POINTER(ctypes.c_uint32), ctypes.POINTER(ctypes.c_bool), _size],
+    ),
+    "bp_context_canonicalize": (_handle, [_handle, _handle, ctypes.POINTER(ctypes.c_int)]),
+    "bp_context_last_error": (ctypes.c_void_p, [_handle]),
+    "bp_version": (ctypes.c_char_p, []),
+}
+
+for _name, (_restype, _argtypes) in _signatures.items():
+    _function = getattr(lib, _name)
+    _function.restype = _restype
+    _function.argtypes = _argtypes
+
+
+def sizes(values):
+    """Packs Python integers into a C array of size_t."""
+    return (_size * len(values))(*values)
+
+
+def last_error(context):
+    """Returns and frees the last error message recorded in a context."""
+    message = lib.bp_context_last_error(context)
+    if not message:
+        return "unknown error"
+    try:
+        return ctypes.string_at(message).decode()
+    finally:
+        lib.bp_string_free(message)
@@ -0,0 +1,278 @@
+"""A tensor API shaped after ``sympy.tensor.tensor``.
+
+Scripts written against SymPy's tensor module port by changing the import::
+
+    from butler_portugal.tensor import (
+        TensorIndexType, tensor_indices, TensorHead, TensorSymmetry, canon_bp,
+    )
+
+    Lorentz = TensorIndexType("Lorentz")
+    a, b, c, d = tensor_indices("a b c d", Lorentz)
+    R = TensorHead("R", [Lorentz] * 4, TensorSymmetry.riemann())
+    canon_bp(R(b, a, d, c))  # R(a, b, c, d)
+
+As in SymPy, ``a`` is an upper (contravariant) index and ``-a`` a lower one.
+Only single tensors with integer coefficients are supported; products and
+sums of tensors are out of scope.
+"""
+
+import ctypes
+import threading
+
+from . import _ffi
+
+__all__ = [
+    "TensorIndexType",
+    "TensorIndex",
+    "tensor_indices",
+    "TensorSymmetry",
+    "TensorHead",
+    "Tensor",
+    "canon_bp",
+]
+
+
+class TensorIndexType:
+    """A kind of index, e.g. Lorentz indices of a spacetime."""
+
+    def __init__(self, name, dummy_name=None):
+        self.name = name
+        self.dummy_name = dummy_name or name[0]
+
+    def __repr__(self):
+        return self.name
+
+
+class TensorIndex:
+    """An index label of some type, upper unless negated."""
+
+    def __init__(self, name, tensor_index_type, is_up=True):
+        self.name = name
+        self.tensor_index_type = tensor_index_type
+        self.is_up = is_up
+
+    def __neg__(self):
+        return TensorIndex(self.name, self.tensor_index_type, not self.is_up)
+
+    def _key(self):
+        return (self.name, self.tensor_index_type.name, self.is_up)
+
+    def __eq__(self, other):
+        return isinstance(other, TensorIndex) and self._key() == other._key()
+
+    def __hash__(self):
+        return hash(self._key())
+
+    def __repr__(self):
+        return self.name if self.is_up else "-" + self.name
+
+
+def tensor_indices(s, index_type):
+    """Creates upper indices from names separated by spaces or commas."""
+    names = s.replace(",", " ").split()
+    return [TensorIndex(name, index_type) for name in names]
+
+
+class TensorSymmetry:
+    """Monoterm slot symmetries of a tensor head.
+
+    Each generator is a ``(kind, slots)`` pair understood by the C interface.
+    """
+
+    def __init__(self, rank, generators=()):
+        self.rank = rank
+        self.generators = list(generators)
+
+    @classmethod
+    def fully_symmetric(cls, rank):
+        """Totally symmetric in ``rank`` slots, antisymmetric if ``rank`` is negative."""
+        kind = "antisymmetric" if rank < 0 else "symmetric"
+        rank = abs(rank)
+        return cls(rank, [(kind, list(range(rank)))])
+
+    @classmethod
+    def direct_product(cls, *ranks):
+        """Consecutive blocks, each fully (anti)symmetric as in ``fully_symmetric``."""
+        generators = []
+        offset = 0
+        for rank in ranks:
+            kind = "antisymmetric" if rank < 0 else "symmetric"
+            size = abs(rank)
+            generators.append((kind, list(range(offset, offset + size))))
+            offset += size
+        return cls(offset, generators)
+
+    @classmethod
+    def riemann(cls):
+        """The monoterm symmetries of the Riemann tensor."""
+        return cls(
+            4,
+            [
+                ("antisymmetric", [0, 1]),
+                ("antisymmetric", [2, 3]),
+                ("symmetric_pairs", [0, 1, 2, 3]),
+            ],
+        )
+
+    @classmethod
+    def no_symmetry(cls, rank):
+        """No symmetry in ``rank`` slots."""
+        return cls(rank)
+
+    @classmethod
+    def cyclic(cls, rank):
+        """Invariance under cyclic shifts of ``rank`` slots."""
+        return cls(rank, [("cyclic", list(range(rank)))])
+
+    def __repr__(self):
+        return f"TensorSymmetry({self.rank}, {self.generators})"
+
+
+class TensorHead:
+    """A tensor symbol with fixed index types and symmetry."""
+
+    def __init__(self, name, index_types, symmetry=None):
+        self.name = name
+        self.index_types = list(index_types)
+        self.symmetry = symmetry or TensorSymmetry.no_symmetry(len(self.index_types))
+        if self.symmetry.rank != len(self.index_types):
+            raise ValueError(
+                f"{name} has {len(self.index_types)} slots but its symmetry has {self.symmetry.rank}"
+            )
+
+    @property
+    def rank(self):
+        return len(self.index_types)
+
+    def __call__(self, *indices):
+        if len(indices) != self.rank:
+            raise ValueError(f"{self.name} takes {self.rank} indices, got {len(indices)}")
+        for index, index_type in zip(indices, self.index_types):
+            if index.tensor_index_type is not index_type:
+                raise ValueError(f"Index {index} is not of type {index_type}")
+        return Tensor(self, indices)
+
+    def __repr__(self):
+        return self.name
+
+
+class Tensor:
+    """A tensor head applied to indices, with an integer coefficient."""
+
+    def __init__(self, head, indices, coeff=1):
+        self.head = head
+        self.indices = tuple(indices)
+        self.coeff = coeff
+
+    def get_indices(self):
+        return list(self.indices)
+
+    def __neg__(self):
+        return Tensor(self.head, self.indices, -self.coeff)
+
+    def __mul__(self, other):
+        if not isinstance(other, int):
+            return NotImplemented
+        return Tensor(self.head, self.indices, self.coeff * other)
+
+    __rmul__ = __mul__
+
+    def __eq__(self, other):
+        if isinstance(other, int):
+            return self.coeff == 0 and other == 0
+        return (
+            isinstance(other, Tensor)
+            and self.head is other.head
+            and self.indices == other.indices
+            and self.coeff == other.coeff
+        )
+
+    def __hash__(self):
+        return hash((self.head.name, self.indices, self.coeff))
+
+    def __repr__(self):
+        body = f"{self.head.name}({', '.join(map(repr, self.indices))})"
+        if self.coeff == 1:
+            return body
+        if self.coeff == -1:
+            return "-" + body
+        return f"{self.coeff}*{body}"
+
+
+class _Session:
+    """A library context with index names registered under stable ids."""
+
+    def __init__(self):
+        self.context = _ffi.lib.bp_context_new()
+        self.ids = {}
+        self.lock = threading.Lock()
+
+    def __del__(self):
+        _ffi.lib.bp_context_free(self.context)
+
+    def id_of(self, name):
+        with self.lock:
+            if name not in self.ids:
+                identifier = len(self.ids)
+                status = _ffi.lib.bp_context_register_name(self.context, identifier, name.encode())
+                if status != _ffi.BP_SUCCESS:
+                    raise ValueError(_ffi.last_error(self.context))
+                self.ids[name] = identifier
+            return self.ids[name]
+
+
+_session = _Session()
+
+_CONSTRUCTORS = {
+    "symmetric": _ffi.lib.bp_symmetry_symmetric,
+    "antisymmetric": _ffi.lib.bp_symmetry_antisymmetric,
+    "cyclic": _ffi.lib.bp_symmetry_cyclic,
+}
+
+
+def _add_symmetries(handle, symmetry):
+    for kind, slots in symmetry.generators:
+        if kind == "symmetric_pairs":
+            sym = _ffi.lib.bp_symmetry_symmetric_pairs(_ffi.sizes(slots), len(slots) // 2)
+        else:
+            sym = _CONSTRUCTORS[kind](_ffi.sizes(slots), len(slots))
+        try:
+            _ffi.lib.bp_tensor_add_symmetry(handle, sym)
+        finally:
+            _ffi.lib.bp_symmetry_free(sym)
+
+
+def canon_bp(p):
+    """Returns the canonical form of a tensor, or 0 if it vanishes by symmetry."""
+    context = _session.context
+    rank = len(p.indices)
+    ids = (ctypes.c_uint32 * rank)(*(_session.id_of(i.name) for i in p.indices))
+    flags = (ctypes.c_bool * rank)(*(i.is_up for i in p.indices))
+    by_name = {i.name: i.tensor_index_type for i in p.indices}
+
+    handle = _ffi.lib.bp_context_tensor_from_ids(context, p.head.name.encode(), ids, flags, rank)
+    if not handle:
+        raise ValueError(_ffi.last_error(context))
+    try:
+        _add_symmetries(handle, p.head.symmetry)
+        error = ctypes.c_int(_ffi.BP_SUCCESS)
+        canonical = _ffi.lib.bp_context_canonicalize(context, handle, ctypes.byref(error))
+    finally:
+        _ffi.lib.bp_tensor_free(handle)
+    if not canonical:
+        raise ValueError(_ffi.last_error(context))
+    try:
+        coeff = _ffi.lib.bp_tensor_coefficient(canonical) * p.coeff
+        status = _ffi.lib.bp_context_tensor_ids(context, canonical, ids, flags, rank)
+        if status != _ffi.BP_SUCCESS:
+            raise ValueError(_ffi.last_error(context))
+    finally:
+        _ffi.lib.bp_tensor_free(canonical)
+    if coeff == 0:
+        return 0
+    names = {identifier: name for name, identifier in _session.ids.items()}
+    indices = [
+        TensorIndex(names[identifier], by_name[names[identifier]], bool(up))
+        for identifier, up in zip(ids, flags)
+    ]
+    return Tensor(p.head, indices, coeff)
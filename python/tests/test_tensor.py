@@ -0,0 +1,41 @@
+"""Run with ``python -m unittest discover python/tests`` after ``cargo build --release``."""
+
+import sys
+import unittest
+from pathlib import Path
+
+sys.path.insert(0, str(Path(__file__).resolve().parents[1]))
+
+from butler_portugal.tensor import (  # noqa: E402
+    TensorHead,
+    TensorIndexType,
+    TensorSymmetry,
+    canon_bp,
+    tensor_indices,
+)
+
+
+class CanonBpTest(unittest.TestCase):
+    def setUp(self):
+        self.Lorentz = TensorIndexType("Lorentz")
+        self.a, self.b, self.c, self.d = tensor_indices("a b c d", self.Lorentz)
+
+    def test_riemann(self):
+        R = TensorHead("R", [self.Lorentz] * 4, TensorSymmetry.riemann())
+        a, b, c, d = self.a, self.b, self.c, self.d
+        self.assertEqual(canon_bp(R(b, a, d, c)), R(a, b, c, d))
+        self.assertEqual(canon_bp(R(c, d, b, a)), -R(a, b, c, d))
+
+    def test_antisymmetric_vanishes(self):
+        F = TensorHead("F", [self.Lorentz] * 2, TensorSymmetry.fully_symmetric(-2))
+        self.assertEqual(canon_bp(F(self.a, self.a)), 0)
+        self.assertEqual(canon_bp(2 * F(self.b, -self.a)), -2 * F(-self.a, self.b))
+
+    def test_head_checks_arity(self):
+        S = TensorHead("S", [self.Lorentz] * 2, TensorSymmetry.fully_symmetric(2))
+        with self.assertRaises(ValueError):
+            S(self.a)
+
+
+if __name__ == "__main__":
+    unittest.main()
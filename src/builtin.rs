@@ -0,0 +1,1127 @@
+//! Built-in tensors: the Levi-Civita symbol, the metric and the Hodge dual
+//!
+//! These constructors give the structural tensors of a metric manifold fixed
+//! names and symmetries, so later simplification passes can recognize them.
+//! The Levi-Civita tensor is always named `epsilon` and the metric `g`; the
+//! flat metric on frame indices is `eta` and the vielbein `e`; for 3+1 splits
+//! the spatial metric is `h` and the unit normal `n`.
+//!
+//! Products of two contracted Levi-Civita tensors reduce to generalized
+//! Kronecker deltas through [`contract_epsilons`].
+
+use crate::coefficient::Coefficient;
+use crate::derivatives::fresh_index_name;
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::{contract, TensorExpression, TensorProduct};
+use crate::index::{IndexClass, TensorIndex};
+use crate::permutation::Permutation;
+use crate::presets::has_riemann_symmetries;
+use crate::rational::Rational;
+use crate::spinors::is_spinor_class;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+use std::collections::HashSet;
+
+/// Name given to the Levi-Civita tensor
+pub const EPSILON_NAME: &str = "epsilon";
+
+/// Name given to the metric tensor and its inverse
+pub const METRIC_NAME: &str = "g";
+
+/// Name given to the flat metric on frame indices
+pub const FRAME_METRIC_NAME: &str = "eta";
+
+/// Name given to the vielbein converting coordinate to frame indices
+pub const VIELBEIN_NAME: &str = "e";
+
+/// Name given to the spatial metric of a 3+1 split
+pub const SPATIAL_METRIC_NAME: &str = "h";
+
+/// Name given to the unit normal of a 3+1 split
+pub const UNIT_NORMAL_NAME: &str = "n";
+
+/// Signature class of the metric, which fixes the sign of its determinant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetricSignature {
+    /// Positive definite metric, `det g > 0`
+    Riemannian,
+    /// Metric with a single timelike direction, `det g < 0`
+    Lorentzian,
+}
+
+impl MetricSignature {
+    /// Returns the sign of the metric determinant
+    pub fn determinant_sign(&self) -> i32 {
+        match self {
+            MetricSignature::Riemannian => 1,
+            MetricSignature::Lorentzian => -1,
+        }
+    }
+}
+
+/// Creates the Levi-Civita tensor `ε_{a1…an}` of an n-dimensional manifold
+///
+/// The tensor is totally antisymmetric; its rank must equal the dimension.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::epsilon;
+/// use butler_portugal::TensorIndex;
+///
+/// let eps = epsilon(
+///     3,
+///     vec![
+///         TensorIndex::new("a", 0),
+///         TensorIndex::new("b", 1),
+///         TensorIndex::new("c", 2),
+///     ],
+/// )?;
+/// assert_eq!(eps.name(), "epsilon");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn epsilon(dimension: usize, indices: Vec<TensorIndex>) -> Result<Tensor> {
+    if indices.len() != dimension {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "Levi-Civita tensor in {} dimensions needs {} indices, got {}",
+            dimension,
+            dimension,
+            indices.len()
+        )));
+    }
+    let mut tensor = Tensor::new(EPSILON_NAME, indices);
+    if dimension > 1 {
+        tensor.add_symmetry(Symmetry::antisymmetric((0..dimension).collect()));
+    }
+    Ok(tensor)
+}
+
+/// Creates the metric `g_{ab}`, or its inverse `g^{ab}` when both indices are contravariant
+pub fn metric(first: TensorIndex, second: TensorIndex) -> Tensor {
+    let mut tensor = Tensor::new(METRIC_NAME, vec![first, second]);
+    tensor.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+    tensor
+}
+
+/// Creates the flat frame metric `η_{ab}` acting on frame indices
+pub fn frame_metric(first: TensorIndex, second: TensorIndex) -> Tensor {
+    let mut tensor = Tensor::new(FRAME_METRIC_NAME, vec![first, second]);
+    tensor.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+    tensor
+}
+
+/// Creates the metric appropriate to the class of the two indices
+///
+/// Coordinate indices use `g`, frame indices `η`, spatial indices `h` and
+/// spinor indices the antisymmetric `ε` of
+/// [`spinor_epsilon`](crate::spinors::spinor_epsilon); a metric never mixes
+/// two classes.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::metric_for;
+/// use butler_portugal::TensorIndex;
+///
+/// let eta = metric_for(TensorIndex::frame("a", 0), TensorIndex::frame("b", 1))?;
+/// assert_eq!(eta.name(), "eta");
+/// assert!(metric_for(TensorIndex::frame("a", 0), TensorIndex::new("mu", 1)).is_err());
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn metric_for(first: TensorIndex, second: TensorIndex) -> Result<Tensor> {
+    match (first.class(), second.class()) {
+        (IndexClass::Spacetime, IndexClass::Spacetime) => Ok(metric(first, second)),
+        (IndexClass::Frame, IndexClass::Frame) => Ok(frame_metric(first, second)),
+        (IndexClass::Spatial, IndexClass::Spatial) => Ok(spatial_metric(first, second)),
+        (IndexClass::Undotted, IndexClass::Undotted) | (IndexClass::Dotted, IndexClass::Dotted) => {
+            crate::spinors::spinor_epsilon(first, second)
+        }
+        (a, b) => Err(ButlerPortugalError::InvalidTensor(format!(
+            "No metric relates {a:?} and {b:?} indices"
+        ))),
+    }
+}
+
+/// Creates the vielbein `e^a{}_μ` converting coordinate indices to frame indices
+///
+/// The first slot must hold a frame index and the second a coordinate index.
+pub fn vielbein(frame: TensorIndex, coordinate: TensorIndex) -> Result<Tensor> {
+    if frame.class() != IndexClass::Frame || coordinate.class() != IndexClass::Spacetime {
+        return Err(ButlerPortugalError::InvalidTensor(
+            "The vielbein carries one frame and one coordinate index".to_string(),
+        ));
+    }
+    Ok(Tensor::new(VIELBEIN_NAME, vec![frame, coordinate]))
+}
+
+/// Converts the given slots between coordinate and frame indices
+///
+/// Each converted index `μ` is replaced by a primed dummy `μ'` contracted
+/// with a vielbein, and reappears as a free index of the target class:
+/// `T_{μ…} ↦ e_μ{}^{μ'} T_{μ'…}`. The vielbein factors come first, and their
+/// frame slot always precedes the coordinate slot.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::convert_indices;
+/// use butler_portugal::{IndexClass, Tensor, TensorIndex};
+///
+/// let v = Tensor::new("v", vec![TensorIndex::new("a", 0)]);
+/// let frame = convert_indices(&v, &[0], IndexClass::Frame)?;
+/// assert_eq!(frame.factors()[0].name(), "e");
+/// assert_eq!(frame.factors()[0].indices()[0].class(), IndexClass::Frame);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn convert_indices(
+    tensor: &Tensor,
+    slots: &[usize],
+    target: IndexClass,
+) -> Result<TensorProduct> {
+    let source = match target {
+        IndexClass::Frame => IndexClass::Spacetime,
+        IndexClass::Spacetime => IndexClass::Frame,
+        other => {
+            return Err(ButlerPortugalError::InvalidTensor(format!(
+                "The vielbein cannot convert indices to {other:?}"
+            )))
+        }
+    };
+    let mut converted = tensor.clone();
+    let mut vielbeins = Vec::with_capacity(slots.len());
+    for &slot in slots {
+        let index = tensor
+            .indices()
+            .get(slot)
+            .ok_or(ButlerPortugalError::IndexOutOfBounds {
+                index: slot,
+                max: tensor.rank(),
+            })?;
+        if index.class() != source {
+            return Err(ButlerPortugalError::InvalidTensor(format!(
+                "Slot {slot} of {} does not hold a {source:?} index",
+                tensor.name()
+            )));
+        }
+        let dummy = format!("{}'", index.name());
+        let free = index.with_class(target);
+        let mut contracted = index.with_name(&dummy);
+        contracted.set_contravariant(!index.is_contravariant());
+        let (frame, coordinate) = if target == IndexClass::Frame {
+            (free, contracted)
+        } else {
+            (contracted, free)
+        };
+        vielbeins.push(vielbein(
+            frame.with_position(0),
+            coordinate.with_position(1),
+        )?);
+        converted.indices_mut()[slot] = index.with_name(&dummy);
+    }
+    vielbeins.push(converted);
+    Ok(TensorProduct::new(Rational::ONE, vielbeins))
+}
+
+/// Creates the induced spatial metric `h_{ab} = g_{ab} + n_a n_b` of a 3+1 split
+///
+/// Slots holding spatial indices keep their class, so `h` can also serve as
+/// the projector `h_a{}^b` from spacetime onto spatial indices.
+pub fn spatial_metric(first: TensorIndex, second: TensorIndex) -> Tensor {
+    let mut tensor = Tensor::new(SPATIAL_METRIC_NAME, vec![first, second]);
+    tensor.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+    tensor
+}
+
+/// Creates the future-pointing unit normal `n_a` of the constant-time slices
+pub fn unit_normal(index: TensorIndex) -> Tensor {
+    Tensor::new(UNIT_NORMAL_NAME, vec![index])
+}
+
+/// Projects the given slots of a tensor onto the spatial slices
+///
+/// Each projected spacetime index `a` is replaced by a primed dummy `a'`
+/// contracted with `h_a{}^{a'}`, and reappears as a free spatial index:
+/// `T_{a…} ↦ h_a{}^{a'} T_{a'…}`. The projector factors come first.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::project_spatial;
+/// use butler_portugal::{IndexClass, Tensor, TensorIndex};
+///
+/// let v = Tensor::new("v", vec![TensorIndex::new("a", 0)]);
+/// let projected = project_spatial(&v, &[0])?;
+/// let h = &projected.factors()[0];
+/// assert_eq!(h.name(), "h");
+/// assert_eq!(h.indices()[0].class(), IndexClass::Spatial);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn project_spatial(tensor: &Tensor, slots: &[usize]) -> Result<TensorProduct> {
+    let mut projected = tensor.clone();
+    let mut projectors = Vec::with_capacity(slots.len());
+    for &slot in slots {
+        let index = tensor
+            .indices()
+            .get(slot)
+            .ok_or(ButlerPortugalError::IndexOutOfBounds {
+                index: slot,
+                max: tensor.rank(),
+            })?;
+        if index.class() != IndexClass::Spacetime {
+            return Err(ButlerPortugalError::InvalidTensor(format!(
+                "Only spacetime indices can be projected, slot {slot} of {} is not",
+                tensor.name()
+            )));
+        }
+        let dummy = format!("{}'", index.name());
+        let mut contracted = index.with_name(&dummy).with_position(1);
+        contracted.set_contravariant(!index.is_contravariant());
+        projectors.push(spatial_metric(
+            index.with_class(IndexClass::Spatial).with_position(0),
+            contracted,
+        ));
+        projected.indices_mut()[slot] = index.with_name(&dummy);
+    }
+    projectors.push(projected);
+    Ok(TensorProduct::new(Rational::ONE, projectors))
+}
+
+/// Returns true if the tensor is the built-in Levi-Civita tensor
+pub fn is_epsilon(tensor: &Tensor) -> bool {
+    tensor.name() == EPSILON_NAME
+}
+
+/// Returns true if the tensor is one of the built-in metrics `g`, `η` or `h`
+pub fn is_metric(tensor: &Tensor) -> bool {
+    [METRIC_NAME, FRAME_METRIC_NAME, SPATIAL_METRIC_NAME].contains(&tensor.name())
+        && tensor.rank() == 2
+}
+
+/// Computes the Hodge dual of a totally antisymmetric tensor
+///
+/// For a p-form `ω` on an n-dimensional manifold this returns
+/// `(⋆ω)_{b1…b(n-p)} = (1/p!) ε_{a1…ap b1…b(n-p)} ω^{a1…ap}` as a product of
+/// the Levi-Civita tensor, one inverse metric for every covariant index of
+/// `ω` (chosen by index class, see [`metric_for`]), and `ω` itself. Contravariant indices of `ω` contract directly with
+/// `ε`; covariant ones are raised through `g^{a c}` with a primed dummy `a'`.
+///
+/// # Arguments
+/// * `tensor` - The totally antisymmetric tensor to dualize
+/// * `dimension` - The dimension n of the manifold
+/// * `dual_indices` - The n - p free indices of the result
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::hodge_dual;
+/// use butler_portugal::rational::Rational;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let mut f = Tensor::new("F", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+/// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+/// let dual = hodge_dual(&f, 4, vec![TensorIndex::new("c", 0), TensorIndex::new("d", 1)])?;
+/// assert_eq!(dual.coefficient(), Rational::new(1, 2));
+/// assert_eq!(dual.factors().len(), 4);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn hodge_dual(
+    tensor: &Tensor,
+    dimension: usize,
+    dual_indices: Vec<TensorIndex>,
+) -> Result<TensorProduct> {
+    let degree = tensor.rank();
+    if degree > dimension {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "Cannot dualize a rank {degree} tensor in {dimension} dimensions"
+        )));
+    }
+    if dual_indices.len() != dimension - degree {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "Hodge dual of a {}-form in {} dimensions needs {} indices, got {}",
+            degree,
+            dimension,
+            dimension - degree,
+            dual_indices.len()
+        )));
+    }
+    if degree > 1 && !is_totally_antisymmetric(tensor) {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "Hodge dual requires a totally antisymmetric tensor, {} is not",
+            tensor.name()
+        )));
+    }
+
+    let mut epsilon_indices = Vec::with_capacity(dimension);
+    let mut metrics = Vec::new();
+    for index in tensor.indices() {
+        let mut lowered = index.with_position(epsilon_indices.len());
+        lowered.set_contravariant(false);
+        if index.is_contravariant() {
+            epsilon_indices.push(lowered);
+        } else {
+            let dummy = format!("{}'", index.name());
+            epsilon_indices.push(lowered.with_name(&dummy));
+            let mut raised = index.clone();
+            raised.set_contravariant(true);
+            metrics.push(metric_for(
+                raised.with_name(&dummy).with_position(0),
+                raised.with_position(1),
+            )?);
+        }
+    }
+    for index in dual_indices {
+        let position = epsilon_indices.len();
+        epsilon_indices.push(index.with_position(position));
+    }
+
+    let mut factors = vec![epsilon(dimension, epsilon_indices)?];
+    factors.extend(metrics);
+    factors.push(tensor.clone());
+    Ok(TensorProduct::new(
+        Rational::new(1, factorial(degree)),
+        factors,
+    ))
+}
+
+/// Returns the sign `s` in `⋆⋆ω = s ω` for a p-form in n dimensions
+///
+/// This is `(-1)^{p(n-p)}` times the sign of the metric determinant.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::{hodge_double_dual_sign, MetricSignature};
+///
+/// // Two-forms in four-dimensional spacetime: ⋆⋆F = -F
+/// assert_eq!(hodge_double_dual_sign(2, 4, MetricSignature::Lorentzian), -1);
+/// assert_eq!(hodge_double_dual_sign(1, 3, MetricSignature::Riemannian), 1);
+/// ```
+pub fn hodge_double_dual_sign(degree: usize, dimension: usize, signature: MetricSignature) -> i32 {
+    let parity = if degree <= dimension && (degree * (dimension - degree)) % 2 == 1 {
+        -1
+    } else {
+        1
+    };
+    parity * signature.determinant_sign()
+}
+
+/// Expands contractions of two Levi-Civita tensors into Kronecker deltas
+///
+/// Two `ε` factors of the same rank n and index class sharing m contracted
+/// pairs are replaced, after moving the contracted indices to the end of both
+/// in the same order, by
+/// `ε^{a1…ak c1…cm} ε_{b1…bk c1…cm} = s m! δ^{a1…ak}_{b1…bk}`, where `s` is the
+/// sign of the metric determinant and the generalized delta is the signed sum
+/// over the k! pairings of the `a` with the `b`. Each `δ` is the metric of the
+/// index class (see [`metric_for`]) with the variances of the indices it
+/// pairs, so pairs of lower indices give `g_{ab}`. Pairs are expanded until
+/// no two contracted `ε` remain, and the resulting metrics are absorbed into
+/// the factors they contract with by [`contract`]. Spinor `ε`, products
+/// declaring symmetries of their own, and `ε` that share no contracted pair
+/// are left alone.
+///
+/// # Errors
+/// Returns the errors of [`contract`] for a product whose indices do not pair
+/// up under the summation convention.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::{contract_epsilons, epsilon, MetricSignature};
+/// use butler_portugal::expression::TensorProduct;
+/// use butler_portugal::rational::Rational;
+/// use butler_portugal::TensorIndex;
+///
+/// // ε^{abc} ε_{abd} = 2 δ^c_d in three Euclidean dimensions
+/// let upper = ["a", "b", "c"].iter().enumerate().map(|(i, n)| TensorIndex::contravariant(n, i)).collect();
+/// let lower = ["a", "b", "d"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect();
+/// let product = TensorProduct::new(Rational::ONE, vec![epsilon(3, upper)?, epsilon(3, lower)?]);
+/// let deltas = contract_epsilons(&product, MetricSignature::Riemannian)?;
+/// assert_eq!(deltas.to_string(), "2 g_^c _d");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn contract_epsilons<C: Coefficient>(
+    product: &TensorProduct<C>,
+    signature: MetricSignature,
+) -> Result<TensorExpression<C>> {
+    let mut expanded = TensorExpression::default();
+    let mut pending = vec![product.clone()];
+    while let Some(term) = pending.pop() {
+        match expand_epsilon_pair(&term, signature)? {
+            Some(terms) => pending.extend(terms),
+            None => expanded.push(term),
+        }
+    }
+    Ok(expanded)
+}
+
+/// Replaces the first contracted pair of `ε` factors by its generalized
+/// delta, or returns `None` if the product has no such pair
+fn expand_epsilon_pair<C: Coefficient>(
+    product: &TensorProduct<C>,
+    signature: MetricSignature,
+) -> Result<Option<Vec<TensorProduct<C>>>> {
+    if !product.symmetries().is_empty() {
+        return Ok(None);
+    }
+    let factors = product.factors();
+    let contracted = |first: &Tensor, second: &Tensor| -> Vec<(usize, usize)> {
+        first
+            .indices()
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, index)| {
+                second
+                    .indices()
+                    .iter()
+                    .position(|other| index.can_contract_with(other))
+                    .map(|other| (slot, other))
+            })
+            .collect()
+    };
+    let expandable = |factor: &Tensor| {
+        is_epsilon(factor)
+            && factor.rank() > 0
+            && factor
+                .indices()
+                .iter()
+                .all(|index| index.class() == factor.indices()[0].class())
+            && !is_spinor_class(factor.indices()[0].class())
+    };
+    let Some((i, j, pairs)) = (0..factors.len())
+        .flat_map(|i| (i + 1..factors.len()).map(move |j| (i, j)))
+        .filter(|&(i, j)| {
+            expandable(&factors[i])
+                && expandable(&factors[j])
+                && factors[i].rank() == factors[j].rank()
+                && factors[i].indices()[0].class() == factors[j].indices()[0].class()
+        })
+        .map(|(i, j)| (i, j, contracted(&factors[i], &factors[j])))
+        .find(|(_, _, pairs)| !pairs.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    // Free slots first, then the contracted ones in the order of the first ε
+    let (first, second) = (&factors[i], &factors[j]);
+    let free_of = |factor: &Tensor, used: &[usize]| -> Vec<usize> {
+        (0..factor.rank())
+            .filter(|slot| !used.contains(slot))
+            .collect()
+    };
+    let first_used: Vec<usize> = pairs.iter().map(|&(slot, _)| slot).collect();
+    let second_used: Vec<usize> = pairs.iter().map(|&(_, slot)| slot).collect();
+    let first_free = free_of(first, &first_used);
+    let second_free = free_of(second, &second_used);
+    let first_order: Permutation = first_free.iter().chain(&first_used).copied().collect();
+    let second_order: Permutation = second_free.iter().chain(&second_used).copied().collect();
+    let sign =
+        i64::from(first_order.parity() * second_order.parity() * signature.determinant_sign())
+            * factorial(pairs.len());
+
+    let others: Vec<Tensor> = factors
+        .iter()
+        .enumerate()
+        .filter(|&(f, _)| f != i && f != j)
+        .map(|(_, factor)| factor.clone())
+        .collect();
+    let mut terms = Vec::new();
+    for pairing in all_permutations(first_free.len()) {
+        let mut term_factors = others.clone();
+        for (&a, &b) in first_free.iter().zip(pairing.iter()) {
+            term_factors.push(metric_for(
+                first.indices()[a].with_position(0),
+                second.indices()[second_free[b]].with_position(1),
+            )?);
+        }
+        let coefficient = product.coefficient()
+            * C::from_rational(Rational::integer(sign * i64::from(pairing.parity())));
+        terms.push(contract(&TensorProduct::new(coefficient, term_factors))?);
+    }
+    Ok(Some(terms))
+}
+
+/// Lists the permutations of `0..n`
+fn all_permutations(n: usize) -> Vec<Permutation> {
+    let mut permutations = vec![Vec::new()];
+    for point in 0..n {
+        permutations = permutations
+            .into_iter()
+            .flat_map(|partial: Vec<usize>| {
+                (0..=partial.len()).map(move |at| {
+                    let mut extended = partial.clone();
+                    extended.insert(at, point);
+                    extended
+                })
+            })
+            .collect();
+    }
+    permutations.into_iter().map(Permutation::from).collect()
+}
+
+/// Projects a tensor onto its trace-free part with respect to the metric
+///
+/// Supported are totally symmetric tensors of any rank, arbitrary rank-2
+/// tensors, tensors with the Riemann symmetries (giving the Weyl-type
+/// decomposition) and totally antisymmetric tensors, which are trace-free
+/// already. For a symmetric rank-ℓ tensor in n dimensions every distinct term
+/// with k metric factors, `g_{(a1a2}…T_{…)c1}{}^{c1}…`, carries the coefficient
+/// `(-1)^k / Π_{j=1..k} (2ℓ + n - 2 - 2j)`. Traces are written as contractions
+/// of the tensor itself with fresh dummy indices, and metrics follow the index
+/// class (see [`metric_for`]).
+///
+/// # Arguments
+/// * `tensor` - The tensor to project
+/// * `dimension` - The dimension n of the manifold
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::trace_free_part;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let mut t = Tensor::new("T", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+/// t.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+/// let tf = trace_free_part(&t, 4)?;
+/// assert_eq!(tf.to_string(), "T__a _b - 1/4 g__a _b T__c ^c");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn trace_free_part(tensor: &Tensor, dimension: usize) -> Result<TensorExpression> {
+    let rank = tensor.rank();
+    if dimension == 0 {
+        return Err(ButlerPortugalError::InvalidTensor(
+            "Trace-free projection needs a positive dimension".to_string(),
+        ));
+    }
+    if rank <= 1 || (rank > 2 && is_totally_antisymmetric(tensor)) {
+        return Ok(TensorExpression::from(tensor.clone()));
+    }
+    if rank == 2 || is_totally_symmetric(tensor) {
+        return symmetric_trace_free_part(tensor, dimension);
+    }
+    if has_riemann_symmetries(tensor) {
+        return riemann_trace_free_part(tensor, dimension);
+    }
+    Err(ButlerPortugalError::InvalidTensor(format!(
+        "Trace-free projection of {} needs a symmetric, rank-2 or Riemann-symmetric tensor",
+        tensor.name()
+    )))
+}
+
+/// Trace-free part of a totally symmetric (or any rank-2) tensor
+fn symmetric_trace_free_part(tensor: &Tensor, dimension: usize) -> Result<TensorExpression> {
+    let rank = tensor.rank();
+    let coefficient = Rational::from(tensor.coefficient());
+    let mut bare = tensor.clone();
+    bare.set_coefficient(1);
+    let mut used: HashSet<String> = tensor
+        .indices()
+        .iter()
+        .map(|i| i.name().to_string())
+        .collect();
+    let dummies: Vec<String> = (0..rank / 2)
+        .map(|_| {
+            let name = fresh_index_name(&used);
+            used.insert(name.clone());
+            name
+        })
+        .collect();
+
+    let mut expression = TensorExpression::new();
+    let mut weight = coefficient;
+    for traces in 0..=rank / 2 {
+        if traces > 0 {
+            let denominator = 2 * rank + dimension - 2 - 2 * traces;
+            weight = -weight / Rational::integer(denominator as i64);
+        }
+        for pairs in slot_pairings(rank, traces) {
+            let paired: HashSet<usize> = pairs.iter().flat_map(|&(a, b)| [a, b]).collect();
+            let mut factors = Vec::with_capacity(traces + 1);
+            for &(a, b) in &pairs {
+                factors.push(metric_for(
+                    tensor.indices()[a].with_position(0),
+                    tensor.indices()[b].with_position(1),
+                )?);
+            }
+            let mut indices: Vec<TensorIndex> = (0..rank)
+                .filter(|slot| !paired.contains(slot))
+                .map(|slot| tensor.indices()[slot].clone())
+                .collect();
+            for (pair, dummy) in pairs.iter().zip(&dummies) {
+                let template = &tensor.indices()[pair.0];
+                let mut lower = template.with_name(dummy);
+                lower.set_contravariant(false);
+                let mut upper = lower.clone();
+                upper.set_contravariant(true);
+                indices.push(lower);
+                indices.push(upper);
+            }
+            let mut trace = bare.clone();
+            *trace.indices_mut() = indices
+                .into_iter()
+                .enumerate()
+                .map(|(position, index)| index.with_position(position))
+                .collect();
+            factors.push(trace);
+            expression.push(TensorProduct::new(weight, factors));
+        }
+    }
+    Ok(expression)
+}
+
+/// Weyl-type trace-free part of a tensor with the Riemann symmetries
+fn riemann_trace_free_part(tensor: &Tensor, dimension: usize) -> Result<TensorExpression> {
+    // In two dimensions such a tensor is pure trace
+    if dimension <= 2 {
+        return Ok(TensorExpression::new());
+    }
+    let coefficient = Rational::from(tensor.coefficient());
+    let mut bare = tensor.clone();
+    bare.set_coefficient(1);
+    let free = tensor.indices();
+    let mut used: HashSet<String> = free.iter().map(|i| i.name().to_string()).collect();
+    let e = fresh_index_name(&used);
+    used.insert(e.clone());
+    let f = fresh_index_name(&used);
+
+    let dummy = |name: &str, contravariant: bool| {
+        let mut index = free[0].with_name(name);
+        index.set_contravariant(contravariant);
+        index
+    };
+    let with_indices = |indices: Vec<TensorIndex>| {
+        let mut t = bare.clone();
+        *t.indices_mut() = indices
+            .into_iter()
+            .enumerate()
+            .map(|(position, index)| index.with_position(position))
+            .collect();
+        t
+    };
+    // R_{xy} = R^e{}_{x e y} and R = R^{ef}{}_{ef}
+    let ricci = |x: usize, y: usize| {
+        with_indices(vec![
+            dummy(&e, true),
+            free[x].clone(),
+            dummy(&e, false),
+            free[y].clone(),
+        ])
+    };
+    let scalar = with_indices(vec![
+        dummy(&e, true),
+        dummy(&f, true),
+        dummy(&e, false),
+        dummy(&f, false),
+    ]);
+    let g = |x: usize, y: usize| metric_for(free[x].with_position(0), free[y].with_position(1));
+
+    let n = dimension as i64;
+    let ricci_weight = -coefficient / Rational::integer(n - 2);
+    let scalar_weight = coefficient / Rational::integer((n - 1) * (n - 2));
+    let mut expression =
+        TensorExpression::from(TensorProduct::new(coefficient, vec![bare.clone()]));
+    for (sign, (x, y), (u, v)) in [
+        (1, (0, 2), (1, 3)),
+        (-1, (0, 3), (1, 2)),
+        (-1, (1, 2), (0, 3)),
+        (1, (1, 3), (0, 2)),
+    ] {
+        expression.push(TensorProduct::new(
+            ricci_weight * Rational::from(sign),
+            vec![g(x, y)?, ricci(u, v)],
+        ));
+    }
+    for (sign, (x, y), (u, v)) in [(1, (0, 2), (1, 3)), (-1, (0, 3), (1, 2))] {
+        expression.push(TensorProduct::new(
+            scalar_weight * Rational::from(sign),
+            vec![g(x, y)?, g(u, v)?, scalar.clone()],
+        ));
+    }
+    Ok(expression)
+}
+
+/// Enumerates all sets of `count` disjoint slot pairs among `rank` slots
+fn slot_pairings(rank: usize, count: usize) -> Vec<Vec<(usize, usize)>> {
+    fn extend(
+        start: usize,
+        rank: usize,
+        count: usize,
+        used: &mut Vec<bool>,
+        current: &mut Vec<(usize, usize)>,
+        out: &mut Vec<Vec<(usize, usize)>>,
+    ) {
+        if current.len() == count {
+            out.push(current.clone());
+            return;
+        }
+        for a in start..rank {
+            if used[a] {
+                continue;
+            }
+            for b in a + 1..rank {
+                if used[b] {
+                    continue;
+                }
+                used[a] = true;
+                used[b] = true;
+                current.push((a, b));
+                extend(a + 1, rank, count, used, current, out);
+                current.pop();
+                used[a] = false;
+                used[b] = false;
+            }
+        }
+    }
+    let mut out = Vec::new();
+    extend(
+        0,
+        rank,
+        count,
+        &mut vec![false; rank],
+        &mut Vec::new(),
+        &mut out,
+    );
+    out
+}
+
+/// Returns true if one of the tensor's symmetries symmetrizes all of its slots
+fn is_totally_symmetric(tensor: &Tensor) -> bool {
+    covers_all_slots(tensor, |symmetry| match symmetry {
+        Symmetry::Symmetric { indices } => Some(indices),
+        _ => None,
+    })
+}
+
+/// Returns true if one of the tensor's symmetries antisymmetrizes all of its slots
+fn is_totally_antisymmetric(tensor: &Tensor) -> bool {
+    covers_all_slots(tensor, |symmetry| match symmetry {
+        Symmetry::Antisymmetric { indices } => Some(indices),
+        _ => None,
+    })
+}
+
+/// Returns true if some symmetry selected by `slots_of` acts on every slot
+fn covers_all_slots(tensor: &Tensor, slots_of: impl Fn(&Symmetry) -> Option<&Vec<usize>>) -> bool {
+    let rank = tensor.rank();
+    tensor
+        .symmetries()
+        .iter()
+        .filter_map(slots_of)
+        .any(|indices| {
+            let mut slots = indices.clone();
+            slots.sort_unstable();
+            slots.dedup();
+            slots.len() == rank && slots.iter().all(|&slot| slot < rank)
+        })
+}
+
+/// Computes n! as an i64
+fn factorial(n: usize) -> i64 {
+    (1..=n as i64).product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::identity_residual;
+
+    fn two_form(name: &str, a: TensorIndex, b: TensorIndex) -> Tensor {
+        let mut tensor = Tensor::new(name, vec![a, b]);
+        tensor.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        tensor
+    }
+
+    #[test]
+    fn test_epsilon_rank_must_match_dimension() {
+        let indices = vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)];
+        assert!(epsilon(3, indices.clone()).is_err());
+        let eps = epsilon(2, indices).unwrap();
+        assert!(is_epsilon(&eps));
+        assert_eq!(eps.symmetries().len(), 1);
+    }
+
+    #[test]
+    fn test_hodge_dual_structure() {
+        let f = two_form("F", TensorIndex::new("a", 0), TensorIndex::new("b", 1));
+        let dual = hodge_dual(
+            &f,
+            4,
+            vec![TensorIndex::new("c", 0), TensorIndex::new("d", 1)],
+        )
+        .unwrap();
+
+        assert_eq!(dual.coefficient(), Rational::new(1, 2));
+        let factors = dual.factors();
+        assert!(is_epsilon(&factors[0]));
+        let names: Vec<&str> = factors[0].indices().iter().map(|i| i.name()).collect();
+        assert_eq!(names, vec!["a'", "b'", "c", "d"]);
+        assert!(is_metric(&factors[1]) && is_metric(&factors[2]));
+        assert!(factors[1].indices().iter().all(|i| i.is_contravariant()));
+        assert_eq!(factors[3].name(), "F");
+    }
+
+    #[test]
+    fn test_hodge_dual_of_contravariant_tensor_needs_no_metric() {
+        let f = two_form(
+            "F",
+            TensorIndex::contravariant("a", 0),
+            TensorIndex::contravariant("b", 1),
+        );
+        let dual = hodge_dual(&f, 3, vec![TensorIndex::new("c", 0)]).unwrap();
+        assert_eq!(dual.factors().len(), 2);
+        let names: Vec<&str> = dual.factors()[0]
+            .indices()
+            .iter()
+            .map(|i| i.name())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_hodge_dual_validation() {
+        let f = two_form("F", TensorIndex::new("a", 0), TensorIndex::new("b", 1));
+        assert!(hodge_dual(&f, 4, vec![TensorIndex::new("c", 0)]).is_err());
+        assert!(hodge_dual(&f, 1, vec![]).is_err());
+
+        let plain = Tensor::new(
+            "T",
+            vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)],
+        );
+        assert!(hodge_dual(&plain, 2, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_double_dual_signs() {
+        use MetricSignature::*;
+        // Euclidean R^3: ⋆⋆ = 1 on all degrees
+        for p in 0..=3 {
+            assert_eq!(hodge_double_dual_sign(p, 3, Riemannian), 1);
+        }
+        // Minkowski space: ⋆⋆ = -(-1)^{p(4-p)}
+        assert_eq!(hodge_double_dual_sign(0, 4, Lorentzian), -1);
+        assert_eq!(hodge_double_dual_sign(1, 4, Lorentzian), 1);
+        assert_eq!(hodge_double_dual_sign(2, 4, Lorentzian), -1);
+        // Euclidean R^4 two-forms are self-dual capable: ⋆⋆ = 1
+        assert_eq!(hodge_double_dual_sign(2, 4, Riemannian), 1);
+        assert_eq!(hodge_double_dual_sign(1, 2, Riemannian), -1);
+    }
+
+    #[test]
+    fn test_project_spatial() {
+        let mut k = Tensor::new(
+            "K",
+            vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)],
+        );
+        k.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        let projected = project_spatial(&k, &[0, 1]).unwrap();
+        let factors = projected.factors();
+        assert_eq!(factors.len(), 3);
+        for (h, name) in factors[..2].iter().zip(["a", "b"]) {
+            assert_eq!(h.name(), SPATIAL_METRIC_NAME);
+            assert_eq!(h.indices()[0].name(), name);
+            assert_eq!(h.indices()[0].class(), IndexClass::Spatial);
+            assert!(h.indices()[1].is_contravariant());
+        }
+        let names: Vec<&str> = factors[2].indices().iter().map(|i| i.name()).collect();
+        assert_eq!(names, vec!["a'", "b'"]);
+
+        assert!(project_spatial(&k, &[2]).is_err());
+        let spatial = Tensor::new("v", vec![TensorIndex::spatial("i", 0)]);
+        assert!(project_spatial(&spatial, &[0]).is_err());
+        assert_eq!(unit_normal(TensorIndex::new("a", 0)).name(), "n");
+    }
+
+    #[test]
+    fn test_frame_conversion_round_trip() {
+        let v = Tensor::new("v", vec![TensorIndex::contravariant("mu", 0)]);
+        let frame = convert_indices(&v, &[0], IndexClass::Frame).unwrap();
+        let e = &frame.factors()[0];
+        assert_eq!(e.indices()[0].class(), IndexClass::Frame);
+        assert!(e.indices()[0].is_contravariant());
+        assert_eq!(e.indices()[1].name(), "mu'");
+        assert!(e.indices()[1].is_covariant());
+
+        let mut frame_vector = frame.factors()[1].clone();
+        frame_vector.indices_mut()[0] =
+            TensorIndex::contravariant("a", 0).with_class(IndexClass::Frame);
+        let back = convert_indices(&frame_vector, &[0], IndexClass::Spacetime).unwrap();
+        assert_eq!(
+            back.factors()[0].indices()[1].class(),
+            IndexClass::Spacetime
+        );
+
+        assert!(convert_indices(&frame_vector, &[0], IndexClass::Frame).is_err());
+        assert!(convert_indices(&v, &[0], IndexClass::Spatial).is_err());
+        assert!(vielbein(TensorIndex::new("mu", 0), TensorIndex::new("nu", 1)).is_err());
+    }
+
+    #[test]
+    fn test_hodge_dual_uses_frame_metric() {
+        let f = two_form("F", TensorIndex::frame("a", 0), TensorIndex::frame("b", 1));
+        let dual = hodge_dual(&f, 2, vec![]).unwrap();
+        assert!(dual.factors()[0]
+            .indices()
+            .iter()
+            .all(|i| i.class() == IndexClass::Frame));
+        assert_eq!(dual.factors()[1].name(), FRAME_METRIC_NAME);
+    }
+
+    #[test]
+    fn test_symmetric_trace_free_coefficients() {
+        let mut t = Tensor::new(
+            "T",
+            ["a", "b", "c"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        );
+        t.add_symmetry(Symmetry::symmetric(vec![0, 1, 2]));
+        // T_abc - 1/5 (g_ab T_c + g_ac T_b + g_bc T_a) in three dimensions
+        let tf = trace_free_part(&t, 3).unwrap();
+        assert_eq!(tf.terms().len(), 4);
+        assert!(tf.terms()[1..]
+            .iter()
+            .all(|term| term.coefficient() == Rational::new(-1, 5)));
+
+        // Rank 4 in four dimensions: 1 + 6 single traces + 3 double traces
+        let mut t = Tensor::new(
+            "T",
+            (0..4)
+                .map(|i| TensorIndex::new(&format!("a{i}"), i))
+                .collect(),
+        );
+        t.add_symmetry(Symmetry::symmetric(vec![0, 1, 2, 3]));
+        let tf = trace_free_part(&t, 4).unwrap();
+        let coefficients: Vec<Rational> = tf.terms().iter().map(|t| t.coefficient()).collect();
+        assert_eq!(coefficients.len(), 10);
+        assert_eq!(coefficients[1], Rational::new(-1, 8));
+        assert_eq!(coefficients[9], Rational::new(1, 48));
+    }
+
+    #[test]
+    fn test_weyl_decomposition() {
+        let r = crate::presets::riemann(
+            ["a", "b", "c", "d"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap();
+        let weyl = trace_free_part(&r, 4).unwrap();
+        assert_eq!(weyl.terms().len(), 7);
+        assert_eq!(weyl.terms()[1].coefficient(), Rational::new(-1, 2));
+        assert_eq!(weyl.terms()[5].coefficient(), Rational::new(1, 6));
+        assert_eq!(weyl.terms()[1].to_string(), "-1/2 g__a _c R_^e _b _e _d");
+        assert!(trace_free_part(&r, 2).unwrap().is_zero());
+    }
+
+    #[test]
+    fn test_trace_free_trivial_and_unsupported() {
+        let f = two_form("F", TensorIndex::new("a", 0), TensorIndex::new("b", 1));
+        // Rank-2 tensors always have a single trace to remove
+        assert_eq!(trace_free_part(&f, 4).unwrap().terms().len(), 2);
+        let v = Tensor::new("v", vec![TensorIndex::new("a", 0)]);
+        assert_eq!(trace_free_part(&v, 4).unwrap().terms().len(), 1);
+        let t = Tensor::new(
+            "T",
+            ["a", "b", "c"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        );
+        assert!(trace_free_part(&t, 4).is_err());
+    }
+
+    #[test]
+    fn test_epsilon_contractions() {
+        let eps = |names: &[&str], upper: bool| {
+            let indices = names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| {
+                    let mut index = TensorIndex::new(n, i);
+                    index.set_contravariant(upper);
+                    index
+                })
+                .collect::<Vec<_>>();
+            epsilon(names.len(), indices).unwrap()
+        };
+        let delta =
+            |a: &str, b: &str| metric(TensorIndex::contravariant(a, 0), TensorIndex::new(b, 1));
+
+        // ε^{abcd} ε_{abef} = -2 (δ^c_e δ^d_f - δ^c_f δ^d_e), with the
+        // contracted indices in a different order in the second factor
+        let product = TensorProduct::new(
+            Rational::ONE,
+            vec![
+                eps(&["a", "b", "c", "d"], true),
+                eps(&["e", "b", "a", "f"], false),
+            ],
+        );
+        let expanded = TensorExpression::from(product)
+            .contract_epsilons(MetricSignature::Lorentzian)
+            .unwrap();
+        let expected: TensorExpression = [
+            TensorProduct::new(Rational::integer(2), vec![delta("c", "e"), delta("d", "f")]),
+            TensorProduct::new(
+                Rational::integer(-2),
+                vec![delta("c", "f"), delta("d", "e")],
+            ),
+        ]
+        .into_iter()
+        .collect();
+        assert!(identity_residual(&expanded, &expected).unwrap().is_zero());
+        let flipped = expected.scale(Rational::integer(-1));
+        assert!(!identity_residual(&expanded, &flipped).unwrap().is_zero());
+
+        // The deltas are absorbed into the other factors: ε^{abc} ε_{ade} u_b v^d = u_e v^c - (u·v) δ^c_e
+        let u = Tensor::new("u", vec![TensorIndex::new("b", 0)]);
+        let v = Tensor::new("v", vec![TensorIndex::contravariant("d", 0)]);
+        let product = TensorProduct::new(
+            Rational::ONE,
+            vec![
+                eps(&["a", "b", "c"], true),
+                eps(&["a", "d", "e"], false),
+                u,
+                v,
+            ],
+        );
+        let expanded = contract_epsilons(&product, MetricSignature::Riemannian).unwrap();
+        assert_eq!(expanded.terms().len(), 2);
+        assert!(expanded
+            .terms()
+            .iter()
+            .all(|term| term.factors().iter().all(|factor| !is_epsilon(factor))));
+        assert!(expanded
+            .terms()
+            .iter()
+            .any(|term| term.factors().len() == 2));
+
+        // Uncontracted and spinor ε are left alone
+        let apart = TensorProduct::new(
+            Rational::ONE,
+            vec![eps(&["a", "b"], true), eps(&["c", "d"], false)],
+        );
+        assert_eq!(
+            contract_epsilons(&apart, MetricSignature::Riemannian)
+                .unwrap()
+                .terms(),
+            &[apart]
+        );
+        let mut raised_b = TensorIndex::undotted("B", 1);
+        raised_b.set_contravariant(true);
+        let spinors = TensorProduct::new(
+            Rational::ONE,
+            vec![
+                crate::spinors::spinor_epsilon(
+                    TensorIndex::undotted("A", 0),
+                    TensorIndex::undotted("B", 1),
+                )
+                .unwrap(),
+                crate::spinors::spinor_epsilon(TensorIndex::undotted("C", 0), raised_b).unwrap(),
+            ],
+        );
+        assert_eq!(
+            contract_epsilons(&spinors, MetricSignature::Riemannian)
+                .unwrap()
+                .terms()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,655 @@
+//! Caching of canonicalization results
+//!
+//! Canonicalizing the same tensor, or tensors sharing a symmetry structure,
+//! over and over is common in expression simplification. A
+//! `CanonicalizationCache` remembers the slot symmetry group, as a
+//! stabilizer chain, of the symmetry structures it has seen and the canonical
+//! forms of the tensors it has canonicalized. Both stores hold at most a
+//! configurable number of entries and evict the least recently used one when
+//! full, so long runs keep a bounded memory footprint; [`CacheStats`] tells
+//! how well the cache is doing.
+//!
+//! Callers who do not want to thread a cache handle through their code can
+//! opt into a process-global cache with [`enable_global_cache`]; the plain
+//! [`canonicalize`](crate::canonicalize) entry point then consults it.
+
+use crate::builtin::epsilon;
+use crate::canonicalization::{
+    apply_minimal, settle_trivial, slot_group, validate_symmetries, SlotGroup,
+};
+use crate::error::Result;
+use crate::index::{IndexClass, TensorIndex};
+use crate::presets::riemann;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Whether the plain `canonicalize` entry point uses the global cache
+static GLOBAL_CACHE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The process-global cache, created on first use
+static GLOBAL_CACHE: OnceLock<Mutex<CanonicalizationCache>> = OnceLock::new();
+
+/// Symmetry groups of the standard presets, for warming a cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetGroup {
+    /// The Riemann monoterm symmetries, as declared by `presets::riemann`
+    Riemann,
+    /// The Levi-Civita tensor in `n` dimensions, as declared by `builtin::epsilon`
+    Epsilon(usize),
+    /// Total symmetry in `n` slots
+    Symmetric(usize),
+    /// Total antisymmetry in `n` slots
+    Antisymmetric(usize),
+}
+
+impl PresetGroup {
+    /// Builds a tensor carrying the preset's symmetry declarations
+    fn representative(self) -> Result<Tensor> {
+        let indices = |n: usize| {
+            (0..n)
+                .map(|i| TensorIndex::new(&format!("i{i}"), i))
+                .collect::<Vec<_>>()
+        };
+        match self {
+            PresetGroup::Riemann => riemann(indices(4)),
+            PresetGroup::Epsilon(n) => epsilon(n, indices(n)),
+            PresetGroup::Symmetric(n) => {
+                let mut tensor = Tensor::new("S", indices(n));
+                tensor.add_symmetry(Symmetry::symmetric((0..n).collect()));
+                Ok(tensor)
+            }
+            PresetGroup::Antisymmetric(n) => {
+                let mut tensor = Tensor::new("A", indices(n));
+                tensor.add_symmetry(Symmetry::antisymmetric((0..n).collect()));
+                Ok(tensor)
+            }
+        }
+    }
+}
+
+/// Number of symmetry groups, and of canonical forms, a cache holds by default
+pub const DEFAULT_CAPACITY: usize = 4096;
+
+/// Hit and miss counts of a [`CanonicalizationCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Canonical forms answered from the cache
+    pub hits: u64,
+    /// Canonical forms that had to be computed
+    pub misses: u64,
+    /// Symmetry groups found in the cache
+    pub group_hits: u64,
+    /// Symmetry groups that had to be built
+    pub group_misses: u64,
+    /// Entries of either store dropped to stay within the capacity
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Returns the fraction of canonical forms answered from the cache, or
+    /// zero before the first lookup
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "forms   {:>8} hits {:>8} misses  ({:.1}%)",
+            self.hits,
+            self.misses,
+            100.0 * self.hit_rate()
+        )?;
+        writeln!(
+            f,
+            "groups  {:>8} hits {:>8} misses",
+            self.group_hits, self.group_misses
+        )?;
+        write!(f, "evicted {:>8}", self.evictions)
+    }
+}
+
+/// A map holding at most `capacity` entries, dropping the least recently
+/// used one to make room
+#[derive(Debug)]
+struct LruMap<K, V> {
+    capacity: usize,
+    /// Values with the tick of their last use
+    entries: HashMap<K, (V, u64)>,
+    /// Keys by the tick of their last use, oldest first
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> LruMap<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Returns the value of a key, marking it as the most recently used
+    fn get(&mut self, key: &K) -> Option<V> {
+        let (value, used) = self.entries.get_mut(key)?;
+        self.tick += 1;
+        if let Some(key) = self.recency.remove(used) {
+            self.recency.insert(self.tick, key);
+        }
+        *used = self.tick;
+        Some(value.clone())
+    }
+
+    /// Stores a value as the most recently used, returning the number of
+    /// entries evicted
+    fn insert(&mut self, key: K, value: V) -> usize {
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.recency.remove(&used);
+        }
+        self.recency.insert(self.tick, key);
+        self.shrink()
+    }
+
+    /// Changes the capacity, returning the number of entries evicted
+    fn set_capacity(&mut self, capacity: usize) -> usize {
+        self.capacity = capacity;
+        self.shrink()
+    }
+
+    /// Evicts the least recently used entries beyond the capacity
+    fn shrink(&mut self) -> usize {
+        let mut evicted = 0;
+        while self.entries.len() > self.capacity {
+            let Some((_, key)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&key);
+            evicted += 1;
+        }
+        evicted
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    #[cfg(test)]
+    fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.values().map(|(value, _)| value)
+    }
+}
+
+/// A cache of slot symmetry groups and canonical forms
+///
+/// Each of the two stores holds at most [`capacity`](Self::capacity)
+/// entries and evicts the least recently used one when full.
+#[derive(Debug)]
+pub struct CanonicalizationCache {
+    groups: LruMap<SymmetryKey, Arc<SlotGroup>>,
+    results: LruMap<Tensor, Tensor>,
+    stats: CacheStats,
+}
+
+impl Default for CanonicalizationCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl CanonicalizationCache {
+    /// Creates an empty cache holding up to [`DEFAULT_CAPACITY`] groups and
+    /// forms
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty cache holding up to `capacity` groups and `capacity`
+    /// canonical forms
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::cache::CanonicalizationCache;
+    /// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+    ///
+    /// let mut cache = CanonicalizationCache::with_capacity(2);
+    /// for name in ["a", "b", "c", "a"] {
+    ///     let mut f = Tensor::new("F", vec![TensorIndex::new(name, 0), TensorIndex::new("x", 1)]);
+    ///     f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    ///     cache.canonicalize(&f)?;
+    /// }
+    /// // F_{ax} was evicted by F_{cx} and computed again
+    /// assert_eq!(cache.len(), 2);
+    /// let stats = cache.stats();
+    /// assert_eq!((stats.hits, stats.misses, stats.evictions), (0, 4, 2));
+    /// assert_eq!((stats.group_hits, stats.group_misses), (3, 1));
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            groups: LruMap::new(capacity),
+            results: LruMap::new(capacity),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Returns the number of groups, and of canonical forms, the cache holds
+    /// at most
+    pub fn capacity(&self) -> usize {
+        self.results.capacity
+    }
+
+    /// Changes the capacity, evicting the least recently used entries that
+    /// no longer fit
+    pub fn set_capacity(&mut self, capacity: usize) {
+        let evicted = self.groups.set_capacity(capacity) + self.results.set_capacity(capacity);
+        self.stats.evictions += evicted as u64;
+    }
+
+    /// Returns the hit and miss counts since the cache was created or its
+    /// statistics were reset
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Sets the hit and miss counts back to zero, keeping the cached data
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Canonicalizes a tensor, reusing and recording cached data
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::cache::CanonicalizationCache;
+    /// use butler_portugal::{canonicalize, Symmetry, Tensor, TensorIndex};
+    ///
+    /// let mut f = Tensor::new("F", vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)]);
+    /// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    ///
+    /// let mut cache = CanonicalizationCache::new();
+    /// assert_eq!(cache.canonicalize(&f)?, canonicalize(&f)?);
+    /// assert_eq!(cache.len(), 1);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn canonicalize(&mut self, tensor: &Tensor) -> Result<Tensor> {
+        if let Some(cached) = self.lookup(tensor) {
+            return Ok(cached);
+        }
+        validate_symmetries(tensor)?;
+        let mut canonical = tensor.clone();
+        if !settle_trivial(&mut canonical) {
+            let group = self.group(tensor);
+            apply_minimal(&mut canonical, &group);
+        }
+        self.insert(tensor, &canonical);
+        Ok(canonical)
+    }
+
+    /// Builds and stores the symmetry groups of presets ahead of a batch run
+    ///
+    /// Tensors declared with the same symmetries as a warmed preset then skip
+    /// building the group on their first canonicalization.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::cache::{CanonicalizationCache, PresetGroup};
+    ///
+    /// let mut cache = CanonicalizationCache::new();
+    /// cache.warm(&[PresetGroup::Riemann, PresetGroup::Epsilon(4), PresetGroup::Symmetric(3)])?;
+    /// assert_eq!(cache.group_count(), 3);
+    /// assert!(cache.is_empty());
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn warm(&mut self, presets: &[PresetGroup]) -> Result<()> {
+        for preset in presets {
+            self.group(&preset.representative()?);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of cached symmetry groups
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Returns the number of cached canonical forms
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns true if no canonical forms are cached
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Removes all cached data, keeping the statistics
+    pub fn clear(&mut self) {
+        self.groups.clear();
+        self.results.clear();
+    }
+
+    /// Returns the cached canonical form of a tensor
+    fn lookup(&mut self, tensor: &Tensor) -> Option<Tensor> {
+        let cached = self.results.get(tensor);
+        if cached.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        cached
+    }
+
+    /// Records the canonical form of a tensor
+    fn insert(&mut self, tensor: &Tensor, canonical: &Tensor) {
+        let evicted = self.results.insert(tensor.clone(), canonical.clone());
+        self.stats.evictions += evicted as u64;
+    }
+
+    /// Returns the tensor's slot symmetry group, building it on a miss
+    fn group(&mut self, tensor: &Tensor) -> Arc<SlotGroup> {
+        let key = SymmetryKey::of(tensor);
+        if let Some(group) = self.groups.get(&key) {
+            self.stats.group_hits += 1;
+            return group;
+        }
+        self.stats.group_misses += 1;
+        let group = Arc::new(slot_group(tensor));
+        let evicted = self.groups.insert(key, group.clone());
+        self.stats.evictions += evicted as u64;
+        group
+    }
+}
+
+/// Makes the plain `canonicalize` entry point use a process-global cache
+///
+/// The cache lives until the process exits; call [`clear_global_cache`] to
+/// release its memory.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::cache::{disable_global_cache, enable_global_cache, global_cache_len};
+/// use butler_portugal::{canonicalize, Symmetry, Tensor, TensorIndex};
+///
+/// let mut s = Tensor::new("S", vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)]);
+/// s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+///
+/// enable_global_cache();
+/// canonicalize(&s)?;
+/// assert!(global_cache_len() >= 1);
+/// disable_global_cache();
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn enable_global_cache() {
+    GLOBAL_CACHE_ENABLED.store(true, Ordering::Release);
+}
+
+/// Stops the plain `canonicalize` entry point from using the global cache
+///
+/// Cached data is kept, so re-enabling the cache picks up where it left off.
+pub fn disable_global_cache() {
+    GLOBAL_CACHE_ENABLED.store(false, Ordering::Release);
+}
+
+/// Returns true if the global cache is enabled
+pub fn global_cache_enabled() -> bool {
+    GLOBAL_CACHE_ENABLED.load(Ordering::Acquire)
+}
+
+/// Removes all data from the global cache
+pub fn clear_global_cache() {
+    with_global_cache(CanonicalizationCache::clear);
+}
+
+/// Returns the number of canonical forms in the global cache
+pub fn global_cache_len() -> usize {
+    with_global_cache(|cache| cache.len()).unwrap_or(0)
+}
+
+/// Returns the hit and miss counts of the global cache
+pub fn global_cache_stats() -> CacheStats {
+    with_global_cache(|cache| cache.stats()).unwrap_or_default()
+}
+
+/// Changes the capacity of the global cache, which starts out at
+/// [`DEFAULT_CAPACITY`]
+pub fn set_global_cache_capacity(capacity: usize) {
+    with_global_cache(|cache| cache.set_capacity(capacity));
+}
+
+/// Canonicalizes through the global cache
+///
+/// The lock is only held for lookups and insertions, so threads canonicalize
+/// different tensors concurrently.
+pub(crate) fn canonicalize_with_global_cache(tensor: &Tensor) -> Result<Tensor> {
+    if let Some(cached) = with_global_cache(|cache| cache.lookup(tensor)).flatten() {
+        return Ok(cached);
+    }
+    validate_symmetries(tensor)?;
+    let mut canonical = tensor.clone();
+    if !settle_trivial(&mut canonical) {
+        let group = with_global_cache(|cache| cache.group(tensor))
+            .unwrap_or_else(|| Arc::new(slot_group(tensor)));
+        apply_minimal(&mut canonical, &group);
+    }
+    with_global_cache(|cache| cache.insert(tensor, &canonical));
+    Ok(canonical)
+}
+
+/// Runs `f` on the global cache, or returns `None` if its lock is poisoned
+fn with_global_cache<R>(f: impl FnOnce(&mut CanonicalizationCache) -> R) -> Option<R> {
+    let cache = GLOBAL_CACHE.get_or_init(|| Mutex::new(CanonicalizationCache::new()));
+    cache.lock().ok().map(|mut guard| f(&mut guard))
+}
+
+/// Identifies everything the slot group of a tensor is built from
+///
+/// Besides the rank and the symmetry declarations, the index class of every
+/// slot decides whether the group is searched as a stabilizer chain, so
+/// tensors with the same declarations over indices of other classes do not
+/// share a group. The slots of symmetric and antisymmetric declarations are
+/// sorted, as the group does not depend on the order they are listed in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SymmetryKey {
+    rank: usize,
+    symmetries: Vec<Symmetry>,
+    classes: Vec<IndexClass>,
+}
+
+impl SymmetryKey {
+    pub(crate) fn of(tensor: &Tensor) -> Self {
+        let symmetries = tensor
+            .symmetries()
+            .iter()
+            .map(|symmetry| match symmetry {
+                Symmetry::Symmetric { indices } => Symmetry::symmetric(sorted(indices)),
+                Symmetry::Antisymmetric { indices } => Symmetry::antisymmetric(sorted(indices)),
+                other => other.clone(),
+            })
+            .collect();
+        Self {
+            rank: tensor.rank(),
+            symmetries,
+            classes: tensor.indices().iter().map(TensorIndex::class).collect(),
+        }
+    }
+}
+
+/// Returns the slots of a declaration in increasing order
+fn sorted(indices: &[usize]) -> Vec<usize> {
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalization::canonicalize_in_place;
+    use crate::symmetry::Symmetry;
+    use crate::TensorIndex;
+
+    fn antisymmetric(first: &str, second: &str) -> Tensor {
+        let mut f = Tensor::new(
+            "F",
+            vec![TensorIndex::new(first, 0), TensorIndex::new(second, 1)],
+        );
+        f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        f
+    }
+
+    #[test]
+    fn test_cache_matches_uncached_results() {
+        let mut cache = CanonicalizationCache::new();
+        for (first, second) in [("b", "a"), ("a", "b"), ("a", "a"), ("b", "a")] {
+            let tensor = antisymmetric(first, second);
+            let mut expected = tensor.clone();
+            canonicalize_in_place(&mut expected).unwrap();
+            assert_eq!(cache.canonicalize(&tensor).unwrap(), expected);
+        }
+        assert_eq!(cache.len(), 3);
+        // All three tensors share one symmetry structure; the vanishing one never needs it
+        assert_eq!(cache.groups.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_different_structures_do_not_collide() {
+        let mut cache = CanonicalizationCache::new();
+        let with = |symmetry: Symmetry, first: TensorIndex| {
+            let mut t = Tensor::new("T", vec![first, TensorIndex::new("a", 1)]);
+            t.add_symmetry(symmetry);
+            t
+        };
+        let b = || TensorIndex::new("b", 0);
+        let tensors = [
+            with(Symmetry::symmetric(vec![0, 1]), b()),
+            with(Symmetry::antisymmetric(vec![0, 1]), b()),
+            with(Symmetry::cyclic(vec![0, 1]), b()),
+            with(Symmetry::custom(vec![vec![1, 0]], vec![-1]), b()),
+            // Same declaration, but an index of another class
+            with(
+                Symmetry::antisymmetric(vec![0, 1]),
+                TensorIndex::undotted("B", 0),
+            ),
+        ];
+        for tensor in &tensors {
+            let mut expected = tensor.clone();
+            canonicalize_in_place(&mut expected).unwrap();
+            assert_eq!(cache.canonicalize(tensor).unwrap(), expected);
+        }
+        assert_eq!(cache.group_count(), tensors.len());
+
+        // Listing the slots of a declaration in another order shares its group
+        cache
+            .canonicalize(&with(Symmetry::symmetric(vec![1, 0]), b()))
+            .unwrap();
+        assert_eq!(cache.group_count(), tensors.len());
+        assert_eq!(
+            SymmetryKey::of(&with(Symmetry::antisymmetric(vec![1, 0]), b())),
+            SymmetryKey::of(&tensors[1])
+        );
+    }
+
+    #[test]
+    fn test_least_recently_used_form_is_evicted() {
+        let mut cache = CanonicalizationCache::with_capacity(2);
+        let (first, second, third) = (
+            antisymmetric("b", "a"),
+            antisymmetric("c", "a"),
+            antisymmetric("d", "a"),
+        );
+        cache.canonicalize(&first).unwrap();
+        cache.canonicalize(&second).unwrap();
+        // Using the first form again makes the second the oldest
+        cache.canonicalize(&first).unwrap();
+        cache.canonicalize(&third).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().hits, 1);
+        cache.canonicalize(&first).unwrap();
+        assert_eq!(cache.stats().hits, 2);
+        cache.canonicalize(&second).unwrap();
+        assert_eq!(cache.stats().hits, 2);
+        assert_eq!(cache.stats().misses, 4);
+        assert_eq!(cache.stats().evictions, 2);
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.group_count(), 1);
+        assert_eq!(cache.stats().evictions, 3);
+        cache.set_capacity(0);
+        assert!(cache.is_empty());
+        cache.canonicalize(&first).unwrap();
+        assert!(cache.is_empty());
+        assert!(cache.stats().to_string().contains("misses"));
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn test_global_cache_round_trip() {
+        enable_global_cache();
+        assert!(global_cache_enabled());
+        let tensor = antisymmetric("y", "x");
+        let first = crate::canonicalize(&tensor).unwrap();
+        let second = crate::canonicalize(&tensor).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.coefficient(), -1);
+        assert!(global_cache_len() >= 1);
+        disable_global_cache();
+    }
+
+    #[test]
+    fn test_warmed_groups_are_reused() {
+        let mut cache = CanonicalizationCache::new();
+        cache
+            .warm(&[
+                PresetGroup::Riemann,
+                PresetGroup::Epsilon(3),
+                PresetGroup::Antisymmetric(3),
+            ])
+            .unwrap();
+        // ε_3 and Alt_3 share one symmetry declaration
+        assert_eq!(cache.group_count(), 2);
+        let riemann_group = cache.groups.values().map(|g| g.order()).max();
+        assert_eq!(riemann_group, Some(8));
+
+        let r = riemann(
+            ["d", "c", "b", "a"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(
+            cache.canonicalize(&r).unwrap(),
+            crate::canonicalize(&r).unwrap()
+        );
+        assert_eq!(cache.group_count(), 2);
+    }
+}
@@ -0,0 +1,179 @@
+//! Equality up to symmetry
+//!
+//! [`Canonical`] wraps a value together with its canonical form, computed
+//! once on construction, and compares and hashes by that form. Wrapped
+//! tensors can be put in hash sets, ordered sets and map keys where two
+//! tensors related by their slot symmetries count as the same element.
+
+use crate::canonicalization::{canonicalize, key_of_canonical, CanonicalKey};
+use crate::error::Result;
+use crate::tensor::Tensor;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// Values with a canonical form under their symmetries
+pub trait CanonicalForm: Sized {
+    /// Identifies a canonical form; equal keys mean equal up to symmetry
+    type Key: Eq + Ord + Hash;
+
+    /// Returns the canonical form of the value and its key
+    ///
+    /// # Errors
+    /// Returns any error raised while canonicalizing.
+    fn canonical_form(&self) -> Result<(Self, Self::Key)>;
+}
+
+impl CanonicalForm for Tensor {
+    type Key = CanonicalKey;
+
+    fn canonical_form(&self) -> Result<(Self, CanonicalKey)> {
+        let canonical = canonicalize(self)?;
+        let key = key_of_canonical(&canonical);
+        Ok((canonical, key))
+    }
+}
+
+/// A value that compares, orders and hashes by its canonical form
+///
+/// Dereferences to the wrapped value as it was given.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonical::Canonical;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+/// use std::collections::HashSet;
+///
+/// let s = |first: &str, second: &str| {
+///     let mut s = Tensor::new("S", vec![TensorIndex::new(first, 0), TensorIndex::new(second, 1)]);
+///     s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+///     Canonical::new(s)
+/// };
+/// let set: HashSet<_> = [s("a", "b")?, s("b", "a")?, s("a", "c")?].into_iter().collect();
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(s("b", "a")?.to_string(), "S__b _a");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Canonical<T: CanonicalForm> {
+    value: T,
+    canonical: T,
+    key: T::Key,
+}
+
+impl<T: CanonicalForm> Canonical<T> {
+    /// Wraps a value, computing its canonical form
+    ///
+    /// # Errors
+    /// Returns any error raised while canonicalizing the value.
+    pub fn new(value: T) -> Result<Self> {
+        let (canonical, key) = value.canonical_form()?;
+        Ok(Self {
+            value,
+            canonical,
+            key,
+        })
+    }
+
+    /// Returns the canonical form
+    pub fn canonical(&self) -> &T {
+        &self.canonical
+    }
+
+    /// Returns the key the wrapper compares by
+    pub fn key(&self) -> &T::Key {
+        &self.key
+    }
+
+    /// Returns the wrapped value as it was given
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: CanonicalForm> Deref for Canonical<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: CanonicalForm> PartialEq for Canonical<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T: CanonicalForm> Eq for Canonical<T> {}
+
+impl<T: CanonicalForm> Hash for Canonical<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl<T: CanonicalForm> PartialOrd for Canonical<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: CanonicalForm> Ord for Canonical<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<T: CanonicalForm + fmt::Display> fmt::Display for Canonical<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl TryFrom<Tensor> for Canonical<Tensor> {
+    type Error = crate::error::ButlerPortugalError;
+
+    fn try_from(tensor: Tensor) -> Result<Self> {
+        Self::new(tensor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::riemann;
+    use crate::TensorIndex;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn test_canonical_collapses_orbits() {
+        let r = riemann(
+            ["d", "b", "c", "a"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap();
+        let forms: BTreeSet<Canonical<Tensor>> = r
+            .orbit()
+            .map(|form| Canonical::new(form).unwrap())
+            .collect();
+        assert_eq!(forms.len(), 1);
+
+        let mut counts: HashMap<Canonical<Tensor>, usize> = HashMap::new();
+        for form in r.orbit() {
+            *counts
+                .entry(Canonical::try_from(form).unwrap())
+                .or_default() += 1;
+        }
+        assert_eq!(counts.values().copied().collect::<Vec<_>>(), [8]);
+
+        let wrapped = Canonical::new(r.clone()).unwrap();
+        assert_eq!(wrapped.rank(), 4);
+        assert_eq!(*wrapped.canonical(), crate::canonicalize(&r).unwrap());
+        assert_eq!(wrapped.into_inner(), r);
+    }
+}
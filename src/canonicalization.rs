@@ -7,14 +7,85 @@
 //! slot symmetries S and dummy symmetries D is canonicalized by finding
 //! the minimal representative in the double coset D*g*S.
 
-use crate::error::Result;
-use crate::index::TensorIndex;
-use crate::schreier_sims::schreier_sims;
+use crate::cache::{self, SymmetryKey};
+use crate::config::{CanonicalizationConfig, OrderingVersion};
+use crate::error::{validate_permutation, ButlerPortugalError, Result};
+use crate::index::{IndexClass, TensorIndex};
+use crate::invariants::dummy_name;
+use crate::permutation::SignedPermutation;
+use crate::presets::trace_self_contractions;
+use crate::schreier_sims::{
+    compose_permutations, inverse_permutation, schreier_sims_with_base,
+    signed_schreier_sims_with_base, SchreierVector, SignedBSGS,
+};
+use crate::spinors::is_spinor_class;
+use crate::stats::{CanonicalizationStats, ProgressEvent};
 use crate::symmetry::Symmetry;
 use crate::tensor::Tensor;
+use crate::young_tableaux::column_generators;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 
-/// Represents a permutation in array form
-pub type Permutation = Vec<usize>;
+pub use crate::permutation::Permutation;
+
+/// How a permutation in array form acts on a tensor
+///
+/// The two conventions in common use differ by an inverse, and mixing them up
+/// silently flips signs, so APIs accepting permutations from other systems
+/// take the convention explicitly.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::PermutationAction;
+/// use butler_portugal::{Tensor, TensorIndex};
+///
+/// let t = Tensor::new(
+///     "T",
+///     vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1), TensorIndex::new("c", 2)],
+/// );
+/// // Slot 0 receives the index of slot 1, slot 1 that of slot 2, ...
+/// let by_slots = t.permute_with(&[1, 2, 0], PermutationAction::Slots)?;
+/// assert_eq!(by_slots.to_string(), "T__b _c _a");
+/// // ... versus the index in slot 0 moving to slot 1, and so on
+/// let by_labels = t.permute_with(&[1, 2, 0], PermutationAction::Labels)?;
+/// assert_eq!(by_labels.to_string(), "T__c _a _b");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PermutationAction {
+    /// `perm[k]` is the slot whose index moves into slot `k`: `new[k] = old[perm[k]]`
+    ///
+    /// This is the convention of [`Tensor::permute`] and used throughout the crate.
+    #[default]
+    Slots,
+    /// `perm[k]` is the slot the index in slot `k` moves to: `new[perm[k]] = old[k]`
+    Labels,
+}
+
+impl PermutationAction {
+    /// Converts a permutation given in this convention into the `Slots` convention
+    ///
+    /// # Errors
+    /// Returns an `InvalidPermutation` error if `perm` is not a permutation.
+    pub fn to_slots(self, perm: &[usize]) -> Result<Permutation> {
+        validate_permutation(perm, perm.len())?;
+        Ok(match self {
+            PermutationAction::Slots => Permutation::from(perm),
+            PermutationAction::Labels => inverse_permutation(perm),
+        })
+    }
+
+    /// Converts a permutation in the `Slots` convention into this convention
+    ///
+    /// # Errors
+    /// Returns an `InvalidPermutation` error if `perm` is not a permutation.
+    pub fn from_slots(self, perm: &[usize]) -> Result<Permutation> {
+        // Both conversions are involutions
+        self.to_slots(perm)
+    }
+}
 
 /// Represents a base and strong generating set (BSGS)
 #[derive(Debug, Clone)]
@@ -80,125 +151,1397 @@ impl BSGS {
 /// let canonical = canonicalize(&tensor);
 /// ```
 pub fn canonicalize(tensor: &Tensor) -> Result<Tensor> {
-    // Handle trivial cases
-    if tensor.is_zero() {
-        let mut zero_tensor = tensor.clone();
-        zero_tensor.set_coefficient(0);
-        return Ok(zero_tensor);
+    if cache::global_cache_enabled() {
+        return cache::canonicalize_with_global_cache(tensor);
     }
+    let mut canonical = tensor.clone();
+    canonicalize_in_place(&mut canonical)?;
+    Ok(canonical)
+}
 
-    if tensor.rank() <= 1 {
-        return Ok(tensor.clone());
+/// Canonicalizes a tensor under the conventions of a configuration
+///
+/// With [`OrderingVersion::V1`] this agrees with [`canonicalize`]; canonical
+/// forms for a given version are stable across releases. Other ordering
+/// versions and [`CanonicalizationConfig::parallel_processing`] bypass the
+/// global cache.
+/// If the configuration asks for it, contractions of the tensor with itself
+/// are traced out before canonicalizing, which can lower the rank, and the
+/// remaining contracted pairs are relabelled as described for
+/// [`CanonicalizationConfig::relabel_dummies`].
+///
+/// # Errors
+/// Returns the same errors as [`canonicalize`], and a `ComputationError` if
+/// dummies are relabelled and the tensor has more than [`MAX_DUMMY_PAIRS`]
+/// contracted pairs.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::canonicalize_with_config;
+/// use butler_portugal::config::CanonicalizationConfig;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// // Exchanging the two slots flips the sign but not the arrangement, so the
+/// // tensor equals its own negative
+/// let mut t = Tensor::new("T", vec![TensorIndex::new("a", 0), TensorIndex::new("a", 1)]);
+/// t.add_symmetry(Symmetry::anti_cyclic(vec![0, 1]));
+/// let config = CanonicalizationConfig::new();
+/// assert_eq!(canonicalize_with_config(&t, &config)?.coefficient(), 0);
+/// let parallel = config.with_parallel_processing(true);
+/// assert_eq!(canonicalize_with_config(&t, &parallel)?.coefficient(), 0);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn canonicalize_with_config(
+    tensor: &Tensor,
+    config: &CanonicalizationConfig,
+) -> Result<Tensor> {
+    if config.trace_self_contractions {
+        let traced = trace_self_contractions(tensor)?;
+        let config = config.clone().with_self_contractions_traced(false);
+        return canonicalize_with_config(&traced, &config);
+    }
+    if config.relabel_dummies {
+        let config = config.clone().with_dummies_relabeled(false);
+        return canonicalize_dummies(tensor, &config);
+    }
+    match config.ordering_version {
+        OrderingVersion::V1 if !config.parallel_processing && !config.is_supervised() => {
+            canonicalize(tensor)
+        }
+        _ => {
+            config.check_timeout()?;
+            let mut canonical = tensor.clone();
+            validate_symmetries(tensor)?;
+            if settle_trivial(&mut canonical) {
+                return Ok(canonical);
+            }
+            // The stabilizer chain is descended without enumerating the
+            // group, so only enumerated groups are searched in parallel
+            let group = slot_group(tensor);
+            config.check_timeout()?;
+            report_group_built(config, &group);
+            let bsgs = match group {
+                SlotGroup::Elements(bsgs) if config.parallel_processing => bsgs,
+                group => {
+                    apply_minimal_with(&mut canonical, &group, config)?;
+                    return Ok(canonical);
+                }
+            };
+            if bsgs.is_vanishing() {
+                canonical.set_coefficient(0);
+                return Ok(canonical);
+            }
+            let valid_permutations = bsgs
+                .elements()
+                .enumerate()
+                .map(|(candidate, element)| {
+                    if candidate % CHECK_INTERVAL == 0 {
+                        config.check_timeout()?;
+                    }
+                    Ok(element)
+                })
+                .collect::<Result<Vec<SignedPermutation>>>()?;
+            let (best, _) = minimal_permutation_with(tensor, &valid_permutations, config)?;
+            place_minimal(&mut canonical, &valid_permutations, best);
+            Ok(canonical)
+        }
+    }
+}
+
+/// Canonicalizes many tensors, building each slot symmetry group only once
+///
+/// Tensors are grouped by their symmetry signature, the rank, symmetry
+/// declarations and index class of every slot, and the group of each
+/// signature is built from its first tensor and shared by the others.
+/// Results are the same as [`canonicalize_with_config`] gives for every
+/// tensor and come back in input order; the global cache is not consulted.
+/// Configurations tracing self-contractions or relabelling dummies
+/// canonicalize every tensor on its own. With [`CanonicalizationConfig::parallel_processing`] and the
+/// `parallel` feature the groups are built, and the tensors canonicalized,
+/// on the rayon thread pool.
+///
+/// # Errors
+/// Returns the error of a tensor that fails to canonicalize, see
+/// [`canonicalize_with_config`].
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::{canonicalize_batch, canonicalize_with_config};
+/// use butler_portugal::config::CanonicalizationConfig;
+/// use butler_portugal::presets::riemann;
+/// use butler_portugal::TensorIndex;
+///
+/// let names = [["d", "c", "b", "a"], ["b", "a", "d", "c"], ["c", "d", "a", "b"]];
+/// let tensors = names
+///     .iter()
+///     .map(|n| riemann(n.iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect()))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// let config = CanonicalizationConfig::new();
+/// let canonical = canonicalize_batch(&tensors, &config)?;
+/// for (tensor, form) in tensors.iter().zip(&canonical) {
+///     assert_eq!(*form, canonicalize_with_config(tensor, &config)?);
+/// }
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn canonicalize_batch(
+    tensors: &[Tensor],
+    config: &CanonicalizationConfig,
+) -> Result<Vec<Tensor>> {
+    let parallel = config.parallel_processing;
+    let shares_groups = config.ordering_version == OrderingVersion::V1
+        && !config.trace_self_contractions
+        && !config.relabel_dummies;
+    if !shares_groups {
+        return map_batch(tensors, parallel, |tensor| {
+            canonicalize_with_config(tensor, config)
+        });
     }
 
-    // Check for zero tensor due to symmetry constraints
-    for symmetry in tensor.symmetries() {
-        if symmetry.makes_tensor_zero(tensor.indices()) {
-            let mut zero_tensor = tensor.clone();
-            zero_tensor.set_coefficient(0);
-            return Ok(zero_tensor);
+    let mut signatures: HashMap<SymmetryKey, usize> = HashMap::new();
+    let mut representatives: Vec<Tensor> = Vec::new();
+    let membership: Vec<usize> = tensors
+        .iter()
+        .map(|tensor| {
+            *signatures
+                .entry(SymmetryKey::of(tensor))
+                .or_insert_with(|| {
+                    representatives.push(tensor.clone());
+                    representatives.len() - 1
+                })
+        })
+        .collect();
+    // Signatures with inconsistent symmetries get no group; their tensors
+    // fail validation below
+    let groups = map_batch(&representatives, parallel, |tensor| {
+        Ok(validate_symmetries(tensor).ok().map(|()| {
+            let group = slot_group(tensor);
+            report_group_built(config, &group);
+            group
+        }))
+    })?;
+
+    let positions: Vec<usize> = (0..tensors.len()).collect();
+    map_batch(&positions, parallel, |&position| {
+        let tensor = &tensors[position];
+        validate_symmetries(tensor)?;
+        let mut canonical = tensor.clone();
+        if !settle_trivial(&mut canonical) {
+            if let Some(group) = &groups[membership[position]] {
+                apply_minimal_with(&mut canonical, group, config)?;
+            }
         }
+        Ok(canonical)
+    })
+}
+
+/// Maps `f` over a batch in order, on the rayon thread pool with `parallel`
+/// and the `parallel` feature
+fn map_batch<T: Sync, R: Send>(
+    items: &[T],
+    parallel: bool,
+    f: impl Fn(&T) -> Result<R> + Sync + Send,
+) -> Result<Vec<R>> {
+    #[cfg(feature = "parallel")]
+    if parallel {
+        use rayon::prelude::*;
+        return items.par_iter().map(f).collect();
     }
+    #[cfg(not(feature = "parallel"))]
+    let _ = parallel;
+    items.iter().map(f).collect()
+}
 
-    // Generate all valid permutations considering symmetries
-    let valid_permutations = generate_valid_permutations(tensor);
+/// Upper bound on the contracted pairs of a tensor whose relabellings are searched
+///
+/// The dummy group of `n` pairs has up to `n! 2^n` elements.
+pub const MAX_DUMMY_PAIRS: usize = 6;
 
-    if valid_permutations.is_empty() {
-        return Ok(tensor.clone());
+/// Canonicalizes a tensor under its slot symmetries and the dummy group `D`
+///
+/// `D` renames contracted pairs among themselves and exchanges the upper and
+/// lower index of a pair, which is free for metric indices and costs a sign
+/// for spinor indices, whose `ε` is antisymmetric. Pairs take the first names
+/// of `a`, `b`, … (`A`, `B`, … for spinors) not carried by another index, so
+/// the result does not depend on the names the dummies came with. Tetrad
+/// labels name components and are never relabelled. A tensor with two
+/// minimal forms of opposite sign vanishes.
+fn canonicalize_dummies(tensor: &Tensor, config: &CanonicalizationConfig) -> Result<Tensor> {
+    let pairs: Vec<(usize, usize)> = tensor
+        .contracted_pairs()
+        .into_iter()
+        .filter(|&(upper, _)| tensor.indices()[upper].class() != IndexClass::Tetrad)
+        .collect();
+    if pairs.is_empty() {
+        return canonicalize_with_config(tensor, config);
     }
+    if pairs.len() > MAX_DUMMY_PAIRS {
+        return Err(ButlerPortugalError::ComputationError(format!(
+            "{} has {} contracted pairs, more than the {MAX_DUMMY_PAIRS} that are relabelled",
+            tensor.name(),
+            pairs.len()
+        )));
+    }
+    let class = |pair: usize| tensor.indices()[pairs[pair].0].class();
+    let names = dummy_names(tensor, &pairs);
 
-    // Find lexicographically minimal tensor form
-    let mut best_tensor = None;
-    let mut best_canonical_key = None;
+    let mut best: Option<Tensor> = None;
+    let mut vanishes = false;
+    // Pair `order[k]` takes the k-th name, which belongs to the class of pair k
+    let mut order: Vec<usize> = (0..pairs.len()).collect();
+    loop {
+        if order
+            .iter()
+            .enumerate()
+            .all(|(k, &pair)| class(pair) == class(k))
+        {
+            for flips in 0..1usize << pairs.len() {
+                let mut relabeled = tensor.clone();
+                let mut sign = 1;
+                let indices = relabeled.indices_mut();
+                for (k, &pair) in order.iter().enumerate() {
+                    let (upper, lower) = pairs[pair];
+                    indices[upper] = indices[upper].with_name(&names[k]);
+                    indices[lower] = indices[lower].with_name(&names[k]);
+                    if flips & (1 << k) != 0 {
+                        indices[upper].set_contravariant(false);
+                        indices[lower].set_contravariant(true);
+                        if is_spinor_class(class(pair)) {
+                            sign = -sign;
+                        }
+                    }
+                }
+                relabeled.set_coefficient(tensor.coefficient() * sign);
+                let canonical = canonicalize_with_config(&relabeled, config)?;
+                if canonical.coefficient() == 0 {
+                    return Ok(canonical);
+                }
+                let order = best.as_ref().map(|best_form| {
+                    compare_arrangements_with(
+                        config.ordering_version,
+                        canonical.indices(),
+                        best_form.indices(),
+                    )
+                });
+                match (order, &best) {
+                    (Some(Ordering::Greater), _) => {}
+                    (Some(Ordering::Equal), Some(best_form)) => {
+                        vanishes |= canonical.coefficient() != best_form.coefficient();
+                    }
+                    _ => {
+                        best = Some(canonical);
+                        vanishes = false;
+                    }
+                }
+            }
+        }
+        if !next_permutation(&mut order) {
+            break;
+        }
+    }
+
+    let mut canonical = best.unwrap_or_else(|| tensor.clone());
+    if vanishes {
+        canonical.set_coefficient(0);
+    }
+    Ok(canonical)
+}
+
+/// Picks the canonical names of contracted pairs: the first names not
+/// carried by another index, lowercase for metric and uppercase for spinor
+/// pairs
+fn dummy_names(tensor: &Tensor, pairs: &[(usize, usize)]) -> Vec<String> {
+    let used: HashSet<&str> = tensor
+        .indices()
+        .iter()
+        .enumerate()
+        .filter(|(slot, _)| !pairs.iter().any(|&(u, l)| u == *slot || l == *slot))
+        .map(|(_, index)| index.name())
+        .collect();
+    let mut metric = (0..)
+        .map(dummy_name)
+        .filter(|name| !used.contains(name.as_str()));
+    let mut spinor = (0..)
+        .map(|n| dummy_name(n).to_uppercase())
+        .filter(|name| !used.contains(name.as_str()));
+    pairs
+        .iter()
+        .map(|&(upper, _)| {
+            let names = if is_spinor_class(tensor.indices()[upper].class()) {
+                spinor.next()
+            } else {
+                metric.next()
+            };
+            names.unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Canonicalizes a tensor in place, reusing its index buffer
+///
+/// Produces the same result as [`canonicalize`] but rearranges the existing
+/// indices instead of building a new tensor for every candidate, which is
+/// useful in hot loops that do not need the original afterwards.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::canonicalize_in_place;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let mut f = Tensor::new("F", vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)]);
+/// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+/// canonicalize_in_place(&mut f)?;
+/// assert_eq!(f.to_string(), "-F__a _b");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn canonicalize_in_place(tensor: &mut Tensor) -> Result<()> {
+    validate_symmetries(tensor)?;
+    if settle_trivial(tensor) {
+        return Ok(());
+    }
+
+    apply_minimal(tensor, &slot_group(tensor));
+    Ok(())
+}
+
+/// The slot symmetry group of a tensor, in the form searched for the
+/// minimal arrangement
+#[derive(Debug, Clone)]
+pub(crate) enum SlotGroup {
+    /// Transversals of the signed stabilizer chain along the slots in order:
+    /// level `i` holds the orbit of slot `i` under the elements fixing slots
+    /// `0..i`, with representatives encoded as in [`SignedBSGS`]
+    Chain(Vec<SchreierVector>),
+    /// The signed BSGS of a group moving indices between slots of different
+    /// classes or containing the identity with sign `-1`, whose elements are
+    /// streamed through
+    Elements(SignedBSGS),
+}
+
+impl SlotGroup {
+    /// Returns the order of the group, saturating at `usize::MAX`
+    pub(crate) fn order(&self) -> usize {
+        match self {
+            SlotGroup::Chain(levels) => levels
+                .iter()
+                .map(|level| level.orbit().len())
+                .fold(1, usize::saturating_mul),
+            SlotGroup::Elements(bsgs) => usize::try_from(bsgs.order()).unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Returns the number of levels of the stabilizer chain
+    pub(crate) fn base_length(&self) -> usize {
+        match self {
+            SlotGroup::Chain(levels) => levels.len(),
+            SlotGroup::Elements(bsgs) => bsgs.bsgs().base.len(),
+        }
+    }
+}
+
+/// Reports a slot symmetry group to the progress callback of `config`
+fn report_group_built(config: &CanonicalizationConfig, group: &SlotGroup) {
+    config.report(|| ProgressEvent::GroupBuilt {
+        order: group.order(),
+        base_length: group.base_length(),
+        enumerated: matches!(group, SlotGroup::Elements(_)),
+    });
+}
+
+/// Builds the slot symmetry group of a tensor for [`apply_minimal`]
+///
+/// The stabilizer chain is used whenever every generator keeps each index in
+/// a slot of its class, so that the whole group does; the elements of other
+/// groups are streamed from their BSGS without being collected.
+pub(crate) fn slot_group(tensor: &Tensor) -> SlotGroup {
+    match slot_chain(tensor) {
+        Some(levels) => SlotGroup::Chain(levels),
+        None => SlotGroup::Elements(tensor.signed_symmetry_group()),
+    }
+}
+
+/// Returns the signed stabilizer chain of the slot group along the slots in
+/// order, or `None` if a generator moves an index into a slot of another
+/// class or the group holds the identity with sign `-1`, which no level
+/// along the slots accounts for
+pub(crate) fn slot_chain(tensor: &Tensor) -> Option<Vec<SchreierVector>> {
+    let n = tensor.rank();
+    let generators = tensor_signed_generators(tensor);
+    if !generators
+        .iter()
+        .all(|generator| preserves_index_classes(tensor, &generator.permutation))
+    {
+        return None;
+    }
+    let slots: Vec<usize> = (0..n).collect();
+    let signed = signed_schreier_sims_with_base(&generators, n, &slots);
+    if signed.is_vanishing() {
+        return None;
+    }
+    let bsgs = signed.bsgs();
+    let levels = slots
+        .iter()
+        .map(|&slot| {
+            let fixing: Vec<Permutation> = bsgs
+                .generators
+                .iter()
+                .filter(|g| g[..slot].iter().enumerate().all(|(k, &image)| k == image))
+                .cloned()
+                .collect();
+            SchreierVector::new(slot, &fixing, n + 2)
+        })
+        .collect();
+    Some(levels)
+}
+
+/// Rearranges the tensor into its lexicographically minimal form under the
+/// slot group, or sets its coefficient to zero if it vanishes
+///
+/// Returns the number of candidate forms that were compared.
+pub(crate) fn apply_minimal(tensor: &mut Tensor, group: &SlotGroup) -> usize {
+    // Without a deadline or cancellation token the search cannot time out
+    apply_minimal_with(tensor, group, &CanonicalizationConfig::new()).unwrap_or_default()
+}
+
+/// Rearranges the tensor as [`apply_minimal`] does, giving up with a
+/// `Timeout` error once the deadline or cancellation of `config` is reached
+pub(crate) fn apply_minimal_with(
+    tensor: &mut Tensor,
+    group: &SlotGroup,
+    config: &CanonicalizationConfig,
+) -> Result<usize> {
+    apply_minimal_in(tensor, group, config, &mut ChainScratch::default())
+}
+
+/// Rearranges the tensor as [`apply_minimal_with`] does, descending a
+/// stabilizer chain in the buffers of `scratch`
+pub(crate) fn apply_minimal_in(
+    tensor: &mut Tensor,
+    group: &SlotGroup,
+    config: &CanonicalizationConfig,
+    scratch: &mut ChainScratch,
+) -> Result<usize> {
+    match group {
+        // The identity with sign -1 makes the tensor its own negative
+        SlotGroup::Elements(bsgs) if bsgs.is_vanishing() => {
+            tensor.set_coefficient(0);
+            Ok(0)
+        }
+        SlotGroup::Elements(bsgs) => {
+            let (best, compared) = minimal_element(tensor, bsgs.elements(), group.order(), config)?;
+            // The winner is found again rather than keeping every element
+            let best = best.and_then(|(candidate, coefficient)| {
+                Some((bsgs.elements().nth(candidate)?.permutation, coefficient))
+            });
+            place_element(tensor, best);
+            Ok(compared)
+        }
+        SlotGroup::Chain(levels) => {
+            let (best, compared) = descend_chain(tensor, levels, config, scratch)?;
+            place_element(tensor, best);
+            Ok(compared)
+        }
+    }
+}
 
-    for perm in valid_permutations {
-        let candidate = tensor.permute(&perm)?;
+/// Buffers of the chain descent, kept between searches by a
+/// [`Workspace`](crate::workspace::Workspace)
+#[derive(Debug, Default)]
+pub(crate) struct ChainScratch {
+    /// Elements reaching the minimal arrangement of the slots filled so far,
+    /// with their signs
+    frontier: Vec<(Permutation, i32)>,
+    /// The frontier being built for the next level
+    next: Vec<(Permutation, i32)>,
+    /// Frontier entries and orbit points bringing the smallest index to the
+    /// current slot
+    choices: Vec<(usize, usize)>,
+    /// For each index, the first index comparing equal to it
+    aliases: Vec<usize>,
+    /// Arrangements, as aliases slot by slot, and signs already in the next
+    /// frontier
+    reached: HashSet<(Vec<usize>, i32)>,
+}
 
-        if candidate.is_zero() {
-            continue;
+/// Applies the minimal element with its coefficient, or sets the coefficient
+/// to zero if the tensor vanishes
+fn place_element(tensor: &mut Tensor, best: Option<(Permutation, i32)>) {
+    match best {
+        Some((perm, coefficient)) => {
+            apply_permutation(tensor.indices_mut(), &perm);
+            tensor.set_coefficient(coefficient);
         }
+        None => tensor.set_coefficient(0),
+    }
+}
+
+/// Finds the minimal form by descending the stabilizer chain of the slots
+///
+/// This is the right-coset half of the Butler-Portugal algorithm. Level `i`
+/// fills slot `i` with the smallest index any remaining group element can
+/// bring there, and keeps only the elements doing so; these form cosets of
+/// the next stabilizer, each represented by one element. Repeated index names
+/// can make several cosets tie, and all are followed, but two reaching the
+/// same arrangement with the same sign lead to the same forms, so only one of
+/// them is kept. The group is never enumerated, and the sign of every
+/// element is read off the two points encoding it.
+///
+/// Returns the element with the coefficient it gives, or `None` if the
+/// tensor vanishes, together with the number of candidates compared. The
+/// minimal arrangement reached with both signs equals its own negative, so
+/// the tensor vanishes then too.
+fn descend_chain(
+    tensor: &Tensor,
+    levels: &[SchreierVector],
+    config: &CanonicalizationConfig,
+    scratch: &mut ChainScratch,
+) -> Result<(Option<(Permutation, i32)>, usize)> {
+    let indices = tensor.indices();
+    let n = tensor.rank();
+    let ChainScratch {
+        frontier,
+        next,
+        choices,
+        aliases,
+        reached,
+    } = scratch;
+    aliases.clear();
+    aliases.extend((0..n).map(|i| {
+        let key = SlotKey::of(&indices[i]);
+        (0..i)
+            .find(|&j| SlotKey::of(&indices[j]) == key)
+            .unwrap_or(i)
+    }));
+    let ordering = config.ordering_version;
+    frontier.clear();
+    frontier.push((Permutation::identity(n + 2), 1));
+    let mut compared = 0;
 
-        let canonical_key = tensor_canonical_key(&candidate);
+    for (depth, level) in levels.iter().enumerate() {
+        config.check_timeout()?;
+        let mut minimum: Option<SlotKey> = None;
+        choices.clear();
+        for (state, (perm, _)) in frontier.iter().enumerate() {
+            for &point in level.orbit() {
+                compared += 1;
+                let key = SlotKey::ordered(&indices[perm[point]], ordering);
+                match &minimum {
+                    Some(best) if key > *best => continue,
+                    Some(best) if key == *best => {}
+                    _ => {
+                        minimum = Some(key);
+                        choices.clear();
+                    }
+                }
+                choices.push((state, point));
+            }
+        }
 
-        if let Some(ref best_key) = best_canonical_key {
-            if canonical_key < *best_key {
-                best_canonical_key = Some(canonical_key);
-                best_tensor = Some(candidate);
+        next.clear();
+        reached.clear();
+        for &(state, point) in choices.iter() {
+            let Some(coset) = level.representative(point) else {
+                continue;
+            };
+            // Follow the coset representative first, then the state
+            let perm = compose_permutations(&coset, &frontier[state].0);
+            let sign = SignedPermutation::from_extended(&perm).sign;
+            let arrangement: Vec<usize> = perm[..n].iter().map(|&i| aliases[i]).collect();
+            if reached.insert((arrangement, sign)) {
+                next.push((perm, sign));
             }
-        } else {
-            best_canonical_key = Some(canonical_key);
-            best_tensor = Some(candidate);
         }
+        std::mem::swap(frontier, next);
+        config.report(|| ProgressEvent::LevelDescended {
+            level: depth,
+            levels: levels.len(),
+            frontier: frontier.len(),
+            explored: compared,
+            best: frontier.first().map_or_else(Vec::new, |(perm, _)| {
+                perm[..=depth].iter().map(|&i| indices[i].clone()).collect()
+            }),
+        });
     }
 
-    if let Some(tensor) = best_tensor {
-        Ok(tensor)
-    } else {
+    if frontier.iter().any(|(_, sign)| *sign < 0) && frontier.iter().any(|(_, sign)| *sign > 0) {
+        return Ok((None, compared));
+    }
+    Ok(match frontier.first() {
+        Some((perm, sign)) if !vanishes_after(tensor, perm) => (
+            Some((Permutation::from(&perm[..n]), tensor.coefficient() * sign)),
+            compared,
+        ),
+        _ => (None, compared),
+    })
+}
+
+/// Checks that every symmetry of the tensor is consistent, see [`Symmetry::validate`]
+pub(crate) fn validate_symmetries(tensor: &Tensor) -> Result<()> {
+    tensor
+        .symmetries()
+        .iter()
+        .try_for_each(|symmetry| symmetry.validate(tensor.rank()))
+}
+
+/// Handles zero tensors and tensors of rank at most one, returning true if
+/// the tensor is already canonical
+pub(crate) fn settle_trivial(tensor: &mut Tensor) -> bool {
+    if tensor.is_zero() {
+        tensor.set_coefficient(0);
+        return true;
+    }
+    tensor.rank() <= 1
+}
+
+/// Applies the element chosen by [`minimal_permutation`], or sets the
+/// coefficient to zero if every candidate vanished
+fn place_minimal(
+    tensor: &mut Tensor,
+    valid_permutations: &[SignedPermutation],
+    best: Option<(usize, i32)>,
+) {
+    if let Some((candidate, coefficient)) = best {
+        apply_permutation(
+            tensor.indices_mut(),
+            &valid_permutations[candidate].permutation,
+        );
+        tensor.set_coefficient(coefficient);
+    } else if !valid_permutations.is_empty() {
         // All permutations resulted in zero
-        let mut zero_tensor = tensor.clone();
-        zero_tensor.set_coefficient(0);
-        Ok(zero_tensor)
+        tensor.set_coefficient(0);
     }
 }
 
-/// Generates all valid permutations respecting symmetries using Schreier-Sims BSGS
-fn generate_valid_permutations(tensor: &Tensor) -> Vec<Permutation> {
-    let n = tensor.rank();
-    let generators = tensor_symmetry_generators(tensor);
-    let bsgs = schreier_sims(&generators, n);
-    enumerate_group(&bsgs, n)
+/// Finds the group element giving the lexicographically minimal form
+///
+/// Returns its position in `valid_permutations` with the resulting
+/// coefficient, or `None` if every candidate vanishes or the minimal
+/// arrangement is reached with both signs, together with the number of
+/// candidate forms that were compared.
+pub(crate) fn minimal_permutation(
+    tensor: &Tensor,
+    valid_permutations: &[SignedPermutation],
+) -> (Option<(usize, i32)>, usize) {
+    // Without a deadline the search cannot fail
+    minimal_permutation_with(tensor, valid_permutations, &CanonicalizationConfig::new())
+        .unwrap_or((None, 0))
+}
+
+/// Finds the element of a stream of group elements giving the
+/// lexicographically minimal form, as [`minimal_permutation`] does for a list
+fn minimal_element<P: Borrow<SignedPermutation>>(
+    tensor: &Tensor,
+    elements: impl IntoIterator<Item = P>,
+    total: usize,
+    config: &CanonicalizationConfig,
+) -> Result<(Option<(usize, i32)>, usize)> {
+    let (best, compared) = fold_candidates(tensor, elements, total, config)?;
+    Ok((best.and_then(|(_, ties)| settle_ties(&ties)), compared))
+}
+
+/// Returns the first candidate reaching the minimal arrangement, or `None`
+/// if candidates reach it with both signs, since the tensor then equals its
+/// own negative
+fn settle_ties(ties: &[Candidate]) -> Option<Candidate> {
+    let first = ties[0];
+    ties.iter()
+        .all(|tie| tie.1.signum() == first.1.signum())
+        .then_some(first)
+}
+
+/// A candidate form: its position in the list of group elements and its
+/// coefficient
+type Candidate = (usize, i32);
+
+/// The first element reaching the minimal arrangement seen so far, with
+/// every candidate reaching it in the order of the group elements
+type Ties = Option<(Permutation, Vec<Candidate>)>;
+
+/// Finds the group element giving the lexicographically minimal form, as
+/// [`minimal_permutation`] does, under the deadline of `config`
+///
+/// With [`CanonicalizationConfig::parallel_processing`] and the `parallel`
+/// feature the candidates are evaluated on the rayon thread pool. The
+/// reduction keeps the candidates in the order of the group elements, so the
+/// result is the same as a sequential search.
+pub(crate) fn minimal_permutation_with(
+    tensor: &Tensor,
+    valid_permutations: &[SignedPermutation],
+    config: &CanonicalizationConfig,
+) -> Result<(Option<(usize, i32)>, usize)> {
+    let (best, compared) = minimal_candidates(tensor, valid_permutations, config)?;
+    Ok((best.and_then(|(_, ties)| settle_ties(&ties)), compared))
+}
+
+/// Evaluates every group element, keeping the minimal arrangement and the
+/// candidates reaching it, and counts the candidates compared
+fn minimal_candidates(
+    tensor: &Tensor,
+    valid_permutations: &[SignedPermutation],
+    config: &CanonicalizationConfig,
+) -> Result<(Ties, usize)> {
+    #[cfg(feature = "parallel")]
+    if config.parallel_processing {
+        use rayon::prelude::*;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        // Once the deadline passes the remaining candidates are skipped
+        let expired = AtomicBool::new(false);
+        let found = valid_permutations
+            .par_iter()
+            .enumerate()
+            .filter_map(|(candidate, perm)| {
+                if candidate % CHECK_INTERVAL == 0 && config.check_timeout().is_err() {
+                    expired.store(true, Ordering::Relaxed);
+                }
+                if expired.load(Ordering::Relaxed) {
+                    return None;
+                }
+                evaluate_candidate(tensor, candidate, perm)
+            })
+            .fold(
+                || (None, 0),
+                |found, (perm, candidate)| {
+                    add_candidate(tensor, config.ordering_version, found, perm, candidate)
+                },
+            )
+            .reduce(
+                || (None, 0),
+                |(left, m), (right, n)| {
+                    let ties = merge_ties(tensor, config.ordering_version, left, right);
+                    (ties, m + n)
+                },
+            );
+        if expired.load(Ordering::Relaxed) {
+            return Err(ButlerPortugalError::Timeout);
+        }
+        // Candidates finish out of order, so progress is only reported at the end
+        let total = valid_permutations.len();
+        config.report(|| explored(tensor, total, total, &found.0));
+        return Ok(found);
+    }
+    fold_candidates(tensor, valid_permutations, valid_permutations.len(), config)
+}
+
+/// Number of candidates evaluated between two checks of the deadline and
+/// reports of progress
+const CHECK_INTERVAL: usize = 1024;
+
+/// Evaluates group elements one at a time, as [`minimal_candidates`] does
+/// for a list of them
+fn fold_candidates<P: Borrow<SignedPermutation>>(
+    tensor: &Tensor,
+    elements: impl IntoIterator<Item = P>,
+    total: usize,
+    config: &CanonicalizationConfig,
+) -> Result<(Ties, usize)> {
+    let found = elements.into_iter().enumerate().try_fold(
+        (None, 0),
+        |found: (Ties, usize), (candidate, element)| -> Result<(Ties, usize)> {
+            if candidate % CHECK_INTERVAL == 0 {
+                config.check_timeout()?;
+                if candidate > 0 {
+                    config.report(|| explored(tensor, candidate, total, &found.0));
+                }
+            }
+            Ok(
+                match evaluate_candidate(tensor, candidate, element.borrow()) {
+                    Some((perm, candidate)) => {
+                        add_candidate(tensor, config.ordering_version, found, perm, candidate)
+                    }
+                    None => found,
+                },
+            )
+        },
+    )?;
+    config.report(|| explored(tensor, total, total, &found.0));
+    Ok(found)
+}
+
+/// The progress of an enumeration having evaluated `explored` candidates
+fn explored(tensor: &Tensor, explored: usize, total: usize, best: &Ties) -> ProgressEvent {
+    let indices = tensor.indices();
+    ProgressEvent::CandidatesExplored {
+        explored,
+        total,
+        best: best
+            .as_ref()
+            .map(|(perm, _)| perm.iter().map(|&i| indices[i].clone()).collect()),
+    }
+}
+
+/// Adds an evaluated candidate to the ties and the count of candidates
+fn add_candidate(
+    tensor: &Tensor,
+    ordering: OrderingVersion,
+    (mut best, compared): (Ties, usize),
+    perm: &[usize],
+    candidate: Candidate,
+) -> (Ties, usize) {
+    let indices = tensor.indices();
+    let order = best.as_ref().map(|(best_perm, _)| {
+        compare_arrangements_with(
+            ordering,
+            perm.iter().map(|&i| &indices[i]),
+            best_perm.iter().map(|&i| &indices[i]),
+        )
+    });
+    match (order, &mut best) {
+        (Some(Ordering::Equal), Some((_, ties))) => ties.push(candidate),
+        (Some(Ordering::Greater), _) => {}
+        _ => best = Some((Permutation::from(perm), vec![candidate])),
+    }
+    (best, compared + 1)
+}
+
+/// Returns a group element with its candidate, or `None` if it moves an
+/// index into a slot of another class or the form vanishes
+fn evaluate_candidate<'a>(
+    tensor: &Tensor,
+    candidate: usize,
+    element: &'a SignedPermutation,
+) -> Option<(&'a [usize], Candidate)> {
+    let perm = element.permutation.images();
+    // Never move an index into a slot of a different class
+    if !preserves_index_classes(tensor, perm) {
+        return None;
+    }
+    let coefficient = tensor.coefficient() * element.sign;
+    if coefficient == 0 || vanishes_after(tensor, perm) {
+        return None;
+    }
+    Some((perm, (candidate, coefficient)))
+}
+
+/// Combines the ties of two runs of candidates, the first run preceding the
+/// second
+#[cfg(feature = "parallel")]
+fn merge_ties(tensor: &Tensor, ordering: OrderingVersion, first: Ties, second: Ties) -> Ties {
+    match (first, second) {
+        (None, ties) | (ties, None) => ties,
+        (Some((perm, mut ties)), Some((other, more))) => {
+            let indices = tensor.indices();
+            match compare_arrangements_with(
+                ordering,
+                perm.iter().map(|&i| &indices[i]),
+                other.iter().map(|&i| &indices[i]),
+            ) {
+                Ordering::Less => Some((perm, ties)),
+                Ordering::Greater => Some((other, more)),
+                Ordering::Equal => {
+                    ties.extend(more);
+                    Some((perm, ties))
+                }
+            }
+        }
+    }
+}
+
+/// Canonicalizes a tensor like [`canonicalize`] and reports where the time went
+///
+/// The global cache is bypassed so that every phase actually runs.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::canonicalize_with_stats;
+/// use butler_portugal::presets::riemann;
+/// use butler_portugal::{canonicalize, TensorIndex};
+///
+/// let r = riemann((0..4).map(|i| TensorIndex::new(&i.to_string(), 3 - i)).collect())?;
+/// let (canonical, stats) = canonicalize_with_stats(&r)?;
+/// assert_eq!(canonical, canonicalize(&r)?);
+/// assert_eq!(stats.group_order, 8);
+/// println!("{stats}");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn canonicalize_with_stats(tensor: &Tensor) -> Result<(Tensor, CanonicalizationStats)> {
+    let mut stats = CanonicalizationStats::default();
+    let mut canonical = tensor.clone();
+
+    let start = Instant::now();
+    validate_symmetries(tensor)?;
+    let settled = settle_trivial(&mut canonical);
+    stats.validation = start.elapsed();
+    if settled {
+        return Ok((canonical, stats));
+    }
+
+    let start = Instant::now();
+    let group = slot_group(tensor);
+    match group {
+        SlotGroup::Chain(_) => stats.bsgs = start.elapsed(),
+        SlotGroup::Elements(_) => stats.enumeration = start.elapsed(),
+    }
+    stats.group_order = group.order();
+
+    let start = Instant::now();
+    stats.candidates = apply_minimal(&mut canonical, &group);
+    stats.evaluation = start.elapsed();
+    Ok((canonical, stats))
+}
+
+/// Checks whether the tensor vanishes by antisymmetry once `perm` is applied
+pub(crate) fn vanishes_after(tensor: &Tensor, perm: &[usize]) -> bool {
+    let name = |slot: &usize| {
+        perm.get(*slot)
+            .map(|&source| tensor.indices()[source].name())
+    };
+    tensor.symmetries().iter().any(|symmetry| match symmetry {
+        Symmetry::Antisymmetric { indices } => indices.iter().enumerate().any(|(i, slot)| {
+            name(slot).is_some_and(|current| {
+                indices[..i]
+                    .iter()
+                    .any(|earlier| name(earlier) == Some(current))
+            })
+        }),
+        _ => false,
+    })
+}
+
+/// Rearranges `items` so that `items[k]` becomes the old `items[perm[k]]`,
+/// following the cycles of `perm` with swaps
+fn apply_permutation<T>(items: &mut [T], perm: &[usize]) {
+    let mut done = vec![false; perm.len()];
+    for start in 0..perm.len() {
+        let mut current = start;
+        while !done[current] {
+            done[current] = true;
+            let next = perm[current];
+            if next == start {
+                break;
+            }
+            items.swap(current, next);
+            current = next;
+        }
+    }
+}
+
+/// Finds the canonical representative of the double coset `D·g·S`
+///
+/// This is the permutation-level core of the Butler-Portugal algorithm, for
+/// callers that manage the symmetry groups themselves. A configuration is a
+/// permutation `g` with `g[slot] = label`, i.e. it says which label (index
+/// name, numbered in canonical order) sits in each slot.
+///
+/// * The slot group `S` acts on the right and rearranges slots:
+///   `(g·s)[i] = g[s[i]]`, matching [`Tensor::permute`].
+/// * The label group `D` acts on the left and renames labels:
+///   `(d·g)[i] = d[g[i]]`, as for relabelling dummy indices.
+///
+/// The result is the lexicographically minimal element of `D·g·S`.
+///
+/// # Arguments
+/// * `g` - The configuration to canonicalize
+/// * `slot_bsgs` - BSGS of the slot symmetry group `S`
+/// * `label_bsgs` - BSGS of the label symmetry group `D`
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::BSGS;
+/// use butler_portugal::canonicalize_double_coset;
+/// use butler_portugal::schreier_sims::schreier_sims;
+///
+/// // Symmetric slots 0 and 1, no label symmetry
+/// let slots = schreier_sims(&[vec![1, 0, 2].into()], 3);
+/// let labels = BSGS::identity(3);
+/// let canonical = canonicalize_double_coset(&[2, 0, 1], &slots, &labels).unwrap();
+/// assert_eq!(canonical, vec![0, 2, 1]);
+/// ```
+pub fn canonicalize_double_coset(
+    g: &[usize],
+    slot_bsgs: &BSGS,
+    label_bsgs: &BSGS,
+) -> Result<Permutation> {
+    let degree = g.len();
+    validate_permutation(g, degree)?;
+    check_group_degrees(slot_bsgs, label_bsgs, degree)?;
+
+    let slots: Vec<usize> = (0..degree).collect();
+    let slot_chain = schreier_sims_with_base(&slot_bsgs.generators, degree, &slots);
+    let label_generators: Vec<Permutation> = label_bsgs
+        .generators
+        .iter()
+        .filter(|d| !is_identity(d))
+        .cloned()
+        .collect();
+    // Built once and re-pointed to the labels placed by every configuration
+    let label_group = schreier_sims_with_base(&label_generators, degree, &[]);
+
+    // Configurations d·g·s reached so far; they agree on the slots filled
+    let mut frontier: Vec<Permutation> = vec![Permutation::from(g)];
+    for slot in 0..degree {
+        let fixing: Vec<Permutation> = slot_chain
+            .generators
+            .iter()
+            .filter(|s| s[..slot].iter().enumerate().all(|(k, &image)| k == image))
+            .cloned()
+            .collect();
+        let slot_orbit = SchreierVector::new(slot, &fixing, degree);
+
+        // For every configuration and slot coset, the labels reachable through
+        // the label elements fixing the labels already placed
+        let mut minimum = usize::MAX;
+        let mut choices: Vec<(Permutation, SchreierVector, usize)> = Vec::new();
+        for config in &frontier {
+            let placed = &config[..slot];
+            let label_stabilizer = if label_generators.is_empty() {
+                Vec::new()
+            } else {
+                label_group
+                    .rebase(placed)
+                    .generators
+                    .into_iter()
+                    .filter(|d| placed.iter().all(|&label| d[label] == label))
+                    .collect()
+            };
+            for &point in slot_orbit.orbit() {
+                let label = config[point];
+                let labels = SchreierVector::new(label, &label_stabilizer, degree);
+                let Some(&smallest) = labels.orbit().iter().min() else {
+                    continue;
+                };
+                if smallest < minimum {
+                    minimum = smallest;
+                    choices.clear();
+                }
+                if smallest == minimum {
+                    if let Some(coset) = slot_orbit.representative(point) {
+                        // c·u moves the label in `point` into `slot`
+                        let moved = compose_permutations(&coset, config);
+                        choices.push((moved, labels, smallest));
+                    }
+                }
+            }
+        }
+
+        let mut next: Vec<Permutation> = Vec::with_capacity(choices.len());
+        for (moved, labels, smallest) in choices {
+            let Some(relabel) = labels.representative(smallest) else {
+                continue;
+            };
+            let config = compose_permutations(&moved, &relabel);
+            if !next.contains(&config) {
+                next.push(config);
+            }
+        }
+        frontier = next;
+    }
+    Ok(frontier
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| Permutation::from(g)))
+}
+
+/// Finds the canonical representative of the double coset `D·g·S` with `g`
+/// and the result given in the chosen convention
+///
+/// With [`PermutationAction::Slots`] a configuration says which label sits in
+/// each slot, exactly as for [`canonicalize_double_coset`]; with
+/// [`PermutationAction::Labels`] it says which slot each label sits in.
+pub fn canonicalize_double_coset_with(
+    g: &[usize],
+    slot_bsgs: &BSGS,
+    label_bsgs: &BSGS,
+    action: PermutationAction,
+) -> Result<Permutation> {
+    let canonical = canonicalize_double_coset(&action.to_slots(g)?, slot_bsgs, label_bsgs)?;
+    action.from_slots(&canonical)
 }
 
-/// Enumerate all group elements from a BSGS by recursively applying all strong generators to the identity permutation, using a HashSet to avoid duplicates. This efficiently generates the full permutation group defined by the base and strong generating set, and is much faster than brute-force BFS for most practical tensor symmetry groups.
-fn enumerate_group(bsgs: &BSGS, degree: usize) -> Vec<Permutation> {
+/// Enumerates one representative of every double coset `D·g·S` in the symmetric group
+///
+/// Each representative is the lexicographically minimal element of its double
+/// coset, i.e. the result of [`canonicalize_double_coset`] on any of its
+/// members, so the list describes every inequivalent index configuration.
+/// Representatives are returned in increasing order. Conventions for the slot
+/// group `S` and the label group `D` are those of [`canonicalize_double_coset`].
+///
+/// # Errors
+/// Returns a `ComputationError` if `degree` exceeds
+/// [`MAX_ENUMERATION_DEGREE`], since all `degree!` configurations are visited.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::{double_coset_representatives, BSGS};
+/// use butler_portugal::schreier_sims::schreier_sims;
+///
+/// // Riemann slot symmetries with four distinct free labels: three configurations
+/// // (R_abcd, R_acbd, R_adbc) before the Bianchi identity
+/// let slots = schreier_sims(&[vec![1, 0, 2, 3].into(), vec![0, 1, 3, 2].into(), vec![2, 3, 0, 1].into()], 4);
+/// let labels = BSGS::identity(4);
+/// let representatives = double_coset_representatives(&slots, &labels, 4)?;
+/// assert_eq!(representatives, [vec![0, 1, 2, 3], vec![0, 2, 1, 3], vec![0, 3, 1, 2]]);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn double_coset_representatives(
+    slot_bsgs: &BSGS,
+    label_bsgs: &BSGS,
+    degree: usize,
+) -> Result<Vec<Permutation>> {
+    if degree > MAX_ENUMERATION_DEGREE {
+        return Err(ButlerPortugalError::ComputationError(format!(
+            "Enumerating configurations of degree {degree} exceeds the limit of {MAX_ENUMERATION_DEGREE}"
+        )));
+    }
+    check_group_degrees(slot_bsgs, label_bsgs, degree)?;
+
+    let slot_elements = enumerate_group(slot_bsgs, degree);
+    let label_elements = enumerate_group(label_bsgs, degree);
+
+    let mut representatives = Vec::new();
+    let mut g: Permutation = (0..degree).collect();
+    loop {
+        // g represents its double coset iff no element of D·g·S is smaller
+        let is_minimal = slot_elements.iter().all(|s| {
+            label_elements
+                .iter()
+                .all(|d| s.iter().map(|&i| d[g[i]]).cmp(g.iter().copied()).is_ge())
+        });
+        if is_minimal {
+            representatives.push(g.clone());
+        }
+        if !next_permutation(&mut g) {
+            return Ok(representatives);
+        }
+    }
+}
+
+/// Enumerates one representative of every right coset `g·S` of the slot group
+///
+/// This is [`double_coset_representatives`] with a trivial label group: with
+/// all labels distinct, it lists every arrangement of the labels that the
+/// slot symmetries cannot transform into one another.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::coset_representatives;
+/// use butler_portugal::schreier_sims::schreier_sims;
+///
+/// // Antisymmetric slots 0 and 1 halve the 3! arrangements
+/// let slots = schreier_sims(&[vec![1, 0, 2].into()], 3);
+/// assert_eq!(coset_representatives(&slots, 3)?.len(), 3);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn coset_representatives(slot_bsgs: &BSGS, degree: usize) -> Result<Vec<Permutation>> {
+    double_coset_representatives(slot_bsgs, &BSGS::identity(degree), degree)
+}
+
+/// Upper bound on the degree for which every configuration is enumerated
+pub const MAX_ENUMERATION_DEGREE: usize = 10;
+
+/// Checks that the generators of both groups act on `degree` points
+fn check_group_degrees(slot_bsgs: &BSGS, label_bsgs: &BSGS, degree: usize) -> Result<()> {
+    for (group, bsgs) in [("slot", slot_bsgs), ("label", label_bsgs)] {
+        if let Some(gen) = bsgs.generators.iter().find(|gen| gen.len() != degree) {
+            return Err(ButlerPortugalError::InvalidPermutation(format!(
+                "{group} group generator has degree {}, expected {degree}",
+                gen.len()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Advances to the next permutation in lexicographic order, returning false
+/// after the last one
+pub(crate) fn next_permutation(perm: &mut [usize]) -> bool {
+    let Some(pivot) = (1..perm.len()).rev().find(|&i| perm[i - 1] < perm[i]) else {
+        return false;
+    };
+    let pivot = pivot - 1;
+    let Some(successor) = (pivot + 1..perm.len())
+        .rev()
+        .find(|&j| perm[j] > perm[pivot])
+    else {
+        return false;
+    };
+    perm.swap(pivot, successor);
+    perm[pivot + 1..].reverse();
+    true
+}
+
+/// Enumerates every group element of a BSGS, collecting [`BSGS::elements`]
+pub(crate) fn enumerate_group(bsgs: &BSGS, degree: usize) -> Vec<Permutation> {
     // If there is no base, just return the identity
     if bsgs.base.is_empty() {
         return vec![(0..degree).collect()];
     }
+    bsgs.elements().collect()
+}
 
-    // Recursive helper to build up group elements
-    fn enumerate_recursive(
-        generators: &[Permutation],
-        current: &[usize],
-        results: &mut Vec<Permutation>,
-        visited: &mut std::collections::HashSet<Vec<usize>>,
-    ) {
-        if !visited.insert(current.to_owned()) {
-            return;
-        }
-        results.push(current.to_owned());
-        for gen in generators {
-            let next = crate::schreier_sims::compose_permutations(current, gen);
-            enumerate_recursive(generators, &next, results, visited);
+/// Checks that a slot permutation keeps every slot's index class unchanged
+pub(crate) fn preserves_index_classes(tensor: &Tensor, perm: &[usize]) -> bool {
+    let indices = tensor.indices();
+    perm.iter()
+        .enumerate()
+        .all(|(slot, &source)| indices[source].class() == indices[slot].class())
+}
+
+/// Checks whether all indices of the tensor belong to the same class
+fn has_single_index_class(tensor: &Tensor) -> bool {
+    tensor
+        .indices()
+        .windows(2)
+        .all(|pair| pair[0].class() == pair[1].class())
+}
+
+/// Opaque sort key identifying a tensor by its canonical form
+///
+/// Two tensors have equal keys exactly when they canonicalize to the same
+/// name, perturbative order, index arrangement and coefficient, so keys can
+/// index hash maps and ordered sets of tensors up to symmetry. Keys compare
+/// by name, then perturbative order, then the slots' labels, variance and
+/// classes, then coefficient; the details of the order are otherwise
+/// unspecified.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CanonicalKey {
+    name: String,
+    perturbative_order: u32,
+    slots: Vec<(String, bool, IndexClass)>,
+    coefficient: i32,
+}
+
+/// Returns the key of a tensor's canonical form
+///
+/// # Errors
+/// Returns any error raised while canonicalizing the tensor.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::{canonical_key, Symmetry, Tensor, TensorIndex};
+///
+/// let s = |first: &str, second: &str| {
+///     let mut s = Tensor::new("S", vec![TensorIndex::new(first, 0), TensorIndex::new(second, 1)]);
+///     s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+///     s
+/// };
+/// assert_eq!(canonical_key(&s("b", "a"))?, canonical_key(&s("a", "b"))?);
+/// assert!(canonical_key(&s("a", "b"))? < canonical_key(&s("a", "c"))?);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn canonical_key(tensor: &Tensor) -> Result<CanonicalKey> {
+    Ok(key_of_canonical(&canonicalize(tensor)?))
+}
+
+/// Returns the key of a tensor that is already in canonical form
+pub(crate) fn key_of_canonical(canonical: &Tensor) -> CanonicalKey {
+    CanonicalKey {
+        name: canonical.name().to_string(),
+        perturbative_order: canonical.perturbative_order(),
+        slots: canonical
+            .indices()
+            .iter()
+            .map(|index| {
+                (
+                    index.name().to_string(),
+                    index.is_contravariant(),
+                    index.class(),
+                )
+            })
+            .collect(),
+        coefficient: canonical.coefficient(),
+    }
+}
+
+/// One slot of an index arrangement, ordered as in an [`OrderingVersion`]
+///
+/// Under [`OrderingVersion::V1`] slots compare as the bytes of the index
+/// name followed by `^` for a contravariant or `_` for a covariant index and
+/// a `|` separator. This is the order canonical forms have always been
+/// chosen by and is kept for stability: it puts `^` before `_`, but also
+/// sorts `a0` and `A'` before `a` and `A`. [`OrderingVersion::V2`] compares
+/// the names as strings, so that a name sorts before its extensions, and
+/// then puts contravariant before covariant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct SlotKey<'a> {
+    name: &'a str,
+    contravariant: bool,
+    ordering: OrderingVersion,
+}
+
+impl<'a> SlotKey<'a> {
+    /// Returns the key of the slot holding `index` under [`OrderingVersion::V1`]
+    pub(crate) fn of(index: &'a TensorIndex) -> Self {
+        Self::ordered(index, OrderingVersion::V1)
+    }
+
+    /// Returns the key of the slot holding `index` under `ordering`
+    pub(crate) fn ordered(index: &'a TensorIndex, ordering: OrderingVersion) -> Self {
+        Self {
+            name: index.name(),
+            contravariant: index.is_contravariant(),
+            ordering,
         }
     }
 
-    let mut results = Vec::new();
-    let mut visited = std::collections::HashSet::new();
-    let identity: Permutation = (0..degree).collect();
-    enumerate_recursive(&bsgs.generators, &identity, &mut results, &mut visited);
-    results
+    /// Returns the bytes the slot is compared by
+    fn bytes(self) -> impl Iterator<Item = u8> + 'a {
+        let variance = if self.contravariant { b'^' } else { b'_' };
+        self.name.bytes().chain([variance, b'|'])
+    }
 }
 
-/// Creates a canonical key for tensor comparison
-fn tensor_canonical_key(tensor: &Tensor) -> String {
-    let mut key = String::new();
+impl Ord for SlotKey<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.ordering {
+            OrderingVersion::V1 => self.bytes().cmp(other.bytes()),
+            OrderingVersion::V2 => self
+                .name
+                .cmp(other.name)
+                .then(other.contravariant.cmp(&self.contravariant)),
+        }
+    }
+}
 
-    // Add index names in order with their variance
-    for index in tensor.indices() {
-        key.push_str(index.name());
-        key.push(if index.is_contravariant() { '^' } else { '_' });
-        key.push('|'); // separator
+impl PartialOrd for SlotKey<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
+}
+
+/// Compares two index arrangements in the order of [`OrderingVersion::V1`],
+/// as the concatenated bytes of their [`SlotKey`]s
+pub(crate) fn compare_arrangements<'a, 'b>(
+    first: impl IntoIterator<Item = &'a TensorIndex>,
+    second: impl IntoIterator<Item = &'b TensorIndex>,
+) -> Ordering {
+    compare_arrangements_with(OrderingVersion::V1, first, second)
+}
 
-    // Add coefficient at the end (so lexicographic ordering of indices takes precedence)
-    key.push_str(&format!("#{}", tensor.coefficient()));
+/// Compares two index arrangements in the order of `ordering`
+pub(crate) fn compare_arrangements_with<'a, 'b>(
+    ordering: OrderingVersion,
+    first: impl IntoIterator<Item = &'a TensorIndex>,
+    second: impl IntoIterator<Item = &'b TensorIndex>,
+) -> Ordering {
+    match ordering {
+        OrderingVersion::V1 => first
+            .into_iter()
+            .flat_map(|index| SlotKey::of(index).bytes())
+            .cmp(
+                second
+                    .into_iter()
+                    .flat_map(|index| SlotKey::of(index).bytes()),
+            ),
+        OrderingVersion::V2 => first
+            .into_iter()
+            .map(|index| SlotKey::ordered(index, ordering))
+            .cmp(
+                second
+                    .into_iter()
+                    .map(|index| SlotKey::ordered(index, ordering)),
+            ),
+    }
+}
 
-    key
+/// Compares two tensors by index arrangement and then coefficient, the order
+/// candidate forms are chosen by
+pub(crate) fn compare_forms(first: &Tensor, second: &Tensor) -> Ordering {
+    compare_arrangements(first.indices(), second.indices())
+        .then(first.coefficient().cmp(&second.coefficient()))
 }
 
 /// Converts a symmetry to permutation generators
-fn symmetry_to_generators(symmetry: &Symmetry, size: usize) -> Vec<Permutation> {
+pub(crate) fn symmetry_to_generators(symmetry: &Symmetry, size: usize) -> Vec<Permutation> {
     match symmetry {
         Symmetry::Symmetric { indices } => {
             let mut generators = Vec::new();
@@ -208,7 +1551,7 @@ fn symmetry_to_generators(symmetry: &Symmetry, size: usize) -> Vec<Permutation>
                 if indices[i] < size && indices[i + 1] < size {
                     perm.swap(indices[i], indices[i + 1]);
                 }
-                generators.push(perm);
+                generators.push(perm.into());
             }
             generators
         }
@@ -220,7 +1563,7 @@ fn symmetry_to_generators(symmetry: &Symmetry, size: usize) -> Vec<Permutation>
                 if indices[i] < size && indices[i + 1] < size {
                     perm.swap(indices[i], indices[i + 1]);
                 }
-                generators.push(perm);
+                generators.push(perm.into());
             }
             generators
         }
@@ -232,7 +1575,7 @@ fn symmetry_to_generators(symmetry: &Symmetry, size: usize) -> Vec<Permutation>
                 if i < size && j < size {
                     let mut perm: Vec<usize> = (0..size).collect();
                     perm.swap(i, j);
-                    generators.push(perm);
+                    generators.push(perm.into());
                 }
             }
 
@@ -248,13 +1591,13 @@ fn symmetry_to_generators(symmetry: &Symmetry, size: usize) -> Vec<Permutation>
                     perm[j1] = j2;
                     perm[i2] = i1;
                     perm[j2] = j1;
-                    generators.push(perm);
+                    generators.push(perm.into());
                 }
             }
 
             generators
         }
-        Symmetry::Cyclic { indices } => {
+        Symmetry::Cyclic { indices } | Symmetry::AntiCyclic { indices } => {
             if indices.len() > 1 {
                 let mut perm: Vec<usize> = (0..size).collect();
                 // Create cyclic permutation
@@ -265,15 +1608,25 @@ fn symmetry_to_generators(symmetry: &Symmetry, size: usize) -> Vec<Permutation>
                     }
                     perm[indices[indices.len() - 1]] = first;
                 }
-                vec![perm]
+                vec![perm.into()]
             } else {
                 vec![(0..size).collect()]
             }
         }
+        Symmetry::YoungShape { columns } => {
+            if columns.iter().flatten().any(|&slot| slot >= size) {
+                return Vec::new();
+            }
+            column_generators(columns, size)
+        }
         Symmetry::Custom {
             valid_permutations,
             signs: _,
-        } => valid_permutations.clone(),
+        } => valid_permutations
+            .iter()
+            .cloned()
+            .map(Permutation::from)
+            .collect(),
     }
 }
 
@@ -286,6 +1639,8 @@ fn is_identity(perm: &[usize]) -> bool {
 /// Canonicalization method options
 pub enum CanonicalizationMethod {
     SchreierSims,
+    /// Requires the `tableaux` feature
+    #[cfg(feature = "tableaux")]
     YoungSymmetrizer,
 }
 
@@ -299,7 +1654,10 @@ pub fn canonicalize_with_optimizations(
 ) -> Result<Tensor> {
     match method {
         CanonicalizationMethod::SchreierSims => {
-            let mut result = if is_riemann_like(tensor) {
+            // The sorting shortcuts assume every slot may receive every index
+            let mut result = if !has_single_index_class(tensor) {
+                canonicalize(tensor)
+            } else if is_riemann_like(tensor) {
                 canonicalize_riemann_tensor(tensor)
             } else if is_symmetric_tensor(tensor) {
                 canonicalize_symmetric_tensor(tensor)
@@ -309,12 +1667,15 @@ pub fn canonicalize_with_optimizations(
                 canonicalize(tensor)
             }?;
             if let Some(tab) = tableau {
-                result = result.project_with_tableau(tab)?;
+                result = project_onto(&result, tab)?;
             }
             Ok(result)
         }
+        #[cfg(feature = "tableaux")]
         CanonicalizationMethod::YoungSymmetrizer => {
-            if let Some(tab) = tableau {
+            // Fall back to the tableau of a declared Young symmetry
+            let declared = tensor.symmetries().iter().find_map(Symmetry::tableau);
+            if let Some(tab) = tableau.or(declared.as_ref()) {
                 // First canonicalize the tensor to ensure it's in the correct form
                 // before applying the Young symmetrizer projection
                 let canonicalized = canonicalize(tensor)?;
@@ -328,6 +1689,26 @@ pub fn canonicalize_with_optimizations(
     }
 }
 
+/// Projects a canonical form onto the irreducible representation of a tableau
+#[cfg(feature = "tableaux")]
+fn project_onto(
+    tensor: &Tensor,
+    tableau: &crate::young_tableaux::StandardTableau,
+) -> Result<Tensor> {
+    tensor.project_with_tableau(tableau)
+}
+
+/// Without the `tableaux` feature there is no Young symmetrizer to project with
+#[cfg(not(feature = "tableaux"))]
+fn project_onto(
+    _tensor: &Tensor,
+    _tableau: &crate::young_tableaux::StandardTableau,
+) -> Result<Tensor> {
+    Err(crate::ButlerPortugalError::InvalidPermutation(
+        "Projecting onto a tableau requires the `tableaux` feature".to_string(),
+    ))
+}
+
 /// Checks if tensor has Riemann-like symmetries
 fn is_riemann_like(tensor: &Tensor) -> bool {
     if tensor.rank() != 4 {
@@ -379,8 +1760,57 @@ fn canonicalize_antisymmetric_tensor(tensor: &Tensor) -> Result<Tensor> {
     tensor.permute(&permutation)
 }
 
+/// Converts all tensor symmetries into a flat list of signed generators
+///
+/// Every generator takes its sign from the symmetry that produced it, see
+/// [`generator_sign`]; products of them take the product of the signs, so
+/// declarations that contradict each other make the group contain the
+/// identity with sign `-1`.
+pub(crate) fn tensor_signed_generators(tensor: &Tensor) -> Vec<SignedPermutation> {
+    let n = tensor.rank();
+    tensor
+        .symmetries()
+        .iter()
+        .flat_map(|symmetry| {
+            symmetry_to_generators(symmetry, n)
+                .into_iter()
+                .filter_map(move |generator| {
+                    let sign = generator_sign(tensor, symmetry, &generator);
+                    (sign != 0).then(|| SignedPermutation::new(generator, sign))
+                })
+        })
+        .collect()
+}
+
+/// Returns the sign of a generator produced by `symmetry`
+///
+/// The sign is the one `symmetry` gives the generator. `SymmetricPairs` only
+/// declares its pairs exchangeable, so a swap within one of them instead
+/// follows another symmetry declared on the pair, such as the antisymmetry
+/// of the Riemann tensor, and is symmetric if there is none.
+fn generator_sign(tensor: &Tensor, symmetry: &Symmetry, generator: &[usize]) -> i32 {
+    if let Symmetry::SymmetricPairs { pairs } = symmetry {
+        let moved: Vec<usize> = (0..generator.len())
+            .filter(|&k| generator[k] != k)
+            .collect();
+        let within_pair =
+            moved.len() == 2 && pairs.iter().any(|&(a, b)| moved == [a.min(b), a.max(b)]);
+        if within_pair {
+            return tensor
+                .symmetries()
+                .iter()
+                .filter(|other| !matches!(other, Symmetry::SymmetricPairs { .. }))
+                .filter(|other| moved.iter().all(|slot| other.slots().contains(slot)))
+                .map(|other| other.permutation_sign(generator))
+                .find(|&sign| sign != 0)
+                .unwrap_or(1);
+        }
+    }
+    symmetry.permutation_sign(generator)
+}
+
 /// Converts all tensor symmetries into a flat list of permutation generators
-fn tensor_symmetry_generators(tensor: &Tensor) -> Vec<Permutation> {
+pub(crate) fn tensor_symmetry_generators(tensor: &Tensor) -> Vec<Permutation> {
     let n = tensor.rank();
     let mut gens = Vec::new();
     for sym in tensor.symmetries() {
@@ -392,6 +1822,7 @@ fn tensor_symmetry_generators(tensor: &Tensor) -> Vec<Permutation> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schreier_sims::schreier_sims;
     use crate::symmetry::Symmetry;
 
     #[test]
@@ -455,6 +1886,123 @@ mod tests {
         assert_eq!(result.coefficient(), 0);
     }
 
+    #[test]
+    fn test_dimension_dependent_vanishing() {
+        let form = |dimension: usize, rank: usize| {
+            let indices = ["a", "b", "c", "d", "e"][..rank]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i).with_dimension(dimension))
+                .collect();
+            let mut form = Tensor::new("F", indices);
+            form.add_symmetry(Symmetry::antisymmetric((0..rank).collect()));
+            form
+        };
+        assert!(canonicalize(&form(4, 5)).unwrap().is_zero());
+        assert!(!canonicalize(&form(4, 4)).unwrap().is_zero());
+        assert!(!canonicalize(&form(5, 5)).unwrap().is_zero());
+        let config = CanonicalizationConfig::new().with_parallel_processing(true);
+        assert!(canonicalize_with_config(&form(3, 4), &config)
+            .unwrap()
+            .is_zero());
+
+        // Spinor indices take two values without declaring a dimension, and
+        // only indices of the same class count against it
+        let mut chi = Tensor::new(
+            "chi",
+            vec![
+                TensorIndex::undotted("A", 0),
+                TensorIndex::undotted("B", 1),
+                TensorIndex::undotted("C", 2),
+            ],
+        );
+        chi.add_symmetry(Symmetry::antisymmetric(vec![0, 1, 2]));
+        assert!(canonicalize(&chi).unwrap().is_zero());
+        let mut mixed = Tensor::new(
+            "psi",
+            vec![
+                TensorIndex::undotted("A", 0),
+                TensorIndex::undotted("B", 1),
+                TensorIndex::new("a", 2),
+            ],
+        );
+        mixed.add_symmetry(Symmetry::antisymmetric(vec![0, 1, 2]));
+        assert!(!canonicalize(&mixed).unwrap().is_zero());
+    }
+
+    #[test]
+    fn test_overlapping_symmetries_generate_the_whole_group() {
+        // A cyclic symmetry inside a symmetric one adds nothing, and inside an
+        // antisymmetric one agrees with its signs; neither shrinks the group
+        for (names, sign) in [
+            (["b", "c", "d"], 1),
+            (["b", "d", "c"], -1),
+            (["c", "b", "d"], -1),
+            (["c", "d", "b"], 1),
+            (["d", "b", "c"], 1),
+            (["d", "c", "b"], -1),
+        ] {
+            for (overall, antisymmetric) in [
+                (Symmetry::symmetric(vec![0, 1, 2]), false),
+                (Symmetry::antisymmetric(vec![0, 1, 2]), true),
+            ] {
+                let mut tensor = Tensor::new(
+                    "C",
+                    names
+                        .iter()
+                        .enumerate()
+                        .map(|(i, n)| TensorIndex::new(n, i))
+                        .collect(),
+                );
+                tensor.add_symmetry(overall);
+                tensor.add_symmetry(Symmetry::cyclic(vec![0, 1, 2]));
+                let canonical = canonicalize(&tensor).unwrap();
+                assert_eq!(canonical.to_string().trim_start_matches('-'), "C__b _c _d");
+                let expected = if antisymmetric { sign } else { 1 };
+                assert_eq!(canonical.coefficient(), expected, "{names:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_contradictory_symmetries_vanish() {
+        let tensor = |names: [&str; 3], symmetries: [Symmetry; 2]| {
+            let mut tensor = Tensor::new(
+                "T",
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| TensorIndex::new(n, i))
+                    .collect(),
+            );
+            for symmetry in symmetries {
+                tensor.add_symmetry(symmetry);
+            }
+            tensor
+        };
+        // Together the two declarations make T equal to -T
+        let overlapping = tensor(
+            ["a", "b", "c"],
+            [
+                Symmetry::symmetric(vec![0, 1]),
+                Symmetry::antisymmetric(vec![1, 2]),
+            ],
+        );
+        let repeated = tensor(
+            ["a", "a", "b"],
+            [
+                Symmetry::symmetric(vec![0, 1]),
+                Symmetry::antisymmetric(vec![0, 2]),
+            ],
+        );
+        for t in [overlapping, repeated] {
+            assert!(t.signed_symmetry_group().is_vanishing());
+            assert!(canonicalize(&t).unwrap().is_zero(), "{t}");
+            let config = CanonicalizationConfig::new().with_parallel_processing(true);
+            assert!(canonicalize_with_config(&t, &config).unwrap().is_zero());
+        }
+    }
+
     #[test]
     fn test_identity_permutation() {
         let perm = vec![0, 1, 2, 3];
@@ -465,14 +2013,561 @@ mod tests {
     }
 
     #[test]
-    fn test_tensor_canonical_key() {
-        let tensor = Tensor::new(
+    fn test_public_canonical_key_deduplicates_orbit() {
+        let r = crate::presets::riemann(
+            ["d", "b", "c", "a"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap();
+        let keys: std::collections::HashSet<CanonicalKey> = r
+            .orbit()
+            .map(|form| canonical_key(&form).unwrap())
+            .collect();
+        assert_eq!(keys.len(), 1);
+        let mut other = r.clone();
+        other.set_coefficient(2);
+        assert_ne!(canonical_key(&other).unwrap(), canonical_key(&r).unwrap());
+    }
+
+    #[test]
+    fn test_slot_order_is_v1() {
+        let slot = |name: &str, contravariant: bool| {
+            let mut index = TensorIndex::new(name, 0);
+            index.set_contravariant(contravariant);
+            index
+        };
+        let (upper, lower) = (slot("a", true), slot("a", false));
+        assert!(SlotKey::of(&upper) < SlotKey::of(&lower));
+        assert!(SlotKey::of(&lower) < SlotKey::of(&slot("b", true)));
+        // Kept from the string keys of `OrderingVersion::V1`
+        assert!(SlotKey::of(&slot("a0", false)) < SlotKey::of(&lower));
+        assert_eq!(
+            compare_arrangements([&upper, &lower], [&upper, &lower]),
+            Ordering::Equal
+        );
+        assert_eq!(
+            compare_arrangements([&lower, &upper], [&upper, &lower]),
+            Ordering::Greater
+        );
+
+        let mut negative = Tensor::new("T", vec![upper.clone(), lower.clone()]);
+        let positive = negative.clone();
+        negative.set_coefficient(-1);
+        assert_eq!(compare_forms(&negative, &positive), Ordering::Less);
+    }
+
+    #[test]
+    fn test_v2_orders_names_as_strings() {
+        fn v2(index: &TensorIndex) -> SlotKey<'_> {
+            SlotKey::ordered(index, OrderingVersion::V2)
+        }
+        let (a, a0) = (TensorIndex::new("a", 0), TensorIndex::new("a0", 1));
+        assert!(v2(&a) < v2(&a0));
+        assert!(SlotKey::of(&a0) < SlotKey::of(&a));
+        assert!(v2(&TensorIndex::new("A", 0)) < v2(&TensorIndex::new("A'", 0)));
+        assert!(v2(&TensorIndex::contravariant("a", 0)) < v2(&a));
+        assert_eq!(
+            compare_arrangements_with(OrderingVersion::V2, [&a, &a0], [&a0, &a]),
+            Ordering::Less
+        );
+
+        // The chain descent, the enumeration and its parallel form agree
+        let mut f = Tensor::new("F", vec![a0.clone(), a.clone()]);
+        f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        let mut mixed = Tensor::new(
+            "T",
+            vec![a0.clone(), TensorIndex::tetrad("l", 1), a.clone()],
+        );
+        mixed.add_symmetry(Symmetry::symmetric(vec![0, 1, 2]));
+        let v1 = CanonicalizationConfig::new();
+        let v2 = v1.clone().with_ordering_version(OrderingVersion::V2);
+        let parallel = v2.clone().with_parallel_processing(true);
+        for (tensor, first, second) in [
+            (&f, "F__a0 _a", "-F__a _a0"),
+            (&mixed, "T__a0 _l _a", "T__a _l _a0"),
+        ] {
+            assert_eq!(
+                canonicalize_with_config(tensor, &v1).unwrap().to_string(),
+                first
+            );
+            assert_eq!(
+                canonicalize_with_config(tensor, &v2).unwrap().to_string(),
+                second
+            );
+            assert_eq!(
+                canonicalize_with_config(tensor, &parallel)
+                    .unwrap()
+                    .to_string(),
+                second
+            );
+        }
+    }
+
+    #[test]
+    fn test_double_coset_slot_group_only() {
+        // Antisymmetric slots (0, 1): only the arrangement matters here
+        let slots = schreier_sims(&[vec![1, 0, 2, 3].into()], 4);
+        let labels = BSGS::identity(4);
+        let result = canonicalize_double_coset(&[3, 1, 2, 0], &slots, &labels).unwrap();
+        assert_eq!(result, vec![1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn test_double_coset_label_group_only() {
+        // Labels 0 and 1 form an exchangeable dummy pair
+        let slots = BSGS::identity(3);
+        let labels = schreier_sims(&[vec![1, 0, 2].into()], 3);
+        let result = canonicalize_double_coset(&[2, 1, 0], &slots, &labels).unwrap();
+        assert_eq!(result, vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn test_double_coset_combined() {
+        // Riemann slot group with both index pairs exchangeable as dummies
+        let slots = schreier_sims(
+            &[
+                vec![1, 0, 2, 3].into(),
+                vec![0, 1, 3, 2].into(),
+                vec![2, 3, 0, 1].into(),
+            ],
+            4,
+        );
+        let labels = schreier_sims(&[vec![1, 0, 2, 3].into(), vec![0, 1, 3, 2].into()], 4);
+        let result = canonicalize_double_coset(&[3, 0, 2, 1], &slots, &labels).unwrap();
+        assert_eq!(result, vec![0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_double_coset_rejects_mismatched_degree() {
+        let slots = schreier_sims(&[vec![1, 0].into()], 2);
+        let labels = BSGS::identity(3);
+        assert!(canonicalize_double_coset(&[0, 1, 2], &slots, &labels).is_err());
+        assert!(canonicalize_double_coset(&[0, 0, 2], &labels, &labels).is_err());
+    }
+
+    #[test]
+    fn test_double_coset_with_label_convention() {
+        let slots = schreier_sims(&[vec![1, 0, 2].into()], 3);
+        let labels = BSGS::identity(3);
+        let g = [2, 0, 1];
+        let in_slots =
+            canonicalize_double_coset_with(&g, &slots, &labels, PermutationAction::Slots).unwrap();
+        assert_eq!(
+            in_slots,
+            canonicalize_double_coset(&g, &slots, &labels).unwrap()
+        );
+
+        // The same configuration written as label -> slot gives the inverse answer
+        let inverse = inverse_permutation(&g);
+        let in_labels =
+            canonicalize_double_coset_with(&inverse, &slots, &labels, PermutationAction::Labels)
+                .unwrap();
+        assert_eq!(in_labels, inverse_permutation(&in_slots));
+        assert!(PermutationAction::Labels.to_slots(&[0, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_double_coset_representatives_are_canonical() {
+        // Riemann slots with two exchangeable dummy pairs (0,1) and (2,3)
+        let slots = schreier_sims(
+            &[
+                vec![1, 0, 2, 3].into(),
+                vec![0, 1, 3, 2].into(),
+                vec![2, 3, 0, 1].into(),
+            ],
+            4,
+        );
+        let labels = schreier_sims(&[vec![2, 3, 0, 1].into(), vec![1, 0, 2, 3].into()], 4);
+        let representatives = double_coset_representatives(&slots, &labels, 4).unwrap();
+        for rep in &representatives {
+            assert_eq!(
+                &canonicalize_double_coset(rep, &slots, &labels).unwrap(),
+                rep
+            );
+        }
+        // Every configuration canonicalizes onto one of them
+        let mut g = vec![0, 1, 2, 3];
+        loop {
+            let canonical = canonicalize_double_coset(&g, &slots, &labels).unwrap();
+            assert!(representatives.contains(&canonical));
+            if !next_permutation(&mut g) {
+                break;
+            }
+        }
+        assert!(representatives.len() < coset_representatives(&slots, 4).unwrap().len());
+
+        // The trivial group leaves every permutation as its own coset
+        assert_eq!(
+            coset_representatives(&BSGS::identity(3), 3).unwrap().len(),
+            6
+        );
+        let big = BSGS::identity(MAX_ENUMERATION_DEGREE + 1);
+        assert!(coset_representatives(&big, MAX_ENUMERATION_DEGREE + 1).is_err());
+    }
+
+    #[test]
+    fn test_canonicalize_keeps_index_classes_in_their_slots() {
+        // T_{ia} with i spatial and a spacetime: the symmetry may not swap them
+        let mut tensor = Tensor::new(
+            "T",
+            vec![TensorIndex::spatial("i", 0), TensorIndex::new("a", 1)],
+        );
+        tensor.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        let canonical = canonicalize(&tensor).unwrap();
+        assert_eq!(canonical.indices()[0].name(), "i");
+        let optimized =
+            canonicalize_with_optimizations(&tensor, None, &CanonicalizationMethod::SchreierSims)
+                .unwrap();
+        assert_eq!(optimized.indices()[0].name(), "i");
+
+        // Slots of the same class are still sorted
+        let mut tensor = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::spatial("j", 0),
+                TensorIndex::spatial("i", 1),
+                TensorIndex::new("a", 2),
+            ],
+        );
+        tensor.add_symmetry(Symmetry::symmetric(vec![0, 1, 2]));
+        let canonical = canonicalize(&tensor).unwrap();
+        let names: Vec<&str> = canonical.indices().iter().map(|i| i.name()).collect();
+        assert_eq!(names, vec!["i", "j", "a"]);
+    }
+
+    #[test]
+    fn test_inconsistent_custom_symmetry_is_rejected() {
+        let mut tensor = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::new("c", 0),
+                TensorIndex::new("b", 1),
+                TensorIndex::new("a", 2),
+            ],
+        );
+        // A 3-cycle with sign -1 would give T = -T after three applications
+        tensor.add_symmetry(Symmetry::custom(vec![vec![1, 2, 0]], vec![-1]));
+        assert!(matches!(
+            canonicalize(&tensor),
+            Err(ButlerPortugalError::InvalidSymmetry(_))
+        ));
+        assert!(canonicalize_with_stats(&tensor).is_err());
+
+        let mut tensor = Tensor::new("T", vec![TensorIndex::new("a", 0)]);
+        tensor.add_symmetry(Symmetry::custom(vec![vec![1, 0]], vec![1]));
+        assert!(canonicalize(&tensor).is_err());
+    }
+
+    #[test]
+    fn test_canonicalize_in_place_reuses_buffer() {
+        let mut tensor = Tensor::new(
+            "R",
+            ["d", "c", "b", "a"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        );
+        tensor.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        tensor.add_symmetry(Symmetry::antisymmetric(vec![2, 3]));
+        tensor.add_symmetry(Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]));
+        let expected = canonicalize(&tensor).unwrap();
+
+        let buffer = tensor.indices().as_ptr();
+        canonicalize_in_place(&mut tensor).unwrap();
+        assert_eq!(tensor, expected);
+        assert_eq!(tensor.indices().as_ptr(), buffer);
+
+        // Rotations exercise longer permutation cycles
+        let mut cyclic = Tensor::new(
+            "C",
+            ["c", "a", "b", "e", "d"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        );
+        cyclic.add_symmetry(Symmetry::cyclic(vec![0, 1, 2, 3, 4]));
+        let expected = canonicalize(&cyclic).unwrap();
+        canonicalize_in_place(&mut cyclic).unwrap();
+        assert_eq!(cyclic, expected);
+        let names: Vec<&str> = cyclic.indices().iter().map(|i| i.name()).collect();
+        assert_eq!(names, ["a", "b", "e", "d", "c"]);
+    }
+
+    #[test]
+    fn test_chain_search_agrees_with_enumeration() {
+        let symmetry_sets = [
+            vec![Symmetry::symmetric(vec![0, 1, 2, 3])],
+            vec![
+                Symmetry::antisymmetric(vec![0, 1, 2]),
+                Symmetry::symmetric(vec![3, 4]),
+            ],
+            vec![
+                Symmetry::antisymmetric(vec![0, 1]),
+                Symmetry::antisymmetric(vec![2, 3]),
+                Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]),
+            ],
+            vec![Symmetry::cyclic(vec![0, 1, 2, 3, 4])],
+            vec![Symmetry::symmetric_pairs(vec![(0, 1), (2, 3), (4, 5)])],
+        ];
+        let name_lists: [&[&str]; 5] = [
+            &["d", "c", "b", "a", "e", "f"],
+            &["b", "a", "b", "a", "c", "c"],
+            &["a", "a", "a", "b", "b", "b"],
+            &["c", "b", "a", "a", "d", "e"],
+            &["x", "x", "x", "x", "x", "x"],
+        ];
+        for symmetries in &symmetry_sets {
+            let rank = symmetries
+                .iter()
+                .flat_map(|s| symmetry_to_generators(s, 6))
+                .map(|g| {
+                    g.iter()
+                        .enumerate()
+                        .filter(|(k, &p)| *k != p)
+                        .map(|(k, _)| k + 1)
+                        .max()
+                        .unwrap_or(0)
+                })
+                .max()
+                .unwrap_or(0);
+            for names in name_lists {
+                for upper in [0b0, 0b101, 0b1100] {
+                    let mut tensor = Tensor::new(
+                        "T",
+                        names[..rank]
+                            .iter()
+                            .enumerate()
+                            .map(|(i, n)| {
+                                if upper & (1 << i) != 0 {
+                                    TensorIndex::contravariant(n, i)
+                                } else {
+                                    TensorIndex::new(n, i)
+                                }
+                            })
+                            .collect(),
+                    );
+                    for symmetry in symmetries {
+                        tensor.add_symmetry(symmetry.clone());
+                    }
+                    if tensor.is_zero() {
+                        continue;
+                    }
+                    let mut by_chain = tensor.clone();
+                    apply_minimal(&mut by_chain, &slot_group(&tensor));
+                    let mut by_elements = tensor.clone();
+                    let elements = SlotGroup::Elements(tensor.signed_symmetry_group());
+                    apply_minimal(&mut by_elements, &elements);
+                    assert_eq!(
+                        key_of_canonical(&by_chain),
+                        key_of_canonical(&by_elements),
+                        "{tensor}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_chain_search_vanishes_on_sign_ties() {
+        // Exchanging the slots of T_aa returns the arrangement with sign -1
+        let mut tensor = Tensor::new(
+            "T",
+            vec![TensorIndex::new("a", 0), TensorIndex::new("a", 1)],
+        );
+        tensor.add_symmetry(Symmetry::anti_cyclic(vec![0, 1]));
+        let group = slot_group(&tensor);
+        assert!(matches!(group, SlotGroup::Chain(_)));
+        let mut by_chain = tensor.clone();
+        apply_minimal(&mut by_chain, &group);
+        assert_eq!(by_chain.coefficient(), 0);
+        assert_eq!(canonicalize(&tensor).unwrap().coefficient(), 0);
+    }
+
+    #[test]
+    fn test_high_rank_canonicalization_avoids_enumeration() {
+        // Three Riemann tensors in a row, exchangeable as blocks: order 8^3 * 3!
+        let names = ["l", "k", "j", "i", "h", "g", "f", "e", "d", "c", "b", "a"];
+        let mut tensor = Tensor::new(
+            "RRR",
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        );
+        for block in 0..3 {
+            let o = 4 * block;
+            tensor.add_symmetry(Symmetry::antisymmetric(vec![o, o + 1]));
+            tensor.add_symmetry(Symmetry::antisymmetric(vec![o + 2, o + 3]));
+            tensor.add_symmetry(Symmetry::symmetric_pairs(vec![(o, o + 1), (o + 2, o + 3)]));
+        }
+        tensor.add_symmetry(Symmetry::symmetric_pairs(vec![(0, 1), (4, 5), (8, 9)]));
+        tensor.add_symmetry(Symmetry::symmetric_pairs(vec![(2, 3), (6, 7), (10, 11)]));
+        let (canonical, stats) = canonicalize_with_stats(&tensor).unwrap();
+        assert!(stats.candidates < 100);
+        assert_eq!(canonical.indices()[0].name(), "a");
+
+        // The symmetric group on twelve slots has 479001600 elements
+        let mut symmetric = tensor.clone();
+        symmetric.clear_symmetries();
+        symmetric.add_symmetry(Symmetry::symmetric((0..12).collect()));
+        let canonical = canonicalize(&symmetric).unwrap();
+        let sorted: Vec<&str> = canonical.indices().iter().map(|i| i.name()).collect();
+        assert_eq!(
+            sorted,
+            ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l"]
+        );
+    }
+
+    #[test]
+    fn test_double_coset_agrees_with_enumeration() {
+        let slots = schreier_sims(
+            &[
+                vec![1, 0, 2, 3, 4].into(),
+                vec![0, 1, 3, 2, 4].into(),
+                vec![2, 3, 0, 1, 4].into(),
+            ],
+            5,
+        );
+        let labels = schreier_sims(&[vec![1, 0, 2, 3, 4].into(), vec![0, 1, 2, 4, 3].into()], 5);
+        let slot_elements = enumerate_group(&slots, 5);
+        let label_elements = enumerate_group(&labels, 5);
+        let mut g: Permutation = (0..5).collect();
+        loop {
+            let current = &g;
+            let brute = slot_elements
+                .iter()
+                .flat_map(|s| {
+                    label_elements
+                        .iter()
+                        .map(move |d| s.iter().map(|&i| d[current[i]]).collect::<Permutation>())
+                })
+                .min()
+                .unwrap();
+            assert_eq!(
+                canonicalize_double_coset(&g, &slots, &labels).unwrap(),
+                brute
+            );
+            if !next_permutation(&mut g) {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_dummy_relabeling() {
+        let config = CanonicalizationConfig::new().with_dummies_relabeled(true);
+        let riemann_with = |names: [&str; 4], upper: [usize; 2]| {
+            let mut r = crate::presets::riemann(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| TensorIndex::new(n, i))
+                    .collect(),
+            )
+            .unwrap();
+            for slot in upper {
+                r.indices_mut()[slot].set_contravariant(true);
+            }
+            r
+        };
+        // R_{a b}^{a c}, R_{d b}^{d c} and R^{e}_{b}{}_{e}^{c} are one tensor
+        let first = canonicalize_with_config(&riemann_with(["a", "b", "a", "c"], [2, 3]), &config);
+        let second = canonicalize_with_config(&riemann_with(["d", "b", "d", "c"], [2, 3]), &config);
+        let third = canonicalize_with_config(&riemann_with(["e", "b", "e", "c"], [0, 3]), &config);
+        assert_eq!(first, second);
+        assert_eq!(
+            key_of_canonical(&first.unwrap()),
+            key_of_canonical(&third.unwrap())
+        );
+
+        // Metric and spinor traces of the wrong symmetry vanish
+        let mut f = Tensor::new(
+            "F",
+            vec![TensorIndex::new("b", 0), TensorIndex::contravariant("b", 1)],
+        );
+        f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        assert!(canonicalize_with_config(&f, &config).unwrap().is_zero());
+        let mut phi = Tensor::new(
+            "phi",
+            vec![TensorIndex::undotted("B", 0), TensorIndex::undotted("B", 1)],
+        );
+        phi.indices_mut()[1].set_contravariant(true);
+        phi.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        assert!(canonicalize_with_config(&phi, &config).unwrap().is_zero());
+        phi.clear_symmetries();
+        assert_eq!(
+            canonicalize_with_config(&phi, &config).unwrap().to_string(),
+            "-phi_^A _A"
+        );
+
+        let many = Tensor::new(
             "T",
-            vec![TensorIndex::new("a", 0), TensorIndex::contravariant("b", 1)],
+            (0..14)
+                .map(|i| TensorIndex::new(&format!("x{}", i / 2), i))
+                .map(|mut index| {
+                    index.set_contravariant(index.position() % 2 == 0);
+                    index
+                })
+                .collect(),
         );
+        assert!(canonicalize_with_config(&many, &config).is_err());
+    }
+
+    #[test]
+    fn test_canonicalize_batch() {
+        let riemann = |names: [&str; 4]| {
+            crate::presets::riemann(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| TensorIndex::new(n, i))
+                    .collect(),
+            )
+            .unwrap()
+        };
+        let antisymmetric = |first: TensorIndex, second: TensorIndex| {
+            let mut f = Tensor::new("F", vec![first, second]);
+            f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+            f
+        };
+        let mut tensors = vec![
+            riemann(["d", "c", "b", "a"]),
+            antisymmetric(TensorIndex::new("b", 0), TensorIndex::new("a", 1)),
+            riemann(["b", "a", "b", "a"]),
+            antisymmetric(TensorIndex::new("a", 0), TensorIndex::new("a", 1)),
+            // Same declarations, but the slots hold indices of other classes
+            antisymmetric(TensorIndex::undotted("B", 0), TensorIndex::new("a", 1)),
+            Tensor::new("v", vec![TensorIndex::new("a", 0)]),
+            riemann(["a", "c", "b", "a"]),
+        ];
+        tensors[6].indices_mut()[3].set_contravariant(true);
+
+        let config = CanonicalizationConfig::new();
+        for config in [
+            config.clone(),
+            config.clone().with_parallel_processing(true),
+            config.clone().with_dummies_relabeled(true),
+        ] {
+            let batch = canonicalize_batch(&tensors, &config).unwrap();
+            assert_eq!(batch.len(), tensors.len());
+            for (tensor, canonical) in tensors.iter().zip(&batch) {
+                assert_eq!(
+                    *canonical,
+                    canonicalize_with_config(tensor, &config).unwrap()
+                );
+            }
+        }
+        assert!(canonicalize_batch(&[], &config).unwrap().is_empty());
 
-        let key = tensor_canonical_key(&tensor);
-        assert!(key.contains("a_"));
-        assert!(key.contains("b^"));
+        let mut invalid = Tensor::new("T", vec![TensorIndex::new("a", 0)]);
+        invalid.add_symmetry(Symmetry::custom(vec![vec![1, 0]], vec![1]));
+        tensors.push(invalid);
+        assert!(canonicalize_batch(&tensors, &config).is_err());
     }
 }
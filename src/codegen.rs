@@ -0,0 +1,570 @@
+//! Code generation for component expressions
+//!
+//! [`generate_code`] turns a (canonicalized) tensor expression in a fixed
+//! dimension into code that evaluates its components numerically.
+//!
+//! * For C and Fortran every factor is read from a flat array holding only
+//!   its independent components, in the order of
+//!   [`ComponentLayout::independent_components`]. Sums over dummy indices are
+//!   unrolled; components related by the slot symmetries are replaced by
+//!   their independent representative with the right sign, vanishing ones are
+//!   dropped, and equal products are collected, so no redundant term is
+//!   emitted. The result is written to a flat row-major array.
+//! * For NumPy one `np.einsum` call is emitted per term, acting on the full
+//!   component arrays (see [`ComponentLayout::to_ndarray`]).
+//!
+//! Free indices of the result are ordered by name, and variance is ignored:
+//! the arrays are expected to hold the components with the variance used in
+//! the expression.
+//!
+//! [`generate_layout`] emits the lookup tables of a [`ComponentLayout`], so
+//! simulation codes can store symmetry-compressed arrays in the same order.
+
+use crate::components::ComponentLayout;
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::{TensorExpression, TensorProduct};
+use crate::rational::Rational;
+use crate::tensor::Tensor;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Upper bound on the number of index assignments unrolled for one expression
+const MAX_ASSIGNMENTS: usize = 1 << 22;
+
+/// The language of generated code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    /// C statements on `double` arrays, indexed from 0
+    C,
+    /// Fortran statements on `real(8)` arrays, indexed from 1, with one term
+    /// per continuation line
+    Fortran,
+    /// A NumPy assignment built from `np.einsum` calls
+    NumPy,
+}
+
+/// Generates code assigning the components of `expression` to `output`
+///
+/// # Errors
+/// Returns an `IncompatibleTensors` error if the terms have different free
+/// indices, an `InvalidTensor` error if an index occurs more than twice in a
+/// term, and a `ComputationError` if unrolling the sums would be too large.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::codegen::{generate_code, Language};
+/// use butler_portugal::expression::TensorExpression;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// // The trace S^a_a of a symmetric tensor in two dimensions
+/// let mut s = Tensor::new("S", vec![TensorIndex::contravariant("a", 0), TensorIndex::new("a", 1)]);
+/// s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+/// let trace = TensorExpression::from(s.clone());
+/// assert_eq!(generate_code(&trace, 2, Language::C, "tr")?, "tr[0] = S[0] + S[2];\n");
+/// assert_eq!(generate_code(&trace, 2, Language::Fortran, "tr")?, "tr(1) = S(1) &\n    + S(3)\n");
+/// assert_eq!(generate_code(&trace, 2, Language::NumPy, "tr")?, "tr = np.einsum(\"aa->\", S)\n");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn generate_code(
+    expression: &TensorExpression,
+    dimension: usize,
+    language: Language,
+    output: &str,
+) -> Result<String> {
+    let terms: Vec<&TensorProduct> = expression.terms().iter().filter(|t| !t.is_zero()).collect();
+    let free = match terms.first() {
+        Some(term) => free_indices(term)?,
+        None => Vec::new(),
+    };
+    for term in &terms {
+        if free_indices(term)? != free {
+            return Err(ButlerPortugalError::IncompatibleTensors(format!(
+                "Terms of the expression have different free indices: {term}"
+            )));
+        }
+    }
+    match language {
+        Language::NumPy => Ok(einsum_code(&terms, &free, output)),
+        Language::C | Language::Fortran => {
+            unrolled_code(&terms, &free, dimension, language, output)
+        }
+    }
+}
+
+/// Emits the lookup tables of a symmetry-compressed component layout
+///
+/// Three tables named after `name` are written, indexed like the arrays of
+/// [`generate_code`]:
+///
+/// * `{name}_offset`: for each component of the full array in row-major
+///   order, the offset of its independent component in the compressed array
+///   (`-1`, or `0` in Fortran, if it vanishes by symmetry)
+/// * `{name}_sign`: the sign relating the component to the stored one (`0` if
+///   it vanishes)
+/// * `{name}_component`: for each offset of the compressed array, the indices
+///   of the component stored there, which is the inverse mapping
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::codegen::{generate_layout, Language};
+/// use butler_portugal::components::ComponentLayout;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let mut f = Tensor::new("F", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+/// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+/// let layout = ComponentLayout::new(&f, 2)?;
+/// assert_eq!(
+///     generate_layout(&layout, Language::NumPy, "F"),
+///     "F_offset = np.array([-1, 0, 0, -1])\n\
+///      F_sign = np.array([0, 1, -1, 0])\n\
+///      F_component = np.array([[0, 1]])\n"
+/// );
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn generate_layout(layout: &ComponentLayout, language: Language, name: &str) -> String {
+    let name = identifier(name);
+    let base = match language {
+        Language::Fortran => 1,
+        _ => 0,
+    };
+    let offsets: Vec<i64> = layout
+        .relations()
+        .iter()
+        .map(|relation| relation.map_or(base - 1, |(k, _)| k as i64 + base))
+        .collect();
+    let signs: Vec<i64> = layout
+        .relations()
+        .iter()
+        .map(|relation| relation.map_or(0, |(_, sign)| i64::from(sign)))
+        .collect();
+    let components: Vec<Vec<i64>> = layout
+        .independent_components()
+        .iter()
+        .map(|component| component.iter().map(|&i| i as i64 + base).collect())
+        .collect();
+    let rank = layout.rank();
+    let count = components.len();
+    let list = |values: &[i64]| {
+        values
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    match language {
+        Language::C => {
+            let rows: Vec<String> = components
+                .iter()
+                .map(|c| format!("{{{}}}", list(c)))
+                .collect();
+            format!(
+                "static const int {name}_offset[{}] = {{{}}};\n\
+                 static const signed char {name}_sign[{}] = {{{}}};\n\
+                 static const int {name}_component[{count}][{}] = {{{}}};\n",
+                offsets.len(),
+                list(&offsets),
+                signs.len(),
+                list(&signs),
+                rank.max(1),
+                rows.join(", ")
+            )
+        }
+        Language::Fortran => {
+            // Fortran stores arrays column-major, so the inverse table is
+            // listed component by component, i.e. transposed
+            let flat: Vec<i64> = components.concat();
+            format!(
+                "integer, parameter :: {name}_offset({}) = [{}]\n\
+                 integer, parameter :: {name}_sign({}) = [{}]\n\
+                 integer, parameter :: {name}_component({rank}, {count}) = reshape([{}], [{rank}, {count}])\n",
+                offsets.len(),
+                list(&offsets),
+                signs.len(),
+                list(&signs),
+                list(&flat)
+            )
+        }
+        Language::NumPy => {
+            let rows: Vec<String> = components
+                .iter()
+                .map(|c| format!("[{}]", list(c)))
+                .collect();
+            format!(
+                "{name}_offset = np.array([{}])\n\
+                 {name}_sign = np.array([{}])\n\
+                 {name}_component = np.array([{}])\n",
+                list(&offsets),
+                list(&signs),
+                rows.join(", ")
+            )
+        }
+    }
+}
+
+/// Returns the indices occurring once in a term, sorted by name
+fn free_indices(term: &TensorProduct) -> Result<Vec<String>> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for index in term.factors().iter().flat_map(Tensor::indices) {
+        match counts.iter_mut().find(|(name, _)| *name == index.name()) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((index.name(), 1)),
+        }
+    }
+    if let Some((name, _)) = counts.iter().find(|(_, count)| *count > 2) {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "Index {name} occurs more than twice in {term}"
+        )));
+    }
+    let mut free: Vec<String> = counts
+        .into_iter()
+        .filter(|(_, count)| *count == 1)
+        .map(|(name, _)| name.to_string())
+        .collect();
+    free.sort();
+    Ok(free)
+}
+
+/// Emits one statement per output component with the dummy sums unrolled
+fn unrolled_code(
+    terms: &[&TensorProduct],
+    free: &[String],
+    dimension: usize,
+    language: Language,
+    output: &str,
+) -> Result<String> {
+    let mut layouts: HashMap<&str, ComponentLayout> = HashMap::new();
+    for factor in terms.iter().flat_map(|term| term.factors()) {
+        if !layouts.contains_key(factor.name()) {
+            layouts.insert(factor.name(), ComponentLayout::new(factor, dimension)?);
+        }
+    }
+    // Every index name of a term, free ones first
+    let names: Vec<Vec<&str>> = terms
+        .iter()
+        .map(|term| {
+            let mut names: Vec<&str> = free.iter().map(String::as_str).collect();
+            for index in term.factors().iter().flat_map(Tensor::indices) {
+                if !names.contains(&index.name()) {
+                    names.push(index.name());
+                }
+            }
+            names
+        })
+        .collect();
+    names
+        .iter()
+        .map(|names| assignment_count(dimension, names.len()))
+        .try_fold(0usize, |total, count| total.checked_add(count?))
+        .filter(|&total| total <= MAX_ASSIGNMENTS)
+        .ok_or_else(|| {
+            ButlerPortugalError::ComputationError(format!(
+                "Unrolling the expression needs more than {MAX_ASSIGNMENTS} index assignments"
+            ))
+        })?;
+    let outputs = assignment_count(dimension, free.len()).unwrap_or(0);
+
+    let mut code = String::new();
+    for flat in 0..outputs {
+        let free_values = unflatten(flat, dimension, free.len());
+        // Products of independent components, each a sorted list of (array, component)
+        let mut collected: Vec<(Vec<(&str, usize)>, Rational)> = Vec::new();
+        for (term, names) in terms.iter().zip(&names) {
+            let dummies = names.len() - free.len();
+            for dummy in 0..assignment_count(dimension, dummies).unwrap_or(0) {
+                let values: Vec<usize> = free_values
+                    .iter()
+                    .copied()
+                    .chain(unflatten(dummy, dimension, dummies))
+                    .collect();
+                let Some((monomial, sign)) = component_product(term, names, &values, &layouts)
+                else {
+                    continue;
+                };
+                let coefficient = term.coefficient() * Rational::from(sign);
+                match collected
+                    .iter_mut()
+                    .find(|(existing, _)| *existing == monomial)
+                {
+                    Some((_, total)) => *total += coefficient,
+                    None => collected.push((monomial, coefficient)),
+                }
+            }
+        }
+        collected.retain(|(_, coefficient)| !coefficient.is_zero());
+        let target = element(output, flat, language);
+        let value = render_sum(&collected, language);
+        match language {
+            Language::Fortran => {
+                let _ = writeln!(code, "{target} = {value}");
+            }
+            _ => {
+                let _ = writeln!(code, "{target} = {value};");
+            }
+        }
+    }
+    Ok(code)
+}
+
+/// Returns `dimension^count`, or `None` on overflow
+fn assignment_count(dimension: usize, count: usize) -> Option<usize> {
+    u32::try_from(count)
+        .ok()
+        .and_then(|count| dimension.checked_pow(count))
+}
+
+/// Evaluates the factors of a term at index values, returning the sorted
+/// independent components and the sign, or `None` if a factor vanishes
+fn component_product<'a>(
+    term: &'a TensorProduct,
+    names: &[&str],
+    values: &[usize],
+    layouts: &HashMap<&str, ComponentLayout>,
+) -> Option<(Vec<(&'a str, usize)>, i32)> {
+    let mut monomial = Vec::with_capacity(term.factors().len());
+    let mut sign = 1;
+    for factor in term.factors() {
+        let component: Vec<usize> = factor
+            .indices()
+            .iter()
+            .map(|index| {
+                names
+                    .iter()
+                    .position(|&name| name == index.name())
+                    .map_or(0, |k| values[k])
+            })
+            .collect();
+        let (k, factor_sign) = layouts.get(factor.name())?.relation(&component)?;
+        monomial.push((factor.name(), k));
+        sign *= factor_sign;
+    }
+    monomial.sort_unstable();
+    Some((monomial, sign))
+}
+
+/// Renders a sum of products of array elements
+fn render_sum(terms: &[(Vec<(&str, usize)>, Rational)], language: Language) -> String {
+    if terms.is_empty() {
+        return literal(Rational::ZERO, language);
+    }
+    let separator = match language {
+        Language::Fortran => " &\n    ",
+        _ => " ",
+    };
+    let mut rendered = String::new();
+    for (i, (monomial, coefficient)) in terms.iter().enumerate() {
+        let negative = *coefficient < Rational::ZERO;
+        match (i, negative) {
+            (0, true) => rendered.push('-'),
+            (0, false) => {}
+            (_, true) => rendered.push_str(&format!("{separator}- ")),
+            (_, false) => rendered.push_str(&format!("{separator}+ ")),
+        }
+        let factors: Vec<String> = monomial
+            .iter()
+            .map(|(name, k)| element(name, *k, language))
+            .collect();
+        let magnitude = coefficient.abs();
+        if magnitude != Rational::ONE || factors.is_empty() {
+            rendered.push_str(&literal(magnitude, language));
+            if !factors.is_empty() {
+                rendered.push_str(" * ");
+            }
+        }
+        rendered.push_str(&factors.join(" * "));
+    }
+    rendered
+}
+
+/// Formats an array element with the indexing of the language
+fn element(array: &str, k: usize, language: Language) -> String {
+    let array = identifier(array);
+    match language {
+        Language::Fortran => format!("{array}({})", k + 1),
+        _ => format!("{array}[{k}]"),
+    }
+}
+
+/// Formats a rational constant as a floating-point literal
+fn literal(value: Rational, language: Language) -> String {
+    let number = |n: i64| match language {
+        Language::Fortran => format!("{n}.0d0"),
+        Language::C => format!("{n}.0"),
+        Language::NumPy => n.to_string(),
+    };
+    if value.is_integer() {
+        number(value.numerator())
+    } else {
+        format!(
+            "({}/{})",
+            number(value.numerator()),
+            number(value.denominator())
+        )
+    }
+}
+
+/// Turns a tensor name into an identifier, spelling out derivative symbols
+fn identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| match c {
+            '∇' => "D".to_string(),
+            '∂' => "d".to_string(),
+            c if c.is_ascii_alphanumeric() || c == '_' => c.to_string(),
+            _ => "_".to_string(),
+        })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, 't');
+    }
+    identifier
+}
+
+/// Emits a NumPy assignment with one `np.einsum` call per term
+fn einsum_code(terms: &[&TensorProduct], free: &[String], output: &str) -> String {
+    if terms.is_empty() {
+        return format!("{output} = 0\n");
+    }
+    let mut rendered = String::new();
+    for (i, term) in terms.iter().enumerate() {
+        let mut letters: Vec<&str> = free.iter().map(String::as_str).collect();
+        for index in term.factors().iter().flat_map(Tensor::indices) {
+            if !letters.contains(&index.name()) {
+                letters.push(index.name());
+            }
+        }
+        let letter = |name: &str| {
+            let k = letters.iter().position(|&other| other == name).unwrap_or(0);
+            char::from(if k < 26 {
+                b'a' + k as u8
+            } else {
+                b'A' + (k - 26) as u8
+            })
+        };
+        let inputs: Vec<String> = term
+            .factors()
+            .iter()
+            .map(|factor| {
+                factor
+                    .indices()
+                    .iter()
+                    .map(|index| letter(index.name()))
+                    .collect()
+            })
+            .collect();
+        let result: String = free.iter().map(|name| letter(name)).collect();
+        let arrays: Vec<String> = term
+            .factors()
+            .iter()
+            .map(|f| identifier(f.name()))
+            .collect();
+
+        let coefficient = term.coefficient();
+        match (i, coefficient < Rational::ZERO) {
+            (0, true) => rendered.push('-'),
+            (0, false) => {}
+            (_, true) => rendered.push_str(" - "),
+            (_, false) => rendered.push_str(" + "),
+        }
+        if coefficient.abs() != Rational::ONE {
+            rendered.push_str(&literal(coefficient.abs(), Language::NumPy));
+            rendered.push_str(" * ");
+        }
+        if term.factors().is_empty() {
+            rendered.push('1');
+        } else {
+            let _ = write!(
+                rendered,
+                "np.einsum(\"{}->{result}\", {})",
+                inputs.join(","),
+                arrays.join(", ")
+            );
+        }
+    }
+    format!("{output} = {rendered}\n")
+}
+
+/// Converts a row-major flat position into index values
+fn unflatten(mut flat: usize, dimension: usize, count: usize) -> Vec<usize> {
+    let mut values = vec![0; count];
+    for slot in (0..count).rev() {
+        values[slot] = flat % dimension;
+        flat /= dimension;
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetry::Symmetry;
+    use crate::TensorIndex;
+
+    #[test]
+    fn test_generated_code_uses_independent_components() {
+        // F_{ab} F^{ab} with F antisymmetric: each pair of the three
+        // independent components contributes twice, so the sum collapses
+        let f = |up: bool| {
+            let index = |name, i| {
+                if up {
+                    TensorIndex::contravariant(name, i)
+                } else {
+                    TensorIndex::new(name, i)
+                }
+            };
+            let mut f = Tensor::new("F", vec![index("a", 0), index("b", 1)]);
+            f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+            f
+        };
+        let square =
+            TensorExpression::from(TensorProduct::new(Rational::ONE, vec![f(false), f(true)]));
+        assert_eq!(
+            generate_code(&square, 3, Language::C, "out").unwrap(),
+            "out[0] = 2.0 * F[0] * F[0] + 2.0 * F[1] * F[1] + 2.0 * F[2] * F[2];\n"
+        );
+        assert_eq!(
+            generate_code(&square, 3, Language::NumPy, "out").unwrap(),
+            "out = np.einsum(\"ab,ab->\", F, F)\n"
+        );
+
+        // A vector contracted with F: F_{ab} V^b, free index a
+        let v = Tensor::new("V", vec![TensorIndex::contravariant("b", 0)]);
+        let contraction: TensorExpression =
+            [TensorProduct::new(Rational::new(1, 2), vec![f(false), v])]
+                .into_iter()
+                .collect();
+        let fortran = generate_code(&contraction, 2, Language::Fortran, "w").unwrap();
+        assert_eq!(
+            fortran,
+            "w(1) = (1.0d0/2.0d0) * F(1) * V(2)\nw(2) = -(1.0d0/2.0d0) * F(1) * V(1)\n"
+        );
+
+        let mismatched: TensorExpression = [
+            TensorProduct::from(Tensor::new("A", vec![TensorIndex::new("a", 0)])),
+            TensorProduct::from(Tensor::new("B", vec![TensorIndex::new("b", 0)])),
+        ]
+        .into_iter()
+        .collect();
+        assert!(generate_code(&mismatched, 2, Language::C, "x").is_err());
+        assert_eq!(identifier("∇∇R"), "DDR");
+    }
+
+    #[test]
+    fn test_generated_layout_tables() {
+        let mut s = Tensor::new(
+            "S",
+            vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)],
+        );
+        s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        let layout = ComponentLayout::new(&s, 2).unwrap();
+        assert_eq!(
+            generate_layout(&layout, Language::C, "S"),
+            "static const int S_offset[4] = {0, 1, 1, 2};\n\
+             static const signed char S_sign[4] = {1, 1, 1, 1};\n\
+             static const int S_component[3][2] = {{0, 0}, {0, 1}, {1, 1}};\n"
+        );
+        let fortran = generate_layout(&layout, Language::Fortran, "S");
+        assert!(fortran.contains("S_offset(4) = [1, 2, 2, 3]"));
+        assert!(fortran.contains("reshape([1, 1, 1, 2, 2, 2], [2, 3])"));
+    }
+}
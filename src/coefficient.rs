@@ -0,0 +1,208 @@
+//! Scalar coefficients of tensor expressions
+//!
+//! `TensorProduct` and `TensorExpression` are generic over their coefficient
+//! type, defaulting to the exact `Rational`. Symbolic scalars from an external
+//! computer algebra system can be carried through canonicalization, collection
+//! of like terms and display by implementing `Coefficient` for them (or for a
+//! newtype wrapping them), without this crate depending on that system.
+//!
+//! Symmetry signs and combinatorial prefactors produced by this crate are
+//! exact rationals and enter through [`Coefficient::from_rational`].
+
+use crate::rational::Rational;
+use std::fmt;
+use std::ops::{Add, Mul, Neg};
+
+/// A scalar type usable as the coefficient of a tensor monomial
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::coefficient::Coefficient;
+/// use butler_portugal::expression::{TensorExpression, TensorProduct};
+/// use butler_portugal::{Tensor, TensorIndex};
+///
+/// let u = Tensor::new("u", vec![TensorIndex::new("a", 0)]);
+/// let expr: TensorExpression<f64> = [
+///     TensorProduct::new(0.5, vec![u.clone()]),
+///     TensorProduct::new(0.25, vec![u]),
+/// ]
+/// .into_iter()
+/// .collect();
+/// assert_eq!(expr.simplify()?.terms()[0].coefficient(), 0.75);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub trait Coefficient:
+    Clone
+    + PartialEq
+    + fmt::Debug
+    + fmt::Display
+    + Add<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    /// Converts an exact rational number into the coefficient type
+    fn from_rational(value: Rational) -> Self;
+
+    /// Returns the additive identity
+    fn zero() -> Self {
+        Self::from_rational(Rational::ZERO)
+    }
+
+    /// Returns the multiplicative identity
+    fn one() -> Self {
+        Self::from_rational(Rational::ONE)
+    }
+
+    /// Returns true if the coefficient is identically zero, so its term can be dropped
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /// Returns true if the coefficient should be displayed as a subtraction
+    ///
+    /// Symbolic types without a notion of sign can keep the default.
+    fn is_negative(&self) -> bool {
+        false
+    }
+}
+
+impl Coefficient for Rational {
+    fn from_rational(value: Rational) -> Self {
+        value
+    }
+
+    fn is_zero(&self) -> bool {
+        Rational::is_zero(self)
+    }
+
+    fn is_negative(&self) -> bool {
+        *self < Rational::ZERO
+    }
+}
+
+impl Coefficient for f64 {
+    fn from_rational(value: Rational) -> Self {
+        value.numerator() as f64 / value.denominator() as f64
+    }
+
+    fn is_negative(&self) -> bool {
+        *self < 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::{TensorExpression, TensorProduct};
+    use crate::symmetry::Symmetry;
+    use crate::tensor::Tensor;
+    use crate::TensorIndex;
+    use std::collections::BTreeMap;
+
+    /// A linear combination of named symbols, standing in for a CAS scalar
+    #[derive(Debug, Clone, PartialEq)]
+    struct Linear(BTreeMap<String, Rational>);
+
+    impl Linear {
+        fn symbol(name: &str) -> Self {
+            Linear([(name.to_string(), Rational::ONE)].into_iter().collect())
+        }
+    }
+
+    impl fmt::Display for Linear {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let parts: Vec<String> = self
+                .0
+                .iter()
+                .map(|(symbol, c)| match symbol.as_str() {
+                    "" => c.to_string(),
+                    _ if *c == Rational::ONE => symbol.clone(),
+                    _ => format!("{c}{symbol}"),
+                })
+                .collect();
+            write!(f, "({})", parts.join(" + "))
+        }
+    }
+
+    impl Add for Linear {
+        type Output = Linear;
+
+        fn add(mut self, other: Linear) -> Linear {
+            for (symbol, c) in other.0 {
+                *self.0.entry(symbol).or_default() += c;
+            }
+            self.0.retain(|_, c| !c.is_zero());
+            self
+        }
+    }
+
+    impl Mul for Linear {
+        type Output = Linear;
+
+        /// Only products with a constant are needed here
+        fn mul(self, other: Linear) -> Linear {
+            let (constant, linear) = match self.0.get("") {
+                Some(c) if self.0.len() == 1 => (*c, other),
+                _ => (other.0.get("").copied().unwrap_or_default(), self),
+            };
+            Linear(
+                linear
+                    .0
+                    .into_iter()
+                    .map(|(symbol, c)| (symbol, c * constant))
+                    .filter(|(_, c)| !c.is_zero())
+                    .collect(),
+            )
+        }
+    }
+
+    impl Neg for Linear {
+        type Output = Linear;
+
+        fn neg(self) -> Linear {
+            self * Linear::from_rational(-Rational::ONE)
+        }
+    }
+
+    impl Coefficient for Linear {
+        fn from_rational(value: Rational) -> Self {
+            Linear(
+                [(String::new(), value)]
+                    .into_iter()
+                    .filter(|(_, c)| !c.is_zero())
+                    .collect(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_symbolic_coefficients_are_collected() {
+        let s = |first: &str, second: &str| {
+            let mut s = Tensor::new(
+                "S",
+                vec![TensorIndex::new(first, 0), TensorIndex::new(second, 1)],
+            );
+            s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+            s
+        };
+        let mut a = s("b", "a");
+        a.set_coefficient(-1);
+        let expr: TensorExpression<Linear> = [
+            TensorProduct::new(Linear::symbol("x"), vec![s("a", "b")]),
+            TensorProduct::new(Linear::symbol("y"), vec![s("b", "a")]),
+            TensorProduct::new(Linear::symbol("x"), vec![a]),
+        ]
+        .into_iter()
+        .collect();
+        let simplified = expr.simplify().unwrap();
+        assert_eq!(simplified.terms().len(), 1);
+        assert_eq!(simplified.to_string(), "(y) S__a _b");
+    }
+
+    #[test]
+    fn test_rational_conversions() {
+        assert_eq!(f64::from_rational(Rational::new(3, 4)), 0.75);
+        assert!(Coefficient::is_negative(&Rational::new(-1, 2)));
+        assert!(<Rational as Coefficient>::is_zero(&Rational::ZERO));
+    }
+}
@@ -0,0 +1,310 @@
+//! Independent components of a tensor in a fixed dimension
+//!
+//! For a given dimension every component `T[i1, …, ir]` is related by the
+//! slot symmetries to a canonical component (the lexicographically smallest
+//! one in its orbit), up to a sign, or vanishes identically. A
+//! `ComponentLayout` records that relation so numerical codes can store only
+//! the independent components and rebuild the full array on demand.
+//!
+//! With the `ndarray` feature the layout exports both the independent
+//! components and the full symmetrized array as `ndarray` arrays.
+
+use crate::canonicalization::Permutation;
+use crate::error::{ButlerPortugalError, Result};
+use crate::tensor::Tensor;
+use std::collections::HashMap;
+use std::ops::Neg;
+
+/// Upper bound on the number of components of the full array
+const MAX_COMPONENTS: usize = 1 << 24;
+
+/// How every component of a tensor relates to its independent components
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentLayout {
+    dimension: usize,
+    rank: usize,
+    independent: Vec<Vec<usize>>,
+    /// For each component in row-major order, its independent component and sign
+    relations: Vec<Option<(usize, i32)>>,
+}
+
+impl ComponentLayout {
+    /// Computes the component layout of a tensor in `dimension` dimensions
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::components::ComponentLayout;
+    /// use butler_portugal::presets::riemann;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// let r = riemann((0..4).map(|i| TensorIndex::new(&i.to_string(), i)).collect())?;
+    /// // 21 components in four dimensions before the first Bianchi identity
+    /// let layout = ComponentLayout::new(&r, 4)?;
+    /// assert_eq!(layout.independent_components().len(), 21);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn new(tensor: &Tensor, dimension: usize) -> Result<Self> {
+        let rank = tensor.rank();
+        let total = u32::try_from(rank)
+            .ok()
+            .and_then(|rank| dimension.checked_pow(rank))
+            .filter(|&total| total <= MAX_COMPONENTS)
+            .ok_or_else(|| {
+                ButlerPortugalError::ComputationError(format!(
+                    "{} has more than {MAX_COMPONENTS} components in dimension {dimension}",
+                    tensor.name()
+                ))
+            })?;
+        let group = signed_slot_group(tensor);
+
+        let mut independent = Vec::new();
+        let mut positions: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut relations = Vec::with_capacity(total);
+        for flat in 0..total {
+            let component = unflatten(flat, dimension, rank);
+            let Some((canonical, sign)) = canonical_component(&component, &group) else {
+                relations.push(None);
+                continue;
+            };
+            let next = independent.len();
+            let position = *positions.entry(canonical.clone()).or_insert(next);
+            if position == next {
+                independent.push(canonical);
+            }
+            relations.push(Some((position, sign)));
+        }
+        Ok(Self {
+            dimension,
+            rank,
+            independent,
+            relations,
+        })
+    }
+
+    /// Returns the dimension
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Returns the rank
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    /// Returns the independent components as index tuples, in increasing order
+    pub fn independent_components(&self) -> &[Vec<usize>] {
+        &self.independent
+    }
+
+    /// Expresses a component through an independent one
+    ///
+    /// Returns `Some((k, sign))` if `T[component] = sign · T[independent[k]]`
+    /// and `None` if the component vanishes by symmetry or is out of range.
+    pub fn relation(&self, component: &[usize]) -> Option<(usize, i32)> {
+        if component.len() != self.rank || component.iter().any(|&i| i >= self.dimension) {
+            return None;
+        }
+        let flat = component
+            .iter()
+            .fold(0, |flat, &i| flat * self.dimension + i);
+        self.relations[flat]
+    }
+
+    /// Returns the index tuple stored at an offset of the compressed array
+    ///
+    /// This inverts [`relation`](Self::relation) on independent components.
+    pub fn component(&self, offset: usize) -> Option<&[usize]> {
+        self.independent.get(offset).map(Vec::as_slice)
+    }
+
+    /// Returns the relation of every component in row-major order
+    ///
+    /// Entry `flat` is what [`relation`](Self::relation) returns for the
+    /// component at row-major position `flat` of the full array.
+    pub fn relations(&self) -> &[Option<(usize, i32)>] {
+        &self.relations
+    }
+
+    /// Builds the full component array in row-major order from the independent values
+    ///
+    /// # Arguments
+    /// * `values` - One value per independent component, in layout order
+    pub fn expand<T>(&self, values: &[T]) -> Result<Vec<T>>
+    where
+        T: Clone + Default + Neg<Output = T>,
+    {
+        if values.len() != self.independent.len() {
+            return Err(ButlerPortugalError::IncompatibleTensors(format!(
+                "Expected {} independent component values, got {}",
+                self.independent.len(),
+                values.len()
+            )));
+        }
+        Ok(self
+            .relations
+            .iter()
+            .map(|relation| match relation {
+                Some((k, 1)) => values[*k].clone(),
+                Some((k, _)) => -values[*k].clone(),
+                None => T::default(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl ComponentLayout {
+    /// Returns the independent components as a `count × rank` array of indices
+    pub fn independent_array(&self) -> ndarray::Array2<usize> {
+        let flat: Vec<usize> = self.independent.iter().flatten().copied().collect();
+        ndarray::Array2::from_shape_vec((self.independent.len(), self.rank), flat)
+            .unwrap_or_else(|_| ndarray::Array2::zeros((0, self.rank)))
+    }
+
+    /// Builds the full symmetrized array of shape `[dimension; rank]` from the independent values
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::components::ComponentLayout;
+    /// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+    ///
+    /// let mut f = Tensor::new("F", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+    /// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    /// let layout = ComponentLayout::new(&f, 2)?;
+    /// let array = layout.to_ndarray(&[3.0])?;
+    /// assert_eq!(array[[0, 1]], 3.0);
+    /// assert_eq!(array[[1, 0]], -3.0);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn to_ndarray<T>(&self, values: &[T]) -> Result<ndarray::ArrayD<T>>
+    where
+        T: Clone + Default + Neg<Output = T>,
+    {
+        ndarray::ArrayD::from_shape_vec(vec![self.dimension; self.rank], self.expand(values)?)
+            .map_err(|e| ButlerPortugalError::ComputationError(e.to_string()))
+    }
+}
+
+/// Enumerates the slot symmetry group of a tensor together with the sign of each element
+fn signed_slot_group(tensor: &Tensor) -> Vec<(Permutation, i32)> {
+    tensor
+        .signed_symmetry_group()
+        .elements()
+        .map(|element| (element.permutation, element.sign))
+        .collect()
+}
+
+/// Returns the smallest component in the orbit of `component` and the sign
+/// relating the two, or `None` if the component vanishes by symmetry
+fn canonical_component(
+    component: &[usize],
+    group: &[(Permutation, i32)],
+) -> Option<(Vec<usize>, i32)> {
+    let mut best: Option<(Vec<usize>, i32)> = None;
+    let mut fixed_sign = 1;
+    for (perm, sign) in group {
+        // Tensor::permute convention: new[k] = old[perm[k]]
+        let image: Vec<usize> = perm.iter().map(|&slot| component[slot]).collect();
+        if image == component {
+            fixed_sign = fixed_sign.min(*sign);
+        }
+        if best.as_ref().map_or(true, |(b, _)| image < *b) {
+            best = Some((image, *sign));
+        }
+    }
+    if fixed_sign < 0 {
+        None
+    } else {
+        best
+    }
+}
+
+/// Converts a row-major flat position into an index tuple
+fn unflatten(mut flat: usize, dimension: usize, rank: usize) -> Vec<usize> {
+    let mut component = vec![0; rank];
+    for slot in (0..rank).rev() {
+        component[slot] = flat % dimension;
+        flat /= dimension;
+    }
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetry::Symmetry;
+    use crate::TensorIndex;
+
+    fn tensor(rank: usize, symmetries: Vec<Symmetry>) -> Tensor {
+        let mut t = Tensor::new(
+            "T",
+            (0..rank)
+                .map(|i| TensorIndex::new(&format!("a{i}"), i))
+                .collect(),
+        );
+        for symmetry in symmetries {
+            t.add_symmetry(symmetry);
+        }
+        t
+    }
+
+    #[test]
+    fn test_independent_component_counts() {
+        let count = |t: &Tensor, n| {
+            ComponentLayout::new(t, n)
+                .unwrap()
+                .independent_components()
+                .len()
+        };
+        assert_eq!(count(&tensor(2, vec![]), 3), 9);
+        assert_eq!(
+            count(&tensor(2, vec![Symmetry::symmetric(vec![0, 1])]), 4),
+            10
+        );
+        assert_eq!(
+            count(&tensor(3, vec![Symmetry::antisymmetric(vec![0, 1, 2])]), 4),
+            4
+        );
+        assert_eq!(
+            count(&tensor(3, vec![Symmetry::symmetric(vec![0, 1, 2])]), 3),
+            10
+        );
+    }
+
+    #[test]
+    fn test_relations_and_expansion() {
+        let f = tensor(2, vec![Symmetry::antisymmetric(vec![0, 1])]);
+        let layout = ComponentLayout::new(&f, 3).unwrap();
+        assert_eq!(
+            layout.independent_components(),
+            [vec![0, 1], vec![0, 2], vec![1, 2]]
+        );
+        assert_eq!(layout.relation(&[2, 1]), Some((2, -1)));
+        assert_eq!(layout.relation(&[1, 1]), None);
+        assert_eq!(layout.relation(&[3, 0]), None);
+        assert_eq!(layout.component(2), Some(&[1, 2][..]));
+        assert_eq!(layout.component(3), None);
+        assert_eq!(layout.relations()[7], Some((2, -1)));
+        assert_eq!(
+            layout.expand(&[1, 2, 3]).unwrap(),
+            vec![0, 1, 2, -1, 0, 3, -2, -3, 0]
+        );
+        assert!(layout.expand(&[1]).is_err());
+    }
+
+    #[test]
+    fn test_too_many_components() {
+        assert!(ComponentLayout::new(&tensor(30, vec![]), 4).is_err());
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_ndarray_export() {
+        let s = tensor(2, vec![Symmetry::symmetric(vec![0, 1])]);
+        let layout = ComponentLayout::new(&s, 2).unwrap();
+        assert_eq!(layout.independent_array().shape(), [3, 2]);
+        let array = layout.to_ndarray(&[1.0, 2.0, 3.0]).unwrap();
+        assert_eq!(array.shape(), [2, 2]);
+        assert_eq!(array[[1, 0]], 2.0);
+    }
+}
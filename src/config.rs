@@ -0,0 +1,493 @@
+//! Configuration of canonicalization
+//!
+//! A `CanonicalizationConfig` pins the conventions that decide which member
+//! of a symmetry orbit is chosen as canonical. For a fixed
+//! [`OrderingVersion`] the canonical form of every tensor is part of the
+//! crate's stability guarantee: it does not change across releases, so
+//! results computed and published with one release can be reproduced with
+//! any later one. Changed or new conventions are only ever introduced under
+//! a new version number, and the previous versions keep their behaviour.
+//!
+//! A configuration can also carry a deadline and a [`CancellationToken`], so
+//! that a long search can be abandoned with a `Timeout` error, and a
+//! [`ProgressCallback`] following the search as it goes.
+
+use crate::error::{ButlerPortugalError, Result};
+use crate::stats::ProgressEvent;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A frozen set of conventions for choosing canonical forms
+///
+/// New variants are added as conventions evolve; existing variants never
+/// change meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[non_exhaustive]
+pub enum OrderingVersion {
+    /// The lexicographically smallest arrangement of index labels, compared
+    /// slot by slot by name and then variance (`^` before `_`); a tensor
+    /// reaching that arrangement with both signs vanishes
+    ///
+    /// Names are compared as the bytes of the keys `name^|` and `name_|`,
+    /// which sorts `a0` and `A'` before `a` and `A`.
+    #[default]
+    V1,
+    /// As [`V1`](Self::V1), but names compare as strings, so that `a`
+    /// sorts before `a0` and `A` before `A'`; equal names then put
+    /// contravariant before covariant
+    V2,
+}
+
+impl OrderingVersion {
+    /// The newest ordering version
+    pub const LATEST: Self = Self::V2;
+}
+
+/// A flag for aborting canonicalizations from another thread
+///
+/// Clones share the flag, so a token handed to a configuration can be
+/// cancelled through any of its clones. Tokens compare equal when they share
+/// a flag.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::canonicalize_with_config;
+/// use butler_portugal::config::{CancellationToken, CanonicalizationConfig};
+/// use butler_portugal::{ButlerPortugalError, Symmetry, Tensor, TensorIndex};
+///
+/// let token = CancellationToken::new();
+/// let config = CanonicalizationConfig::new().with_cancellation(token.clone());
+/// let mut f = Tensor::new("F", vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)]);
+/// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+/// assert!(canonicalize_with_config(&f, &config).is_ok());
+/// token.cancel();
+/// assert_eq!(canonicalize_with_config(&f, &config), Err(ButlerPortugalError::Timeout));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks every canonicalization using this token to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Returns true once the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+impl Hash for CancellationToken {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+/// A function receiving [`ProgressEvent`]s while a tensor is canonicalized
+///
+/// Clones share the function. Callbacks compare equal when they share a
+/// function.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::canonicalize_with_config;
+/// use butler_portugal::config::CanonicalizationConfig;
+/// use butler_portugal::presets::riemann;
+/// use butler_portugal::stats::ProgressEvent;
+/// use butler_portugal::TensorIndex;
+/// use std::sync::{Arc, Mutex};
+///
+/// let events = Arc::new(Mutex::new(Vec::new()));
+/// let seen = events.clone();
+/// let config = CanonicalizationConfig::new().with_progress(move |event: &ProgressEvent| {
+///     seen.lock().unwrap().push(event.clone());
+/// });
+/// let r = riemann(["d", "c", "b", "a"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+/// canonicalize_with_config(&r, &config)?;
+/// let events = events.lock().unwrap();
+/// assert!(matches!(events[0], ProgressEvent::GroupBuilt { order: 8, .. }));
+/// assert_eq!(events.len(), 5);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(&ProgressEvent) + Send + Sync>);
+
+impl ProgressCallback {
+    /// Wraps a function as a callback
+    pub fn new(callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    /// Passes an event to the function
+    pub fn report(&self, event: &ProgressEvent) {
+        (self.0)(event);
+    }
+
+    /// Address of the shared function, which identifies the callback
+    fn address(&self) -> *const () {
+        Arc::as_ptr(&self.0).cast()
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ProgressCallback")
+            .field(&self.address())
+            .finish()
+    }
+}
+
+impl PartialEq for ProgressCallback {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.address(), other.address())
+    }
+}
+
+impl Eq for ProgressCallback {}
+
+impl Hash for ProgressCallback {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address().hash(state);
+    }
+}
+
+/// Options controlling canonicalization
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::canonicalization::canonicalize_with_config;
+/// use butler_portugal::config::{CanonicalizationConfig, OrderingVersion};
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let config = CanonicalizationConfig::new().with_ordering_version(OrderingVersion::V1);
+/// let mut f = Tensor::new("F", vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)]);
+/// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+/// assert_eq!(canonicalize_with_config(&f, &config)?.to_string(), "-F__a _b");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CanonicalizationConfig {
+    /// The conventions used to choose canonical forms
+    pub ordering_version: OrderingVersion,
+    /// Whether contractions of a tensor with itself are traced out first,
+    /// as by [`trace_self_contractions`](crate::presets::trace_self_contractions)
+    pub trace_self_contractions: bool,
+    /// Whether contracted index pairs are renamed as part of the search, so
+    /// that `T_a{}^a` and `T_b{}^b` share a canonical form
+    pub relabel_dummies: bool,
+    /// Whether the candidate forms of an enumerated slot group are evaluated
+    /// on the rayon thread pool; needs the `parallel` feature and is ignored
+    /// without it. Results are the same either way.
+    pub parallel_processing: bool,
+    /// When to give up with a `Timeout` error; `None` waits for as long as
+    /// the search takes
+    pub deadline: Option<Instant>,
+    /// A token whose cancellation stops the search with a `Timeout` error
+    pub cancellation: Option<CancellationToken>,
+    /// A function following the search, see [`ProgressEvent`]; events are
+    /// reported from the thread canonicalizing the tensor
+    pub progress: Option<ProgressCallback>,
+}
+
+impl CanonicalizationConfig {
+    /// Creates the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the configuration with the given ordering version
+    pub fn with_ordering_version(mut self, ordering_version: OrderingVersion) -> Self {
+        self.ordering_version = ordering_version;
+        self
+    }
+
+    /// Returns the configuration with self-contractions traced out or kept
+    pub fn with_self_contractions_traced(mut self, trace: bool) -> Self {
+        self.trace_self_contractions = trace;
+        self
+    }
+
+    /// Returns the configuration with contracted pairs relabelled or kept
+    pub fn with_dummies_relabeled(mut self, relabel: bool) -> Self {
+        self.relabel_dummies = relabel;
+        self
+    }
+
+    /// Returns the configuration with candidates evaluated in parallel or not
+    pub fn with_parallel_processing(mut self, parallel: bool) -> Self {
+        self.parallel_processing = parallel;
+        self
+    }
+
+    /// Returns the configuration giving up once `timeout` has passed from now
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.with_deadline(deadline),
+            None => self,
+        }
+    }
+
+    /// Returns the configuration giving up at `deadline`
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Returns the configuration stopping when `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Returns the configuration reporting progress to `callback`
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(&ProgressEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(ProgressCallback::new(callback));
+        self
+    }
+
+    /// Returns true if the search has to stop at checkpoints, for a deadline,
+    /// a cancellation token or a progress callback
+    pub(crate) fn is_supervised(&self) -> bool {
+        self.deadline.is_some() || self.cancellation.is_some() || self.progress.is_some()
+    }
+
+    /// Reports the event built by `event` to the progress callback, if any
+    pub(crate) fn report(&self, event: impl FnOnce() -> ProgressEvent) {
+        if let Some(progress) = &self.progress {
+            progress.report(&event());
+        }
+    }
+
+    /// Checks the deadline and the cancellation token
+    ///
+    /// # Errors
+    /// Returns a `Timeout` error if the deadline has passed or the token has
+    /// been cancelled.
+    pub fn check_timeout(&self) -> Result<()> {
+        let expired = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        let cancelled = self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled);
+        if expired || cancelled {
+            Err(ButlerPortugalError::Timeout)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalization::canonicalize_with_config;
+    use crate::presets::riemann;
+    use crate::symmetry::Symmetry;
+    use crate::{Tensor, TensorIndex};
+
+    fn indices(names: &[&str]) -> Vec<TensorIndex> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| TensorIndex::new(n, i))
+            .collect()
+    }
+
+    /// Canonical forms under `OrderingVersion::V1`; these must never change
+    #[test]
+    fn test_v1_outputs_are_frozen() {
+        let config = CanonicalizationConfig::new().with_ordering_version(OrderingVersion::V1);
+        let mut mixed = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::new("b", 0),
+                TensorIndex::contravariant("a", 1),
+                TensorIndex::new("a", 2),
+            ],
+        );
+        mixed.add_symmetry(Symmetry::symmetric(vec![0, 1, 2]));
+        let mut cyclic = Tensor::new("C", indices(&["c", "a", "b"]));
+        cyclic.add_symmetry(Symmetry::cyclic(vec![0, 1, 2]));
+        let cases = [
+            (
+                riemann(indices(&["d", "c", "b", "a"])).unwrap(),
+                "R__a _b _c _d",
+            ),
+            (
+                riemann(indices(&["b", "d", "a", "c"])).unwrap(),
+                "R__a _c _b _d",
+            ),
+            (mixed, "T_^a _a _b"),
+            (cyclic, "C__a _b _c"),
+        ];
+        for (tensor, expected) in cases {
+            let canonical = canonicalize_with_config(&tensor, &config).unwrap();
+            assert_eq!(canonical.to_string(), expected);
+        }
+        // The default stays on the first version, whose forms never change
+        assert_eq!(
+            OrderingVersion::V1,
+            CanonicalizationConfig::new().ordering_version
+        );
+        assert_eq!(OrderingVersion::LATEST, OrderingVersion::V2);
+    }
+
+    #[test]
+    fn test_sign_ties_vanish() {
+        // Exchanging the slots of T_aa gives -T_aa
+        let mut t = Tensor::new("T", indices(&["a", "a"]));
+        t.add_symmetry(Symmetry::anti_cyclic(vec![0, 1]));
+        // T_baa = T_aba = -T_aab = -T_baa
+        let mut u = Tensor::new("T", indices(&["b", "a", "a"]));
+        u.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        u.add_symmetry(Symmetry::anti_cyclic(vec![1, 2]));
+        let config = CanonicalizationConfig::new();
+        let parallel = config.clone().with_parallel_processing(true);
+        for tensor in [&t, &u] {
+            assert_eq!(
+                canonicalize_with_config(tensor, &config)
+                    .unwrap()
+                    .to_string(),
+                "0"
+            );
+            assert_eq!(
+                canonicalize_with_config(tensor, &parallel)
+                    .unwrap()
+                    .to_string(),
+                "0"
+            );
+        }
+    }
+
+    #[test]
+    fn test_trace_self_contractions() {
+        let mut r = riemann(indices(&["a", "c", "a", "b"])).unwrap();
+        r.indices_mut()[0].set_contravariant(true);
+        let config = CanonicalizationConfig::new();
+        assert_eq!(canonicalize_with_config(&r, &config).unwrap().rank(), 4);
+        let traced = config.with_self_contractions_traced(true);
+        assert_eq!(
+            canonicalize_with_config(&r, &traced).unwrap().to_string(),
+            "R__b _c"
+        );
+    }
+
+    #[test]
+    fn test_relabel_dummies() {
+        let trace = |name: &str, upper_first: bool| {
+            let mut t = Tensor::new(
+                "T",
+                vec![
+                    TensorIndex::new(name, 0),
+                    TensorIndex::new(name, 1),
+                    TensorIndex::new("c", 2),
+                ],
+            );
+            t.indices_mut()[usize::from(!upper_first)].set_contravariant(true);
+            t
+        };
+        let config = CanonicalizationConfig::new();
+        let kept = canonicalize_with_config(&trace("b", false), &config).unwrap();
+        assert_eq!(kept.to_string(), "T__b ^b _c");
+
+        let relabeled = config.with_dummies_relabeled(true);
+        for (name, upper_first) in [("a", true), ("b", false), ("d", true)] {
+            let canonical = canonicalize_with_config(&trace(name, upper_first), &relabeled);
+            assert_eq!(canonical.unwrap().to_string(), "T_^a _a _c");
+        }
+    }
+
+    #[test]
+    fn test_parallel_processing_agrees() {
+        // A tetrad index in a symmetric block forces the group to be enumerated
+        let mut mixed = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::new("d", 0),
+                TensorIndex::tetrad("l", 1),
+                TensorIndex::new("b", 2),
+                TensorIndex::new("a", 3),
+            ],
+        );
+        mixed.add_symmetry(Symmetry::symmetric(vec![0, 1, 2, 3]));
+        let mut tied = Tensor::new("T", indices(&["b", "a", "a"]));
+        tied.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        tied.add_symmetry(Symmetry::anti_cyclic(vec![1, 2]));
+        let r = riemann(indices(&["d", "c", "b", "a"])).unwrap();
+        let config = CanonicalizationConfig::new();
+        let parallel = config.clone().with_parallel_processing(true);
+        for tensor in [&mixed, &tied, &r] {
+            assert_eq!(
+                canonicalize_with_config(tensor, &parallel).unwrap(),
+                canonicalize_with_config(tensor, &config).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_timeout_and_cancellation() {
+        use crate::ButlerPortugalError;
+        use crate::IndexClass;
+
+        let r = riemann(indices(&["d", "c", "b", "a"])).unwrap();
+        let config = CanonicalizationConfig::new();
+        let expected = canonicalize_with_config(&r, &config).unwrap();
+        let generous = config.clone().with_timeout(Duration::from_secs(3600));
+        assert_eq!(canonicalize_with_config(&r, &generous).unwrap(), expected);
+        let passed = config.clone().with_deadline(Instant::now());
+        assert_eq!(
+            canonicalize_with_config(&r, &passed),
+            Err(ButlerPortugalError::Timeout)
+        );
+
+        // Indices of two classes make the 11! elements of the group be
+        // enumerated, which is stopped from another thread
+        let mut t = Tensor::new(
+            "T",
+            (0..11)
+                .map(|i| {
+                    let class = if i % 2 == 0 {
+                        IndexClass::Spacetime
+                    } else {
+                        IndexClass::Frame
+                    };
+                    TensorIndex::new(&format!("i{i}"), i).with_class(class)
+                })
+                .collect(),
+        );
+        t.add_symmetry(Symmetry::symmetric((0..11).collect()));
+        let token = CancellationToken::new();
+        let cancellable = config.with_cancellation(token.clone());
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            token.cancel();
+        });
+        assert_eq!(
+            canonicalize_with_config(&t, &cancellable),
+            Err(ButlerPortugalError::Timeout)
+        );
+        assert!(canceller.join().is_ok());
+    }
+}
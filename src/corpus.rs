@@ -0,0 +1,325 @@
+//! Golden corpora pinning canonical forms across releases
+//!
+//! A [`Corpus`] is a list of tensors with the canonical forms this crate is
+//! expected to give them. Downstream projects record one once, check it in,
+//! and [`replay`] it in their test suite, so that an upgrade, or a rewrite of
+//! the canonicalization algorithm, that changes any of their canonical forms
+//! is reported as a diff rather than noticed in the physics.
+//!
+//! Corpora are stored as JSON, reusing the tensor documents of
+//! [`crate::json`]:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "ordering_version": 1,
+//!   "cases": [
+//!     {
+//!       "label": "field strength",
+//!       "input": { "name": "F", "indices": [{ "name": "b" }, { "name": "a" }],
+//!                  "symmetries": [{ "type": "antisymmetric", "indices": [0, 1] }] },
+//!       "expected": { "name": "F", "coefficient": -1,
+//!                     "indices": [{ "name": "a" }, { "name": "b" }],
+//!                     "symmetries": [{ "type": "antisymmetric", "indices": [0, 1] }] }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The `label` is optional. Cases are replayed under the recorded
+//! `ordering_version` (see [`crate::config`]).
+
+use crate::canonicalization::{canonicalize_with_config, key_of_canonical};
+use crate::config::{CanonicalizationConfig, OrderingVersion};
+use crate::error::{ButlerPortugalError, Result};
+use crate::json::{TensorDoc, FORMAT_VERSION};
+use crate::tensor::Tensor;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A tensor with its expected canonical form
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenCase {
+    /// An optional name for reports
+    pub label: Option<String>,
+    /// The tensor to canonicalize
+    pub input: Tensor,
+    /// Its expected canonical form
+    pub expected: Tensor,
+}
+
+/// A list of golden cases recorded under one ordering version
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::corpus::{replay, Corpus};
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let mut f = Tensor::new("F", vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)]);
+/// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+/// let json = Corpus::record(&[f])?.to_json();
+///
+/// // Later, possibly with a newer release
+/// let report = replay(&Corpus::from_json(&json)?);
+/// assert!(report.is_clean(), "{report}");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Corpus {
+    /// The conventions the expected forms were recorded under
+    pub ordering_version: OrderingVersion,
+    /// The cases, in order
+    pub cases: Vec<GoldenCase>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CorpusDoc {
+    version: u32,
+    #[serde(default = "first_ordering_version")]
+    ordering_version: u32,
+    cases: Vec<CaseDoc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CaseDoc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    input: TensorDoc,
+    expected: TensorDoc,
+}
+
+fn first_ordering_version() -> u32 {
+    1
+}
+
+impl Corpus {
+    /// Creates an empty corpus under the latest ordering version
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the current canonical forms of `tensors` as a corpus
+    ///
+    /// # Errors
+    /// Returns the first error raised while canonicalizing a tensor.
+    pub fn record(tensors: &[Tensor]) -> Result<Self> {
+        let mut corpus = Self::new();
+        for tensor in tensors {
+            corpus.push(None, tensor)?;
+        }
+        Ok(corpus)
+    }
+
+    /// Appends a case, recording the current canonical form of `tensor`
+    ///
+    /// # Errors
+    /// Returns any error raised while canonicalizing the tensor.
+    pub fn push(&mut self, label: Option<&str>, tensor: &Tensor) -> Result<()> {
+        let expected = canonicalize_with_config(tensor, &self.config())?;
+        self.cases.push(GoldenCase {
+            label: label.map(ToString::to_string),
+            input: tensor.clone(),
+            expected,
+        });
+        Ok(())
+    }
+
+    /// Reads a corpus from its JSON form
+    ///
+    /// # Errors
+    /// Returns an `InvalidTensor` error if the document is malformed or has
+    /// an unsupported format or ordering version.
+    pub fn from_json(input: &str) -> Result<Self> {
+        let document: CorpusDoc = serde_json::from_str(input)
+            .map_err(|e| ButlerPortugalError::InvalidTensor(format!("Malformed corpus: {e}")))?;
+        if document.version != FORMAT_VERSION {
+            return Err(ButlerPortugalError::InvalidTensor(format!(
+                "Unsupported format version {}, expected {FORMAT_VERSION}",
+                document.version
+            )));
+        }
+        let ordering_version = match document.ordering_version {
+            1 => OrderingVersion::V1,
+            2 => OrderingVersion::V2,
+            other => {
+                return Err(ButlerPortugalError::InvalidTensor(format!(
+                    "Unknown ordering version {other}"
+                )))
+            }
+        };
+        let cases = document
+            .cases
+            .into_iter()
+            .map(|case| GoldenCase {
+                label: case.label,
+                input: Tensor::from(case.input),
+                expected: Tensor::from(case.expected),
+            })
+            .collect();
+        Ok(Self {
+            ordering_version,
+            cases,
+        })
+    }
+
+    /// Writes the corpus in its JSON form, one case per line
+    pub fn to_json(&self) -> String {
+        let ordering_version = match self.ordering_version {
+            OrderingVersion::V1 => 1,
+            OrderingVersion::V2 => 2,
+        };
+        let cases: Vec<String> = self
+            .cases
+            .iter()
+            .map(|case| {
+                let doc = CaseDoc {
+                    label: case.label.clone(),
+                    input: TensorDoc::from(&case.input),
+                    expected: TensorDoc::from(&case.expected),
+                };
+                serde_json::to_string(&doc).unwrap_or_default()
+            })
+            .collect();
+        format!(
+            "{{\"version\":{FORMAT_VERSION},\"ordering_version\":{ordering_version},\"cases\":[\n{}\n]}}\n",
+            cases.join(",\n")
+        )
+    }
+
+    fn config(&self) -> CanonicalizationConfig {
+        CanonicalizationConfig::new().with_ordering_version(self.ordering_version)
+    }
+}
+
+/// A case whose canonical form no longer matches the corpus
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseDiff {
+    /// Position of the case in the corpus
+    pub case: usize,
+    /// The case as recorded
+    pub golden: GoldenCase,
+    /// The canonical form computed now, or the error raised
+    pub actual: Result<Tensor>,
+}
+
+impl fmt::Display for CaseDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "case {}", self.case)?;
+        if let Some(label) = &self.golden.label {
+            write!(f, " ({label})")?;
+        }
+        write!(
+            f,
+            ": {} expected {}, ",
+            self.golden.input, self.golden.expected
+        )?;
+        match &self.actual {
+            Ok(actual) => write!(f, "got {actual}"),
+            Err(e) => write!(f, "failed: {e}"),
+        }
+    }
+}
+
+/// The outcome of replaying a corpus
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReplayReport {
+    /// Number of cases replayed
+    pub checked: usize,
+    /// The cases that did not reproduce, in corpus order
+    pub diffs: Vec<CaseDiff>,
+}
+
+impl ReplayReport {
+    /// Returns true if every case reproduced
+    pub fn is_clean(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} cases reproduced",
+            self.checked - self.diffs.len(),
+            self.checked
+        )?;
+        for diff in &self.diffs {
+            write!(f, "\n  {diff}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Re-canonicalizes every case of a corpus and reports the ones that differ
+///
+/// Canonical forms are compared by name, coefficient and the label,
+/// variance and class of every slot; recorded index positions are ignored.
+pub fn replay(corpus: &Corpus) -> ReplayReport {
+    let config = corpus.config();
+    let diffs = corpus
+        .cases
+        .iter()
+        .enumerate()
+        .filter_map(|(case, golden)| {
+            let actual = canonicalize_with_config(&golden.input, &config);
+            let reproduced = actual
+                .as_ref()
+                .is_ok_and(|actual| key_of_canonical(actual) == key_of_canonical(&golden.expected));
+            (!reproduced).then(|| CaseDiff {
+                case,
+                golden: golden.clone(),
+                actual,
+            })
+        })
+        .collect();
+    ReplayReport {
+        checked: corpus.cases.len(),
+        diffs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::riemann;
+    use crate::TensorIndex;
+
+    #[test]
+    fn test_corpus_round_trip_and_replay() {
+        let r = riemann(
+            ["d", "c", "b", "a"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap();
+        let mut corpus = Corpus::new();
+        corpus.push(Some("riemann"), &r).unwrap();
+        corpus
+            .push(None, &Tensor::new("v", vec![TensorIndex::undotted("A", 0)]))
+            .unwrap();
+        let json = corpus.to_json();
+        let read = Corpus::from_json(&json).unwrap();
+        assert_eq!(read.cases.len(), 2);
+        assert_eq!(read.cases[0].label.as_deref(), Some("riemann"));
+        assert!(replay(&read).is_clean());
+
+        // A stale expectation is reported with the form computed now
+        let mut stale = read;
+        stale.cases[0].expected.set_coefficient(-1);
+        let report = replay(&stale);
+        assert_eq!(report.diffs.len(), 1);
+        assert_eq!(report.diffs[0].case, 0);
+        assert_eq!(
+            report.to_string(),
+            "1 of 2 cases reproduced\n  case 0 (riemann): R__d _c _b _a expected -R__a _b _c _d, got R__a _b _c _d"
+        );
+
+        assert!(Corpus::from_json(r#"{"version": 2, "cases": []}"#).is_err());
+        assert!(
+            Corpus::from_json(r#"{"version": 1, "ordering_version": 7, "cases": []}"#).is_err()
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Todd-Coxeter coset enumeration for finitely presented symmetry groups
+//!
+//! Some slot symmetries are most naturally stated as a presentation, i.e. a set
+//! of abstract generators together with relations between them, rather than as
+//! explicit permutations. This module enumerates the cosets of a subgroup of
+//! such a group (HLT strategy with coincidence processing) and turns the
+//! resulting coset table into a permutation representation that can be used by
+//! the Schreier-Sims machinery and the canonicalizer.
+//!
+//! Words are written as sequences of nonzero integers: `k` stands for the
+//! generator `k - 1` and `-k` for its inverse. For example, with generators
+//! `a` and `b`, the relator `a b a^-1 b^-1` is `vec![1, 2, -1, -2]`.
+
+use crate::canonicalization::{Permutation, BSGS};
+use crate::error::{ButlerPortugalError, Result};
+use crate::schreier_sims::{compose_permutations, schreier_sims};
+use crate::symmetry::Symmetry;
+use std::collections::{HashSet, VecDeque};
+
+/// A word in the generators and their inverses
+pub type Word = Vec<i32>;
+
+/// Default upper bound on the number of cosets defined during an enumeration
+pub const DEFAULT_MAX_COSETS: usize = 100_000;
+
+/// A finite presentation `<x_1, ..., x_m | r_1, ..., r_k>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Presentation {
+    generators: usize,
+    relators: Vec<Word>,
+}
+
+impl Presentation {
+    /// Creates a presentation with `generators` abstract generators and the given relators
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::coset_enumeration::Presentation;
+    ///
+    /// // S_3 = <a, b | a^2, b^3, (ab)^2>
+    /// let s3 = Presentation::new(2, vec![vec![1, 1], vec![2, 2, 2], vec![1, 2, 1, 2]]).unwrap();
+    /// assert_eq!(s3.generators(), 2);
+    /// ```
+    pub fn new(generators: usize, relators: Vec<Word>) -> Result<Self> {
+        let presentation = Self {
+            generators,
+            relators,
+        };
+        for relator in &presentation.relators {
+            presentation.validate_word(relator)?;
+        }
+        Ok(presentation)
+    }
+
+    /// Returns the number of abstract generators
+    pub fn generators(&self) -> usize {
+        self.generators
+    }
+
+    /// Returns the relators
+    pub fn relators(&self) -> &[Word] {
+        &self.relators
+    }
+
+    /// Checks that every letter of `word` refers to an existing generator
+    fn validate_word(&self, word: &[i32]) -> Result<()> {
+        for &letter in word {
+            if letter == 0 || letter.unsigned_abs() as usize > self.generators {
+                return Err(ButlerPortugalError::InvalidSymmetry(format!(
+                    "Letter {letter} does not refer to one of {} generators",
+                    self.generators
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A complete coset table: the action of each generator on the cosets of a subgroup
+///
+/// Coset `0` is always the subgroup itself; the remaining cosets are numbered
+/// in the order in which they were first reached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CosetTable {
+    /// `table[c][2 * x]` is `c·x`, `table[c][2 * x + 1]` is `c·x^-1`
+    table: Vec<Vec<usize>>,
+    generators: usize,
+}
+
+impl CosetTable {
+    /// Returns the number of cosets (the index of the subgroup)
+    pub fn index(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns the coset reached from `coset` by applying `letter`
+    pub fn apply(&self, coset: usize, letter: i32) -> Option<usize> {
+        if letter == 0 || letter.unsigned_abs() as usize > self.generators {
+            return None;
+        }
+        self.table.get(coset).map(|row| row[column(letter)])
+    }
+
+    /// Returns the permutation of the cosets induced by each generator
+    pub fn permutations(&self) -> Vec<Permutation> {
+        (0..self.generators)
+            .map(|x| self.table.iter().map(|row| row[2 * x]).collect())
+            .collect()
+    }
+
+    /// Computes a BSGS for the permutation representation on the cosets
+    pub fn bsgs(&self) -> BSGS {
+        schreier_sims(&self.permutations(), self.index())
+    }
+
+    /// Converts the permutation representation into a `Symmetry` for a tensor
+    /// whose slots are identified with the cosets
+    ///
+    /// All group elements are listed explicitly with sign `+1`, since a
+    /// presentation carries no sign information.
+    pub fn to_symmetry(&self) -> Symmetry {
+        let degree = self.index();
+        let generators = self.permutations();
+        let identity: Permutation = (0..degree).collect();
+        let mut seen = HashSet::from([identity.clone()]);
+        let mut elements = vec![identity.clone()];
+        let mut queue = VecDeque::from([identity]);
+        while let Some(current) = queue.pop_front() {
+            for g in &generators {
+                let next = compose_permutations(&current, g);
+                if seen.insert(next.clone()) {
+                    elements.push(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+        let signs = vec![1; elements.len()];
+        Symmetry::custom(elements.into_iter().map(Vec::from).collect(), signs)
+    }
+}
+
+/// Maps a letter to its column in the coset table
+fn column(letter: i32) -> usize {
+    let x = letter.unsigned_abs() as usize - 1;
+    if letter > 0 {
+        2 * x
+    } else {
+        2 * x + 1
+    }
+}
+
+/// Working state of the HLT enumeration
+struct Enumeration {
+    table: Vec<Vec<Option<usize>>>,
+    parent: Vec<usize>,
+    live: usize,
+    columns: usize,
+    max_cosets: usize,
+}
+
+impl Enumeration {
+    fn new(generators: usize, max_cosets: usize) -> Self {
+        let columns = 2 * generators;
+        Self {
+            table: vec![vec![None; columns]],
+            parent: vec![0],
+            live: 1,
+            columns,
+            max_cosets,
+        }
+    }
+
+    fn is_live(&self, coset: usize) -> bool {
+        self.parent[coset] == coset
+    }
+
+    fn define(&mut self, coset: usize, col: usize) -> Result<()> {
+        if self.live >= self.max_cosets {
+            return Err(ButlerPortugalError::ComputationError(format!(
+                "Coset enumeration exceeded {} cosets",
+                self.max_cosets
+            )));
+        }
+        let new = self.table.len();
+        self.table.push(vec![None; self.columns]);
+        self.parent.push(new);
+        self.live += 1;
+        self.table[coset][col] = Some(new);
+        self.table[new][col ^ 1] = Some(coset);
+        Ok(())
+    }
+
+    fn rep(&mut self, coset: usize) -> usize {
+        let mut root = coset;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut current = coset;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    fn merge(&mut self, a: usize, b: usize, queue: &mut Vec<usize>) {
+        let (ra, rb) = (self.rep(a), self.rep(b));
+        if ra != rb {
+            let (keep, drop) = (ra.min(rb), ra.max(rb));
+            self.parent[drop] = keep;
+            self.live -= 1;
+            queue.push(drop);
+        }
+    }
+
+    fn coincidence(&mut self, a: usize, b: usize) {
+        let mut queue = Vec::new();
+        self.merge(a, b, &mut queue);
+        let mut i = 0;
+        while i < queue.len() {
+            let gamma = queue[i];
+            i += 1;
+            for col in 0..self.columns {
+                let Some(delta) = self.table[gamma][col] else {
+                    continue;
+                };
+                self.table[delta][col ^ 1] = None;
+                let mu = self.rep(gamma);
+                let nu = self.rep(delta);
+                if let Some(target) = self.table[mu][col] {
+                    self.merge(nu, target, &mut queue);
+                } else if let Some(target) = self.table[nu][col ^ 1] {
+                    self.merge(mu, target, &mut queue);
+                } else {
+                    self.table[mu][col] = Some(nu);
+                    self.table[nu][col ^ 1] = Some(mu);
+                }
+            }
+        }
+    }
+
+    /// Traces `word` from `coset` in both directions, defining new cosets as needed
+    fn scan_and_fill(&mut self, coset: usize, word: &[i32]) -> Result<()> {
+        if word.is_empty() {
+            return Ok(());
+        }
+        let cols: Vec<usize> = word.iter().map(|&l| column(l)).collect();
+        let mut forward = coset;
+        let mut backward = coset;
+        let mut i = 0;
+        let mut end = cols.len();
+        loop {
+            while i < end {
+                match self.table[forward][cols[i]] {
+                    Some(next) => {
+                        forward = next;
+                        i += 1;
+                    }
+                    None => break,
+                }
+            }
+            if i == end {
+                if forward != backward {
+                    self.coincidence(forward, backward);
+                }
+                return Ok(());
+            }
+            while end > i {
+                match self.table[backward][cols[end - 1] ^ 1] {
+                    Some(next) => {
+                        backward = next;
+                        end -= 1;
+                    }
+                    None => break,
+                }
+            }
+            if end == i {
+                self.coincidence(forward, backward);
+                return Ok(());
+            }
+            if end == i + 1 {
+                // Deduction: the single missing entry closes the cycle
+                self.table[forward][cols[i]] = Some(backward);
+                self.table[backward][cols[i] ^ 1] = Some(forward);
+                return Ok(());
+            }
+            self.define(forward, cols[i])?;
+        }
+    }
+
+    /// Renumbers the live cosets consecutively, in order of first appearance
+    fn compress(mut self, generators: usize) -> CosetTable {
+        let live: Vec<usize> = (0..self.table.len()).filter(|&c| self.is_live(c)).collect();
+        let mut renumber = vec![usize::MAX; self.table.len()];
+        for (new, &old) in live.iter().enumerate() {
+            renumber[old] = new;
+        }
+        let mut table = Vec::with_capacity(live.len());
+        for &old in &live {
+            let mut row = Vec::with_capacity(self.columns);
+            for col in 0..self.columns {
+                let target = self.table[old][col].unwrap_or(old);
+                let target = self.rep(target);
+                row.push(renumber[target]);
+            }
+            table.push(row);
+        }
+        CosetTable { table, generators }
+    }
+}
+
+/// Enumerates the cosets of the subgroup generated by `subgroup` in the group
+/// given by `presentation`, using the HLT strategy
+///
+/// Fails if the enumeration needs more than `max_cosets` simultaneously live
+/// cosets (for example because the presented group is infinite).
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::coset_enumeration::{coset_enumeration, Presentation};
+///
+/// // S_3 = <a, b | a^2, b^3, (ab)^2> acting on the cosets of <a>
+/// let s3 = Presentation::new(2, vec![vec![1, 1], vec![2, 2, 2], vec![1, 2, 1, 2]]).unwrap();
+/// let table = coset_enumeration(&s3, &[vec![1]], 1000).unwrap();
+/// assert_eq!(table.index(), 3);
+/// assert_eq!(table.bsgs().order(), 6);
+/// ```
+pub fn coset_enumeration(
+    presentation: &Presentation,
+    subgroup: &[Word],
+    max_cosets: usize,
+) -> Result<CosetTable> {
+    for word in subgroup {
+        presentation.validate_word(word)?;
+    }
+    let mut state = Enumeration::new(presentation.generators(), max_cosets.max(1));
+    for word in subgroup {
+        state.scan_and_fill(0, word)?;
+    }
+
+    let mut coset = 0;
+    while coset < state.table.len() {
+        for relator in presentation.relators() {
+            if !state.is_live(coset) {
+                break;
+            }
+            state.scan_and_fill(coset, relator)?;
+        }
+        if state.is_live(coset) {
+            for col in 0..state.columns {
+                if state.table[coset][col].is_none() {
+                    state.define(coset, col)?;
+                }
+            }
+        }
+        coset += 1;
+    }
+
+    Ok(state.compress(presentation.generators()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalization::canonicalize;
+    use crate::index::TensorIndex;
+    use crate::tensor::Tensor;
+
+    fn s3() -> Presentation {
+        Presentation::new(2, vec![vec![1, 1], vec![2, 2, 2], vec![1, 2, 1, 2]]).unwrap()
+    }
+
+    #[test]
+    fn test_regular_representation() {
+        let table = coset_enumeration(&s3(), &[], DEFAULT_MAX_COSETS).unwrap();
+        assert_eq!(table.index(), 6);
+        assert_eq!(table.bsgs().order(), 6);
+    }
+
+    #[test]
+    fn test_symmetric_group_s4() {
+        // S_4 = <a, b | a^2, b^3, (ab)^4>
+        let s4 = Presentation::new(
+            2,
+            vec![vec![1, 1], vec![2, 2, 2], vec![1, 2, 1, 2, 1, 2, 1, 2]],
+        )
+        .unwrap();
+        let regular = coset_enumeration(&s4, &[], DEFAULT_MAX_COSETS).unwrap();
+        assert_eq!(regular.index(), 24);
+        assert_eq!(regular.bsgs().order(), 24);
+    }
+
+    #[test]
+    fn test_action_on_cosets_of_subgroup() {
+        let table = coset_enumeration(&s3(), &[vec![1]], DEFAULT_MAX_COSETS).unwrap();
+        assert_eq!(table.index(), 3);
+        // The subgroup <a> fixes the trivial coset
+        assert_eq!(table.apply(0, 1), Some(0));
+        for perm in table.permutations() {
+            assert!(crate::error::validate_permutation(&perm, 3).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_collapsing_presentation() {
+        // <a, b | a^2, b^2, ab = ba, a = b> is cyclic of order 2
+        let presentation = Presentation::new(
+            2,
+            vec![vec![1, 1], vec![2, 2], vec![1, 2, -1, -2], vec![1, -2]],
+        )
+        .unwrap();
+        let table = coset_enumeration(&presentation, &[], DEFAULT_MAX_COSETS).unwrap();
+        assert_eq!(table.index(), 2);
+    }
+
+    #[test]
+    fn test_infinite_group_hits_limit() {
+        let free = Presentation::new(1, vec![]).unwrap();
+        assert!(coset_enumeration(&free, &[], 50).is_err());
+    }
+
+    #[test]
+    fn test_invalid_letter() {
+        assert!(Presentation::new(1, vec![vec![2]]).is_err());
+        assert!(coset_enumeration(&s3(), &[vec![0]], 10).is_err());
+    }
+
+    #[test]
+    fn test_presented_cyclic_symmetry_canonicalizes() {
+        // Z_3 = <r | r^3> acting regularly on three slots
+        let z3 = Presentation::new(1, vec![vec![1, 1, 1]]).unwrap();
+        let table = coset_enumeration(&z3, &[], DEFAULT_MAX_COSETS).unwrap();
+        let mut tensor = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::new("c", 0),
+                TensorIndex::new("a", 1),
+                TensorIndex::new("b", 2),
+            ],
+        );
+        tensor.add_symmetry(table.to_symmetry());
+        let canonical = canonicalize(&tensor).unwrap();
+        let names: Vec<&str> = canonical.indices().iter().map(|i| i.name()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,427 @@
+//! Decomposition of tensor symmetries into irreducible Young components
+//!
+//! A tensor of rank n whose slots carry the monoterm symmetries of a signed
+//! group `G ⊂ S_n` lives in the image of `e_G = (1/|G|) Σ_g sign(g) g` on
+//! `V^{⊗n}`. By Schur–Weyl duality this splits under `GL(V)` into the
+//! irreducible modules `S_λ(V)`, one for every Young shape `λ` of n boxes,
+//! with multiplicity
+//!
+//! `m_λ = (1/|G|) Σ_{g ∈ G} sign(g) χ_λ(g)`,
+//!
+//! where the characters `χ_λ` of the symmetric group are computed with the
+//! Murnaghan–Nakayama rule. No plethysm is needed since the tensor is a
+//! single factor. The part of the tensor in the component of shape `λ` is
+//! given by the central idempotent
+//! `P_λ = (f_λ / n!) Σ_{σ ∈ S_n} χ_λ(σ) σ`, with `f_λ = χ_λ(1)` the number
+//! of standard tableaux of the shape; the projections of all shapes add up
+//! to the tensor.
+//!
+//! ```rust
+//! use butler_portugal::decomposition::young_decomposition;
+//! use butler_portugal::{Tensor, TensorIndex};
+//!
+//! // A general rank-3 tensor: one symmetric, two mixed and one antisymmetric part
+//! let t = Tensor::new(
+//!     "T",
+//!     ["a", "b", "c"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect(),
+//! );
+//! let components = young_decomposition(&t)?;
+//! let found: Vec<(Vec<usize>, usize)> =
+//!     components.iter().map(|c| (c.shape.0.clone(), c.multiplicity)).collect();
+//! assert_eq!(found, vec![(vec![3], 1), (vec![2, 1], 2), (vec![1, 1, 1], 1)]);
+//! # Ok::<(), butler_portugal::ButlerPortugalError>(())
+//! ```
+
+use crate::canonicalization::canonicalize;
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::{TensorExpression, TensorProduct};
+use crate::rational::Rational;
+use crate::tensor::Tensor;
+use crate::young_tableaux::Shape;
+use std::collections::HashMap;
+
+/// Largest rank decomposed; the projectors sum over all `n!` slot permutations
+pub const MAX_DECOMPOSITION_RANK: usize = 8;
+
+/// An irreducible component of a tensor's symmetry, with its multiplicity
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct YoungComponent {
+    /// The Young shape labelling the irreducible `GL(V)` module
+    pub shape: Shape,
+    /// How often the module occurs in the tensor
+    pub multiplicity: usize,
+}
+
+/// Decomposes the symmetry of a tensor into irreducible Young components
+///
+/// Returns the shapes of n boxes occurring in the tensor, with their
+/// multiplicities, in decreasing lexicographic order of the shapes, so the
+/// totally symmetric shape comes first. A vanishing tensor has no
+/// components. If every index declares a dimension (see
+/// [`TensorIndex::dimension`](crate::TensorIndex::dimension)), shapes with
+/// more rows than the smallest one are dropped, since their modules vanish.
+///
+/// # Errors
+/// Returns a `ComputationError` if the rank exceeds
+/// [`MAX_DECOMPOSITION_RANK`].
+pub fn young_decomposition(tensor: &Tensor) -> Result<Vec<YoungComponent>> {
+    let rank = checked_rank(tensor)?;
+    let group = tensor.signed_symmetry_group();
+    if tensor.is_zero() || group.is_vanishing() {
+        return Ok(Vec::new());
+    }
+    // Σ sign(g) over the elements of each cycle type
+    let mut weights: HashMap<Vec<usize>, i64> = HashMap::new();
+    let mut order = 0i64;
+    for element in group.elements() {
+        *weights.entry(cycle_type(&element.permutation)).or_default() += i64::from(element.sign);
+        order += 1;
+    }
+    let dimension = tensor
+        .indices()
+        .iter()
+        .map(|index| index.dimension())
+        .min()
+        .flatten();
+    let mut characters = Characters::default();
+    Ok(partitions(rank)
+        .into_iter()
+        .filter(|shape| dimension.map_or(true, |dimension| shape.rows() <= dimension))
+        .filter_map(|shape| {
+            let total: i64 = weights
+                .iter()
+                .map(|(cycles, weight)| weight * characters.value(&shape.0, cycles))
+                .sum();
+            let multiplicity = usize::try_from(total / order).ok()?;
+            (multiplicity > 0).then_some(YoungComponent {
+                shape,
+                multiplicity,
+            })
+        })
+        .collect())
+}
+
+/// Projects a tensor onto the component of a Young shape
+///
+/// Applies the central idempotent of the shape to the slots of the tensor
+/// and collects the permuted copies by their canonical forms. The result is
+/// zero exactly when the shape does not occur in
+/// [`young_decomposition`], and the projections onto all shapes of the
+/// rank sum to the tensor.
+///
+/// # Errors
+/// Returns an `InvalidTensor` error if the shape is not a partition of the
+/// rank, a `ComputationError` if the rank exceeds
+/// [`MAX_DECOMPOSITION_RANK`], and any error raised while canonicalizing.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::decomposition::project_onto_shape;
+/// use butler_portugal::young_tableaux::Shape;
+/// use butler_portugal::{Tensor, TensorIndex};
+///
+/// // The symmetric part of T_ab is (T_ab + T_ba) / 2
+/// let t = Tensor::new("T", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+/// let symmetric = project_onto_shape(&t, &Shape(vec![2]))?;
+/// assert_eq!(symmetric.to_string(), "1/2 T__a _b + 1/2 T__b _a");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn project_onto_shape(tensor: &Tensor, shape: &Shape) -> Result<TensorExpression> {
+    let rank = checked_rank(tensor)?;
+    if shape.size() != rank || shape.0.windows(2).any(|pair| pair[0] < pair[1]) {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "Shape {:?} is not a partition of the rank {rank} of {}",
+            shape.0,
+            tensor.name()
+        )));
+    }
+    let mut characters = Characters::default();
+    let identity = vec![1; rank];
+    let scale = Rational::new(
+        characters.value(&shape.0, &identity),
+        (1..=rank as i64).product(),
+    );
+
+    let mut bare = tensor.clone();
+    bare.set_coefficient(1);
+    let mut forms: Vec<Tensor> = Vec::new();
+    let mut coefficients: HashMap<Tensor, Rational> = HashMap::new();
+    for permutation in all_permutations(rank) {
+        let character = characters.value(&shape.0, &cycle_type(&permutation));
+        if character == 0 {
+            continue;
+        }
+        let mut permuted = bare.clone();
+        *permuted.indices_mut() = permutation
+            .iter()
+            .enumerate()
+            .map(|(slot, &from)| tensor.indices()[from].with_position(slot))
+            .collect();
+        let mut canonical = canonicalize(&permuted)?;
+        let sign = canonical.coefficient();
+        if sign == 0 {
+            continue;
+        }
+        canonical.set_coefficient(1);
+        // Forms are told apart by their labels, not the slots the indices came from
+        let renumbered = canonical
+            .indices()
+            .iter()
+            .enumerate()
+            .map(|(slot, index)| index.with_position(slot))
+            .collect();
+        *canonical.indices_mut() = renumbered;
+        let weight = scale * Rational::integer(character * i64::from(sign));
+        if let Some(coefficient) = coefficients.get_mut(&canonical) {
+            *coefficient += weight;
+        } else {
+            coefficients.insert(canonical.clone(), weight);
+            forms.push(canonical);
+        }
+    }
+    forms.sort_by(|a, b| a.indices().cmp(b.indices()));
+    let coefficient = Rational::integer(i64::from(tensor.coefficient()));
+    Ok(forms
+        .into_iter()
+        .filter_map(|form| {
+            let weight = coefficients[&form] * coefficient;
+            (weight != Rational::ZERO).then(|| TensorProduct::new(weight, vec![form]))
+        })
+        .collect())
+}
+
+/// Returns the rank of the tensor, or an error if it is too large to decompose
+fn checked_rank(tensor: &Tensor) -> Result<usize> {
+    let rank = tensor.rank();
+    if rank > MAX_DECOMPOSITION_RANK {
+        return Err(ButlerPortugalError::ComputationError(format!(
+            "Young decomposition of {} needs rank at most {MAX_DECOMPOSITION_RANK}, got {rank}",
+            tensor.name()
+        )));
+    }
+    Ok(rank)
+}
+
+/// Lists the partitions of `n` in decreasing lexicographic order
+fn partitions(n: usize) -> Vec<Shape> {
+    fn extend(remaining: usize, largest: usize, rows: &mut Vec<usize>, out: &mut Vec<Shape>) {
+        if remaining == 0 {
+            out.push(Shape(rows.clone()));
+            return;
+        }
+        for row in (1..=largest.min(remaining)).rev() {
+            rows.push(row);
+            extend(remaining - row, row, rows, out);
+            rows.pop();
+        }
+    }
+    let mut out = Vec::new();
+    extend(n, n, &mut Vec::new(), &mut out);
+    out
+}
+
+/// Returns the cycle lengths of a permutation, fixed points included, in
+/// decreasing order
+fn cycle_type(permutation: &[usize]) -> Vec<usize> {
+    let mut seen = vec![false; permutation.len()];
+    let mut lengths = Vec::new();
+    for start in 0..permutation.len() {
+        let mut length = 0;
+        let mut current = start;
+        while !seen[current] {
+            seen[current] = true;
+            current = permutation[current];
+            length += 1;
+        }
+        if length > 0 {
+            lengths.push(length);
+        }
+    }
+    lengths.sort_unstable_by(|a, b| b.cmp(a));
+    lengths
+}
+
+/// Lists the permutations of `0..n`
+fn all_permutations(n: usize) -> Vec<Vec<usize>> {
+    let mut permutations = vec![Vec::new()];
+    for point in 0..n {
+        permutations = permutations
+            .into_iter()
+            .flat_map(|partial: Vec<usize>| {
+                (0..=partial.len()).map(move |at| {
+                    let mut extended = partial.clone();
+                    extended.insert(at, point);
+                    extended
+                })
+            })
+            .collect();
+    }
+    permutations
+}
+
+/// Memoized irreducible characters of the symmetric groups
+#[derive(Debug, Default)]
+struct Characters {
+    values: HashMap<(Vec<usize>, Vec<usize>), i64>,
+}
+
+impl Characters {
+    /// Returns `χ_λ` on the class of the given cycle type by the
+    /// Murnaghan–Nakayama rule
+    ///
+    /// The shape is encoded by its beta-numbers `λ_i + k - i`; removing a rim
+    /// hook of length r moves one of them down by r onto a free value, with
+    /// the sign given by the number of beta-numbers jumped over.
+    fn value(&mut self, shape: &[usize], cycles: &[usize]) -> i64 {
+        let Some((&length, rest)) = cycles.split_first() else {
+            return i64::from(shape.is_empty());
+        };
+        let key = (shape.to_vec(), cycles.to_vec());
+        if let Some(&value) = self.values.get(&key) {
+            return value;
+        }
+        let rows = shape.len();
+        let beta: Vec<usize> = shape
+            .iter()
+            .enumerate()
+            .map(|(i, &row)| row + rows - 1 - i)
+            .collect();
+        let mut value = 0;
+        for (i, &bead) in beta.iter().enumerate() {
+            let Some(target) = bead.checked_sub(length) else {
+                continue;
+            };
+            if beta.contains(&target) {
+                continue;
+            }
+            let jumped = beta
+                .iter()
+                .filter(|&&other| target < other && other < bead)
+                .count();
+            let mut moved = beta.clone();
+            moved[i] = target;
+            moved.sort_unstable_by(|a, b| b.cmp(a));
+            let smaller: Vec<usize> = moved
+                .iter()
+                .enumerate()
+                .map(|(j, &b)| b + j + 1 - rows)
+                .filter(|&row| row > 0)
+                .collect();
+            let sign = if jumped % 2 == 0 { 1 } else { -1 };
+            value += sign * self.value(&smaller, rest);
+        }
+        self.values.insert(key, value);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::identity_residual;
+    use crate::presets::riemann;
+    use crate::symmetry::Symmetry;
+    use crate::TensorIndex;
+
+    fn tensor(rank: usize) -> Tensor {
+        let indices = ["a", "b", "c", "d", "e"][..rank]
+            .iter()
+            .enumerate()
+            .map(|(i, n)| TensorIndex::new(n, i))
+            .collect();
+        Tensor::new("T", indices)
+    }
+
+    fn shapes(components: &[YoungComponent]) -> Vec<(Vec<usize>, usize)> {
+        components
+            .iter()
+            .map(|c| (c.shape.0.clone(), c.multiplicity))
+            .collect()
+    }
+
+    #[test]
+    fn test_characters() {
+        let mut characters = Characters::default();
+        // Dimensions are the numbers of standard tableaux
+        assert_eq!(characters.value(&[3, 2], &[1; 5]), 5);
+        assert_eq!(characters.value(&[2, 2, 1], &[1; 5]), 5);
+        assert_eq!(characters.value(&[3, 1, 1], &[1; 5]), 6);
+        // The sign character and the standard representation of S_4
+        assert_eq!(characters.value(&[1, 1, 1, 1], &[4]), -1);
+        assert_eq!(characters.value(&[3, 1], &[2, 1, 1]), 1);
+        assert_eq!(characters.value(&[2, 2], &[3, 1]), -1);
+        assert_eq!(characters.value(&[2, 2], &[2, 2]), 2);
+    }
+
+    #[test]
+    fn test_decomposition_of_monoterm_symmetries() {
+        let mut pair = tensor(3);
+        pair.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        assert_eq!(
+            shapes(&young_decomposition(&pair).unwrap()),
+            vec![(vec![2, 1], 1), (vec![1, 1, 1], 1)]
+        );
+
+        // The monoterm Riemann symmetries leave the Weyl-type shape and a
+        // totally antisymmetric part, which the cyclic identity removes
+        let names = ["a", "b", "c", "d"];
+        let r = riemann(
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(
+            shapes(&young_decomposition(&r).unwrap()),
+            vec![(vec![2, 2], 1), (vec![1, 1, 1, 1], 1)]
+        );
+        let r3 = riemann(
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i).with_dimension(3))
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(
+            shapes(&young_decomposition(&r3).unwrap()),
+            vec![(vec![2, 2], 1)]
+        );
+
+        let mut zero = tensor(2);
+        zero.set_coefficient(0);
+        assert!(young_decomposition(&zero).unwrap().is_empty());
+        let large = Tensor::new(
+            "T",
+            (0..=MAX_DECOMPOSITION_RANK)
+                .map(|i| TensorIndex::new(&format!("a{i}"), i))
+                .collect(),
+        );
+        assert!(young_decomposition(&large).is_err());
+    }
+
+    #[test]
+    fn test_projections_sum_to_tensor() {
+        let mut t = tensor(4);
+        t.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        t.set_coefficient(3);
+        let components = young_decomposition(&t).unwrap();
+        let mut total = TensorExpression::new();
+        for shape in partitions(4) {
+            let projection = project_onto_shape(&t, &shape).unwrap();
+            let occurs = components.iter().any(|c| c.shape == shape);
+            assert_eq!(!projection.is_zero(), occurs, "{shape:?}");
+            total = total.add(&projection);
+        }
+        let original = TensorExpression::from(TensorProduct::new(Rational::integer(3), {
+            let mut bare = t.clone();
+            bare.set_coefficient(1);
+            vec![bare]
+        }));
+        assert!(identity_residual(&total, &original).unwrap().is_zero());
+        assert!(project_onto_shape(&t, &Shape(vec![1, 3])).is_err());
+        assert!(project_onto_shape(&t, &Shape(vec![3])).is_err());
+    }
+}
@@ -0,0 +1,409 @@
+//! Partial, covariant and Lie derivatives of tensors
+//!
+//! A derivative `∇_a T_{bc}` is represented as a single tensor named `∇T`
+//! whose derivative index is prepended: slot 0 carries `a` and the symmetries
+//! of `T` act on the shifted slots. The Lie derivative `£_ξ T` keeps the
+//! indices and symmetries of `T` and is named `£_{ξ}T`; it stays an opaque
+//! factor until expanded into `∂` or `∇` terms with [`expand_lie_derivative`].
+//!
+//! A totally symmetrized tower `∇_{(a1…ak)}φ` is a single tensor named
+//! `∇…∇φ` (k symbols) whose first k slots carry a `Symmetric` symmetry.
+//! Partial derivatives commute, so differentiating a `∂` tower with `∂`
+//! merges into a longer tower; covariant derivatives do not commute and a
+//! further `∇` is simply prepended.
+
+use crate::builtin::is_metric;
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::{TensorExpression, TensorProduct};
+use crate::index::TensorIndex;
+use crate::rational::Rational;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+use std::collections::HashSet;
+
+/// Prefix marking a Lie derivative factor
+const LIE_PREFIX: &str = "£_{";
+
+/// The derivative used when expanding Lie derivatives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DerivativeKind {
+    /// Coordinate partial derivative `∂`
+    Partial,
+    /// Torsion-free, metric-compatible covariant derivative `∇`
+    Covariant,
+}
+
+impl DerivativeKind {
+    /// Returns the symbol prefixed to the name of a differentiated tensor
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            DerivativeKind::Partial => "∂",
+            DerivativeKind::Covariant => "∇",
+        }
+    }
+}
+
+/// Differentiates a tensor, prepending the derivative index as slot 0
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::derivatives::{derivative, DerivativeKind};
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let mut f = Tensor::new("F", vec![TensorIndex::new("b", 0), TensorIndex::new("c", 1)]);
+/// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+/// let df = derivative(&f, TensorIndex::new("a", 0), DerivativeKind::Covariant);
+/// assert_eq!(df.name(), "∇F");
+/// assert_eq!(df.symmetries()[0], Symmetry::antisymmetric(vec![1, 2]));
+/// ```
+pub fn derivative(tensor: &Tensor, index: TensorIndex, kind: DerivativeKind) -> Tensor {
+    let indices = std::iter::once(index)
+        .chain(tensor.indices().iter().cloned())
+        .enumerate()
+        .map(|(position, index)| index.with_position(position))
+        .collect();
+    let name = format!("{}{}", kind.symbol(), tensor.name());
+    let mut result = Tensor::with_coefficient(&name, indices, tensor.coefficient());
+    result.set_perturbative_order(tensor.perturbative_order());
+
+    // ∂_b ∂_{(a1…ak)} T = ∂_{(b a1…ak)} T, so a partial tower absorbs the new slot
+    let order = symmetrized_order(tensor, kind);
+    let merge = kind == DerivativeKind::Partial && order > 0;
+    for symmetry in tensor.symmetries() {
+        if merge && order > 1 && is_tower_symmetry(symmetry, order) {
+            continue;
+        }
+        result.add_symmetry(symmetry.shifted(1));
+    }
+    if merge {
+        result.add_symmetry(Symmetry::symmetric((0..=order).collect()));
+    }
+    result
+}
+
+/// Returns the totally symmetrized derivative `D_{(a1…ak)} T` as a single tensor
+///
+/// The k derivative indices occupy the first k slots and are declared
+/// symmetric; the symmetries of `T` act on the remaining slots.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::derivatives::{symmetrized_derivative, symmetrized_order, DerivativeKind};
+/// use butler_portugal::{canonicalize, Tensor, TensorIndex};
+///
+/// let phi = Tensor::new("phi", vec![]);
+/// let tower = symmetrized_derivative(
+///     &phi,
+///     vec![TensorIndex::new("c", 0), TensorIndex::new("a", 1), TensorIndex::new("b", 2)],
+///     DerivativeKind::Covariant,
+/// );
+/// assert_eq!(tower.name(), "∇∇∇phi");
+/// assert_eq!(symmetrized_order(&tower, DerivativeKind::Covariant), 3);
+/// let canonical = canonicalize(&tower)?;
+/// assert_eq!(canonical.indices()[0].name(), "a");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn symmetrized_derivative(
+    tensor: &Tensor,
+    indices: Vec<TensorIndex>,
+    kind: DerivativeKind,
+) -> Tensor {
+    let order = indices.len();
+    let all_indices = indices
+        .into_iter()
+        .chain(tensor.indices().iter().cloned())
+        .enumerate()
+        .map(|(position, index)| index.with_position(position))
+        .collect();
+    let name = format!("{}{}", kind.symbol().repeat(order), tensor.name());
+    let mut result = Tensor::with_coefficient(&name, all_indices, tensor.coefficient());
+    result.set_perturbative_order(tensor.perturbative_order());
+    for symmetry in tensor.symmetries() {
+        result.add_symmetry(symmetry.shifted(order));
+    }
+    if order > 1 {
+        result.add_symmetry(Symmetry::symmetric((0..order).collect()));
+    }
+    result
+}
+
+/// Returns the order k of a totally symmetrized derivative tower `D_{(a1…ak)} T`
+///
+/// This is the number of leading derivative symbols in the name, provided
+/// the corresponding slots are declared symmetric (a single derivative is
+/// trivially symmetric). Anything else has order 0.
+pub fn symmetrized_order(tensor: &Tensor, kind: DerivativeKind) -> usize {
+    let symbol = kind.symbol();
+    let mut rest = tensor.name();
+    let mut order = 0;
+    while let Some(stripped) = rest.strip_prefix(symbol) {
+        rest = stripped;
+        order += 1;
+    }
+    if order > tensor.rank() {
+        return 0;
+    }
+    let symmetric = order <= 1
+        || tensor
+            .symmetries()
+            .iter()
+            .any(|symmetry| is_tower_symmetry(symmetry, order));
+    if symmetric {
+        order
+    } else {
+        0
+    }
+}
+
+/// Checks whether a symmetry is the full symmetrization of slots `0..order`
+fn is_tower_symmetry(symmetry: &Symmetry, order: usize) -> bool {
+    match symmetry {
+        Symmetry::Symmetric { indices } => {
+            let mut slots = indices.clone();
+            slots.sort_unstable();
+            slots == (0..order).collect::<Vec<_>>()
+        }
+        _ => false,
+    }
+}
+
+/// Returns the partial derivative `∂_a T`
+pub fn partial_derivative(tensor: &Tensor, index: TensorIndex) -> Tensor {
+    derivative(tensor, index, DerivativeKind::Partial)
+}
+
+/// Returns the covariant derivative `∇_a T`
+pub fn covariant_derivative(tensor: &Tensor, index: TensorIndex) -> Tensor {
+    derivative(tensor, index, DerivativeKind::Covariant)
+}
+
+/// Wraps a tensor in an unexpanded Lie derivative `£_ξ T` along the vector `ξ`
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::derivatives::{as_lie_derivative, lie_derivative};
+/// use butler_portugal::{Tensor, TensorIndex};
+///
+/// let g = Tensor::new("g", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+/// let lie = lie_derivative(&g, "xi");
+/// assert_eq!(lie.name(), "£_{xi}g");
+/// let (vector, inner) = as_lie_derivative(&lie).unwrap();
+/// assert_eq!((vector.as_str(), inner.name()), ("xi", "g"));
+/// ```
+pub fn lie_derivative(tensor: &Tensor, vector: &str) -> Tensor {
+    let mut result = tensor.clone();
+    let name = format!("{LIE_PREFIX}{vector}}}{}", tensor.name());
+    result.set_name(&name);
+    result
+}
+
+/// Splits a Lie derivative factor into its vector field and the tensor it acts on
+///
+/// Only the outermost Lie derivative is unwrapped.
+pub fn as_lie_derivative(tensor: &Tensor) -> Option<(String, Tensor)> {
+    let rest = tensor.name().strip_prefix(LIE_PREFIX)?;
+    let (vector, inner_name) = rest.split_once('}')?;
+    let mut inner = tensor.clone();
+    inner.set_name(inner_name);
+    Some((vector.to_string(), inner))
+}
+
+/// Expands a Lie derivative factor into derivative terms
+///
+/// For a torsion-free derivative `D`
+/// `£_ξ T_a{}^b = ξ^c D_c T_a{}^b + T_c{}^b D_a ξ^c - T_a{}^c D_c ξ^b`,
+/// with one such correction for every index. For the covariant derivative
+/// the transport term of a metric vanishes, so `£_ξ g_{ab}` expands to the
+/// Killing form `g_{cb} ∇_a ξ^c + g_{ac} ∇_b ξ^c`.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::derivatives::{expand_lie_derivative, lie_derivative, DerivativeKind};
+/// use butler_portugal::{Tensor, TensorIndex};
+///
+/// let v = Tensor::new("v", vec![TensorIndex::contravariant("a", 0)]);
+/// let lie = lie_derivative(&v, "xi");
+/// let expanded = expand_lie_derivative(&lie, DerivativeKind::Partial)?;
+/// assert_eq!(expanded.to_string(), "xi_^c ∂v__c ^a - v_^c ∂xi__c ^a");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn expand_lie_derivative(tensor: &Tensor, kind: DerivativeKind) -> Result<TensorExpression> {
+    expand_lie_derivative_avoiding(tensor, kind, &HashSet::new())
+}
+
+/// Expands a Lie derivative, choosing a dummy index name outside `avoid`
+pub(crate) fn expand_lie_derivative_avoiding(
+    tensor: &Tensor,
+    kind: DerivativeKind,
+    avoid: &HashSet<String>,
+) -> Result<TensorExpression> {
+    let (vector, inner) = as_lie_derivative(tensor).ok_or_else(|| {
+        ButlerPortugalError::InvalidTensor(format!("{} is not a Lie derivative", tensor.name()))
+    })?;
+    let mut used = avoid.clone();
+    used.extend(tensor.indices().iter().map(|i| i.name().to_string()));
+    let dummy = fresh_index_name(&used);
+
+    let coefficient = Rational::from(inner.coefficient());
+    let mut bare = inner.clone();
+    bare.set_coefficient(1);
+    let vector_with = |index: TensorIndex| Tensor::new(&vector, vec![index.with_position(0)]);
+
+    let mut expression = TensorExpression::new();
+
+    // Transport term ξ^c D_c T
+    if !(kind == DerivativeKind::Covariant && is_metric(&bare)) {
+        expression.push(TensorProduct::new(
+            coefficient,
+            vec![
+                vector_with(TensorIndex::contravariant(&dummy, 0)),
+                derivative(&bare, TensorIndex::covariant(&dummy, 0), kind),
+            ],
+        ));
+    }
+
+    // One correction term per index of T
+    for (slot, index) in inner.indices().iter().enumerate() {
+        let mut replaced = bare.clone();
+        replaced.indices_mut()[slot] = index.with_name(&dummy);
+        // The derivative slot is covariant and ξ's slot contravariant in both cases
+        let (mut lower, mut upper, sign) = if index.is_contravariant() {
+            // -T^{…c…} D_c ξ^b
+            (index.with_name(&dummy), index.clone(), -Rational::ONE)
+        } else {
+            // +T_{…c…} D_a ξ^c
+            (index.clone(), index.with_name(&dummy), Rational::ONE)
+        };
+        lower.set_contravariant(false);
+        upper.set_contravariant(true);
+        expression.push(TensorProduct::new(
+            sign * coefficient,
+            vec![replaced, derivative(&vector_with(upper), lower, kind)],
+        ));
+    }
+    Ok(expression)
+}
+
+/// Picks an index name that does not occur in `used`
+pub(crate) fn fresh_index_name(used: &HashSet<String>) -> String {
+    ('c'..='z')
+        .chain('a'..='b')
+        .map(String::from)
+        .chain((1..).map(|n| format!("c{n}")))
+        .find(|name| !used.contains(name))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtin::metric;
+
+    #[test]
+    fn test_derivative_prepends_slot() {
+        let mut r = Tensor::new(
+            "R",
+            (0..4)
+                .map(|i| TensorIndex::new(&format!("b{i}"), i))
+                .collect(),
+        );
+        r.add_symmetry(Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]));
+        let dr = partial_derivative(&r, TensorIndex::new("a", 0));
+        assert_eq!(dr.name(), "∂R");
+        assert_eq!(dr.rank(), 5);
+        assert_eq!(dr.indices()[0].name(), "a");
+        assert_eq!(dr.indices()[4].position(), 4);
+        assert_eq!(
+            dr.symmetries()[0],
+            Symmetry::symmetric_pairs(vec![(1, 2), (3, 4)])
+        );
+    }
+
+    #[test]
+    fn test_lie_derivative_round_trip() {
+        let t = Tensor::with_coefficient("T", vec![TensorIndex::new("a", 0)], 3);
+        let lie = lie_derivative(&lie_derivative(&t, "eta"), "xi");
+        let (outer, inner) = as_lie_derivative(&lie).unwrap();
+        assert_eq!(outer, "xi");
+        assert_eq!(inner.name(), "£_{eta}T");
+        assert_eq!(inner.coefficient(), 3);
+        assert!(as_lie_derivative(&t).is_none());
+        assert!(expand_lie_derivative(&t, DerivativeKind::Partial).is_err());
+    }
+
+    #[test]
+    fn test_killing_equation() {
+        let g = metric(TensorIndex::new("a", 0), TensorIndex::new("b", 1));
+        let lie = lie_derivative(&g, "xi");
+
+        let covariant = expand_lie_derivative(&lie, DerivativeKind::Covariant).unwrap();
+        assert_eq!(covariant.terms().len(), 2);
+        assert_eq!(
+            covariant.to_string(),
+            "g__c _b ∇xi__a ^c + g__a _c ∇xi__b ^c"
+        );
+
+        // With partial derivatives the transport term survives
+        let partial = expand_lie_derivative(&lie, DerivativeKind::Partial).unwrap();
+        assert_eq!(partial.terms().len(), 3);
+    }
+
+    #[test]
+    fn test_dummy_avoids_existing_names() {
+        let t = Tensor::new(
+            "T",
+            vec![TensorIndex::new("c", 0), TensorIndex::new("d", 1)],
+        );
+        let expanded =
+            expand_lie_derivative(&lie_derivative(&t, "xi"), DerivativeKind::Partial).unwrap();
+        assert!(expanded.terms()[0].factors()[0].indices()[0].name() == "e");
+    }
+
+    #[test]
+    fn test_partial_towers_merge() {
+        let phi = Tensor::new("phi", vec![]);
+        let tower = symmetrized_derivative(
+            &phi,
+            vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)],
+            DerivativeKind::Partial,
+        );
+        let merged = partial_derivative(&tower, TensorIndex::new("c", 0));
+        assert_eq!(merged.name(), "∂∂∂phi");
+        assert_eq!(merged.symmetries(), &[Symmetry::symmetric(vec![0, 1, 2])]);
+        assert_eq!(symmetrized_order(&merged, DerivativeKind::Partial), 3);
+
+        // Repeated partial derivatives build a tower from scratch
+        let d2 = partial_derivative(
+            &partial_derivative(&phi, TensorIndex::new("b", 0)),
+            TensorIndex::new("a", 0),
+        );
+        assert_eq!(symmetrized_order(&d2, DerivativeKind::Partial), 2);
+    }
+
+    #[test]
+    fn test_covariant_towers_do_not_merge() {
+        let mut v = Tensor::new("v", vec![TensorIndex::new("d", 0)]);
+        v.set_coefficient(2);
+        let tower = symmetrized_derivative(
+            &v,
+            vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)],
+            DerivativeKind::Covariant,
+        );
+        assert_eq!(tower.coefficient(), 2);
+        assert_eq!(symmetrized_order(&tower, DerivativeKind::Covariant), 2);
+
+        let outer = covariant_derivative(&tower, TensorIndex::new("c", 0));
+        assert_eq!(outer.symmetries(), &[Symmetry::symmetric(vec![1, 2])]);
+        assert_eq!(symmetrized_order(&outer, DerivativeKind::Covariant), 0);
+
+        // The derivative slots canonicalize among themselves only
+        let canonical = crate::canonicalize(&symmetrized_derivative(
+            &v,
+            vec![TensorIndex::new("z", 0), TensorIndex::new("y", 1)],
+            DerivativeKind::Covariant,
+        ))
+        .unwrap();
+        let names: Vec<&str> = canonical.indices().iter().map(|i| i.name()).collect();
+        assert_eq!(names, vec!["y", "z", "d"]);
+    }
+}
@@ -5,6 +5,7 @@
 
 use std::error::Error;
 use std::fmt;
+use std::ops::Range;
 
 /// Result type for Butler-Portugal operations
 pub type Result<T> = std::result::Result<T, ButlerPortugalError>;
@@ -32,6 +33,90 @@ pub enum ButlerPortugalError {
 
     /// Generic computation error
     ComputationError(String),
+
+    /// Input text that could not be parsed, with the offending span
+    Parse(ParseError),
+
+    /// A canonicalization that passed its deadline or was cancelled, see
+    /// [`CanonicalizationConfig::deadline`](crate::config::CanonicalizationConfig::deadline)
+    Timeout,
+}
+
+/// Where and why parsing failed
+///
+/// The span is a byte range into the parsed text, so the offending characters
+/// can be underlined; see [`ParseError::underline`].
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::error::ParseError;
+///
+/// let err = ParseError::new("unexpected character", 3..4).expecting(&["_", "^"]);
+/// assert_eq!(err.to_string(), "unexpected character at bytes 3..4, expected `_` or `^`");
+/// assert_eq!(err.underline("R_ab!c"), "R_ab!c\n   ^");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// What went wrong
+    pub message: String,
+    /// The byte range of the offending input
+    pub span: Range<usize>,
+    /// The tokens that would have been accepted at the start of the span
+    pub expected: Vec<String>,
+}
+
+impl ParseError {
+    /// Creates a parse error covering `span`, with no expected tokens
+    pub fn new(message: &str, span: Range<usize>) -> Self {
+        Self {
+            message: message.to_string(),
+            span,
+            expected: Vec::new(),
+        }
+    }
+
+    /// Returns the error with the tokens that would have been accepted
+    pub fn expecting(mut self, tokens: &[&str]) -> Self {
+        self.expected = tokens.iter().map(ToString::to_string).collect();
+        self
+    }
+
+    /// Renders the line of `source` containing the span with carets beneath it
+    ///
+    /// Spans are clamped to the line, and an empty span (such as the end of
+    /// input) is marked with a single caret.
+    pub fn underline(&self, source: &str) -> String {
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let line = &source[line_start..line_end];
+        let end = self.span.end.clamp(start, line_end);
+        let indent = source[line_start..start].chars().count();
+        let width = source[start..end].chars().count().max(1);
+        format!("{line}\n{}{}", " ".repeat(indent), "^".repeat(width))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at bytes {}..{}",
+            self.message, self.span.start, self.span.end
+        )?;
+        if let Some((last, rest)) = self.expected.split_last() {
+            let quoted = |token: &String| format!("`{token}`");
+            if rest.is_empty() {
+                write!(f, ", expected {}", quoted(last))?;
+            } else {
+                let rest: Vec<String> = rest.iter().map(quoted).collect();
+                write!(f, ", expected {} or {}", rest.join(", "), quoted(last))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for ButlerPortugalError {
@@ -58,6 +143,15 @@ impl fmt::Display for ButlerPortugalError {
             ButlerPortugalError::ComputationError(msg) => {
                 write!(f, "Computation error: {msg}")
             }
+            ButlerPortugalError::Parse(err) => {
+                write!(f, "Parse error: {err}")
+            }
+            ButlerPortugalError::Timeout => {
+                write!(
+                    f,
+                    "Timeout: canonicalization was cancelled or passed its deadline"
+                )
+            }
         }
     }
 }
@@ -68,6 +162,12 @@ impl Error for ButlerPortugalError {
     }
 }
 
+impl From<ParseError> for ButlerPortugalError {
+    fn from(err: ParseError) -> Self {
+        ButlerPortugalError::Parse(err)
+    }
+}
+
 impl From<std::num::ParseIntError> for ButlerPortugalError {
     fn from(err: std::num::ParseIntError) -> Self {
         ButlerPortugalError::ComputationError(format!("Parse error: {err}"))
@@ -175,6 +275,22 @@ mod tests {
         assert!(display.contains("test message"));
     }
 
+    #[test]
+    fn test_parse_error_spans() {
+        let err = ParseError::new("unknown tensor", 12..15).expecting(&["R", "g", "T"]);
+        assert_eq!(
+            ButlerPortugalError::from(err.clone()).to_string(),
+            "Parse error: unknown tensor at bytes 12..15, expected `R`, `g` or `T`"
+        );
+        assert_eq!(
+            err.underline("A_a +\nB_b + Xyz_c"),
+            "B_b + Xyz_c\n      ^^^"
+        );
+        // The end of input is marked with one caret past the last character
+        let end = ParseError::new("unexpected end of input", 3..3);
+        assert_eq!(end.underline("R_a"), "R_a\n   ^");
+    }
+
     #[test]
     fn test_index_bounds_validation() {
         assert!(validate_index_bounds(0, 5).is_ok());
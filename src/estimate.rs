@@ -0,0 +1,194 @@
+//! Up-front cost estimates for canonicalization
+//!
+//! Canonicalization descends the stabilizer chain of the slot symmetry group
+//! of a tensor, so its cost grows with the basic orbit lengths and, for
+//! repeated index names, with the number of tying cosets; groups moving
+//! indices between index classes are still enumerated in full.
+//! [`estimate_resources`] builds only the stabilizer chain and derives the
+//! sizes that matter from it, so a service can reject or reroute
+//! pathological inputs before running them.
+
+use crate::canonicalization::slot_chain;
+use crate::config::CanonicalizationConfig;
+use crate::error::Result;
+use crate::tensor::Tensor;
+use std::mem::size_of;
+
+/// Predicted sizes of one canonicalization
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ResourceEstimate {
+    /// Number of slots of the tensor
+    pub rank: usize,
+    /// Order of the slot symmetry group, saturating at `u128::MAX`
+    pub group_order: u128,
+    /// Length of the base of the stabilizer chain
+    pub base_length: usize,
+    /// Bytes needed to store the transversals of the stabilizer chain
+    /// explicitly, one permutation per orbit point and level
+    pub transversal_bytes: u128,
+    /// Bytes needed to hold every group element during enumeration, zero
+    /// unless the group is enumerated
+    pub enumeration_bytes: u128,
+    /// Lower bound on the number of candidate forms compared
+    pub min_candidates: u128,
+    /// Upper bound on the number of candidate forms compared
+    pub max_candidates: u128,
+}
+
+impl ResourceEstimate {
+    /// Returns the estimated peak memory in bytes
+    pub fn total_bytes(&self) -> u128 {
+        self.transversal_bytes
+            .saturating_add(self.enumeration_bytes)
+    }
+}
+
+/// Estimates the cost of canonicalizing `tensor` under `config`
+///
+/// Only the stabilizer chain of the slot symmetry group is built; no group
+/// element is enumerated. The candidate count is exact when all index names
+/// are distinct and the group keeps every index in a slot of its class.
+/// Repeated names can make cosets tie, and each tying coset adds candidates
+/// up to the number of cosets at its level. For a group moving indices
+/// between classes every element is a candidate, unless ruled out by the
+/// classes or by repeated names in antisymmetric slots. No option of `config`
+/// changes the cost today; it is taken so the estimate follows the same
+/// settings as [`canonicalize_with_config`](crate::canonicalization::canonicalize_with_config).
+///
+/// # Errors
+/// Returns an `InvalidSymmetry` error if a symmetry of the tensor is
+/// inconsistent with its rank.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::config::CanonicalizationConfig;
+/// use butler_portugal::estimate::estimate_resources;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let mut s = Tensor::new("S", (0..12).map(|i| TensorIndex::new(&format!("i{i}"), i)).collect());
+/// s.add_symmetry(Symmetry::symmetric((0..12).collect()));
+/// let estimate = estimate_resources(&s, &CanonicalizationConfig::new())?;
+/// assert_eq!(estimate.group_order, 479_001_600);
+/// // 12 + 11 + ... + 1 candidates along the chain
+/// assert_eq!(estimate.max_candidates, 78);
+/// assert_eq!(estimate.enumeration_bytes, 0);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn estimate_resources(
+    tensor: &Tensor,
+    _config: &CanonicalizationConfig,
+) -> Result<ResourceEstimate> {
+    crate::canonicalization::validate_symmetries(tensor)?;
+    let rank = tensor.rank();
+    let bsgs = tensor.symmetry_group();
+    let group_order = bsgs.order();
+    let permutation_bytes = (rank * size_of::<usize>()) as u128;
+
+    let trivial = tensor.is_zero() || rank <= 1;
+    let (orbits, enumeration_bytes, min_candidates, max_candidates) =
+        if let Some(levels) = slot_chain(tensor) {
+            let orbits: Vec<u128> = levels
+                .iter()
+                .map(|level| level.orbit().len() as u128)
+                .collect();
+            // Every level compares its orbit once per tying coset, and there
+            // are at most as many of those as cosets of the next stabilizer
+            let along_chain: u128 = orbits.iter().sum();
+            let mut cosets = 1u128;
+            let mut all_cosets = 0u128;
+            for &orbit in &orbits {
+                cosets = cosets.saturating_mul(orbit);
+                all_cosets = all_cosets.saturating_add(cosets);
+            }
+            let (min, max) = if trivial {
+                (0, 0)
+            } else if has_distinct_names(tensor) {
+                (along_chain, along_chain)
+            } else {
+                (along_chain, all_cosets)
+            };
+            (orbits, 0, min, max)
+        } else {
+            let orbits = bsgs
+                .transversals()
+                .iter()
+                .map(|transversal| transversal.orbit().len() as u128)
+                .collect();
+            // The element list plus the set of visited elements
+            let enumeration_bytes = group_order
+                .saturating_mul(permutation_bytes)
+                .saturating_mul(2);
+            let max = if trivial { 0 } else { group_order };
+            (orbits, enumeration_bytes, 0, max)
+        };
+    let orbit_points: u128 = orbits.iter().sum();
+
+    Ok(ResourceEstimate {
+        rank,
+        group_order,
+        base_length: bsgs.base.len(),
+        transversal_bytes: orbit_points.saturating_mul(permutation_bytes),
+        enumeration_bytes,
+        min_candidates,
+        max_candidates,
+    })
+}
+
+/// Checks that no index name occurs twice
+fn has_distinct_names(tensor: &Tensor) -> bool {
+    let indices = tensor.indices();
+    indices.iter().enumerate().all(|(i, index)| {
+        indices[..i]
+            .iter()
+            .all(|other| other.name() != index.name())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalization::canonicalize_with_stats;
+    use crate::presets::riemann;
+    use crate::symmetry::Symmetry;
+    use crate::TensorIndex;
+
+    fn indices(names: &[&str]) -> Vec<TensorIndex> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| TensorIndex::new(n, i))
+            .collect()
+    }
+
+    #[test]
+    fn test_estimate_matches_actual_run() {
+        let config = CanonicalizationConfig::new();
+        let r = riemann(indices(&["d", "c", "b", "a"])).unwrap();
+        let estimate = estimate_resources(&r, &config).unwrap();
+        let (_, stats) = canonicalize_with_stats(&r).unwrap();
+        assert_eq!(estimate.group_order, stats.group_order as u128);
+        assert_eq!(estimate.min_candidates, stats.candidates as u128);
+        assert_eq!(estimate.max_candidates, stats.candidates as u128);
+        assert!(estimate.transversal_bytes > 0);
+        assert!(estimate.total_bytes() >= estimate.enumeration_bytes);
+
+        // Repeated names can make cosets tie
+        let mut f = Tensor::new("F", indices(&["a", "a", "b"]));
+        f.add_symmetry(Symmetry::antisymmetric(vec![1, 2]));
+        let estimate = estimate_resources(&f, &config).unwrap();
+        let (_, stats) = canonicalize_with_stats(&f).unwrap();
+        assert_eq!((estimate.min_candidates, estimate.max_candidates), (4, 5));
+        assert!(stats.candidates as u128 <= estimate.max_candidates);
+        assert!(stats.candidates as u128 >= estimate.min_candidates);
+
+        let scalar = Tensor::new("x", Vec::new());
+        assert_eq!(
+            estimate_resources(&scalar, &config).unwrap().max_candidates,
+            0
+        );
+
+        let mut bad = Tensor::new("B", indices(&["a", "b"]));
+        bad.add_symmetry(Symmetry::custom(vec![vec![1, 0], vec![1, 0]], vec![1, -1]));
+        assert!(estimate_resources(&bad, &config).is_err());
+    }
+}
@@ -0,0 +1,379 @@
+//! Step-by-step accounts of canonicalization
+//!
+//! [`explain`] canonicalizes a tensor like [`canonicalize`] and also records
+//! how the canonical form is reached: a shortest sequence of generators of the
+//! declared symmetries leading from the input to it, with every intermediate
+//! form and its sign. A vanishing tensor is explained by a sequence returning
+//! the input arrangement with the opposite sign. The result renders as plain
+//! text through `Display` and as a LaTeX `aligned` block through
+//! [`ToLatex`], which helps when teaching the method or when auditing a
+//! surprising sign.
+//!
+//! [`canonicalize`]: crate::canonicalization::canonicalize
+
+use crate::canonicalization::{
+    minimal_permutation, symmetry_to_generators, validate_symmetries, Permutation,
+};
+use crate::error::{ButlerPortugalError, Result};
+use crate::latex::ToLatex;
+use crate::permutation::SignedPermutation;
+use crate::schreier_sims::SignedBSGS;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// One generator application in an [`Explanation`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplanationStep {
+    /// Position of the applied symmetry among the tensor's symmetries
+    pub symmetry: usize,
+    /// The generator, acting on slots as in [`Tensor::permute`]
+    pub generator: Permutation,
+    /// The form after applying the generator, with its coefficient
+    pub form: Tensor,
+}
+
+impl ExplanationStep {
+    /// Returns the sign the step introduced relative to the previous form
+    pub fn sign(&self, previous: &Tensor) -> i32 {
+        self.form.coefficient().signum() * previous.coefficient().signum()
+    }
+}
+
+/// The way from a tensor to its canonical form
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// The tensor as given
+    pub input: Tensor,
+    /// The generator applications, in order
+    pub steps: Vec<ExplanationStep>,
+    /// The canonical form, with coefficient zero if the tensor vanishes
+    pub canonical: Tensor,
+}
+
+impl Explanation {
+    /// Returns true if the tensor vanishes by its symmetries
+    pub fn vanishes(&self) -> bool {
+        self.canonical.coefficient() == 0 && self.input.coefficient() != 0
+    }
+
+    /// Describes the generator of a step: its cycles and the symmetry it
+    /// comes from
+    fn describe(&self, step: &ExplanationStep) -> String {
+        let kind = self
+            .input
+            .symmetries()
+            .get(step.symmetry)
+            .map_or("symmetry", kind_name);
+        format!("{} by {kind} #{}", step.generator, step.symmetry)
+    }
+}
+
+/// Largest slot group order explained; finding a shortest sequence of
+/// generators searches through the whole group
+pub const MAX_EXPLAINED_ORDER: u128 = 40_320;
+
+/// Canonicalizes a tensor and records how the canonical form is reached
+///
+/// The canonical form is the one [`canonicalize`](crate::canonicalize)
+/// returns. The steps form a shortest sequence of symmetry generators
+/// leading to it; the form after the last step shows the canonical index
+/// arrangement and sign, or, for a vanishing tensor, the input arrangement
+/// with the opposite sign. Symmetries contradicting each other make every
+/// tensor carrying them vanish; no steps are recorded then.
+///
+/// # Errors
+/// Returns an `InvalidSymmetry` error if a symmetry of the tensor is
+/// inconsistent with its rank, and a `ComputationError` if its slot group
+/// has more than [`MAX_EXPLAINED_ORDER`] elements.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::explain::explain;
+/// use butler_portugal::presets::riemann;
+/// use butler_portugal::TensorIndex;
+///
+/// let r = riemann(["b", "a", "d", "c"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+/// let explanation = explain(&r)?;
+/// assert_eq!(explanation.steps.len(), 2);
+/// assert_eq!(explanation.canonical.to_string(), "R__a _b _c _d");
+/// println!("{explanation}");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn explain(tensor: &Tensor) -> Result<Explanation> {
+    validate_symmetries(tensor)?;
+    let mut canonical = tensor.clone();
+    if tensor.coefficient() == 0 || tensor.rank() <= 1 {
+        return Ok(Explanation {
+            input: tensor.clone(),
+            steps: Vec::new(),
+            canonical,
+        });
+    }
+
+    let signed = tensor.signed_symmetry_group();
+    if signed.is_vanishing() {
+        canonical.set_coefficient(0);
+        return Ok(Explanation {
+            input: tensor.clone(),
+            steps: Vec::new(),
+            canonical,
+        });
+    }
+    if signed.order() > MAX_EXPLAINED_ORDER {
+        return Err(ButlerPortugalError::ComputationError(format!(
+            "The slot group of {} has {} elements, more than the {MAX_EXPLAINED_ORDER} that are explained",
+            tensor.name(),
+            signed.order()
+        )));
+    }
+    let group: Vec<SignedPermutation> = signed.elements().collect();
+    let (best, _) = minimal_permutation(tensor, &group);
+    let target = if let Some((candidate, coefficient)) = best {
+        canonical = tensor.permute(&group[candidate].permutation)?;
+        canonical.set_coefficient(coefficient);
+        Some(group[candidate].permutation.clone())
+    } else {
+        {
+            canonical.set_coefficient(0);
+            // An element fixing the arrangement with sign -1, if there is one
+            group
+                .iter()
+                .find(|element| {
+                    let indices = tensor.indices();
+                    element.sign == -1
+                        && element.permutation.iter().enumerate().all(|(k, &source)| {
+                            indices[source].name() == indices[k].name()
+                                && indices[source].is_contravariant()
+                                    == indices[k].is_contravariant()
+                        })
+                })
+                .map(|element| element.permutation.clone())
+        }
+    };
+
+    let steps = match target {
+        Some(target) => replay(tensor, &signed, &shortest_word(tensor, &target)?)?,
+        None => Vec::new(),
+    };
+    Ok(Explanation {
+        input: tensor.clone(),
+        steps,
+        canonical,
+    })
+}
+
+/// Finds a shortest sequence of symmetry generators composing to `target`,
+/// by breadth-first search over the group
+fn shortest_word(tensor: &Tensor, target: &[usize]) -> Result<Vec<(usize, Permutation)>> {
+    let rank = tensor.rank();
+    let identity: Permutation = (0..rank).collect();
+    let generators: Vec<(usize, Permutation)> = tensor
+        .symmetries()
+        .iter()
+        .enumerate()
+        .flat_map(|(symmetry, declared)| {
+            symmetry_to_generators(declared, rank)
+                .into_iter()
+                .map(move |generator| (symmetry, generator))
+        })
+        .filter(|(_, generator)| *generator != identity)
+        .collect();
+
+    // Each reached element maps to the element and generator it came from
+    let mut parents: HashMap<Permutation, Option<(Permutation, usize)>> = HashMap::new();
+    parents.insert(identity.clone(), None);
+    let mut queue = VecDeque::from([identity]);
+    while let Some(current) = queue.pop_front() {
+        if *current == *target {
+            break;
+        }
+        for (g, (_, generator)) in generators.iter().enumerate() {
+            let next: Permutation = generator.iter().map(|&slot| current[slot]).collect();
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some((current.clone(), g)));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let mut word = Vec::new();
+    let mut current = Permutation::from(target);
+    loop {
+        match parents.get(&current) {
+            Some(Some((parent, g))) => {
+                word.push(generators[*g].clone());
+                current = parent.clone();
+            }
+            Some(None) => break,
+            None => {
+                return Err(ButlerPortugalError::InvalidPermutation(format!(
+                    "Permutation {target:?} is not generated by the symmetries of {}",
+                    tensor.name()
+                )))
+            }
+        }
+    }
+    word.reverse();
+    Ok(word)
+}
+
+/// Applies the generators in turn, recording every intermediate form with
+/// its sign in the group
+fn replay(
+    tensor: &Tensor,
+    group: &SignedBSGS,
+    word: &[(usize, Permutation)],
+) -> Result<Vec<ExplanationStep>> {
+    let mut reached: Permutation = (0..tensor.rank()).collect();
+    word.iter()
+        .map(|(symmetry, generator)| {
+            reached = generator.iter().map(|&slot| reached[slot]).collect();
+            let mut form = tensor.permute(&reached)?;
+            form.set_coefficient(tensor.coefficient() * group.sign(&reached).unwrap_or(0));
+            Ok(ExplanationStep {
+                symmetry: *symmetry,
+                generator: generator.clone(),
+                form,
+            })
+        })
+        .collect()
+}
+
+/// Returns a short name for the kind of a symmetry
+fn kind_name(symmetry: &Symmetry) -> &'static str {
+    match symmetry {
+        Symmetry::Symmetric { .. } => "symmetric",
+        Symmetry::Antisymmetric { .. } => "antisymmetric",
+        Symmetry::SymmetricPairs { .. } => "pair exchange",
+        Symmetry::Cyclic { .. } => "cyclic",
+        Symmetry::AntiCyclic { .. } => "anticyclic",
+        Symmetry::YoungShape { .. } => "Young shape",
+        Symmetry::Custom { .. } => "custom",
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "  {}", self.input)?;
+        for step in &self.steps {
+            write!(f, "\n= {}    [{}]", step.form, self.describe(step))?;
+        }
+        if self.vanishes() {
+            if self.steps.is_empty() {
+                write!(f, "\n= 0")?;
+            } else {
+                write!(f, "\n= 0    [equal to its own negative]")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ToLatex for Explanation {
+    fn to_latex(&self) -> String {
+        let mut lines = vec![format!("& {}", self.input.to_latex())];
+        for step in &self.steps {
+            lines.push(format!(
+                "&= {} && \\text{{{}}}",
+                step.form.to_latex(),
+                self.describe(step).replace('#', "\\#")
+            ));
+        }
+        if self.vanishes() {
+            lines.push("&= 0".to_string());
+        }
+        format!(
+            "\\begin{{aligned}}\n{}\n\\end{{aligned}}",
+            lines.join(" \\\\\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalize;
+    use crate::TensorIndex;
+
+    fn tensor(names: &[&str], symmetries: Vec<Symmetry>) -> Tensor {
+        let mut t = Tensor::new(
+            "T",
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        );
+        for symmetry in symmetries {
+            t.add_symmetry(symmetry);
+        }
+        t
+    }
+
+    #[test]
+    fn test_explanation_reaches_canonical_form() {
+        let t = tensor(
+            &["d", "c", "b", "a"],
+            vec![
+                Symmetry::antisymmetric(vec![0, 1, 2]),
+                Symmetry::cyclic(vec![1, 2, 3]),
+            ],
+        );
+        let explanation = explain(&t).unwrap();
+        let canonical = canonicalize(&t).unwrap();
+        assert_eq!(explanation.canonical.to_string(), canonical.to_string());
+        let last = explanation.steps.last().unwrap();
+        assert_eq!(last.form.to_string(), canonical.to_string());
+        let mut previous = &t;
+        for step in &explanation.steps {
+            assert_eq!(step.sign(previous).abs(), 1);
+            previous = &step.form;
+        }
+        assert!(explanation.to_string().starts_with("  T__d _c _b _a\n= "));
+
+        // F_aa vanishes: one transposition returns -F_aa
+        let f = tensor(&["a", "a"], vec![Symmetry::antisymmetric(vec![0, 1])]);
+        let explanation = explain(&f).unwrap();
+        assert!(explanation.vanishes());
+        assert_eq!(
+            explanation.to_string(),
+            "  T__a _a\n= -T__a _a    [(0 1) by antisymmetric #0]\n= 0    [equal to its own negative]"
+        );
+        assert_eq!(
+            explanation.to_latex(),
+            "\\begin{aligned}\n& T_{a a} \\\\\n&= -T_{a a} && \\text{(0 1) by antisymmetric \\#0} \\\\\n&= 0\n\\end{aligned}"
+        );
+
+        // Antisymmetry over three slots and symmetry over two of them and a
+        // fourth contradict each other: no steps, only the zero
+        let contradictory = tensor(
+            &["d", "c", "b", "a"],
+            vec![
+                Symmetry::antisymmetric(vec![0, 1, 2]),
+                Symmetry::symmetric(vec![2, 3]),
+            ],
+        );
+        let explanation = explain(&contradictory).unwrap();
+        assert!(explanation.vanishes());
+        assert!(explanation.steps.is_empty());
+        assert_eq!(explanation.to_string(), "  T__d _c _b _a\n= 0");
+        assert_eq!(canonicalize(&contradictory).unwrap().coefficient(), 0);
+
+        // Too large a group is refused rather than searched
+        let names = ["i", "h", "g", "f", "e", "d", "c", "b", "a"];
+        let large = tensor(&names, vec![Symmetry::symmetric((0..9).collect())]);
+        assert!(matches!(
+            explain(&large),
+            Err(ButlerPortugalError::ComputationError(_))
+        ));
+        assert_eq!(
+            canonicalize(&large).unwrap().to_string(),
+            "T__a _b _c _d _e _f _g _h _i"
+        );
+
+        let scalar = tensor(&["a"], Vec::new());
+        assert!(explain(&scalar).unwrap().steps.is_empty());
+    }
+}
@@ -0,0 +1,1739 @@
+//! Tensor expressions: products and sums of tensors
+//!
+//! This module provides `TensorProduct`, a monomial made of several tensor
+//! factors with a rational coefficient, and `TensorExpression`, a sum of such
+//! monomials. Factor coefficients are folded into the monomial coefficient on
+//! construction, so every factor carries a unit coefficient.
+//!
+//! Both types are generic over their coefficient type (see
+//! [`Coefficient`]), which defaults to the exact [`Rational`].
+//!
+//! `TensorExpression::simplify` canonicalizes every factor with its monoterm
+//! symmetries and collects like terms. Factors with the Riemann symmetries are
+//! additionally brought into a first-Bianchi normal form: for index labels
+//! `a < b < c < d` (in canonical order) the ordering `R_{acbd}` is eliminated
+//! through `R_{acbd} = R_{abcd} + R_{adbc}`, so only `R_{abcd}` and `R_{adbc}`
+//! type orderings remain. Products contracting a symmetric slot pair against
+//! an antisymmetric one are recognized as zero and dropped.
+//!
+//! In a product with several identical factors (same name, slots and
+//! symmetries) the exchange of those factors joins their slot symmetries in
+//! the symmetry group of the product, so equivalent contraction patterns such
+//! as `R_{ab}^{cd} R_{cd}^{ef} R_{ef}^{ab}` and `R_{ab}^{ef} R_{ef}^{cd}
+//! R_{cd}^{ab}` share one canonical form. Dummy indices of every product are
+//! relabelled to the first names not taken by a free index, so `A_a B^a` and
+//! `A_b B^b` do too.
+
+use crate::builtin::{contract_epsilons, is_metric, MetricSignature};
+use crate::canonicalization::{canonicalize, compare_forms, next_permutation, Permutation};
+use crate::coefficient::Coefficient;
+use crate::derivatives::{
+    as_lie_derivative, expand_lie_derivative_avoiding, lie_derivative, DerivativeKind,
+};
+use crate::error::{ButlerPortugalError, Result};
+use crate::index::{IndexClass, TensorIndex};
+use crate::invariants::dummy_name;
+use crate::presets::trace_self_contractions;
+use crate::rational::Rational;
+use crate::spinors::is_spinor_class;
+use crate::symmetry::{MultiTermSymmetry, Symmetry};
+use crate::tensor::Tensor;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Upper bound on the candidates searched when exchanging identical factors
+const MAX_EXCHANGE_CANDIDATES: usize = 100_000;
+
+/// A product of tensor factors with a scalar coefficient
+///
+/// Besides the slot symmetries of the individual factors, a product can carry
+/// symmetries relating slots of different factors, such as the
+/// antisymmetrization in `∂_{[a}A_{b]}`. These act on the slots of the
+/// product numbered across its factors in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorProduct<C = Rational> {
+    coefficient: C,
+    factors: Vec<Tensor>,
+    symmetries: Vec<Symmetry>,
+}
+
+impl<C: Coefficient> TensorProduct<C> {
+    /// Creates a new product, folding the factor coefficients into `coefficient`
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::TensorProduct;
+    /// use butler_portugal::rational::Rational;
+    /// use butler_portugal::{Tensor, TensorIndex};
+    ///
+    /// let g = Tensor::with_coefficient("g", vec![TensorIndex::contravariant("a", 0)], -2);
+    /// let product = TensorProduct::new(Rational::new(1, 2), vec![g]);
+    /// assert_eq!(product.coefficient(), Rational::integer(-1));
+    /// assert_eq!(product.factors()[0].coefficient(), 1);
+    /// ```
+    pub fn new(coefficient: C, factors: Vec<Tensor>) -> Self {
+        let mut coefficient = coefficient;
+        let mut factors = factors;
+        for factor in &mut factors {
+            if factor.coefficient() != 1 {
+                coefficient = coefficient * C::from_rational(Rational::from(factor.coefficient()));
+                factor.set_coefficient(1);
+            }
+        }
+        Self {
+            coefficient,
+            factors,
+            symmetries: Vec::new(),
+        }
+    }
+
+    /// Declares a symmetry between slots of the product, numbered across its factors
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::TensorProduct;
+    /// use butler_portugal::rational::Rational;
+    /// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+    ///
+    /// // ∂_{[b}A_{a]}: the antisymmetrization spans the derivative and the field
+    /// let d = Tensor::new("∂", vec![TensorIndex::new("b", 0)]);
+    /// let a = Tensor::new("A", vec![TensorIndex::new("a", 0)]);
+    /// let mut product = TensorProduct::new(Rational::ONE, vec![d, a]);
+    /// product.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    /// assert_eq!(product.canonicalize_factors()?.to_string(), "-∂__a A__b");
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn add_symmetry(&mut self, symmetry: Symmetry) {
+        self.symmetries.push(symmetry);
+    }
+
+    /// Returns the symmetries declared between slots of the product
+    pub fn symmetries(&self) -> &[Symmetry] {
+        &self.symmetries
+    }
+
+    /// Returns the total number of slots of the product
+    pub fn rank(&self) -> usize {
+        self.factors.iter().map(Tensor::rank).sum()
+    }
+
+    /// Returns the coefficient
+    pub fn coefficient(&self) -> C {
+        self.coefficient.clone()
+    }
+
+    /// Sets the coefficient
+    pub fn set_coefficient(&mut self, coefficient: C) {
+        self.coefficient = coefficient;
+    }
+
+    /// Returns the factors in order
+    pub fn factors(&self) -> &[Tensor] {
+        &self.factors
+    }
+
+    /// Returns true if the product vanishes
+    pub fn is_zero(&self) -> bool {
+        Coefficient::is_zero(&self.coefficient)
+    }
+
+    /// Returns the perturbative order of the product, the sum of its factors' orders
+    pub fn perturbative_order(&self) -> u32 {
+        self.factors.iter().map(Tensor::perturbative_order).sum()
+    }
+
+    /// Returns the product of two monomials
+    pub fn multiply(&self, other: &TensorProduct<C>) -> TensorProduct<C> {
+        let mut factors = self.factors.clone();
+        factors.extend(other.factors.iter().cloned());
+        let offset = self.rank();
+        let mut symmetries = self.symmetries.clone();
+        symmetries.extend(other.symmetries.iter().map(|s| s.shifted(offset)));
+        TensorProduct {
+            coefficient: self.coefficient.clone() * other.coefficient.clone(),
+            factors,
+            symmetries,
+        }
+    }
+
+    /// Returns the monomial multiplied by a scalar
+    pub fn scale(&self, factor: C) -> TensorProduct<C> {
+        TensorProduct {
+            coefficient: self.coefficient.clone() * factor,
+            factors: self.factors.clone(),
+            symmetries: self.symmetries.clone(),
+        }
+    }
+
+    /// Canonicalizes every factor with its monoterm symmetries, folding the
+    /// resulting signs into the coefficient
+    ///
+    /// Identical factors are exchanged and their dummy indices relabelled as
+    /// described in the module documentation. A product with a vanishing
+    /// contraction (see [`TensorProduct::has_vanishing_contraction`]) gets a
+    /// zero coefficient.
+    ///
+    /// If the product declares symmetries of its own (see
+    /// [`add_symmetry`](Self::add_symmetry)), all of its slots are instead
+    /// canonicalized together under the group generated by those and the
+    /// factor symmetries, and factors are not exchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::TensorProduct;
+    /// use butler_portugal::presets::riemann;
+    /// use butler_portugal::rational::Rational;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// // R_{pqrs} R^{rspq} is the Kretschmann scalar R_{abcd} R^{abcd}
+    /// let lower = riemann(["p", "q", "r", "s"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+    /// let upper = riemann(["r", "s", "p", "q"].iter().enumerate().map(|(i, n)| TensorIndex::contravariant(n, i)).collect())?;
+    /// let product = TensorProduct::new(Rational::ONE, vec![lower, upper]);
+    /// assert_eq!(product.canonicalize_factors()?.to_string(), "R__a _b _c _d R_^a ^b ^c ^d");
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn canonicalize_factors(&self) -> Result<TensorProduct<C>> {
+        if !self.symmetries.is_empty() {
+            return self.canonicalize_joined();
+        }
+        let factors = self
+            .factors
+            .iter()
+            .map(canonicalize)
+            .collect::<Result<Vec<_>>>()?;
+        let mut product = TensorProduct::new(self.coefficient.clone(), factors);
+        if let Some(exchanged) = product.canonicalize_across_factors()? {
+            product = exchanged;
+        }
+        if product.has_vanishing_contraction() {
+            product.coefficient = C::zero();
+        }
+        Ok(product)
+    }
+
+    /// Canonicalizes the slots of all factors as one tensor carrying the
+    /// factor symmetries and the symmetries declared on the product
+    fn canonicalize_joined(&self) -> Result<TensorProduct<C>> {
+        let rank = self.rank();
+        let indices = self
+            .factors
+            .iter()
+            .flat_map(|factor| factor.indices().iter())
+            .enumerate()
+            .map(|(position, index)| index.with_position(position))
+            .collect();
+        let mut joined = Tensor::new("", indices);
+        let mut offset = 0;
+        for factor in &self.factors {
+            for symmetry in factor.symmetries() {
+                joined.add_symmetry(embed_symmetry(symmetry, offset, rank));
+            }
+            offset += factor.rank();
+        }
+        for symmetry in &self.symmetries {
+            joined.add_symmetry(embed_symmetry(symmetry, 0, rank));
+        }
+        let canonical = canonicalize(&joined)?;
+
+        let mut factors = Vec::with_capacity(self.factors.len());
+        let mut offset = 0;
+        for factor in &self.factors {
+            let mut split = factor.clone();
+            *split.indices_mut() = canonical.indices()[offset..offset + factor.rank()]
+                .iter()
+                .enumerate()
+                .map(|(position, index)| index.with_position(position))
+                .collect();
+            offset += factor.rank();
+            factors.push(split);
+        }
+        let mut product = TensorProduct {
+            coefficient: self.coefficient.clone()
+                * C::from_rational(Rational::from(canonical.coefficient())),
+            factors,
+            symmetries: self.symmetries.clone(),
+        };
+        if product.has_vanishing_contraction() {
+            product.coefficient = C::zero();
+        }
+        Ok(product)
+    }
+
+    /// Returns true if two dummy pairs contract a symmetric slot pair against
+    /// an antisymmetric one, as in `S^{ab}A_{ab} = 0`
+    ///
+    /// Exchanging the two dummy labels leaves the product unchanged but, by the
+    /// slot symmetries, also flips its sign. The slot pairs may sit in the same
+    /// factor or in two different ones.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::TensorProduct;
+    /// use butler_portugal::rational::Rational;
+    /// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+    ///
+    /// let mut s = Tensor::new("S", vec![TensorIndex::contravariant("a", 0), TensorIndex::contravariant("b", 1)]);
+    /// s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+    /// let mut a = Tensor::new("A", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+    /// a.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    /// let product = TensorProduct::new(Rational::ONE, vec![s, a]);
+    /// assert!(product.has_vanishing_contraction());
+    /// assert!(product.canonicalize_factors()?.is_zero());
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn has_vanishing_contraction(&self) -> bool {
+        let mut occurrences: Vec<(&str, Vec<(usize, usize)>)> = Vec::new();
+        for (f, factor) in self.factors.iter().enumerate() {
+            for (slot, index) in factor.indices().iter().enumerate() {
+                match occurrences
+                    .iter_mut()
+                    .find(|(name, _)| *name == index.name())
+                {
+                    Some((_, slots)) => slots.push((f, slot)),
+                    None => occurrences.push((index.name(), vec![(f, slot)])),
+                }
+            }
+        }
+        let dummies: Vec<[(usize, usize); 2]> = occurrences
+            .into_iter()
+            .filter_map(|(_, slots)| match slots[..] {
+                [first, second] => Some([first, second]),
+                _ => None,
+            })
+            .collect();
+
+        // Exchanging the labels amounts to swapping slots only when both slots
+        // share a factor and a variance
+        let swap_sign = |(f, i): (usize, usize), (g, j): (usize, usize)| {
+            let indices = self.factors[f].indices();
+            if f == g && indices[i].is_contravariant() == indices[j].is_contravariant() {
+                pair_swap_sign(&self.factors[f], i, j)
+            } else {
+                None
+            }
+        };
+        for (k, x) in dummies.iter().enumerate() {
+            for y in &dummies[k + 1..] {
+                for y in [*y, [y[1], y[0]]] {
+                    if let (Some(first), Some(second)) =
+                        (swap_sign(x[0], y[0]), swap_sign(x[1], y[1]))
+                    {
+                        if first * second < 0 {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Rewrites Riemann-symmetric factors into the first-Bianchi normal form
+    ///
+    /// This is [`reduce_multi_term`](Self::reduce_multi_term), since the
+    /// first Bianchi identity is the only multi-term symmetry implied by
+    /// monoterm ones.
+    pub fn reduce_first_bianchi(&self) -> Result<TensorExpression<C>> {
+        self.reduce_multi_term()
+    }
+
+    /// Rewrites factors onto a basis respecting their multi-term symmetries
+    ///
+    /// Factors should already be canonicalized. For every relation implied
+    /// by the symmetries of a factor (see [`MultiTermSymmetry::implied_by`])
+    /// the arrangements it relates are canonicalized and sorted by their
+    /// canonical keys, and the second one is eliminated in favour of the
+    /// others; an `R_{acbd}`-type factor is thus replaced by `R_{abcd} +
+    /// R_{adbc}` (with the signs of the canonical forms). The result may
+    /// contain several terms.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::TensorProduct;
+    /// use butler_portugal::presets::riemann;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// let r = riemann(["a", "c", "b", "d"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+    /// let reduced = TensorProduct::from(r).reduce_multi_term()?;
+    /// assert_eq!(reduced.to_string(), "R__a _b _c _d + R__a _d _b _c");
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn reduce_multi_term(&self) -> Result<TensorExpression<C>> {
+        let mut pending = vec![self.clone()];
+        let mut reduced = TensorExpression::default();
+        'products: while let Some(product) = pending.pop() {
+            for (i, factor) in product.factors.iter().enumerate() {
+                for symmetry in MultiTermSymmetry::implied_by(factor) {
+                    let Some(replacement) = multi_term_replacement(factor, &symmetry)? else {
+                        continue;
+                    };
+                    for (sign, tensor) in replacement {
+                        let mut factors = product.factors.clone();
+                        factors[i] = tensor;
+                        pending.push(TensorProduct {
+                            coefficient: product.coefficient.clone()
+                                * C::from_rational(Rational::from(sign)),
+                            factors,
+                            symmetries: product.symmetries.clone(),
+                        });
+                    }
+                    continue 'products;
+                }
+            }
+            reduced.push(product);
+        }
+        reduced.terms.reverse();
+        Ok(reduced)
+    }
+
+    /// Canonicalizes the product under the slot symmetries of its factors,
+    /// the exchange of identical factors and the relabelling of dummy indices
+    ///
+    /// Returns `None` if no two factors are identical and none share a dummy
+    /// index, or if the search would exceed [`MAX_EXCHANGE_CANDIDATES`]
+    /// candidates.
+    fn canonicalize_across_factors(&self) -> Result<Option<TensorProduct<C>>> {
+        // Order factors by species, so identical factors form contiguous blocks
+        let mut factors = self.factors.clone();
+        factors.sort_by_cached_key(factor_species);
+        let species: Vec<String> = factors.iter().map(factor_species).collect();
+        let blocks: Vec<(usize, usize)> = species
+            .iter()
+            .enumerate()
+            .filter(|&(i, key)| i == 0 || species[i - 1] != *key)
+            .map(|(start, key)| {
+                (
+                    start,
+                    species[start..].iter().take_while(|k| *k == key).count(),
+                )
+            })
+            .collect();
+        let dummies = DummyNames::new(&factors);
+        if blocks.iter().all(|&(_, len)| len < 2) && dummies.is_empty() {
+            return Ok(None);
+        }
+
+        let elements: Vec<Vec<(Permutation, i32)>> =
+            factors.iter().map(signed_slot_symmetries).collect();
+        let exchanges: usize = blocks
+            .iter()
+            .map(|&(_, len)| (1..=len).product::<usize>())
+            .product();
+        let candidates = elements
+            .iter()
+            .try_fold(exchanges, |count, e| count.checked_mul(e.len()))
+            .filter(|&count| count > 0 && count <= MAX_EXCHANGE_CANDIDATES);
+        if candidates.is_none() {
+            return Ok(None);
+        }
+
+        let Some(best) = minimal_exchange(&factors, &elements, &blocks, &dummies) else {
+            return Ok(None);
+        };
+        let mut renamed = Default::default();
+        let mut arranged = Vec::with_capacity(factors.len());
+        for (&f, &choice) in best.order.iter().zip(&best.choice) {
+            let mut factor = factors[f].permute(&elements[f][choice].0)?;
+            for index in factor.indices_mut() {
+                if let Some(name) = dummies.relabel(index, &mut renamed) {
+                    *index = index.with_name(name);
+                }
+            }
+            arranged.push(factor);
+        }
+        let mut product = TensorProduct::new(self.coefficient.clone(), arranged);
+        if best.vanishes {
+            product.coefficient = C::zero();
+        }
+        Ok(Some(product))
+    }
+
+    /// Returns the factors sorted into a fixed order, which is valid since
+    /// tensor components commute
+    ///
+    /// Products with declared symmetries keep their order, since the
+    /// symmetries refer to slot numbers.
+    fn sorted(&self) -> TensorProduct<C> {
+        if !self.symmetries.is_empty() {
+            return self.clone();
+        }
+        let mut factors = self.factors.clone();
+        factors.sort_by_cached_key(|factor| factor.to_string());
+        TensorProduct {
+            coefficient: self.coefficient.clone(),
+            factors,
+            symmetries: Vec::new(),
+        }
+    }
+
+    /// Returns a key identifying the product up to its coefficient
+    pub(crate) fn structure_key(&self) -> String {
+        self.factors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Traces out the contractions of each factor with itself, as by
+    /// [`trace_self_contractions`](crate::presets::trace_self_contractions)
+    ///
+    /// Signs and vanishing traces go into the coefficient. A product that
+    /// declares symmetries of its own is returned unchanged, since tracing
+    /// would renumber its slots.
+    pub fn trace_self_contractions(&self) -> Result<TensorProduct<C>> {
+        if !self.symmetries.is_empty() {
+            return Ok(self.clone());
+        }
+        let factors = self
+            .factors
+            .iter()
+            .map(trace_self_contractions)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TensorProduct::new(self.coefficient.clone(), factors))
+    }
+
+    /// Returns the Lie derivative `£_ξ` of the product by the Leibniz rule
+    ///
+    /// Each term wraps one factor in an unexpanded Lie derivative. Symmetries
+    /// declared on the product are kept, since the Lie derivative adds no slots.
+    pub fn lie_derivative(&self, vector: &str) -> TensorExpression<C> {
+        (0..self.factors.len())
+            .map(|i| {
+                let mut factors = self.factors.clone();
+                factors[i] = lie_derivative(&factors[i], vector);
+                TensorProduct {
+                    coefficient: self.coefficient.clone(),
+                    factors,
+                    symmetries: self.symmetries.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Expands every Lie derivative factor into `∂` or `∇` terms
+    ///
+    /// Dummy indices introduced by the expansion avoid all index names in the product.
+    pub fn expand_lie_derivatives(&self, kind: DerivativeKind) -> Result<TensorExpression<C>> {
+        let mut used: HashSet<String> = self
+            .factors
+            .iter()
+            .flat_map(|t| t.indices().iter().map(|i| i.name().to_string()))
+            .collect();
+        let mut result =
+            TensorExpression::from(TensorProduct::new(self.coefficient.clone(), Vec::new()));
+        for factor in &self.factors {
+            let expanded = if as_lie_derivative(factor).is_some() {
+                let expanded = expand_lie_derivative_avoiding(factor, kind, &used)?;
+                for term in expanded.terms() {
+                    for tensor in term.factors() {
+                        used.extend(tensor.indices().iter().map(|i| i.name().to_string()));
+                    }
+                }
+                expanded.map_coefficients(|c| C::from_rational(*c))
+            } else {
+                TensorExpression::from(TensorProduct::new(C::one(), vec![factor.clone()]))
+            };
+            result = result.multiply(&expanded);
+        }
+        Ok(result)
+    }
+}
+
+/// Returns the replacement of a canonical factor under a multi-term
+/// symmetry, or `None` if it is already in normal form
+///
+/// For the first Bianchi identity the three orderings `R_{wxyz}`,
+/// `R_{wyzx}`, `R_{wzxy}` sum to zero. After canonicalization they take the
+/// forms `R_{abcd}`, `R_{acbd}`, `R_{adbc}` (sorted by their canonical keys);
+/// the second one is eliminated. Factors with repeated labels, or with a
+/// vanishing related arrangement, are left alone.
+fn multi_term_replacement(
+    factor: &Tensor,
+    symmetry: &MultiTermSymmetry,
+) -> Result<Option<Vec<(i32, Tensor)>>> {
+    let terms = symmetry.terms(factor.rank());
+    let mut forms = Vec::with_capacity(terms.len());
+    for perm in &terms {
+        let mut raw = factor.permute(perm)?;
+        raw.set_coefficient(1);
+        let mut canonical = canonicalize(&raw)?;
+        let sign = canonical.coefficient();
+        if sign == 0 {
+            return Ok(None);
+        }
+        canonical.set_coefficient(1);
+        forms.push((sign, canonical));
+    }
+    forms.sort_by(|a, b| compare_forms(&a.1, &b.1));
+    if forms.len() < 2
+        || forms
+            .windows(2)
+            .any(|pair| compare_forms(&pair[0].1, &pair[1].1) == Ordering::Equal)
+    {
+        return Ok(None);
+    }
+    let mut bare = factor.clone();
+    bare.set_coefficient(1);
+    if compare_forms(&bare, &forms[1].1) != Ordering::Equal {
+        return Ok(None);
+    }
+    // Σ c_i T_i = 0  =>  T_1 = -c_1 Σ_{i≠1} c_i T_i, with c_1 = ±1
+    let eliminated = forms[1].0;
+    Ok(Some(
+        forms
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != 1)
+            .map(|(_, (sign, tensor))| (-sign * eliminated, tensor.clone()))
+            .collect(),
+    ))
+}
+
+/// Returns a symmetry of a factor as a symmetry of the `rank` slots of a
+/// product in which the factor starts at slot `offset`
+///
+/// Explicit permutations of `Custom` symmetries are padded to the full rank,
+/// since their signs are looked up by exact match.
+fn embed_symmetry(symmetry: &Symmetry, offset: usize, rank: usize) -> Symmetry {
+    match symmetry.shifted(offset) {
+        Symmetry::Custom {
+            valid_permutations,
+            signs,
+        } => Symmetry::Custom {
+            valid_permutations: valid_permutations
+                .into_iter()
+                .map(|mut perm| {
+                    perm.extend(perm.len()..rank);
+                    perm
+                })
+                .collect(),
+            signs,
+        },
+        shifted => shifted,
+    }
+}
+
+/// Adds a canonical term to a list of collected terms, merging it with the
+/// term of the same structure if there is one
+fn collect_term<C: Coefficient>(collected: &mut Vec<TensorProduct<C>>, term: TensorProduct<C>) {
+    let key = term.structure_key();
+    match collected.iter_mut().find(|t| t.structure_key() == key) {
+        Some(existing) => {
+            existing.coefficient = existing.coefficient.clone() + term.coefficient;
+        }
+        None => collected.push(term),
+    }
+}
+
+/// Identifies the factors that may be exchanged: same name, slots and symmetries
+fn factor_species(factor: &Tensor) -> String {
+    let classes: Vec<_> = factor.indices().iter().map(|index| index.class()).collect();
+    format!(
+        "{}|{}|{:?}|{:?}",
+        factor.name(),
+        factor.perturbative_order(),
+        classes,
+        factor.symmetries()
+    )
+}
+
+/// The best arrangement found while exchanging identical factors
+struct ExchangeCandidate {
+    key: Vec<(String, bool)>,
+    /// Factor placed at each position
+    order: Vec<usize>,
+    /// Chosen slot symmetry of the factor at each position
+    choice: Vec<usize>,
+    sign: i32,
+    /// Whether the same arrangement was also reached with the opposite sign
+    vanishes: bool,
+}
+
+/// Lists the slot symmetries of a factor that keep index classes in place, with their signs
+fn signed_slot_symmetries(factor: &Tensor) -> Vec<(Permutation, i32)> {
+    let indices = factor.indices();
+    factor
+        .signed_symmetry_group()
+        .elements()
+        .filter(|element| {
+            element
+                .permutation
+                .iter()
+                .enumerate()
+                .all(|(slot, &source)| indices[source].class() == indices[slot].class())
+        })
+        .map(|element| (element.permutation, element.sign))
+        .collect()
+}
+
+/// Searches all exchanges of identical factors and slot symmetries for the
+/// arrangement with the smallest relabelled index sequence
+fn minimal_exchange(
+    factors: &[Tensor],
+    elements: &[Vec<(Permutation, i32)>],
+    blocks: &[(usize, usize)],
+    dummies: &DummyNames,
+) -> Option<ExchangeCandidate> {
+    let mut best: Option<ExchangeCandidate> = None;
+    let mut order: Vec<usize> = (0..factors.len()).collect();
+    loop {
+        let mut choice = vec![0; factors.len()];
+        loop {
+            let sign: i32 = order
+                .iter()
+                .zip(&choice)
+                .map(|(&f, &c)| elements[f][c].1)
+                .product();
+            let mut renamed = Default::default();
+            let mut key = Vec::new();
+            for (&f, &c) in order.iter().zip(&choice) {
+                for &source in &elements[f][c].0 {
+                    let index = &factors[f].indices()[source];
+                    let name = dummies.relabel(index, &mut renamed).unwrap_or(index.name());
+                    key.push((name.to_string(), index.is_contravariant()));
+                }
+            }
+            match &mut best {
+                Some(current) if key > current.key => {}
+                Some(current) if key == current.key => current.vanishes |= sign != current.sign,
+                _ => {
+                    best = Some(ExchangeCandidate {
+                        key,
+                        order: order.clone(),
+                        choice: choice.clone(),
+                        sign,
+                        vanishes: false,
+                    });
+                }
+            }
+            // Advance the per-position choices like a mixed-radix counter
+            let Some(p) = (0..choice.len()).find(|&p| choice[p] + 1 < elements[order[p]].len())
+            else {
+                break;
+            };
+            choice[p] += 1;
+            choice[..p].iter_mut().for_each(|c| *c = 0);
+        }
+        if !next_exchange(&mut order, blocks) {
+            return best;
+        }
+    }
+}
+
+/// The dummy indices of a product and the canonical names they are given
+///
+/// Dummies are renamed in order of first occurrence to the first names of
+/// `a`, `b`, … (`A`, `B`, … for spinor indices) not carried by a free index,
+/// so the result does not depend on the names they came with.
+struct DummyNames<'a> {
+    /// Names of the dummies as given, with whether they are spinor indices
+    dummies: Vec<(&'a str, bool)>,
+    /// Canonical names for metric and for spinor dummies
+    names: [Vec<String>; 2],
+}
+
+impl<'a> DummyNames<'a> {
+    fn new(factors: &'a [Tensor]) -> Self {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut dummies: Vec<(&str, bool)> = Vec::new();
+        for index in factors.iter().flat_map(|f| f.indices().iter()) {
+            if !seen.insert(index.name()) && dummies.iter().all(|(d, _)| *d != index.name()) {
+                dummies.push((index.name(), is_spinor_class(index.class())));
+            }
+        }
+        let free: HashSet<&str> = seen
+            .into_iter()
+            .filter(|name| dummies.iter().all(|(d, _)| d != name))
+            .collect();
+        let names = [false, true].map(|spinor| {
+            (0..)
+                .map(|n| {
+                    let name = dummy_name(n);
+                    if spinor {
+                        name.to_uppercase()
+                    } else {
+                        name
+                    }
+                })
+                .filter(|name| !free.contains(name.as_str()))
+                .take(dummies.iter().filter(|(_, s)| *s == spinor).count())
+                .collect()
+        });
+        Self { dummies, names }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.dummies.is_empty()
+    }
+
+    /// Returns the new name of a dummy index, given the dummies of each kind
+    /// renamed so far
+    fn relabel(&self, index: &TensorIndex, renamed: &mut [Vec<String>; 2]) -> Option<&str> {
+        let &(_, spinor) = self.dummies.iter().find(|(d, _)| *d == index.name())?;
+        let renamed = &mut renamed[usize::from(spinor)];
+        let position = renamed
+            .iter()
+            .position(|old| old == index.name())
+            .unwrap_or_else(|| {
+                renamed.push(index.name().to_string());
+                renamed.len() - 1
+            });
+        self.names[usize::from(spinor)]
+            .get(position)
+            .map(String::as_str)
+    }
+}
+
+/// Advances to the next arrangement of factors permuting only within blocks
+/// of identical factors, returning false after the last one
+fn next_exchange(order: &mut [usize], blocks: &[(usize, usize)]) -> bool {
+    for &(start, len) in blocks {
+        let block = &mut order[start..start + len];
+        if next_permutation(block) {
+            return true;
+        }
+        // Wrapped around: reset the block to increasing order and carry
+        block.sort_unstable();
+    }
+    false
+}
+
+/// Returns the sign a tensor picks up when two of its slots are exchanged, if
+/// a single symmetric or antisymmetric symmetry covers both
+fn pair_swap_sign(tensor: &Tensor, i: usize, j: usize) -> Option<i32> {
+    tensor
+        .symmetries()
+        .iter()
+        .find_map(|symmetry| match symmetry {
+            Symmetry::Symmetric { indices } if indices.contains(&i) && indices.contains(&j) => {
+                Some(1)
+            }
+            Symmetry::Antisymmetric { indices } if indices.contains(&i) && indices.contains(&j) => {
+                Some(-1)
+            }
+            _ => None,
+        })
+}
+
+impl From<Tensor> for TensorProduct {
+    fn from(tensor: Tensor) -> Self {
+        TensorProduct::new(Rational::ONE, vec![tensor])
+    }
+}
+
+impl<C: Coefficient> fmt::Display for TensorProduct<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        if self.factors.is_empty() {
+            return write!(f, "{}", self.coefficient);
+        }
+        if self.coefficient == -C::one() {
+            write!(f, "-")?;
+        } else if self.coefficient != C::one() {
+            write!(f, "{} ", self.coefficient)?;
+        }
+        for (i, factor) in self.factors.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{factor}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A sum of tensor monomials
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorExpression<C = Rational> {
+    terms: Vec<TensorProduct<C>>,
+}
+
+/// A sum of tensor monomials, under the name used by other tensor algebra systems
+pub type TensorSum<C = Rational> = TensorExpression<C>;
+
+impl<C> Default for TensorExpression<C> {
+    fn default() -> Self {
+        Self { terms: Vec::new() }
+    }
+}
+
+impl TensorExpression {
+    /// Creates an empty (zero) expression with rational coefficients
+    ///
+    /// Use `TensorExpression::default()` for other coefficient types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C: Coefficient> TensorExpression<C> {
+    /// Returns the terms
+    pub fn terms(&self) -> &[TensorProduct<C>] {
+        &self.terms
+    }
+
+    /// Adds a term to the expression
+    pub fn push(&mut self, term: TensorProduct<C>) {
+        self.terms.push(term);
+    }
+
+    /// Returns the expression with every coefficient mapped through `f`,
+    /// e.g. to move between coefficient types or substitute symbol values
+    pub fn map_coefficients<D: Coefficient>(&self, f: impl Fn(&C) -> D) -> TensorExpression<D> {
+        self.terms
+            .iter()
+            .map(|term| TensorProduct {
+                coefficient: f(&term.coefficient),
+                factors: term.factors.clone(),
+                symmetries: term.symmetries.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the sum of two expressions
+    pub fn add(&self, other: &TensorExpression<C>) -> TensorExpression<C> {
+        let mut terms = self.terms.clone();
+        terms.extend(other.terms.iter().cloned());
+        TensorExpression { terms }
+    }
+
+    /// Returns the expression multiplied by a scalar
+    pub fn scale(&self, factor: C) -> TensorExpression<C> {
+        self.terms
+            .iter()
+            .map(move |t| t.scale(factor.clone()))
+            .collect()
+    }
+
+    /// Returns the product of two expressions, distributing over both sums
+    pub fn multiply(&self, other: &TensorExpression<C>) -> TensorExpression<C> {
+        self.terms
+            .iter()
+            .flat_map(|a| other.terms.iter().map(move |b| a.multiply(b)))
+            .collect()
+    }
+
+    /// Returns the product of two expressions, dropping terms above `max_order`
+    ///
+    /// Equivalent to `self.multiply(other).truncate(max_order)` but never
+    /// builds the discarded terms.
+    pub fn multiply_truncated(
+        &self,
+        other: &TensorExpression<C>,
+        max_order: u32,
+    ) -> TensorExpression<C> {
+        self.terms
+            .iter()
+            .flat_map(|a| {
+                let remaining = max_order.checked_sub(a.perturbative_order());
+                other
+                    .terms
+                    .iter()
+                    .filter(move |b| remaining.is_some_and(|r| b.perturbative_order() <= r))
+                    .map(move |b| a.multiply(b))
+            })
+            .collect()
+    }
+
+    /// Drops every term whose perturbative order exceeds `max_order`
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::{TensorExpression, TensorProduct};
+    /// use butler_portugal::{Tensor, TensorIndex};
+    ///
+    /// let g = Tensor::new("g", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+    /// let mut h = Tensor::new("h", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+    /// h.set_perturbative_order(1);
+    ///
+    /// // (g + h)(g + h) to first order: g g + g h + h g
+    /// let metric = TensorExpression::from(g).add(&TensorExpression::from(h));
+    /// let square = metric.multiply(&metric).truncate(1);
+    /// assert_eq!(square.terms().len(), 3);
+    /// ```
+    pub fn truncate(&self, max_order: u32) -> TensorExpression<C> {
+        self.terms
+            .iter()
+            .filter(|term| term.perturbative_order() <= max_order)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the terms of exactly the given perturbative order
+    pub fn at_order(&self, order: u32) -> TensorExpression<C> {
+        self.terms
+            .iter()
+            .filter(|term| term.perturbative_order() == order)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the Lie derivative `£_ξ` of the expression, distributed over
+    /// terms and factors
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::{TensorExpression, TensorProduct};
+    /// use butler_portugal::rational::Rational;
+    /// use butler_portugal::{Tensor, TensorIndex};
+    ///
+    /// let u = Tensor::new("u", vec![TensorIndex::new("a", 0)]);
+    /// let v = Tensor::new("v", vec![TensorIndex::new("b", 1)]);
+    /// let uv = TensorExpression::from(TensorProduct::new(Rational::ONE, vec![u, v]));
+    /// assert_eq!(uv.lie_derivative("xi").to_string(), "£_{xi}u__a v__b + u__a £_{xi}v__b");
+    /// ```
+    pub fn lie_derivative(&self, vector: &str) -> TensorExpression<C> {
+        self.terms
+            .iter()
+            .flat_map(|term| term.lie_derivative(vector).terms)
+            .collect()
+    }
+
+    /// Traces out the contractions of every factor with itself, so that for
+    /// instance `R^a{}_{bac}` becomes the Ricci tensor `R_{bc}`
+    ///
+    /// Terms whose trace vanishes are dropped. Run [`simplify`](Self::simplify)
+    /// afterwards to collect the traced terms.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::parser::parse_expression;
+    ///
+    /// let traced = parse_expression("R^{a}_{bac} - R_{cb} + C^{a}_{bac}")?.trace_self_contractions()?;
+    /// assert!(traced.simplify()?.is_zero());
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn trace_self_contractions(&self) -> Result<TensorExpression<C>> {
+        let mut traced = Vec::with_capacity(self.terms.len());
+        for term in &self.terms {
+            let term = term.trace_self_contractions()?;
+            if !term.is_zero() {
+                traced.push(term);
+            }
+        }
+        Ok(TensorExpression { terms: traced })
+    }
+
+    /// Expands the contractions of Levi-Civita tensors in every term and
+    /// simplifies the result
+    ///
+    /// Contracted `ε` pairs become generalized Kronecker deltas as described
+    /// for [`contract_epsilons`](crate::builtin::contract_epsilons); the
+    /// expanded terms are then canonicalized and collected by
+    /// [`simplify`](Self::simplify), so identities between `ε` products reduce
+    /// to zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::builtin::{epsilon, MetricSignature};
+    /// use butler_portugal::expression::{TensorExpression, TensorProduct};
+    /// use butler_portugal::rational::Rational;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// // ε^{abcd} ε_{abcd} = -24 in four-dimensional spacetime
+    /// let names = ["a", "b", "c", "d"];
+    /// let upper = names.iter().enumerate().map(|(i, n)| TensorIndex::contravariant(n, i)).collect();
+    /// let lower = names.iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect();
+    /// let product = TensorProduct::new(Rational::ONE, vec![epsilon(4, upper)?, epsilon(4, lower)?]);
+    /// let scalar = TensorExpression::from(product).contract_epsilons(MetricSignature::Lorentzian)?;
+    /// assert_eq!(scalar.to_string(), "-24");
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn contract_epsilons(&self, signature: MetricSignature) -> Result<TensorExpression<C>> {
+        let mut expanded = TensorExpression::default();
+        for term in &self.terms {
+            expanded = expanded.add(&contract_epsilons(term, signature)?);
+        }
+        expanded.simplify()
+    }
+
+    /// Expands every Lie derivative factor into `∂` or `∇` terms
+    pub fn expand_lie_derivatives(&self, kind: DerivativeKind) -> Result<TensorExpression<C>> {
+        let mut result = TensorExpression::default();
+        for term in &self.terms {
+            result = result.add(&term.expand_lie_derivatives(kind)?);
+        }
+        Ok(result)
+    }
+
+    /// Simplifies the expression
+    ///
+    /// Every factor is canonicalized with its monoterm symmetries, Riemann
+    /// factors are brought into the first-Bianchi normal form (see the module
+    /// documentation), factors are sorted, and like terms are collected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::{TensorExpression, TensorProduct};
+    /// use butler_portugal::presets::riemann;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// let r = |names: [&str; 4]| {
+    ///     riemann(names.iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())
+    /// };
+    /// // The cyclic sum R_abcd + R_acdb + R_adbc vanishes
+    /// let cyclic: TensorExpression = [r(["a", "b", "c", "d"])?, r(["a", "c", "d", "b"])?, r(["a", "d", "b", "c"])?]
+    ///     .into_iter()
+    ///     .map(TensorProduct::from)
+    ///     .collect();
+    /// assert!(cyclic.simplify()?.is_zero());
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn simplify(&self) -> Result<TensorExpression<C>> {
+        let mut collected: Vec<TensorProduct<C>> = Vec::new();
+        for term in &self.terms {
+            let canonical = term.canonicalize_factors()?;
+            if canonical.is_zero() {
+                continue;
+            }
+            for reduced in canonical.reduce_first_bianchi()?.terms {
+                collect_term(&mut collected, reduced.sorted());
+            }
+        }
+        Ok(collected.into_iter().filter(|t| !t.is_zero()).collect())
+    }
+
+    /// Canonicalizes every term and merges the terms whose canonical forms
+    /// match, summing their coefficients and dropping those that cancel
+    ///
+    /// Unlike [`simplify`](Self::simplify), no multi-term identities are
+    /// applied, so the result only relies on the monoterm symmetries of the
+    /// factors and the relabelling of dummy indices.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::expression::{TensorProduct, TensorSum};
+    /// use butler_portugal::rational::Rational;
+    /// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+    ///
+    /// let f = |first: &str, second: &str| {
+    ///     let mut f = Tensor::new("F", vec![TensorIndex::new(first, 0), TensorIndex::new(second, 1)]);
+    ///     f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    ///     TensorProduct::from(f)
+    /// };
+    /// // F_ab + 2 F_ba = -F_ab
+    /// let sum: TensorSum = [f("a", "b"), f("b", "a").scale(Rational::integer(2))].into_iter().collect();
+    /// assert_eq!(sum.collect_like_terms()?.to_string(), "-F__a _b");
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn collect_like_terms(&self) -> Result<TensorExpression<C>> {
+        let mut collected: Vec<TensorProduct<C>> = Vec::new();
+        for term in &self.terms {
+            let canonical = term.canonicalize_factors()?;
+            if !canonical.is_zero() {
+                collect_term(&mut collected, canonical.sorted());
+            }
+        }
+        Ok(collected.into_iter().filter(|t| !t.is_zero()).collect())
+    }
+
+    /// Returns true if the expression has no nonzero terms
+    pub fn is_zero(&self) -> bool {
+        self.terms.iter().all(TensorProduct::is_zero)
+    }
+}
+
+/// Applies the Einstein summation convention to a product
+///
+/// A name carried by two slots of the product marks a dummy pair summed
+/// over, which requires one contravariant and one covariant index of the same
+/// class; tetrad labels name components and may repeat freely. Metric factors
+/// contracted with another factor are then absorbed by raising or lowering
+/// the index they contract with, and the contractions of every other factor
+/// with itself are traced as by
+/// [`trace_self_contractions`](crate::presets::trace_self_contractions). A
+/// product declaring symmetries of its own is only checked, since absorbing a
+/// metric would renumber its slots.
+///
+/// # Errors
+/// Returns an `IncompatibleTensors` error if a name occurs more than twice or
+/// its two occurrences cannot contract.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::builtin::metric;
+/// use butler_portugal::expression::{contract, TensorProduct};
+/// use butler_portugal::presets::ricci;
+/// use butler_portugal::rational::Rational;
+/// use butler_portugal::TensorIndex;
+///
+/// // g^{ab} R_{ab} is the Ricci scalar
+/// let g = metric(TensorIndex::contravariant("a", 0), TensorIndex::contravariant("b", 1));
+/// let r = ricci(vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)])?;
+/// let scalar = contract(&TensorProduct::new(Rational::ONE, vec![g, r]))?;
+/// assert_eq!(scalar.to_string(), "R");
+///
+/// // Two lower indices named alike do not contract
+/// let u = TensorIndex::new("a", 0);
+/// let v = TensorProduct::new(Rational::ONE, vec![metric(u.clone(), u.with_position(1))]);
+/// assert!(contract(&v).is_err());
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn contract<C: Coefficient>(product: &TensorProduct<C>) -> Result<TensorProduct<C>> {
+    let mut occurrences: Vec<(&TensorIndex, Vec<&TensorIndex>)> = Vec::new();
+    for index in product.factors.iter().flat_map(|f| f.indices().iter()) {
+        if index.class() == IndexClass::Tetrad {
+            continue;
+        }
+        match occurrences
+            .iter_mut()
+            .find(|(first, _)| first.name() == index.name())
+        {
+            Some((_, others)) => others.push(index),
+            None => occurrences.push((index, Vec::new())),
+        }
+    }
+    for (first, others) in &occurrences {
+        let contracts = match others.as_slice() {
+            [] => true,
+            [second] => first.can_contract_with(second),
+            _ => false,
+        };
+        if !contracts {
+            return Err(ButlerPortugalError::IncompatibleTensors(format!(
+                "Index {} occurs {} times in {product} and cannot be summed over",
+                first.name(),
+                others.len() + 1
+            )));
+        }
+    }
+    if !product.symmetries.is_empty() {
+        return Ok(product.clone());
+    }
+
+    let mut factors = product.factors.clone();
+    while let Some((m, slot, target, target_slot)) = absorbable_metric(&factors) {
+        let raised = factors[m].indices()[1 - slot].with_position(target_slot);
+        factors[target].indices_mut()[target_slot] = raised;
+        factors.remove(m);
+    }
+    let factors = factors
+        .iter()
+        .map(|factor| {
+            if is_metric(factor) {
+                Ok(factor.clone())
+            } else {
+                trace_self_contractions(factor)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(TensorProduct::new(product.coefficient.clone(), factors))
+}
+
+/// Finds a metric factor contracted with another factor, returning the
+/// metric, its contracted slot and the factor and slot it contracts with
+fn absorbable_metric(factors: &[Tensor]) -> Option<(usize, usize, usize, usize)> {
+    factors
+        .iter()
+        .enumerate()
+        .filter(|(_, factor)| is_metric(factor))
+        .find_map(|(m, metric)| {
+            metric
+                .indices()
+                .iter()
+                .enumerate()
+                .find_map(|(slot, index)| {
+                    factors
+                        .iter()
+                        .enumerate()
+                        .filter(|&(target, _)| target != m)
+                        .find_map(|(target, factor)| {
+                            factor
+                                .indices()
+                                .iter()
+                                .position(|other| other.can_contract_with(index))
+                                .map(|target_slot| (m, slot, target, target_slot))
+                        })
+                })
+        })
+}
+
+/// Returns the terms by which `lhs = rhs` fails to hold
+///
+/// Both sides are moved to one side as `lhs - rhs`, which is then
+/// [simplified](TensorExpression::simplify): every factor is canonicalized,
+/// the first Bianchi identity is applied and like terms are collected. The
+/// surviving terms are the counter-terms of the equation; none survive if it
+/// is an identity.
+///
+/// # Errors
+/// Returns any error raised while canonicalizing a factor.
+pub fn identity_residual<C: Coefficient>(
+    lhs: &TensorExpression<C>,
+    rhs: &TensorExpression<C>,
+) -> Result<TensorExpression<C>> {
+    lhs.add(&rhs.scale(-C::one())).simplify()
+}
+
+/// Reports whether `lhs = rhs` holds up to symmetry and the first Bianchi identity
+///
+/// Use [`identity_residual`] to list the counter-terms when it does not.
+///
+/// # Errors
+/// Returns any error raised while canonicalizing a factor.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::expression::{identity_residual, verify_identity};
+/// use butler_portugal::parser::parse_expression;
+///
+/// // Pair symmetry and the cyclic identity of the Riemann tensor
+/// let lhs = parse_expression("R_{abcd} + R_{acdb}")?;
+/// assert!(verify_identity(&lhs, &parse_expression("-R_{adbc}")?)?);
+///
+/// let rhs = parse_expression("R_{cdab} + 2 g_{ab} g_{cd}")?;
+/// assert!(!verify_identity(&parse_expression("R_{abcd}")?, &rhs)?);
+/// let residual = identity_residual(&parse_expression("R_{abcd}")?, &rhs)?;
+/// assert_eq!(residual.terms().len(), 1);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn verify_identity<C: Coefficient>(
+    lhs: &TensorExpression<C>,
+    rhs: &TensorExpression<C>,
+) -> Result<bool> {
+    Ok(identity_residual(lhs, rhs)?.is_zero())
+}
+
+impl<C> From<TensorProduct<C>> for TensorExpression<C> {
+    fn from(term: TensorProduct<C>) -> Self {
+        TensorExpression { terms: vec![term] }
+    }
+}
+
+impl From<Tensor> for TensorExpression {
+    fn from(tensor: Tensor) -> Self {
+        TensorExpression::from(TensorProduct::from(tensor))
+    }
+}
+
+impl<C> FromIterator<TensorProduct<C>> for TensorExpression<C> {
+    fn from_iter<I: IntoIterator<Item = TensorProduct<C>>>(iter: I) -> Self {
+        TensorExpression {
+            terms: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl FromStr for TensorExpression {
+    type Err = ButlerPortugalError;
+
+    /// Parses an expression with [`parse_expression`](crate::parser::parse_expression)
+    fn from_str(input: &str) -> Result<Self> {
+        crate::parser::parse_expression(input)
+    }
+}
+
+impl<C: Coefficient> fmt::Display for TensorExpression<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<&TensorProduct<C>> = self.terms.iter().filter(|t| !t.is_zero()).collect();
+        if terms.is_empty() {
+            return write!(f, "0");
+        }
+        for (i, term) in terms.iter().enumerate() {
+            if i == 0 {
+                write!(f, "{term}")?;
+            } else if term.coefficient.is_negative() {
+                write!(f, " - {}", term.scale(-C::one()))?;
+            } else {
+                write!(f, " + {term}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::TensorIndex;
+
+    fn vector(name: &str, index: &str) -> Tensor {
+        Tensor::new(name, vec![TensorIndex::new(index, 0)])
+    }
+
+    #[test]
+    fn test_product_folds_coefficients() {
+        let mut u = vector("u", "a");
+        u.set_coefficient(3);
+        let product = TensorProduct::new(Rational::new(1, 6), vec![u, vector("v", "b")]);
+        assert_eq!(product.coefficient(), Rational::new(1, 2));
+        assert!(product.factors().iter().all(|t| t.coefficient() == 1));
+    }
+
+    #[test]
+    fn test_multiply_and_scale() {
+        let a = TensorProduct::from(vector("u", "a"));
+        let b = TensorProduct::from(vector("v", "b")).scale(Rational::integer(-2));
+        let ab = a.multiply(&b);
+        assert_eq!(ab.factors().len(), 2);
+        assert_eq!(ab.coefficient(), Rational::integer(-2));
+    }
+
+    #[test]
+    fn test_expression_display() {
+        let mut expr = TensorExpression::from(vector("u", "a"));
+        expr.push(TensorProduct::from(vector("v", "a")).scale(Rational::new(-1, 2)));
+        assert_eq!(expr.to_string(), "u__a - 1/2 v__a");
+        assert_eq!(TensorExpression::new().to_string(), "0");
+    }
+
+    #[test]
+    fn test_lie_derivative_of_product_expands() {
+        // £_ξ (2 u_a v^a): two Leibniz terms, each a transport term plus one correction
+        let u = vector("u", "a");
+        let v = Tensor::new("v", vec![TensorIndex::contravariant("a", 0)]);
+        let expr = TensorExpression::from(TensorProduct::new(Rational::integer(2), vec![u, v]));
+        let lie = expr.lie_derivative("xi");
+        assert_eq!(lie.terms().len(), 2);
+
+        let expanded = lie.expand_lie_derivatives(DerivativeKind::Partial).unwrap();
+        assert_eq!(expanded.terms().len(), 4);
+        assert!(expanded
+            .terms()
+            .iter()
+            .all(|t| t.coefficient().abs() == Rational::integer(2)));
+        // Dummies never clash with the contracted index a
+        for term in expanded.terms() {
+            let xi = term
+                .factors()
+                .iter()
+                .find(|t| t.name() == "xi")
+                .map(|t| t.indices()[0].name());
+            assert_ne!(xi, Some("a"));
+        }
+    }
+
+    #[test]
+    fn test_perturbative_truncation() {
+        let background = vector("u", "a");
+        let mut first = vector("v", "a");
+        first.set_perturbative_order(1);
+        let mut second = vector("w", "a");
+        second.set_perturbative_order(2);
+        let series: TensorExpression = [background, first, second]
+            .into_iter()
+            .map(TensorProduct::from)
+            .collect();
+
+        let cube = series.multiply(&series).multiply(&series);
+        assert_eq!(cube.terms().len(), 27);
+        let truncated = cube.truncate(2);
+        // Orders 0, 1 and 2: 1 + 3 + (3 + 3) terms
+        assert_eq!(truncated.terms().len(), 10);
+        assert_eq!(truncated.at_order(1).terms().len(), 3);
+
+        let direct = series
+            .multiply_truncated(&series, 2)
+            .multiply_truncated(&series, 2);
+        assert_eq!(direct, truncated);
+    }
+
+    fn riemann_term(names: [&str; 4]) -> TensorProduct {
+        TensorProduct::from(
+            crate::presets::riemann(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| TensorIndex::new(n, i))
+                    .collect(),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_simplify_collects_like_terms() {
+        // R_abcd - R_cdab + R_bacd = -R_abcd
+        let expr: TensorExpression = [
+            riemann_term(["a", "b", "c", "d"]),
+            riemann_term(["c", "d", "a", "b"]).scale(-Rational::ONE),
+            riemann_term(["b", "a", "c", "d"]),
+        ]
+        .into_iter()
+        .collect();
+        let simplified = expr.simplify().unwrap();
+        assert_eq!(simplified.to_string(), "-R__a _b _c _d");
+        assert!(TensorExpression::from(riemann_term(["a", "a", "c", "d"]))
+            .simplify()
+            .unwrap()
+            .is_zero());
+    }
+
+    #[test]
+    fn test_collect_like_terms_keeps_bianchi_orderings() {
+        // R_acbd + R_bdac = 2 R_acbd, which simplify would expand further
+        let expr: TensorSum = [
+            riemann_term(["a", "c", "b", "d"]),
+            riemann_term(["b", "d", "a", "c"]),
+            riemann_term(["a", "a", "c", "d"]),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            expr.collect_like_terms().unwrap().to_string(),
+            "2 R__a _c _b _d"
+        );
+
+        // Terms differing only in dummy names merge
+        let vector = |name: &str, index: &str, up: bool| {
+            let mut v = Tensor::new(name, vec![TensorIndex::new(index, 0)]);
+            v.indices_mut()[0].set_contravariant(up);
+            v
+        };
+        let contraction = |dummy: &str| {
+            TensorProduct::new(
+                Rational::ONE,
+                vec![vector("u", dummy, true), vector("v", dummy, false)],
+            )
+        };
+        let expr: TensorSum = [contraction("b"), contraction("c").scale(-Rational::ONE)]
+            .into_iter()
+            .collect();
+        assert!(expr.collect_like_terms().unwrap().terms().is_empty());
+    }
+
+    #[test]
+    fn test_first_bianchi_normal_form() {
+        // R_acbd is eliminated in favour of R_abcd + R_adbc
+        let simplified = TensorExpression::from(riemann_term(["a", "c", "b", "d"]))
+            .simplify()
+            .unwrap();
+        assert_eq!(simplified.to_string(), "R__a _b _c _d + R__a _d _b _c");
+
+        // Orderings already in normal form are untouched
+        let normal = TensorExpression::from(riemann_term(["b", "c", "a", "d"]))
+            .simplify()
+            .unwrap();
+        assert_eq!(normal.to_string(), "R__a _d _b _c");
+
+        // R_abcd R^acbd reduces in the second factor only
+        let r_up = crate::presets::riemann(
+            ["a", "c", "b", "d"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::contravariant(n, i))
+                .collect(),
+        )
+        .unwrap();
+        let product = TensorProduct::new(
+            Rational::ONE,
+            vec![
+                riemann_term(["a", "b", "c", "d"]).factors()[0].clone(),
+                r_up,
+            ],
+        );
+        let reduced = TensorExpression::from(product).simplify().unwrap();
+        assert_eq!(reduced.terms().len(), 2);
+    }
+
+    #[test]
+    fn test_symmetric_antisymmetric_contraction_vanishes() {
+        let pair = |name: &str, symmetry: Symmetry, up: bool| {
+            let index = |n: &str, i| {
+                if up {
+                    TensorIndex::contravariant(n, i)
+                } else {
+                    TensorIndex::new(n, i)
+                }
+            };
+            let mut t = Tensor::new(name, vec![index("a", 0), index("b", 1)]);
+            t.add_symmetry(symmetry);
+            t
+        };
+        let s = pair("S", Symmetry::symmetric(vec![0, 1]), true);
+        let a = pair("A", Symmetry::antisymmetric(vec![0, 1]), false);
+        let f = pair("F", Symmetry::antisymmetric(vec![0, 1]), true);
+
+        let dead = TensorProduct::new(Rational::ONE, vec![s.clone(), a.clone()]);
+        let alive = TensorProduct::new(Rational::ONE, vec![f, a]);
+        assert!(dead.has_vanishing_contraction());
+        assert!(!alive.has_vanishing_contraction());
+        let sum: TensorExpression = [dead, alive].into_iter().collect();
+        assert_eq!(sum.simplify().unwrap().terms().len(), 1);
+
+        // A single shared label is not enough: S^{ab} v_a w_b survives
+        let v = Tensor::new("v", vec![TensorIndex::new("a", 0)]);
+        let w = Tensor::new("w", vec![TensorIndex::new("b", 0)]);
+        assert!(!TensorProduct::new(Rational::ONE, vec![s, v, w]).has_vanishing_contraction());
+
+        // Within one factor: ∇_a ∇_b F^{ab} with symmetrized derivatives
+        let mut t = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::new("a", 0),
+                TensorIndex::new("b", 1),
+                TensorIndex::contravariant("a", 2),
+                TensorIndex::contravariant("b", 3),
+            ],
+        );
+        t.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        t.add_symmetry(Symmetry::antisymmetric(vec![2, 3]));
+        assert!(TensorProduct::from(t).has_vanishing_contraction());
+    }
+
+    #[test]
+    fn test_identical_factors_are_exchanged_and_relabelled() {
+        // R_ab^cd R_cd^ef R_ef^ab and R_ab^ef R_ef^cd R_cd^ab differ by renaming c <-> e
+        let r = |names: [(&str, bool); 4]| {
+            crate::presets::riemann(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(n, up))| {
+                        if up {
+                            TensorIndex::contravariant(n, i)
+                        } else {
+                            TensorIndex::new(n, i)
+                        }
+                    })
+                    .collect(),
+            )
+            .unwrap()
+        };
+        let cubic = |x: &str, y: &str| {
+            TensorProduct::new(
+                Rational::ONE,
+                vec![
+                    r([("a", false), ("b", false), (x, true), ("d", true)]),
+                    r([(x, false), ("d", false), (y, true), ("f", true)]),
+                    r([(y, false), ("f", false), ("a", true), ("b", true)]),
+                ],
+            )
+        };
+        let first = cubic("c", "e").canonicalize_factors().unwrap();
+        let second = cubic("e", "c").canonicalize_factors().unwrap();
+        assert_eq!(
+            first.sorted().structure_key(),
+            second.sorted().structure_key()
+        );
+        let difference: TensorExpression = [cubic("c", "e"), cubic("e", "c").scale(-Rational::ONE)]
+            .into_iter()
+            .collect();
+        assert!(difference.simplify().unwrap().is_zero());
+
+        // Dummies shared by distinct factors are renamed too; free ones stay
+        let product = TensorProduct::new(Rational::ONE, vec![vector("v", "b"), vector("u", "b")]);
+        assert_eq!(
+            product.canonicalize_factors().unwrap().to_string(),
+            "u__a v__a"
+        );
+        let product = TensorProduct::new(
+            Rational::ONE,
+            vec![vector("v", "c"), vector("u", "c"), vector("w", "a")],
+        );
+        assert_eq!(
+            product.canonicalize_factors().unwrap().to_string(),
+            "u__b v__b w__a"
+        );
+    }
+
+    #[test]
+    fn test_cross_factor_symmetries() {
+        let vector = |name: &str, index: &str| Tensor::new(name, vec![TensorIndex::new(index, 0)]);
+        let mut f = Tensor::new(
+            "F",
+            vec![TensorIndex::new("d", 0), TensorIndex::new("c", 1)],
+        );
+        f.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+
+        // d_b A_a F_dc, antisymmetric in the first two slots of the product
+        let mut product = TensorProduct::new(
+            Rational::ONE,
+            vec![vector("d", "b"), vector("A", "a"), f.clone()],
+        );
+        product.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        let canonical = product.canonicalize_factors().unwrap();
+        assert_eq!(canonical.to_string(), "-d__a A__b F__c _d");
+
+        // Declared symmetries follow the slots through multiplication
+        let mut repeated = TensorProduct::new(Rational::ONE, vec![f, vector("B", "c")]);
+        repeated.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        let shifted = TensorProduct::new(Rational::ONE, vec![vector("x", "e")]).multiply(&repeated);
+        assert_eq!(shifted.symmetries(), [Symmetry::antisymmetric(vec![1, 2])]);
+        // ∂_{[a}A_{a]} vanishes
+        let mut vanishing =
+            TensorProduct::new(Rational::ONE, vec![vector("d", "a"), vector("A", "a")]);
+        vanishing.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        assert!(vanishing.canonicalize_factors().unwrap().is_zero());
+        let expression = TensorExpression::from(vanishing);
+        assert!(expression.simplify().unwrap().is_zero());
+    }
+
+    #[test]
+    fn test_verify_identity_lists_counter_terms() {
+        let mut s = Tensor::new(
+            "S",
+            vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)],
+        );
+        s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        let swapped = s.permute(&[1, 0]).unwrap();
+        let lhs = TensorExpression::from(s.clone()).scale(Rational::from(2));
+        let rhs: TensorExpression = [s.clone(), swapped]
+            .into_iter()
+            .map(TensorProduct::from)
+            .collect();
+        assert!(verify_identity(&lhs, &rhs).unwrap());
+
+        let rhs = rhs.add(&TensorExpression::from(vector("u", "a")));
+        assert!(!verify_identity(&lhs, &rhs).unwrap());
+        assert_eq!(identity_residual(&lhs, &rhs).unwrap().to_string(), "-u__a");
+    }
+
+    #[test]
+    fn test_contract_absorbs_metrics_and_traces() {
+        use crate::builtin::metric;
+
+        let up = |name: &str, position: usize| TensorIndex::contravariant(name, position);
+        let down = |name: &str, position: usize| TensorIndex::new(name, position);
+        // g^{ac} R_{abcd} = R_{bd}
+        let r =
+            crate::presets::riemann(vec![down("a", 0), down("b", 1), down("c", 2), down("d", 3)])
+                .unwrap();
+        let product = TensorProduct::new(
+            Rational::integer(2),
+            vec![metric(up("a", 0), up("c", 1)), r.clone()],
+        );
+        assert_eq!(contract(&product).unwrap().to_string(), "2 R__b _d");
+
+        // g^{ae} g_{ef} V^f raises and lowers back, leaving a free index
+        let v = Tensor::new("V", vec![up("f", 0)]);
+        let product = TensorProduct::new(
+            Rational::ONE,
+            vec![
+                metric(up("a", 0), up("e", 1)),
+                metric(down("e", 0), down("f", 1)),
+                v,
+            ],
+        );
+        assert_eq!(contract(&product).unwrap().to_string(), "V_^a");
+
+        // Free metrics and tetrad labels are left alone
+        let product = TensorProduct::new(
+            Rational::ONE,
+            vec![
+                metric(down("a", 0), down("b", 1)),
+                crate::presets::weyl_scalar(0).unwrap(),
+            ],
+        );
+        assert_eq!(contract(&product).unwrap(), product);
+
+        // A name may occur at most twice
+        let product = TensorProduct::new(
+            Rational::ONE,
+            vec![
+                metric(up("a", 0), up("b", 1)),
+                r,
+                Tensor::new("V", vec![up("a", 0)]),
+            ],
+        );
+        assert!(matches!(
+            contract(&product),
+            Err(ButlerPortugalError::IncompatibleTensors(_))
+        ));
+    }
+}
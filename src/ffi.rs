@@ -3,11 +3,15 @@
 //! Provides C-compatible bindings for tensor canonicalization functionality.
 //! All types are exposed as opaque pointers with explicit lifetime management.
 
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::ptr;
+use std::sync::{Mutex, PoisonError};
 
-use crate::canonicalization::canonicalize;
+use crate::cache::CanonicalizationCache;
+use crate::canonicalization::{canonicalize, canonicalize_with_config};
+use crate::config::{CanonicalizationConfig, OrderingVersion};
 use crate::index::TensorIndex;
 use crate::symmetry::Symmetry;
 use crate::tensor::Tensor;
@@ -19,9 +23,128 @@ pub type TensorHandle = *mut Tensor;
 pub type TensorIndexHandle = *mut TensorIndex;
 /// Opaque handle to a Symmetry
 pub type SymmetryHandle = *mut Symmetry;
+/// Opaque handle to a Context
+pub type ContextHandle = *mut Context;
+/// Opaque handle to an Arena
+pub type ArenaHandle = *mut Arena;
+
+/// Number of objects per arena chunk
+const ARENA_CHUNK: usize = 1024;
+
+/// Owner of many indices and tensors, freed together in one call
+///
+/// Objects are stored in fixed-capacity chunks, so allocating one is a push
+/// into an existing buffer and handles stay valid until the arena is freed.
+/// An arena is not thread-safe.
+#[derive(Debug, Default)]
+pub struct Arena {
+    indices: Chunks<TensorIndex>,
+    tensors: Chunks<Tensor>,
+}
+
+/// Append-only storage whose elements never move
+#[derive(Debug)]
+struct Chunks<T> {
+    chunks: Vec<Vec<T>>,
+}
+
+impl<T> Default for Chunks<T> {
+    fn default() -> Self {
+        Self { chunks: Vec::new() }
+    }
+}
+
+impl<T> Chunks<T> {
+    /// Stores a value and returns a pointer to it, valid as long as `self`
+    fn alloc(&mut self, value: T) -> *mut T {
+        // A chunk is never pushed past its capacity, so it never reallocates
+        if self.chunks.last().map_or(true, |c| c.len() == c.capacity()) {
+            self.chunks.push(Vec::with_capacity(ARENA_CHUNK));
+        }
+        let Some(chunk) = self.chunks.last_mut() else {
+            return ptr::null_mut();
+        };
+        chunk.push(value);
+        chunk.last_mut().map_or(ptr::null_mut(), |v| v as *mut T)
+    }
+
+    /// Returns the number of stored values
+    fn len(&self) -> usize {
+        self.chunks.iter().map(Vec::len).sum()
+    }
+}
+
+/// Configuration, cache and error state for FFI calls made through one handle
+///
+/// A context replaces the process-global state of the plain entry points.
+/// It is safe to share between threads: calls on the same context are
+/// serialized by an internal lock, and calls on different contexts run
+/// concurrently.
+#[derive(Debug, Default)]
+pub struct Context {
+    state: Mutex<ContextState>,
+}
+
+/// The state guarded by a context's lock
+#[derive(Debug, Default)]
+struct ContextState {
+    config: CanonicalizationConfig,
+    cache: CanonicalizationCache,
+    last_error: Option<String>,
+    names: NameTable,
+}
+
+/// Index names registered under numeric identifiers
+#[derive(Debug, Default)]
+struct NameTable {
+    names: HashMap<u32, String>,
+    ids: HashMap<String, u32>,
+}
+
+impl NameTable {
+    /// Binds `id` to `name`, failing if either is already bound to something else
+    fn register(&mut self, id: u32, name: &str) -> std::result::Result<(), String> {
+        match (self.names.get(&id), self.ids.get(name)) {
+            (Some(existing), _) if existing != name => Err(format!(
+                "Index id {id} is already registered as {existing:?}"
+            )),
+            (_, Some(&existing)) if existing != id => Err(format!(
+                "Index name {name:?} is already registered as id {existing}"
+            )),
+            _ => {
+                self.names.insert(id, name.to_string());
+                self.ids.insert(name.to_string(), id);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl ContextState {
+    /// Replaces the configuration, dropping cached forms it invalidates
+    fn set_config(&mut self, config: CanonicalizationConfig) {
+        // Cached forms were chosen under the previous conventions
+        if self.config.ordering_version != config.ordering_version {
+            self.cache.clear();
+        }
+        self.config = config;
+    }
+}
+
+impl Context {
+    /// Runs `f` on the context state, recovering it if another caller panicked
+    fn with_state<R>(&self, f: impl FnOnce(&mut ContextState) -> R) -> R {
+        let mut guard = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&mut guard)
+    }
+}
 
 /// Result codes for FFI operations
+///
+/// The discriminants are part of the C ABI: existing codes keep their values
+/// in every release and new codes are only ever appended.
 #[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BPResult {
     /// Operation succeeded
     Success = 0,
@@ -35,6 +158,53 @@ pub enum BPResult {
     AllocationError = 4,
 }
 
+/// Plain-old-data canonicalization configuration for FFI
+///
+/// The layout is fixed: fields are only ever appended, and `struct_size`
+/// records how many bytes the caller's version of the struct has, so older
+/// bindings keep working against newer libraries. Obtain an initialized value
+/// from `bp_config_default`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BPConfig {
+    /// `size_of::<BPConfig>()` as known to the caller
+    pub struct_size: u32,
+    /// Ordering version; see [`OrderingVersion`]. Versions 1 and 2 are defined.
+    pub ordering_version: u32,
+}
+
+impl BPConfig {
+    /// Converts to a configuration, or `None` if a field is out of range
+    fn to_config(self) -> Option<CanonicalizationConfig> {
+        if (self.struct_size as usize) < std::mem::size_of::<BPConfig>() {
+            return None;
+        }
+        let ordering_version = ordering_version_from_u32(self.ordering_version)?;
+        Some(CanonicalizationConfig::new().with_ordering_version(ordering_version))
+    }
+}
+
+impl From<CanonicalizationConfig> for BPConfig {
+    fn from(config: CanonicalizationConfig) -> Self {
+        Self {
+            struct_size: std::mem::size_of::<BPConfig>() as u32,
+            ordering_version: match config.ordering_version {
+                OrderingVersion::V1 => 1,
+                OrderingVersion::V2 => 2,
+            },
+        }
+    }
+}
+
+/// Maps the C representation of an ordering version to the Rust one
+fn ordering_version_from_u32(version: u32) -> Option<OrderingVersion> {
+    match version {
+        1 => Some(OrderingVersion::V1),
+        2 => Some(OrderingVersion::V2),
+        _ => None,
+    }
+}
+
 // -----------------------------------------------------------------------------
 // TensorIndex Functions
 // -----------------------------------------------------------------------------
@@ -99,6 +269,49 @@ pub unsafe extern "C" fn bp_index_clone(index: TensorIndexHandle) -> TensorIndex
     Box::into_raw(Box::new((*index).clone()))
 }
 
+/// Create `count` tensor indices in one call, the `i`-th at position `i`.
+///
+/// # Safety
+/// - `names` must point to `count` valid null-terminated C strings.
+/// - `contravariant` must be null (all covariant) or point to `count` flags.
+/// - `out` must point to space for `count` handles. On failure no handles are
+///   written.
+/// - Every returned handle must be freed, e.g. with `bp_index_free_all`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_indices_new(
+    names: *const *const c_char,
+    contravariant: *const bool,
+    count: usize,
+    out: *mut TensorIndexHandle,
+) -> BPResult {
+    match read_indices(names, contravariant, count) {
+        Ok(indices) => {
+            if out.is_null() && count > 0 {
+                return BPResult::NullPointer;
+            }
+            for (i, index) in indices.into_iter().enumerate() {
+                *out.add(i) = Box::into_raw(Box::new(index));
+            }
+            BPResult::Success
+        }
+        Err(code) => code,
+    }
+}
+
+/// Free `count` tensor indices in one call.
+///
+/// # Safety
+/// - `handles` must point to `count` handles, each valid or null.
+#[no_mangle]
+pub unsafe extern "C" fn bp_index_free_all(handles: *const TensorIndexHandle, count: usize) {
+    if handles.is_null() {
+        return;
+    }
+    for &handle in std::slice::from_raw_parts(handles, count) {
+        bp_index_free(handle);
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Symmetry Functions
 // -----------------------------------------------------------------------------
@@ -298,6 +511,36 @@ pub unsafe extern "C" fn bp_tensor_with_coefficient(
     )))
 }
 
+/// Parse a tensor written in LaTeX index notation, such as `R_{mu nu}^{rho sigma}`.
+/// Returns null and sets `error_out` to `InvalidArgument` if the text is not a
+/// single tensor.
+///
+/// # Safety
+/// - `input` must be a valid null-terminated C string.
+/// - The returned handle must be freed with `bp_tensor_free`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_tensor_parse(
+    input: *const c_char,
+    error_out: *mut BPResult,
+) -> TensorHandle {
+    let set_error = |result: BPResult| {
+        if !error_out.is_null() {
+            *error_out = result;
+        }
+    };
+    if input.is_null() {
+        set_error(BPResult::NullPointer);
+        return ptr::null_mut();
+    }
+    if let Ok(Ok(tensor)) = CStr::from_ptr(input).to_str().map(Tensor::parse) {
+        set_error(BPResult::Success);
+        Box::into_raw(Box::new(tensor))
+    } else {
+        set_error(BPResult::InvalidArgument);
+        ptr::null_mut()
+    }
+}
+
 /// Free a tensor.
 ///
 /// # Safety
@@ -309,6 +552,20 @@ pub unsafe extern "C" fn bp_tensor_free(tensor: TensorHandle) {
     }
 }
 
+/// Free `count` tensors in one call.
+///
+/// # Safety
+/// - `handles` must point to `count` handles, each valid or null.
+#[no_mangle]
+pub unsafe extern "C" fn bp_tensor_free_all(handles: *const TensorHandle, count: usize) {
+    if handles.is_null() {
+        return;
+    }
+    for &handle in std::slice::from_raw_parts(handles, count) {
+        bp_tensor_free(handle);
+    }
+}
+
 /// Clone a tensor.
 ///
 /// # Safety
@@ -440,6 +697,434 @@ pub unsafe extern "C" fn bp_canonicalize(
     }
 }
 
+// -----------------------------------------------------------------------------
+// Arena Functions
+// -----------------------------------------------------------------------------
+
+/// Create an empty arena.
+/// The returned handle must be freed with `bp_arena_free`.
+#[no_mangle]
+pub extern "C" fn bp_arena_new() -> ArenaHandle {
+    Box::into_raw(Box::default())
+}
+
+/// Free an arena together with every index and tensor allocated in it.
+///
+/// # Safety
+/// - `arena` must be a valid handle or null.
+/// - Handles allocated in the arena must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn bp_arena_free(arena: ArenaHandle) {
+    if !arena.is_null() {
+        drop(Box::from_raw(arena));
+    }
+}
+
+/// Create `count` tensor indices owned by an arena, the `i`-th at position `i`.
+///
+/// # Safety
+/// - `arena` must be a valid non-null handle.
+/// - `names`, `contravariant` and `out` as for `bp_indices_new`.
+/// - The handles are owned by the arena and must not be passed to `bp_index_free`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_arena_indices_new(
+    arena: ArenaHandle,
+    names: *const *const c_char,
+    contravariant: *const bool,
+    count: usize,
+    out: *mut TensorIndexHandle,
+) -> BPResult {
+    if arena.is_null() || (out.is_null() && count > 0) {
+        return BPResult::NullPointer;
+    }
+    match read_indices(names, contravariant, count) {
+        Ok(indices) => {
+            for (i, index) in indices.into_iter().enumerate() {
+                *out.add(i) = (*arena).indices.alloc(index);
+            }
+            BPResult::Success
+        }
+        Err(code) => code,
+    }
+}
+
+/// Create a tensor owned by an arena.
+///
+/// # Safety
+/// - `arena` must be a valid non-null handle.
+/// - `name` and `indices` as for `bp_tensor_new`; the indices may come from any arena.
+/// - The handle is owned by the arena and must not be passed to `bp_tensor_free`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_arena_tensor_new(
+    arena: ArenaHandle,
+    name: *const c_char,
+    indices: *const TensorIndexHandle,
+    num_indices: usize,
+) -> TensorHandle {
+    if arena.is_null() {
+        return ptr::null_mut();
+    }
+    let tensor = bp_tensor_new(name, indices, num_indices);
+    if tensor.is_null() {
+        return ptr::null_mut();
+    }
+    (*arena).tensors.alloc(*Box::from_raw(tensor))
+}
+
+/// Get the number of indices and tensors owned by an arena.
+///
+/// # Safety
+/// - `arena` must be a valid non-null handle.
+#[no_mangle]
+pub unsafe extern "C" fn bp_arena_len(arena: ArenaHandle) -> usize {
+    if arena.is_null() {
+        return 0;
+    }
+    (*arena).indices.len() + (*arena).tensors.len()
+}
+
+/// Reads `count` indices from parallel arrays of names and variance flags
+///
+/// # Safety
+/// - Same requirements as `bp_indices_new` for `names` and `contravariant`.
+unsafe fn read_indices(
+    names: *const *const c_char,
+    contravariant: *const bool,
+    count: usize,
+) -> Result<Vec<TensorIndex>, BPResult> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    if names.is_null() {
+        return Err(BPResult::NullPointer);
+    }
+    let names = std::slice::from_raw_parts(names, count);
+    let flags =
+        (!contravariant.is_null()).then(|| std::slice::from_raw_parts(contravariant, count));
+    let mut indices = Vec::with_capacity(count);
+    for (position, &name) in names.iter().enumerate() {
+        if name.is_null() {
+            return Err(BPResult::NullPointer);
+        }
+        let Ok(name) = CStr::from_ptr(name).to_str() else {
+            return Err(BPResult::InvalidArgument);
+        };
+        indices.push(if flags.is_some_and(|flags| flags[position]) {
+            TensorIndex::contravariant(name, position)
+        } else {
+            TensorIndex::new(name, position)
+        });
+    }
+    Ok(indices)
+}
+
+// -----------------------------------------------------------------------------
+// Context Functions
+// -----------------------------------------------------------------------------
+
+/// Create a new context with the default configuration and an empty cache.
+/// The returned handle must be freed with `bp_context_free`.
+#[no_mangle]
+pub extern "C" fn bp_context_new() -> ContextHandle {
+    Box::into_raw(Box::default())
+}
+
+/// Create a new context with the given configuration and an empty cache.
+/// Returns null if `config` is null or holds an unknown value.
+/// The returned handle must be freed with `bp_context_free`.
+///
+/// # Safety
+/// - `config` must point to a `BPConfig` or be null.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_new_with_config(config: *const BPConfig) -> ContextHandle {
+    let Some(config) = config.as_ref().and_then(|c| c.to_config()) else {
+        return ptr::null_mut();
+    };
+    let context = Context::default();
+    context.with_state(|state| state.config = config);
+    Box::into_raw(Box::new(context))
+}
+
+/// Return the default configuration, with `struct_size` filled in.
+#[no_mangle]
+pub extern "C" fn bp_config_default() -> BPConfig {
+    BPConfig::from(CanonicalizationConfig::default())
+}
+
+/// Read the configuration of a context.
+///
+/// # Safety
+/// - `context` must be a valid non-null handle.
+/// - `out` must point to a writable `BPConfig`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_config(context: ContextHandle, out: *mut BPConfig) -> BPResult {
+    if context.is_null() || out.is_null() {
+        return BPResult::NullPointer;
+    }
+    *out = BPConfig::from((*context).with_state(|state| state.config.clone()));
+    BPResult::Success
+}
+
+/// Replace the configuration of a context.
+///
+/// # Safety
+/// - `context` must be a valid non-null handle.
+/// - `config` must point to a `BPConfig`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_set_config(
+    context: ContextHandle,
+    config: *const BPConfig,
+) -> BPResult {
+    if context.is_null() || config.is_null() {
+        return BPResult::NullPointer;
+    }
+    let Some(config) = (*config).to_config() else {
+        return BPResult::InvalidArgument;
+    };
+    (*context).with_state(|state| state.set_config(config));
+    BPResult::Success
+}
+
+/// Free a context.
+///
+/// # Safety
+/// - `context` must be a valid handle or null.
+/// - No other thread may be using the context.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_free(context: ContextHandle) {
+    if !context.is_null() {
+        drop(Box::from_raw(context));
+    }
+}
+
+/// Select the ordering version used by canonicalizations through a context.
+///
+/// # Safety
+/// - `context` must be a valid non-null handle.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_set_ordering_version(
+    context: ContextHandle,
+    version: u32,
+) -> BPResult {
+    if context.is_null() {
+        return BPResult::NullPointer;
+    }
+    let Some(ordering_version) = ordering_version_from_u32(version) else {
+        return BPResult::InvalidArgument;
+    };
+    (*context).with_state(|state| {
+        let config = state.config.clone().with_ordering_version(ordering_version);
+        state.set_config(config);
+    });
+    BPResult::Success
+}
+
+/// Canonicalize a tensor through a context, reusing and filling its cache.
+///
+/// On failure the error message is recorded in the context; see
+/// `bp_context_last_error`.
+///
+/// # Safety
+/// - `context` and `tensor` must be valid non-null handles.
+/// - The returned handle must be freed with `bp_tensor_free`.
+/// - On error, returns null and sets `error_out` if provided.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_canonicalize(
+    context: ContextHandle,
+    tensor: TensorHandle,
+    error_out: *mut BPResult,
+) -> TensorHandle {
+    let set_error = |code: BPResult| {
+        if !error_out.is_null() {
+            *error_out = code;
+        }
+    };
+    if context.is_null() || tensor.is_null() {
+        set_error(BPResult::NullPointer);
+        return ptr::null_mut();
+    }
+    let result = (*context).with_state(|state| {
+        let result = match state.config.ordering_version {
+            OrderingVersion::V1 => state.cache.canonicalize(&*tensor),
+            _ => canonicalize_with_config(&*tensor, &state.config),
+        };
+        state.last_error = result.as_ref().err().map(ToString::to_string);
+        result
+    });
+    if let Ok(canonical) = result {
+        set_error(BPResult::Success);
+        Box::into_raw(Box::new(canonical))
+    } else {
+        set_error(BPResult::CanonicalizationError);
+        ptr::null_mut()
+    }
+}
+
+/// Register the name an integer index identifier stands for in a context.
+///
+/// Registering the same pair twice is allowed; rebinding an identifier or a
+/// name is an error.
+///
+/// # Safety
+/// - `context` must be a valid non-null handle.
+/// - `name` must be a valid null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_register_name(
+    context: ContextHandle,
+    id: u32,
+    name: *const c_char,
+) -> BPResult {
+    if context.is_null() || name.is_null() {
+        return BPResult::NullPointer;
+    }
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return BPResult::InvalidArgument;
+    };
+    (*context).with_state(|state| {
+        let result = state.names.register(id, name);
+        state.last_error = result.as_ref().err().cloned();
+        if result.is_ok() {
+            BPResult::Success
+        } else {
+            BPResult::InvalidArgument
+        }
+    })
+}
+
+/// Create a tensor whose indices are given by registered identifiers, the
+/// `i`-th at position `i`.
+///
+/// # Safety
+/// - `context` must be a valid non-null handle.
+/// - `name` must be a valid null-terminated C string.
+/// - `ids` must point to `count` identifiers registered with `bp_context_register_name`.
+/// - `contravariant` must be null (all covariant) or point to `count` flags.
+/// - The returned handle must be freed with `bp_tensor_free`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_tensor_from_ids(
+    context: ContextHandle,
+    name: *const c_char,
+    ids: *const u32,
+    contravariant: *const bool,
+    count: usize,
+) -> TensorHandle {
+    if context.is_null() || name.is_null() || (ids.is_null() && count > 0) {
+        return ptr::null_mut();
+    }
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return ptr::null_mut();
+    };
+    let ids = if count > 0 {
+        std::slice::from_raw_parts(ids, count)
+    } else {
+        &[]
+    };
+    let flags =
+        (!contravariant.is_null()).then(|| std::slice::from_raw_parts(contravariant, count));
+    let indices = (*context).with_state(|state| {
+        let indices: std::result::Result<Vec<TensorIndex>, u32> = ids
+            .iter()
+            .enumerate()
+            .map(|(position, id)| {
+                let name = state.names.names.get(id).ok_or(*id)?;
+                Ok(if flags.is_some_and(|flags| flags[position]) {
+                    TensorIndex::contravariant(name, position)
+                } else {
+                    TensorIndex::new(name, position)
+                })
+            })
+            .collect();
+        state.last_error = indices
+            .as_ref()
+            .err()
+            .map(|id| format!("Index id {id} is not registered"));
+        indices
+    });
+    indices.map_or(ptr::null_mut(), |indices| {
+        Box::into_raw(Box::new(Tensor::new(name, indices)))
+    })
+}
+
+/// Write the registered identifiers of a tensor's indices, slot by slot.
+///
+/// # Safety
+/// - `context` and `tensor` must be valid non-null handles.
+/// - `out_ids` must point to space for `count` identifiers, where `count` is
+///   at least the tensor's rank.
+/// - `out_contravariant` must be null or point to space for `count` flags.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_tensor_ids(
+    context: ContextHandle,
+    tensor: TensorHandle,
+    out_ids: *mut u32,
+    out_contravariant: *mut bool,
+    count: usize,
+) -> BPResult {
+    if context.is_null() || tensor.is_null() || out_ids.is_null() {
+        return BPResult::NullPointer;
+    }
+    let indices = (*tensor).indices();
+    if count < indices.len() {
+        return BPResult::InvalidArgument;
+    }
+    (*context).with_state(|state| {
+        for (slot, index) in indices.iter().enumerate() {
+            let Some(&id) = state.names.ids.get(index.name()) else {
+                state.last_error = Some(format!("Index name {:?} is not registered", index.name()));
+                return BPResult::InvalidArgument;
+            };
+            *out_ids.add(slot) = id;
+            if !out_contravariant.is_null() {
+                *out_contravariant.add(slot) = index.is_contravariant();
+            }
+        }
+        state.last_error = None;
+        BPResult::Success
+    })
+}
+
+/// Get the message of the last failed call on a context.
+/// Returns a newly allocated C string, or null if the last call succeeded.
+///
+/// # Safety
+/// - `context` must be a valid non-null handle.
+/// - The returned string must be freed with `bp_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_last_error(context: ContextHandle) -> *mut c_char {
+    if context.is_null() {
+        return ptr::null_mut();
+    }
+    (*context)
+        .with_state(|state| state.last_error.clone())
+        .and_then(|message| CString::new(message).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Get the number of canonical forms cached by a context.
+///
+/// # Safety
+/// - `context` must be a valid non-null handle.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_cache_len(context: ContextHandle) -> usize {
+    if context.is_null() {
+        return 0;
+    }
+    (*context).with_state(|state| state.cache.len())
+}
+
+/// Remove all cached data from a context.
+///
+/// # Safety
+/// - `context` must be a valid non-null handle.
+#[no_mangle]
+pub unsafe extern "C" fn bp_context_clear_cache(context: ContextHandle) -> BPResult {
+    if context.is_null() {
+        return BPResult::NullPointer;
+    }
+    (*context).with_state(|state| state.cache.clear());
+    BPResult::Success
+}
+
 // -----------------------------------------------------------------------------
 // Version Information
 // -----------------------------------------------------------------------------
@@ -521,4 +1206,216 @@ mod tests {
             assert!(bp_tensor_is_zero(ptr::null_mut()));
         }
     }
+
+    #[test]
+    fn test_ffi_context_shared_between_threads() {
+        unsafe {
+            let context = bp_context_new();
+            assert!(!context.is_null());
+            assert!(matches!(
+                bp_context_set_ordering_version(context, 1),
+                BPResult::Success
+            ));
+            assert!(matches!(
+                bp_context_set_ordering_version(context, 0),
+                BPResult::InvalidArgument
+            ));
+
+            // Raw pointers are not Send; the context itself is Sync
+            let shared = context as usize;
+            std::thread::scope(|scope| {
+                for names in [["b", "a"], ["a", "b"], ["d", "c"], ["b", "a"]] {
+                    scope.spawn(move || {
+                        let context = shared as ContextHandle;
+                        let mut tensor = Tensor::new(
+                            "F",
+                            vec![TensorIndex::new(names[0], 0), TensorIndex::new(names[1], 1)],
+                        );
+                        tensor.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+                        let mut error = BPResult::NullPointer;
+                        let canonical = bp_context_canonicalize(context, &mut tensor, &mut error);
+                        assert!(matches!(error, BPResult::Success));
+                        assert_eq!(
+                            bp_tensor_coefficient(canonical),
+                            if names[0] < names[1] { 1 } else { -1 }
+                        );
+                        bp_tensor_free(canonical);
+                    });
+                }
+            });
+            assert_eq!(bp_context_cache_len(context), 3);
+            assert!(bp_context_last_error(context).is_null());
+
+            // A malformed symmetry is reported through the context
+            let mut bad = Tensor::new("T", vec![TensorIndex::new("a", 0)]);
+            bad.add_symmetry(Symmetry::custom(vec![vec![0, 1]], vec![1]));
+            let canonical = bp_context_canonicalize(context, &mut bad, ptr::null_mut());
+            assert!(canonical.is_null());
+            let message = bp_context_last_error(context);
+            assert!(!message.is_null());
+            bp_string_free(message);
+
+            assert!(matches!(bp_context_clear_cache(context), BPResult::Success));
+            assert_eq!(bp_context_cache_len(context), 0);
+            bp_context_free(context);
+            bp_context_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_ffi_tensor_parse() {
+        unsafe {
+            let input = CString::new("R_{ba}^{cd}").expect("CString::new failed");
+            let mut error = BPResult::AllocationError;
+            let tensor = bp_tensor_parse(input.as_ptr(), &mut error);
+            assert!(matches!(error, BPResult::Success));
+            assert_eq!(bp_tensor_rank(tensor), 4);
+            let canonical = bp_canonicalize(tensor, ptr::null_mut());
+            assert_eq!(bp_tensor_coefficient(canonical), -1);
+            bp_tensor_free(canonical);
+            bp_tensor_free(tensor);
+
+            let sum = CString::new("A_a + B_a").expect("CString::new failed");
+            assert!(bp_tensor_parse(sum.as_ptr(), &mut error).is_null());
+            assert!(matches!(error, BPResult::InvalidArgument));
+            assert!(bp_tensor_parse(ptr::null(), &mut error).is_null());
+            assert!(matches!(error, BPResult::NullPointer));
+        }
+    }
+
+    #[test]
+    fn test_ffi_bulk_and_arena_allocation() {
+        unsafe {
+            let names: Vec<CString> = ["b", "a", "c"]
+                .iter()
+                .map(|n| CString::new(*n).expect("CString::new failed"))
+                .collect();
+            let name_ptrs: Vec<*const c_char> = names.iter().map(|n| n.as_ptr()).collect();
+            let flags = [true, false, false];
+
+            let mut owned = [ptr::null_mut(); 3];
+            let result = bp_indices_new(name_ptrs.as_ptr(), flags.as_ptr(), 3, owned.as_mut_ptr());
+            assert!(matches!(result, BPResult::Success));
+            assert!((*owned[0]).is_contravariant());
+            assert_eq!((*owned[2]).position(), 2);
+
+            let arena = bp_arena_new();
+            let mut borrowed = [ptr::null_mut(); 3];
+            let result = bp_arena_indices_new(
+                arena,
+                name_ptrs.as_ptr(),
+                ptr::null(),
+                3,
+                borrowed.as_mut_ptr(),
+            );
+            assert!(matches!(result, BPResult::Success));
+
+            // Enough tensors to span several chunks
+            let tensor_name = CString::new("T").expect("CString::new failed");
+            let tensors: Vec<TensorHandle> = (0..2 * ARENA_CHUNK)
+                .map(|_| bp_arena_tensor_new(arena, tensor_name.as_ptr(), borrowed.as_ptr(), 3))
+                .collect();
+            assert!(tensors.iter().all(|t| !t.is_null()));
+            assert_eq!(bp_tensor_rank(tensors[0]), 3);
+            assert_eq!(bp_tensor_rank(tensors[2 * ARENA_CHUNK - 1]), 3);
+            assert_eq!(bp_arena_len(arena), 3 + 2 * ARENA_CHUNK);
+
+            let copies: Vec<TensorHandle> =
+                tensors[..2].iter().map(|&t| bp_tensor_clone(t)).collect();
+            bp_tensor_free_all(copies.as_ptr(), copies.len());
+            bp_arena_free(arena);
+            bp_index_free_all(owned.as_ptr(), owned.len());
+        }
+    }
+
+    #[test]
+    fn test_ffi_numeric_index_ids() {
+        unsafe {
+            let context = bp_context_new();
+            for (id, name) in [(7, "a"), (3, "b"), (9, "c")] {
+                let name = CString::new(name).expect("CString::new failed");
+                let result = bp_context_register_name(context, id, name.as_ptr());
+                assert!(matches!(result, BPResult::Success));
+            }
+            let other = CString::new("z").expect("CString::new failed");
+            let result = bp_context_register_name(context, 7, other.as_ptr());
+            assert!(matches!(result, BPResult::InvalidArgument));
+
+            let tensor_name = CString::new("A").expect("CString::new failed");
+            let ids = [9, 3, 7];
+            let tensor = bp_context_tensor_from_ids(
+                context,
+                tensor_name.as_ptr(),
+                ids.as_ptr(),
+                ptr::null(),
+                3,
+            );
+            assert!(!tensor.is_null());
+            let sym_indices: [usize; 3] = [0, 1, 2];
+            let symmetry = bp_symmetry_antisymmetric(sym_indices.as_ptr(), 3);
+            bp_tensor_add_symmetry(tensor, symmetry);
+
+            let canonical = bp_context_canonicalize(context, tensor, ptr::null_mut());
+            let mut out = [0u32; 3];
+            let mut flags = [true; 3];
+            let result =
+                bp_context_tensor_ids(context, canonical, out.as_mut_ptr(), flags.as_mut_ptr(), 3);
+            assert!(matches!(result, BPResult::Success));
+            assert_eq!(out, [7, 3, 9]);
+            assert_eq!(flags, [false; 3]);
+            assert_eq!(bp_tensor_coefficient(canonical), -1);
+
+            let unknown = [1];
+            let missing = bp_context_tensor_from_ids(
+                context,
+                tensor_name.as_ptr(),
+                unknown.as_ptr(),
+                ptr::null(),
+                1,
+            );
+            assert!(missing.is_null());
+            let message = bp_context_last_error(context);
+            assert!(!message.is_null());
+
+            bp_string_free(message);
+            bp_tensor_free(canonical);
+            bp_symmetry_free(symmetry);
+            bp_tensor_free(tensor);
+            bp_context_free(context);
+        }
+    }
+
+    #[test]
+    fn test_ffi_layout_is_fixed() {
+        assert_eq!(BPResult::Success as i32, 0);
+        assert_eq!(BPResult::NullPointer as i32, 1);
+        assert_eq!(BPResult::InvalidArgument as i32, 2);
+        assert_eq!(BPResult::CanonicalizationError as i32, 3);
+        assert_eq!(BPResult::AllocationError as i32, 4);
+        assert_eq!(std::mem::size_of::<BPResult>(), 4);
+        assert_eq!(std::mem::size_of::<BPConfig>(), 8);
+        assert_eq!(std::mem::align_of::<BPConfig>(), 4);
+
+        unsafe {
+            let mut config = bp_config_default();
+            assert_eq!(config.struct_size, 8);
+            assert_eq!(config.ordering_version, 1);
+
+            let context = bp_context_new_with_config(&config);
+            assert!(!context.is_null());
+            config.ordering_version = 7;
+            assert_eq!(
+                bp_context_set_config(context, &config),
+                BPResult::InvalidArgument
+            );
+            assert!(bp_context_new_with_config(&config).is_null());
+            let mut read = BPConfig {
+                struct_size: 0,
+                ordering_version: 0,
+            };
+            assert_eq!(bp_context_config(context, &mut read), BPResult::Success);
+            assert_eq!(read, bp_config_default());
+            bp_context_free(context);
+        }
+    }
 }
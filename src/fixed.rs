@@ -0,0 +1,242 @@
+//! Fixed-rank tensors for allocation-free hot loops
+//!
+//! A [`FixedTensor<R>`] stores `R` integer-labelled indices in an array, so it
+//! lives entirely on the stack. Its slot symmetry group is prepared once as a
+//! [`FixedGroup<R>`], using the same symmetry declarations, group
+//! construction and signs as [`Tensor`]; canonicalizing a fixed tensor then
+//! only scans that group with array-backed permutations and never allocates.
+//!
+//! Labels are compared as integers, playing the role of index names, and
+//! contravariant indices sort before covariant ones with the same label, as in
+//! [`OrderingVersion::V1`](crate::config::OrderingVersion::V1).
+
+use crate::error::{ButlerPortugalError, Result};
+use crate::index::TensorIndex;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+use std::cmp::Ordering;
+
+/// An index of a fixed tensor: an integer label and a variance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FixedIndex {
+    /// The label, standing in for an index name
+    pub label: u32,
+    /// Whether the index is contravariant (true) or covariant (false)
+    pub contravariant: bool,
+}
+
+impl FixedIndex {
+    /// Creates a covariant index
+    pub const fn covariant(label: u32) -> Self {
+        Self {
+            label,
+            contravariant: false,
+        }
+    }
+
+    /// Creates a contravariant index
+    pub const fn contravariant(label: u32) -> Self {
+        Self {
+            label,
+            contravariant: true,
+        }
+    }
+}
+
+impl PartialOrd for FixedIndex {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FixedIndex {
+    /// Orders by label, then contravariant before covariant
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.label
+            .cmp(&other.label)
+            .then_with(|| other.contravariant.cmp(&self.contravariant))
+    }
+}
+
+/// A tensor of compile-time rank `R` with array-backed indices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedTensor<const R: usize> {
+    /// The index in each slot
+    pub indices: [FixedIndex; R],
+    /// The scalar coefficient
+    pub coefficient: i32,
+}
+
+impl<const R: usize> FixedTensor<R> {
+    /// Creates a tensor with unit coefficient
+    pub const fn new(indices: [FixedIndex; R]) -> Self {
+        Self {
+            indices,
+            coefficient: 1,
+        }
+    }
+
+    /// Returns true if the coefficient is zero
+    pub const fn is_zero(&self) -> bool {
+        self.coefficient == 0
+    }
+
+    /// Returns the tensor with `new[k] = old[perm[k]]` and the given sign folded in
+    fn permuted(&self, perm: &[usize; R], sign: i32) -> Self {
+        Self {
+            indices: std::array::from_fn(|k| self.indices[perm[k]]),
+            coefficient: self.coefficient * sign,
+        }
+    }
+}
+
+/// The signed slot symmetry group of a fixed-rank tensor, listed in full
+#[derive(Debug, Clone)]
+pub struct FixedGroup<const R: usize> {
+    elements: Vec<([usize; R], i32)>,
+}
+
+impl<const R: usize> FixedGroup<R> {
+    /// Builds the group generated by `symmetries` on `R` slots
+    ///
+    /// The symmetries are declared exactly as on a [`Tensor`].
+    ///
+    /// # Errors
+    /// Returns an `InvalidSymmetry` error if a symmetry is inconsistent or
+    /// refers to a slot beyond `R`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::fixed::{FixedGroup, FixedIndex, FixedTensor};
+    /// use butler_portugal::Symmetry;
+    ///
+    /// let riemann = FixedGroup::<4>::new(&[
+    ///     Symmetry::antisymmetric(vec![0, 1]),
+    ///     Symmetry::antisymmetric(vec![2, 3]),
+    ///     Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]),
+    /// ])?;
+    /// assert_eq!(riemann.order(), 8);
+    ///
+    /// let r = FixedTensor::new([3, 2, 1, 0].map(FixedIndex::covariant));
+    /// let canonical = riemann.canonicalize(&r);
+    /// assert_eq!(canonical.indices, [0, 1, 2, 3].map(FixedIndex::covariant));
+    /// assert_eq!(canonical.coefficient, 1);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn new(symmetries: &[Symmetry]) -> Result<Self> {
+        let mut template = Tensor::new(
+            "T",
+            (0..R).map(|slot| TensorIndex::new("i", slot)).collect(),
+        );
+        for symmetry in symmetries {
+            if let Some(slot) = symmetry.slots().into_iter().find(|&slot| slot >= R) {
+                return Err(ButlerPortugalError::InvalidSymmetry(format!(
+                    "Slot {slot} is out of range for rank {R}"
+                )));
+            }
+            symmetry.validate(R)?;
+            template.add_symmetry(symmetry.clone());
+        }
+        let mut elements = Vec::new();
+        for element in template.signed_symmetry_group().elements() {
+            let perm: [usize; R] =
+                element
+                    .permutation
+                    .into_images()
+                    .try_into()
+                    .map_err(|perm: Vec<usize>| {
+                        ButlerPortugalError::InvalidSymmetry(format!(
+                            "Group element acts on {} slots, expected {R}",
+                            perm.len()
+                        ))
+                    })?;
+            elements.push((perm, element.sign));
+        }
+        Ok(Self { elements })
+    }
+
+    /// Returns the number of elements of the group
+    pub fn order(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns the canonical form of a tensor under the group, without allocating
+    ///
+    /// The canonical form is the arrangement with the smallest index sequence.
+    /// If that arrangement is reached with both signs, the tensor vanishes and
+    /// the result has coefficient zero.
+    pub fn canonicalize(&self, tensor: &FixedTensor<R>) -> FixedTensor<R> {
+        let mut best = *tensor;
+        let mut vanishes = false;
+        for (perm, sign) in &self.elements {
+            let candidate = tensor.permuted(perm, *sign);
+            match candidate.indices.cmp(&best.indices) {
+                Ordering::Less => {
+                    best = candidate;
+                    vanishes = false;
+                }
+                Ordering::Equal => vanishes |= candidate.coefficient != best.coefficient,
+                Ordering::Greater => {}
+            }
+        }
+        if vanishes {
+            best.coefficient = 0;
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalize;
+
+    #[test]
+    fn test_fixed_matches_dynamic_canonicalization() {
+        let symmetries = [
+            Symmetry::antisymmetric(vec![0, 1, 2]),
+            Symmetry::symmetric(vec![3, 4]),
+        ];
+        let group = FixedGroup::<5>::new(&symmetries).unwrap();
+        assert_eq!(group.order(), 12);
+
+        let names = ["a", "b", "c", "d", "e"];
+        for labels in [
+            [4, 2, 3, 1, 0],
+            [0, 1, 2, 4, 3],
+            [1, 1, 0, 2, 3],
+            [3, 2, 1, 0, 0],
+        ] {
+            let fixed = FixedTensor::new(labels.map(FixedIndex::covariant));
+            let mut dynamic = Tensor::new(
+                "T",
+                labels
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, &label)| TensorIndex::new(names[label as usize], slot))
+                    .collect(),
+            );
+            for symmetry in &symmetries {
+                dynamic.add_symmetry(symmetry.clone());
+            }
+            let expected = canonicalize(&dynamic).unwrap();
+            let canonical = group.canonicalize(&fixed);
+            assert_eq!(canonical.coefficient, expected.coefficient());
+            if !canonical.is_zero() {
+                let names: Vec<&str> = canonical
+                    .indices
+                    .iter()
+                    .map(|index| names[index.label as usize])
+                    .collect();
+                let expected: Vec<&str> = expected.indices().iter().map(|i| i.name()).collect();
+                assert_eq!(names, expected);
+            }
+        }
+
+        // Variance breaks ties between equal labels, contravariant first
+        let sym = FixedGroup::<2>::new(&[Symmetry::symmetric(vec![0, 1])]).unwrap();
+        let mixed = FixedTensor::new([FixedIndex::covariant(0), FixedIndex::contravariant(0)]);
+        assert!(sym.canonicalize(&mixed).indices[0].contravariant);
+        assert!(FixedGroup::<2>::new(&[Symmetry::symmetric(vec![0, 2])]).is_err());
+    }
+}
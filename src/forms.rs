@@ -0,0 +1,791 @@
+//! Differential forms with wedge products, exterior derivatives and interior products
+//!
+//! A p-form is a totally antisymmetric covariant tensor of rank p. This module
+//! keeps the bookkeeping at the level of forms (graded commutativity of the
+//! wedge product, the Leibniz rules for `d` and `ι_V`, `d² = 0`, and Cartan's
+//! formula `L_V = d ι_V + ι_V d`) and lowers the result to
+//! ordinary `Tensor`s carrying the corresponding antisymmetry, so differential
+//! form calculations canonicalize correctly.
+//!
+//! Components follow the convention `ω = (1/p!) ω_{a1…ap} dx^{a1}∧…∧dx^{ap}`,
+//! under which
+//! `(α∧β)_{a…b…} = (p+q)!/(p! q!) α_{[a…} β_{b…]}` and
+//! `(dα)_{a0…ap} = (p+1) ∂_{[a0} α_{a1…ap]}`.
+
+use crate::builtin::hodge_dual;
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::TensorProduct;
+use crate::index::TensorIndex;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+use std::fmt;
+
+/// An operator applied to an elementary form
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FormOperator {
+    /// The exterior derivative `d` (raises the degree by one)
+    Exterior,
+    /// The interior product `ι_V` with a vector field (lowers the degree by one)
+    Interior(String),
+    /// The Lie derivative `L_V` along a vector field (preserves the degree)
+    Lie(String),
+}
+
+impl FormOperator {
+    /// Returns true for the odd derivations `d` and `ι_V`, which pick up a sign
+    /// when moved past an odd-degree form
+    fn is_odd(&self) -> bool {
+        !matches!(self, FormOperator::Lie(_))
+    }
+}
+
+/// An elementary differential form `α` with a chain of operators applied,
+/// such as `dα`, `ι_V α` or `d ι_V α`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Form {
+    /// The name/symbol of the form (e.g., "A" for a gauge potential)
+    name: String,
+    /// The degree of the underlying form, before any operator
+    base_degree: usize,
+    /// Operators applied to the form, innermost first
+    operators: Vec<FormOperator>,
+}
+
+impl Form {
+    /// Creates a new p-form
+    ///
+    /// # Arguments
+    /// * `name` - The name/symbol of the form
+    /// * `degree` - The degree p of the form
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::forms::Form;
+    ///
+    /// let a = Form::new("A", 1);
+    /// assert_eq!(a.degree(), 1);
+    /// ```
+    pub fn new(name: &str, degree: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            base_degree: degree,
+            operators: Vec::new(),
+        }
+    }
+
+    /// Returns the name of the form
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the operators applied to the form, innermost first
+    pub fn operators(&self) -> &[FormOperator] {
+        &self.operators
+    }
+
+    /// Returns the degree of the form
+    pub fn degree(&self) -> usize {
+        self.operators
+            .iter()
+            .fold(self.base_degree, |degree, op| match op {
+                FormOperator::Exterior => degree + 1,
+                FormOperator::Interior(_) => degree.saturating_sub(1),
+                FormOperator::Lie(_) => degree,
+            })
+    }
+
+    /// Returns true if this form is an exterior derivative `dα`
+    pub fn is_exact(&self) -> bool {
+        self.operators.last() == Some(&FormOperator::Exterior)
+    }
+
+    /// Applies an operator, returning `None` if the result vanishes identically
+    ///
+    /// Vanishing rules: `d² = 0`, `ι_V ι_V = 0`, and `ι_V` of a 0-form is zero.
+    pub fn apply(&self, operator: FormOperator) -> Option<Form> {
+        let vanishes = match &operator {
+            FormOperator::Exterior => self.is_exact(),
+            FormOperator::Interior(_) => {
+                self.degree() == 0 || self.operators.last() == Some(&operator)
+            }
+            FormOperator::Lie(_) => false,
+        };
+        if vanishes {
+            return None;
+        }
+        let mut form = self.clone();
+        form.operators.push(operator);
+        Some(form)
+    }
+
+    /// Returns the exterior derivative `dα`, or `None` if it vanishes (`d² = 0`)
+    pub fn exterior_derivative(&self) -> Option<Form> {
+        self.apply(FormOperator::Exterior)
+    }
+
+    /// Returns the interior product `ι_V α`, or `None` if it vanishes
+    pub fn interior_product(&self, vector: &str) -> Option<Form> {
+        self.apply(FormOperator::Interior(vector.to_string()))
+    }
+
+    /// Returns the Lie derivative `L_V α` as an unexpanded factor
+    pub fn lie_derivative(&self, vector: &str) -> Form {
+        let mut form = self.clone();
+        form.operators.push(FormOperator::Lie(vector.to_string()));
+        form
+    }
+
+    /// Returns the wedge product `self ∧ other` as a single term
+    pub fn wedge(&self, other: &Form) -> FormTerm {
+        FormTerm::from(self.clone()).wedge(&FormTerm::from(other.clone()))
+    }
+
+    /// Rewrites the innermost Lie derivative with Cartan's formula
+    /// `L_V = d ι_V + ι_V d`, returning the surviving forms
+    fn expand_lie(&self) -> Option<Vec<Form>> {
+        let k = self
+            .operators
+            .iter()
+            .position(|op| matches!(op, FormOperator::Lie(_)))?;
+        let FormOperator::Lie(vector) = &self.operators[k] else {
+            return None;
+        };
+        let inner = Form {
+            name: self.name.clone(),
+            base_degree: self.base_degree,
+            operators: self.operators[..k].to_vec(),
+        };
+        let interior = FormOperator::Interior(vector.clone());
+        let orders = [
+            [interior.clone(), FormOperator::Exterior],
+            [FormOperator::Exterior, interior],
+        ];
+        let expanded = orders
+            .into_iter()
+            .filter_map(|ops| {
+                ops.into_iter()
+                    .chain(self.operators[k + 1..].iter().cloned())
+                    .try_fold(inner.clone(), |form, op| form.apply(op))
+            })
+            .collect();
+        Some(expanded)
+    }
+
+    /// Recognizes a single Cartan term that equals a Lie derivative because its
+    /// partner term vanishes: `ι_V d f = L_V f` for 0-forms and
+    /// `d ι_V dα = L_V dα` for exact forms
+    fn collapse_to_lie(&self) -> Option<Form> {
+        let n = self.operators.len();
+        let base = |len: usize| Form {
+            name: self.name.clone(),
+            base_degree: self.base_degree,
+            operators: self.operators[..len].to_vec(),
+        };
+        match self.operators.as_slice() {
+            [.., FormOperator::Exterior, FormOperator::Interior(v)] => {
+                let inner = base(n - 2);
+                (inner.degree() == 0).then(|| inner.lie_derivative(v))
+            }
+            [.., FormOperator::Interior(v), FormOperator::Exterior] => {
+                let inner = base(n - 2);
+                inner.is_exact().then(|| inner.lie_derivative(v))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the partner of `d ι_V α` in Cartan's formula, i.e. `ι_V dα`, and
+    /// the corresponding `L_V α`
+    fn cartan_partner(&self) -> Option<(Form, Form)> {
+        let n = self.operators.len();
+        let [.., FormOperator::Interior(v), FormOperator::Exterior] = self.operators.as_slice()
+        else {
+            return None;
+        };
+        let inner = Form {
+            name: self.name.clone(),
+            base_degree: self.base_degree,
+            operators: self.operators[..n - 2].to_vec(),
+        };
+        let partner = inner.exterior_derivative()?.interior_product(v)?;
+        Some((partner, inner.lie_derivative(v)))
+    }
+}
+
+impl fmt::Display for Form {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for op in self.operators.iter().rev() {
+            match op {
+                FormOperator::Exterior => write!(f, "d")?,
+                FormOperator::Interior(v) => write!(f, "ι_{v} ")?,
+                FormOperator::Lie(v) => write!(f, "L_{v} ")?,
+            }
+        }
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A wedge product of forms with an integer coefficient
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormTerm {
+    coefficient: i32,
+    factors: Vec<Form>,
+}
+
+impl FormTerm {
+    /// Creates a term from a coefficient and a list of wedge factors
+    pub fn new(coefficient: i32, factors: Vec<Form>) -> Self {
+        Self {
+            coefficient,
+            factors,
+        }
+    }
+
+    /// Returns the coefficient
+    pub fn coefficient(&self) -> i32 {
+        self.coefficient
+    }
+
+    /// Returns the wedge factors in order
+    pub fn factors(&self) -> &[Form] {
+        &self.factors
+    }
+
+    /// Returns the total degree of the term
+    pub fn degree(&self) -> usize {
+        self.factors.iter().map(Form::degree).sum()
+    }
+
+    /// Returns true if the term vanishes
+    pub fn is_zero(&self) -> bool {
+        self.coefficient == 0
+    }
+
+    /// Returns the wedge product `self ∧ other`
+    pub fn wedge(&self, other: &FormTerm) -> FormTerm {
+        let mut factors = self.factors.clone();
+        factors.extend(other.factors.iter().cloned());
+        FormTerm::new(self.coefficient * other.coefficient, factors)
+    }
+
+    /// Brings the factors into canonical (sorted) order
+    ///
+    /// Exchanging adjacent factors of degrees p and q contributes `(-1)^{pq}`,
+    /// and a repeated factor of odd degree makes the term vanish.
+    pub fn canonicalize(&self) -> FormTerm {
+        let mut factors = self.factors.clone();
+        let mut sign = 1;
+        for i in 1..factors.len() {
+            let mut j = i;
+            while j > 0 && factors[j] < factors[j - 1] {
+                if factors[j].degree() * factors[j - 1].degree() % 2 == 1 {
+                    sign = -sign;
+                }
+                factors.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+        let repeated_odd = factors
+            .windows(2)
+            .any(|w| w[0] == w[1] && w[0].degree() % 2 == 1);
+        let coefficient = if repeated_odd {
+            0
+        } else {
+            self.coefficient * sign
+        };
+        FormTerm::new(coefficient, factors)
+    }
+
+    /// Applies the exterior derivative using the graded Leibniz rule
+    ///
+    /// `d(α∧β) = dα∧β + (-1)^p α∧dβ` for a p-form `α`.
+    pub fn exterior_derivative(&self) -> FormExpression {
+        self.apply_derivation(&FormOperator::Exterior)
+    }
+
+    /// Applies the interior product `ι_V`, an antiderivation of degree -1
+    ///
+    /// `ι_V(α∧β) = ι_Vα∧β + (-1)^p α∧ι_Vβ` for a p-form `α`.
+    pub fn interior_product(&self, vector: &str) -> FormExpression {
+        self.apply_derivation(&FormOperator::Interior(vector.to_string()))
+    }
+
+    /// Applies an operator factor by factor, with the sign rule of its parity
+    fn apply_derivation(&self, operator: &FormOperator) -> FormExpression {
+        let mut terms = Vec::new();
+        let mut preceding_degree = 0;
+        for (i, factor) in self.factors.iter().enumerate() {
+            if let Some(derived) = factor.apply(operator.clone()) {
+                let sign = if operator.is_odd() && preceding_degree % 2 == 1 {
+                    -1
+                } else {
+                    1
+                };
+                let mut factors = self.factors.clone();
+                factors[i] = derived;
+                terms.push(FormTerm::new(sign * self.coefficient, factors));
+            }
+            preceding_degree += factor.degree();
+        }
+        FormExpression { terms }
+    }
+
+    /// Lowers the term to its component tensor
+    ///
+    /// The result is a tensor named after the wedge product (e.g. `A∧dB`),
+    /// antisymmetric in all of its slots, whose coefficient includes the
+    /// combinatorial factors of the wedge product and exterior derivatives.
+    ///
+    /// # Arguments
+    /// * `indices` - One covariant index per unit of degree
+    pub fn to_tensor(&self, indices: Vec<TensorIndex>) -> Result<Tensor> {
+        if indices.len() != self.degree() {
+            return Err(ButlerPortugalError::InvalidTensor(format!(
+                "A {}-form needs {} indices, got {}",
+                self.degree(),
+                self.degree(),
+                indices.len()
+            )));
+        }
+        let mut factor = multinomial(self.factors.iter().map(Form::degree))?;
+        for form in &self.factors {
+            // Each exterior derivative of a q-form contributes a factor q + 1
+            let mut degree = form.base_degree;
+            for op in &form.operators {
+                match op {
+                    FormOperator::Exterior => {
+                        degree += 1;
+                        factor = checked_factor(factor, degree)?;
+                    }
+                    FormOperator::Interior(_) => degree -= 1,
+                    FormOperator::Lie(_) => {}
+                }
+            }
+        }
+        let coefficient = self.coefficient.checked_mul(factor).ok_or_else(|| {
+            ButlerPortugalError::MathematicalError(
+                "Form coefficient overflows i32 when lowered".to_string(),
+            )
+        })?;
+        let name = self.wedge_name();
+        let rank = indices.len();
+        let mut tensor = Tensor::with_coefficient(&name, indices, coefficient);
+        if rank > 1 {
+            tensor.add_symmetry(Symmetry::antisymmetric((0..rank).collect()));
+        }
+        Ok(tensor)
+    }
+
+    /// Lowers the term to components and takes its Hodge dual in `dimension` dimensions
+    ///
+    /// `indices` are the component indices of the term itself and
+    /// `dual_indices` the free indices of the dual. See `builtin::hodge_dual`
+    /// for the convention; `builtin::hodge_double_dual_sign` gives `⋆⋆`.
+    pub fn hodge_dual(
+        &self,
+        indices: Vec<TensorIndex>,
+        dimension: usize,
+        dual_indices: Vec<TensorIndex>,
+    ) -> Result<TensorProduct> {
+        hodge_dual(&self.to_tensor(indices)?, dimension, dual_indices)
+    }
+
+    /// Returns the wedge product written out, e.g. `A∧dB`
+    fn wedge_name(&self) -> String {
+        if self.factors.is_empty() {
+            return "1".to_string();
+        }
+        self.factors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("∧")
+    }
+}
+
+impl From<Form> for FormTerm {
+    fn from(form: Form) -> Self {
+        FormTerm::new(1, vec![form])
+    }
+}
+
+impl fmt::Display for FormTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.coefficient == 0 {
+            return write!(f, "0");
+        }
+        let sign = if self.coefficient < 0 { "-" } else { "" };
+        let coeff = if self.coefficient.abs() == 1 && !self.factors.is_empty() {
+            String::new()
+        } else {
+            self.coefficient.abs().to_string()
+        };
+        write!(f, "{sign}{coeff}{}", self.wedge_name())
+    }
+}
+
+/// A sum of wedge-product terms
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FormExpression {
+    terms: Vec<FormTerm>,
+}
+
+impl FormExpression {
+    /// Creates an empty (zero) expression
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the terms of the expression
+    pub fn terms(&self) -> &[FormTerm] {
+        &self.terms
+    }
+
+    /// Adds a term to the expression
+    pub fn push(&mut self, term: FormTerm) {
+        self.terms.push(term);
+    }
+
+    /// Returns the sum of two expressions
+    pub fn add(&self, other: &FormExpression) -> FormExpression {
+        let mut terms = self.terms.clone();
+        terms.extend(other.terms.iter().cloned());
+        FormExpression { terms }
+    }
+
+    /// Returns the wedge product of two expressions, distributing over sums
+    pub fn wedge(&self, other: &FormExpression) -> FormExpression {
+        let mut terms = Vec::with_capacity(self.terms.len() * other.terms.len());
+        for a in &self.terms {
+            for b in &other.terms {
+                terms.push(a.wedge(b));
+            }
+        }
+        FormExpression { terms }
+    }
+
+    /// Applies the exterior derivative term by term
+    pub fn exterior_derivative(&self) -> FormExpression {
+        let mut result = FormExpression::new();
+        for term in &self.terms {
+            result.terms.extend(term.exterior_derivative().terms);
+        }
+        result
+    }
+
+    /// Applies the interior product `ι_V` term by term
+    pub fn interior_product(&self, vector: &str) -> FormExpression {
+        let mut result = FormExpression::new();
+        for term in &self.terms {
+            result.terms.extend(term.interior_product(vector).terms);
+        }
+        result
+    }
+
+    /// Returns the Lie derivative `L_V` of the expression, expanded with
+    /// Cartan's formula `L_V = d ι_V + ι_V d`
+    pub fn lie_derivative(&self, vector: &str) -> FormExpression {
+        self.interior_product(vector)
+            .exterior_derivative()
+            .add(&self.exterior_derivative().interior_product(vector))
+    }
+
+    /// Replaces every `L_V` factor by `d ι_V + ι_V d`
+    pub fn expand_lie_derivatives(&self) -> FormExpression {
+        let mut pending = self.terms.clone();
+        let mut result = FormExpression::new();
+        while let Some(term) = pending.pop() {
+            let expansion = term
+                .factors
+                .iter()
+                .enumerate()
+                .find_map(|(i, f)| f.expand_lie().map(|forms| (i, forms)));
+            match expansion {
+                Some((i, forms)) => {
+                    for form in forms {
+                        let mut factors = term.factors.clone();
+                        factors[i] = form;
+                        pending.push(FormTerm::new(term.coefficient, factors));
+                    }
+                }
+                None => result.terms.push(term),
+            }
+        }
+        result.terms.reverse();
+        result
+    }
+
+    /// Recognizes Cartan's formula in the expression and rewrites
+    /// `d ι_V α + ι_V dα` (with equal coefficients) into `L_V α`
+    ///
+    /// The degenerate cases `ι_V df = L_V f` for 0-forms and
+    /// `d ι_V dα = L_V dα` are rewritten as well.
+    pub fn collect_lie_derivatives(&self) -> FormExpression {
+        let mut terms = self.simplify().terms;
+        let mut i = 0;
+        while i < terms.len() {
+            let mut rewritten = false;
+            for k in 0..terms[i].factors.len() {
+                if let Some((partner, lie)) = terms[i].factors[k].cartan_partner() {
+                    let mut partner_factors = terms[i].factors.clone();
+                    partner_factors[k] = partner;
+                    let position = terms.iter().position(|t| {
+                        t.factors == partner_factors && t.coefficient == terms[i].coefficient
+                    });
+                    if let Some(j) = position {
+                        terms[i].factors[k] = lie;
+                        terms.remove(j);
+                        rewritten = true;
+                        break;
+                    }
+                }
+                if let Some(lie) = terms[i].factors[k].collapse_to_lie() {
+                    terms[i].factors[k] = lie;
+                    rewritten = true;
+                    break;
+                }
+            }
+            if !rewritten || i >= terms.len() {
+                i += 1;
+            } else {
+                i = 0;
+            }
+        }
+        FormExpression { terms }.simplify()
+    }
+
+    /// Canonicalizes every term and collects like terms, dropping zeros
+    pub fn simplify(&self) -> FormExpression {
+        let mut collected: Vec<FormTerm> = Vec::new();
+        for term in self.terms.iter().map(FormTerm::canonicalize) {
+            if term.is_zero() {
+                continue;
+            }
+            match collected.iter_mut().find(|t| t.factors == term.factors) {
+                Some(existing) => existing.coefficient += term.coefficient,
+                None => collected.push(term),
+            }
+        }
+        collected.retain(|t| !t.is_zero());
+        FormExpression { terms: collected }
+    }
+
+    /// Returns true if the expression simplifies to zero
+    pub fn is_zero(&self) -> bool {
+        self.simplify().terms.is_empty()
+    }
+}
+
+impl From<FormTerm> for FormExpression {
+    fn from(term: FormTerm) -> Self {
+        FormExpression { terms: vec![term] }
+    }
+}
+
+impl From<Form> for FormExpression {
+    fn from(form: Form) -> Self {
+        FormExpression::from(FormTerm::from(form))
+    }
+}
+
+impl fmt::Display for FormExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        for (i, term) in self.terms.iter().enumerate() {
+            if i == 0 {
+                write!(f, "{term}")?;
+            } else if term.coefficient < 0 {
+                write!(
+                    f,
+                    " - {}",
+                    FormTerm::new(-term.coefficient, term.factors.clone())
+                )?;
+            } else {
+                write!(f, " + {term}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Multinomial coefficient `(Σ p_i)! / Π p_i!` as an i32
+fn multinomial(degrees: impl Iterator<Item = usize>) -> Result<i32> {
+    let mut result: i32 = 1;
+    let mut total = 0;
+    for p in degrees {
+        // Multiply by binomial(total + p, p) one factor at a time, keeping it exact
+        for k in 1..=p {
+            total += 1;
+            result = checked_factor(result, total)?;
+            result /= k as i32;
+        }
+    }
+    Ok(result)
+}
+
+/// Multiplies by a small factor, reporting overflow as an error
+fn checked_factor(value: i32, factor: usize) -> Result<i32> {
+    i32::try_from(factor)
+        .ok()
+        .and_then(|f| value.checked_mul(f))
+        .ok_or_else(|| {
+            ButlerPortugalError::MathematicalError("Combinatorial factor overflows i32".to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalization::canonicalize;
+
+    #[test]
+    fn test_graded_commutativity() {
+        let a = Form::new("A", 1);
+        let b = Form::new("B", 1);
+        let c = Form::new("C", 2);
+
+        // B∧A = -A∧B for 1-forms
+        let ba = b.wedge(&a).canonicalize();
+        assert_eq!(ba.factors(), &[a.clone(), b.clone()]);
+        assert_eq!(ba.coefficient(), -1);
+
+        // C∧A = A∧C when one factor has even degree
+        let ca = c.wedge(&a).canonicalize();
+        assert_eq!(ca.coefficient(), 1);
+    }
+
+    #[test]
+    fn test_odd_form_squares_to_zero() {
+        let a = Form::new("A", 1);
+        assert!(a.wedge(&a).canonicalize().is_zero());
+
+        let f = Form::new("F", 2);
+        assert!(!f.wedge(&f).canonicalize().is_zero());
+    }
+
+    #[test]
+    fn test_exterior_derivative_squares_to_zero() {
+        let a = Form::new("A", 1);
+        let da = a.exterior_derivative().unwrap();
+        assert_eq!(da.degree(), 2);
+        assert!(da.exterior_derivative().is_none());
+
+        let expr = FormExpression::from(a.wedge(&Form::new("B", 2)));
+        assert!(expr.exterior_derivative().exterior_derivative().is_zero());
+    }
+
+    #[test]
+    fn test_leibniz_rule() {
+        let a = Form::new("A", 1);
+        let b = Form::new("B", 1);
+        let d = a.wedge(&b).exterior_derivative().simplify();
+        assert_eq!(d.terms().len(), 2);
+        assert_eq!(format!("{d}"), "dA∧B - A∧dB");
+    }
+
+    #[test]
+    fn test_lowering_to_tensor() {
+        let a = Form::new("A", 1);
+        let b = Form::new("B", 2);
+        let term = a.wedge(&b);
+        let tensor = term
+            .to_tensor(vec![
+                TensorIndex::new("c", 0),
+                TensorIndex::new("a", 1),
+                TensorIndex::new("b", 2),
+            ])
+            .unwrap();
+        assert_eq!(tensor.name(), "A∧B");
+        // (1 + 2)! / (1! 2!) = 3
+        assert_eq!(tensor.coefficient(), 3);
+
+        let canonical = canonicalize(&tensor).unwrap();
+        let names: Vec<&str> = canonical.indices().iter().map(|i| i.name()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        // (c a b) -> (a b c) is an even permutation
+        assert_eq!(canonical.coefficient(), 3);
+
+        assert!(term.to_tensor(vec![TensorIndex::new("a", 0)]).is_err());
+    }
+
+    #[test]
+    fn test_exterior_derivative_lowering_factor() {
+        let da = Form::new("A", 1).exterior_derivative().unwrap();
+        let tensor = FormTerm::from(da)
+            .to_tensor(vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)])
+            .unwrap();
+        // (dA)_{ab} = 2 ∂_[a A_b]
+        assert_eq!(tensor.coefficient(), 2);
+        assert_eq!(tensor.name(), "dA");
+    }
+
+    #[test]
+    fn test_interior_product_antiderivation() {
+        let a = Form::new("A", 1);
+        let b = Form::new("B", 2);
+        let expr = FormExpression::from(a.wedge(&b)).interior_product("V");
+        assert_eq!(format!("{expr}"), "ι_V A∧B - A∧ι_V B");
+
+        // ι_V ι_V = 0 and ι_V on a 0-form vanishes
+        let f = Form::new("f", 0);
+        assert!(f.interior_product("V").is_none());
+        let once = b.interior_product("V").unwrap();
+        assert!(once.interior_product("V").is_none());
+        assert_eq!(once.degree(), 1);
+    }
+
+    #[test]
+    fn test_cartan_expansion() {
+        let a = Form::new("A", 1);
+        let lie = FormExpression::from(a.clone()).lie_derivative("V");
+        assert_eq!(format!("{lie}"), "dι_V A + ι_V dA");
+
+        let factor = FormExpression::from(a.lie_derivative("V"));
+        assert_eq!(factor.expand_lie_derivatives().simplify(), lie.simplify());
+    }
+
+    #[test]
+    fn test_cartan_recollection() {
+        let a = Form::new("A", 2);
+        let b = Form::new("B", 1);
+        let expanded =
+            FormExpression::from(a.lie_derivative("V").wedge(&b)).expand_lie_derivatives();
+        assert_eq!(expanded.simplify().terms().len(), 2);
+
+        let collected = expanded.collect_lie_derivatives();
+        assert_eq!(collected.terms().len(), 1);
+        assert_eq!(format!("{collected}"), "L_V A∧B");
+    }
+
+    #[test]
+    fn test_cartan_degenerate_cases() {
+        // L_V f = ι_V df for a function
+        let f = Form::new("f", 0);
+        let lie = FormExpression::from(f.clone()).lie_derivative("V");
+        assert_eq!(format!("{}", lie.simplify()), "ι_V df");
+        assert_eq!(format!("{}", lie.collect_lie_derivatives()), "L_V f");
+
+        // d commutes with L_V: L_V dA = d ι_V dA
+        let da = Form::new("A", 1).exterior_derivative().unwrap();
+        let lie = FormExpression::from(da).lie_derivative("V");
+        assert_eq!(format!("{}", lie.collect_lie_derivatives()), "L_V dA");
+    }
+
+    #[test]
+    fn test_hodge_dual_of_wedge_is_cross_product() {
+        // ⋆(A∧B)_c = ε_{abc} A^a B^b in three dimensions
+        let term = FormTerm::from(Form::new("A", 1)).wedge(&FormTerm::from(Form::new("B", 1)));
+        let dual = term
+            .hodge_dual(
+                vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)],
+                3,
+                vec![TensorIndex::new("c", 0)],
+            )
+            .unwrap();
+        assert_eq!(dual.coefficient(), crate::rational::Rational::ONE);
+        assert_eq!(dual.factors()[0].name(), "epsilon");
+        assert_eq!(dual.factors().last().unwrap().name(), "A∧B");
+    }
+}
@@ -1,10 +1,51 @@
 //! Tensor index representation and manipulation
 //!
 //! This module provides the `TensorIndex` struct for representing
-//! individual tensor indices with names and positions.
+//! individual tensor indices with names and positions, and the `IndexClass`
+//! enum distinguishing the kinds of index a slot may carry.
 
 use std::fmt;
 
+/// The kind of index a slot carries
+///
+/// Indices of different classes live in different vector spaces: they never
+/// contract with each other, and canonical ordering sorts by class first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum IndexClass {
+    /// Abstract or coordinate spacetime index (the default)
+    #[default]
+    Spacetime,
+    /// Newman-Penrose null tetrad label, one of `l`, `n`, `m`, `mbar`
+    Tetrad,
+    /// Spatial index of a 3+1 split, tangent to the constant-time slices
+    Spatial,
+    /// Flat (Lorentz) frame index, raised and lowered with `η` rather than `g`
+    Frame,
+    /// Undotted two-component spinor index, raised and lowered with `ε`
+    Undotted,
+    /// Dotted two-component spinor index, the complex conjugate of `Undotted`
+    Dotted,
+    /// Adjoint index of the Lie algebra of a gauge group, as in `F^a_{μν}`
+    LieAlgebra,
+    /// Flavour index labelling the members of a multiplet of fields
+    Flavor,
+}
+
+impl IndexClass {
+    /// Returns the dimension fixed by the class, if any
+    ///
+    /// Two-component spinor indices take two values and Newman-Penrose tetrad
+    /// labels four; the other classes range over a space whose dimension
+    /// depends on the problem.
+    pub fn dimension(self) -> Option<usize> {
+        match self {
+            IndexClass::Tetrad => Some(4),
+            IndexClass::Undotted | IndexClass::Dotted => Some(2),
+            _ => None,
+        }
+    }
+}
+
 /// Represents a single tensor index
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TensorIndex {
@@ -14,6 +55,12 @@ pub struct TensorIndex {
     position: usize,
     /// Whether the index is contravariant (true) or covariant (false)
     contravariant: bool,
+    /// The class of the index
+    class: IndexClass,
+    /// Names of the underlying indices if this is a block index, else empty
+    components: Vec<String>,
+    /// The dimension of the space the index ranges over, if declared
+    dimension: Option<usize>,
 }
 
 impl TensorIndex {
@@ -34,6 +81,9 @@ impl TensorIndex {
             name: name.to_string(),
             position,
             contravariant: false, // Default to covariant
+            class: IndexClass::Spacetime,
+            components: Vec::new(),
+            dimension: None,
         }
     }
 
@@ -47,6 +97,9 @@ impl TensorIndex {
             name: name.to_string(),
             position,
             contravariant: true,
+            class: IndexClass::Spacetime,
+            components: Vec::new(),
+            dimension: None,
         }
     }
 
@@ -60,7 +113,160 @@ impl TensorIndex {
             name: name.to_string(),
             position,
             contravariant: false,
+            class: IndexClass::Spacetime,
+            components: Vec::new(),
+            dimension: None,
+        }
+    }
+
+    /// Creates a new covariant Newman-Penrose tetrad index
+    ///
+    /// # Arguments
+    /// * `name` - The tetrad label (`l`, `n`, `m` or `mbar`)
+    /// * `position` - The position in the tensor
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::index::{IndexClass, TensorIndex};
+    ///
+    /// let index = TensorIndex::tetrad("m", 0);
+    /// assert_eq!(index.class(), IndexClass::Tetrad);
+    /// ```
+    pub fn tetrad(name: &str, position: usize) -> Self {
+        Self::covariant(name, position).with_class(IndexClass::Tetrad)
+    }
+
+    /// Creates a new covariant spatial index of a 3+1 split
+    ///
+    /// # Arguments
+    /// * `name` - The name of the index
+    /// * `position` - The position in the tensor
+    pub fn spatial(name: &str, position: usize) -> Self {
+        Self::covariant(name, position).with_class(IndexClass::Spatial)
+    }
+
+    /// Creates a new covariant flat frame index
+    ///
+    /// # Arguments
+    /// * `name` - The name of the index
+    /// * `position` - The position in the tensor
+    pub fn frame(name: &str, position: usize) -> Self {
+        Self::covariant(name, position).with_class(IndexClass::Frame)
+    }
+
+    /// Creates a new covariant undotted spinor index
+    ///
+    /// # Arguments
+    /// * `name` - The name of the index
+    /// * `position` - The position in the tensor
+    pub fn undotted(name: &str, position: usize) -> Self {
+        Self::covariant(name, position).with_class(IndexClass::Undotted)
+    }
+
+    /// Creates a new covariant dotted spinor index
+    ///
+    /// # Arguments
+    /// * `name` - The name of the index
+    /// * `position` - The position in the tensor
+    pub fn dotted(name: &str, position: usize) -> Self {
+        Self::covariant(name, position).with_class(IndexClass::Dotted)
+    }
+
+    /// Creates a new covariant Lie-algebra (gauge adjoint) index
+    ///
+    /// # Arguments
+    /// * `name` - The name of the index
+    /// * `position` - The position in the tensor
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::index::{IndexClass, TensorIndex};
+    ///
+    /// let index = TensorIndex::lie_algebra("a", 0);
+    /// assert_eq!(index.class(), IndexClass::LieAlgebra);
+    /// ```
+    pub fn lie_algebra(name: &str, position: usize) -> Self {
+        Self::covariant(name, position).with_class(IndexClass::LieAlgebra)
+    }
+
+    /// Creates a new covariant flavour index
+    ///
+    /// # Arguments
+    /// * `name` - The name of the index
+    /// * `position` - The position in the tensor
+    pub fn flavor(name: &str, position: usize) -> Self {
+        Self::covariant(name, position).with_class(IndexClass::Flavor)
+    }
+
+    /// Creates a covariant block index standing for an antisymmetric group of indices
+    ///
+    /// A block index `A = [a1…ak]` occupies one slot, which keeps high-rank
+    /// form-valued tensors compact; [`crate::blocks`] canonicalizes such
+    /// tensors blockwise or expands them into ordinary indices.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the block
+    /// * `components` - The names of the underlying indices
+    /// * `position` - The position in the tensor
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// let block = TensorIndex::block("A", &["a1", "a2", "a3"], 0);
+    /// assert_eq!(block.block_size(), 3);
+    /// assert_eq!(block.expand()[2].name(), "a3");
+    /// ```
+    pub fn block(name: &str, components: &[&str], position: usize) -> Self {
+        Self {
+            components: components.iter().map(ToString::to_string).collect(),
+            ..Self::covariant(name, position)
+        }
+    }
+
+    /// Returns true if the index stands for a block of underlying indices
+    pub fn is_block(&self) -> bool {
+        !self.components.is_empty()
+    }
+
+    /// Returns the names of the underlying indices of a block index
+    pub fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    /// Returns the number of slots the index occupies once expanded
+    pub fn block_size(&self) -> usize {
+        self.components.len().max(1)
+    }
+
+    /// Creates a copy with new underlying index names
+    pub fn with_components(&self, components: Vec<String>) -> Self {
+        Self {
+            components,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the ordinary indices the index stands for, numbered from its position
+    ///
+    /// The components share the block's variance and class. An ordinary
+    /// index expands to itself.
+    pub fn expand(&self) -> Vec<TensorIndex> {
+        if !self.is_block() {
+            return vec![self.clone()];
         }
+        self.components
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Self {
+                name: name.clone(),
+                position: self.position + i,
+                contravariant: self.contravariant,
+                class: self.class,
+                components: Vec::new(),
+                dimension: self.dimension,
+            })
+            .collect()
     }
 
     /// Returns the name of the index
@@ -93,41 +299,91 @@ impl TensorIndex {
         self.contravariant = contravariant;
     }
 
+    /// Returns the class of the index
+    pub fn class(&self) -> IndexClass {
+        self.class
+    }
+
+    /// Creates a copy with a new class
+    pub fn with_class(&self, class: IndexClass) -> Self {
+        Self {
+            class,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the dimension of the space the index ranges over, if known
+    ///
+    /// A dimension declared with [`TensorIndex::with_dimension`] takes
+    /// precedence over the one fixed by the class.
+    pub fn dimension(&self) -> Option<usize> {
+        self.dimension.or_else(|| self.class.dimension())
+    }
+
+    /// Returns the dimension declared on the index itself, ignoring the class
+    #[cfg(feature = "serde")]
+    pub(crate) fn declared_dimension(&self) -> Option<usize> {
+        self.dimension
+    }
+
+    /// Creates a copy ranging over a space of the given dimension
+    ///
+    /// Canonicalization uses the dimension to recognize tensors that vanish
+    /// because more slots are antisymmetrized than the index takes values.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::{canonicalize, Symmetry, Tensor, TensorIndex};
+    ///
+    /// // A 5-form vanishes in four dimensions
+    /// let indices = ["a", "b", "c", "d", "e"]
+    ///     .iter()
+    ///     .enumerate()
+    ///     .map(|(i, n)| TensorIndex::new(n, i).with_dimension(4))
+    ///     .collect();
+    /// let mut form = Tensor::new("F", indices);
+    /// form.add_symmetry(Symmetry::antisymmetric(vec![0, 1, 2, 3, 4]));
+    /// assert!(canonicalize(&form)?.is_zero());
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn with_dimension(&self, dimension: usize) -> Self {
+        Self {
+            dimension: Some(dimension),
+            ..self.clone()
+        }
+    }
+
     /// Creates a copy with a new name
     pub fn with_name(&self, name: &str) -> Self {
         Self {
             name: name.to_string(),
-            position: self.position,
-            contravariant: self.contravariant,
+            ..self.clone()
         }
     }
 
     /// Creates a copy with a new position
     pub fn with_position(&self, position: usize) -> Self {
         Self {
-            name: self.name.clone(),
             position,
-            contravariant: self.contravariant,
+            ..self.clone()
         }
     }
 
-    /// Checks if two indices can be contracted (same name, different variance)
+    /// Checks if two indices can be contracted (same name and class, different variance)
     pub fn can_contract_with(&self, other: &TensorIndex) -> bool {
-        self.name == other.name && self.contravariant != other.contravariant
+        self.name == other.name
+            && self.class == other.class
+            && self.contravariant != other.contravariant
     }
 
     /// Compares indices for canonical ordering
-    /// Orders by: name (alphabetically), then by variance (covariant first), then by position
+    /// Orders by: class, then name (alphabetically), then by variance (covariant first), then by position
     pub fn canonical_cmp(&self, other: &TensorIndex) -> std::cmp::Ordering {
-        use std::cmp::Ordering;
-
-        match self.name.cmp(&other.name) {
-            Ordering::Equal => match self.contravariant.cmp(&other.contravariant) {
-                Ordering::Equal => self.position.cmp(&other.position),
-                other => other,
-            },
-            other => other,
-        }
+        self.class
+            .cmp(&other.class)
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.contravariant.cmp(&other.contravariant))
+            .then_with(|| self.position.cmp(&other.position))
     }
 }
 
@@ -205,4 +461,30 @@ mod tests {
         assert_eq!(format!("{covariant}"), "_mu");
         assert_eq!(format!("{contravariant}"), "^nu");
     }
+
+    #[test]
+    fn test_index_classes() {
+        let coordinate = TensorIndex::covariant("m", 0);
+        let tetrad = TensorIndex::tetrad("m", 1);
+        assert_eq!(coordinate.class(), IndexClass::Spacetime);
+        assert_eq!(tetrad.class(), IndexClass::Tetrad);
+        assert_eq!(tetrad.with_position(3).class(), IndexClass::Tetrad);
+
+        // Different classes never contract and sort separately
+        assert!(!tetrad.can_contract_with(&TensorIndex::contravariant("m", 2)));
+        assert!(tetrad
+            .can_contract_with(&TensorIndex::contravariant("m", 2).with_class(IndexClass::Tetrad)));
+        assert!(TensorIndex::covariant("z", 0) < TensorIndex::tetrad("a", 0));
+        assert!(TensorIndex::dotted("Z", 0) < TensorIndex::lie_algebra("a", 0));
+        assert_eq!(TensorIndex::undotted("A", 0).dimension(), Some(2));
+        assert_eq!(TensorIndex::new("a", 0).dimension(), None);
+        assert_eq!(
+            TensorIndex::tetrad("l", 0).with_dimension(3).dimension(),
+            Some(3)
+        );
+        assert!(TensorIndex::lie_algebra("z", 0) < TensorIndex::flavor("a", 0));
+        assert!(!TensorIndex::flavor("i", 0).can_contract_with(
+            &TensorIndex::contravariant("i", 1).with_class(IndexClass::LieAlgebra)
+        ));
+    }
 }
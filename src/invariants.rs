@@ -0,0 +1,727 @@
+//! Scalar polynomial invariants of the Riemann tensor
+//!
+//! This module generates every fully contracted monomial in `R_{abcd}` and
+//! its symmetrized covariant derivatives `∇_{(a1…ak)}R_{bcde}` of a given
+//! mass dimension (`R` has dimension 2 and every `∇` adds 1), up to the
+//! monoterm symmetries of each factor, exchange of identical factors and
+//! relabelling of dummy indices. These are the standard building blocks of
+//! effective actions.
+//!
+//! A contraction pattern is a perfect matching of all slots; the generator
+//! enumerates the orbits of matchings under the slot symmetry group and drops
+//! orbits that map onto themselves with a sign (and therefore vanish).
+//! Multi-term relations such as the Bianchi identities are not applied here.
+//!
+//! [`InvariantNames`] maps such monomials, and linear combinations of them,
+//! to their conventional names (the Ricci scalar, the Kretschmann scalar, the
+//! Gauss–Bonnet combination, …) so that scalars in output read the way
+//! physicists write them.
+
+use crate::derivatives::{symmetrized_derivative, symmetrized_order, DerivativeKind};
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::{TensorExpression, TensorProduct};
+use crate::index::TensorIndex;
+use crate::presets::riemann;
+use crate::rational::Rational;
+use crate::schreier_sims::{compose_permutations, Permutation};
+use crate::tensor::Tensor;
+use std::collections::{HashMap, HashSet};
+
+/// Upper bound on the number of slots, which keeps the matching enumeration tractable
+const MAX_SLOTS: usize = 16;
+
+/// A permutation of slots together with the sign it picks up
+type SignedPermutation = (Permutation, i32);
+
+/// Identifies a scalar monomial: the derivative orders of its factors and the
+/// smallest contraction pattern in its orbit
+type MonomialKey = (Vec<usize>, Vec<usize>);
+
+/// Generates all monoterm-inequivalent scalar monomials of a mass dimension
+///
+/// Each monomial is a product of Riemann tensors and symmetrized derivative
+/// towers, ordered by increasing number of derivatives, with dummy indices
+/// named `a`, `b`, … in slot order and lowered at their first occurrence.
+///
+/// # Arguments
+/// * `mass_dimension` - The total mass dimension; odd dimensions have no scalars
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::invariants::riemann_monomials;
+///
+/// // Only the Ricci scalar has mass dimension 2
+/// let monomials = riemann_monomials(2)?;
+/// assert_eq!(monomials.len(), 1);
+/// assert_eq!(monomials[0].to_string(), "R__a _b ^a ^b");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn riemann_monomials(mass_dimension: usize) -> Result<Vec<TensorProduct>> {
+    let mut monomials = Vec::new();
+    for orders in derivative_partitions(mass_dimension) {
+        let slots: usize = orders.iter().map(|k| k + 4).sum();
+        if slots % 2 == 1 {
+            continue;
+        }
+        if slots > MAX_SLOTS {
+            return Err(ButlerPortugalError::ComputationError(format!(
+                "Mass dimension {mass_dimension} needs {slots} slots, more than the supported {MAX_SLOTS}"
+            )));
+        }
+        let group = slot_group(&orders);
+        for matching in inequivalent_matchings(slots, &group) {
+            monomials.push(build_monomial(&orders, &matching)?);
+        }
+    }
+    Ok(monomials)
+}
+
+/// Lists the derivative orders `k1 ≤ k2 ≤ …` of the factors, with `Σ (2 + ki)` equal to the dimension
+fn derivative_partitions(mass_dimension: usize) -> Vec<Vec<usize>> {
+    fn extend(
+        remaining: usize,
+        min_order: usize,
+        current: &mut Vec<usize>,
+        out: &mut Vec<Vec<usize>>,
+    ) {
+        if remaining == 0 {
+            if !current.is_empty() {
+                out.push(current.clone());
+            }
+            return;
+        }
+        if remaining < 2 {
+            return;
+        }
+        for order in min_order..=remaining - 2 {
+            current.push(order);
+            extend(remaining - order - 2, order, current, out);
+            current.pop();
+        }
+    }
+    let mut out = Vec::new();
+    extend(mass_dimension, 0, &mut Vec::new(), &mut out);
+    // Fewer factors (more derivatives) last, so pure curvature products come first
+    out.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    out
+}
+
+/// Enumerates the signed slot symmetry group of a product of derivative towers
+fn slot_group(orders: &[usize]) -> Vec<SignedPermutation> {
+    let degree: usize = orders.iter().map(|k| k + 4).sum();
+    let identity = Permutation::identity(degree);
+    let mut generators: Vec<SignedPermutation> = Vec::new();
+    let mut offset = 0;
+    let mut offsets = Vec::with_capacity(orders.len());
+    for &k in orders {
+        offsets.push(offset);
+        let swap = |a: usize, b: usize| {
+            let mut perm = identity.clone();
+            perm.swap(offset + a, offset + b);
+            perm
+        };
+        // Symmetric derivative slots
+        for d in 1..k {
+            generators.push((swap(d - 1, d), 1));
+        }
+        // Riemann slots k..k+4: antisymmetric pairs and pair exchange
+        generators.push((swap(k, k + 1), -1));
+        generators.push((swap(k + 2, k + 3), -1));
+        let mut exchange = identity.clone();
+        exchange.swap(offset + k, offset + k + 2);
+        exchange.swap(offset + k + 1, offset + k + 3);
+        generators.push((exchange, 1));
+        offset += k + 4;
+    }
+    // Exchange of adjacent identical factors
+    for f in 1..orders.len() {
+        if orders[f] == orders[f - 1] {
+            let width = orders[f] + 4;
+            let mut perm = identity.clone();
+            for s in 0..width {
+                perm.swap(offsets[f - 1] + s, offsets[f] + s);
+            }
+            generators.push((perm, 1));
+        }
+    }
+
+    let mut elements: HashMap<Permutation, i32> = HashMap::new();
+    elements.insert(identity.clone(), 1);
+    let mut queue = vec![identity];
+    while let Some(current) = queue.pop() {
+        let sign = elements[&current];
+        for (generator, generator_sign) in &generators {
+            let next = compose_permutations(&current, generator);
+            if let std::collections::hash_map::Entry::Vacant(entry) = elements.entry(next.clone()) {
+                entry.insert(sign * generator_sign);
+                queue.push(next);
+            }
+        }
+    }
+    elements.into_iter().collect()
+}
+
+/// Returns one representative (the lexicographically smallest partner array)
+/// of every non-vanishing orbit of perfect matchings on `slots` slots
+fn inequivalent_matchings(slots: usize, group: &[SignedPermutation]) -> Vec<Vec<usize>> {
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    let mut representatives = Vec::new();
+    for matching in perfect_matchings(slots) {
+        if seen.contains(&matching) {
+            continue;
+        }
+        let mut signs: HashMap<Vec<usize>, i32> = HashMap::new();
+        let mut vanishes = false;
+        for (perm, sign) in group {
+            let mut image = vec![0; slots];
+            for (slot, &partner) in matching.iter().enumerate() {
+                image[perm[slot]] = perm[partner];
+            }
+            match signs.get(&image) {
+                Some(&existing) if existing != *sign => vanishes = true,
+                Some(_) => {}
+                None => {
+                    signs.insert(image, *sign);
+                }
+            }
+        }
+        let representative = signs.keys().min().cloned();
+        seen.extend(signs.into_keys());
+        if let (false, Some(representative)) = (vanishes, representative) {
+            representatives.push(representative);
+        }
+    }
+    representatives.sort();
+    representatives
+}
+
+/// Enumerates all perfect matchings of `n` slots as partner arrays
+fn perfect_matchings(n: usize) -> Vec<Vec<usize>> {
+    fn extend(partner: &mut Vec<Option<usize>>, out: &mut Vec<Vec<usize>>) {
+        let Some(first) = partner.iter().position(Option::is_none) else {
+            out.push(partner.iter().flatten().copied().collect());
+            return;
+        };
+        for second in first + 1..partner.len() {
+            if partner[second].is_none() {
+                partner[first] = Some(second);
+                partner[second] = Some(first);
+                extend(partner, out);
+                partner[first] = None;
+                partner[second] = None;
+            }
+        }
+    }
+    let mut out = Vec::new();
+    if n % 2 == 0 {
+        extend(&mut vec![None; n], &mut out);
+    }
+    out
+}
+
+/// Builds the tensor monomial for a contraction pattern
+fn build_monomial(orders: &[usize], matching: &[usize]) -> Result<TensorProduct> {
+    let mut names = vec![String::new(); matching.len()];
+    let mut next = 0;
+    for slot in 0..matching.len() {
+        if names[slot].is_empty() {
+            let name = dummy_name(next);
+            next += 1;
+            names[matching[slot]] = name.clone();
+            names[slot] = name;
+        }
+    }
+    let index = |slot: usize, position: usize| {
+        if matching[slot] > slot {
+            TensorIndex::covariant(&names[slot], position)
+        } else {
+            TensorIndex::contravariant(&names[slot], position)
+        }
+    };
+
+    let mut factors: Vec<Tensor> = Vec::with_capacity(orders.len());
+    let mut offset = 0;
+    for &k in orders {
+        let curvature = riemann((0..4).map(|i| index(offset + k + i, i)).collect())?;
+        factors.push(if k == 0 {
+            curvature
+        } else {
+            symmetrized_derivative(
+                &curvature,
+                (0..k).map(|i| index(offset + i, i)).collect(),
+                DerivativeKind::Covariant,
+            )
+        });
+        offset += k + 4;
+    }
+    Ok(TensorProduct::new(Rational::ONE, factors))
+}
+
+/// Names the n-th dummy index `a`, `b`, …, `z`, `a1`, …
+pub(crate) fn dummy_name(n: usize) -> String {
+    let letter = char::from(b'a' + (n % 26) as u8);
+    if n < 26 {
+        letter.to_string()
+    } else {
+        format!("{letter}{}", n / 26)
+    }
+}
+
+/// A scalar invariant with its conventional names
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedInvariant {
+    /// The name used in plain-text output
+    pub name: String,
+    /// The name used in LaTeX output
+    pub latex: String,
+    /// The invariant as a combination of Riemann monomials
+    pub expression: TensorExpression,
+    terms: Vec<(MonomialKey, Rational)>,
+}
+
+/// A database of named scalar invariants
+///
+/// Entries are recognized up to the monoterm symmetries of the factors,
+/// exchange of identical factors and relabelling of dummy indices, exactly
+/// like the monomials of [`riemann_monomials`]. Multi-term relations such as
+/// the Bianchi identities are not applied, so an expression must be written
+/// in the same monomials as the entry to be recognized.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::expression::{TensorExpression, TensorProduct};
+/// use butler_portugal::invariants::InvariantNames;
+/// use butler_portugal::presets::riemann;
+/// use butler_portugal::rational::Rational;
+/// use butler_portugal::TensorIndex;
+///
+/// let r = |names: [&str; 4], up: bool| {
+///     riemann(names.iter().enumerate().map(|(i, n)| {
+///         if up { TensorIndex::contravariant(n, i) } else { TensorIndex::new(n, i) }
+///     }).collect())
+/// };
+/// // R_{pqrs} R^{pqrs}, with dummies named differently from the database
+/// let square = TensorProduct::new(
+///     Rational::from(3),
+///     vec![r(["p", "q", "r", "s"], false)?, r(["p", "q", "r", "s"], true)?],
+/// );
+/// let names = InvariantNames::standard()?;
+/// assert_eq!(names.label(&TensorExpression::from(square.clone()))?, "3 Kretschmann");
+/// assert_eq!(names.label_latex(&TensorExpression::from(square))?, "3 R_{abcd}R^{abcd}");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InvariantNames {
+    entries: Vec<NamedInvariant>,
+}
+
+impl InvariantNames {
+    /// Creates an empty database
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a database of the standard curvature invariants
+    ///
+    /// It names the Ricci scalar `R`, its square `R²`, the square of the
+    /// Ricci tensor `R_{ab}R^{ab}`, the Kretschmann scalar
+    /// `R_{abcd}R^{abcd}` and the Gauss–Bonnet combination
+    /// `R² - 4 R_{ab}R^{ab} + R_{abcd}R^{abcd}`.
+    ///
+    /// # Errors
+    /// Returns an error if building the entries fails.
+    pub fn standard() -> Result<Self> {
+        let ricci_scalar = || riemann_product(&[["a", "b", "a", "b"]]);
+        let ricci_scalar_square = || riemann_product(&[["a", "b", "a", "b"], ["c", "d", "c", "d"]]);
+        let ricci_square = || riemann_product(&[["c", "a", "c", "b"], ["d", "a", "d", "b"]]);
+        let kretschmann = || riemann_product(&[["a", "b", "c", "d"], ["a", "b", "c", "d"]]);
+
+        let mut names = Self::new();
+        names.register("R", "R", TensorExpression::from(ricci_scalar()?))?;
+        names.register("R²", "R^2", TensorExpression::from(ricci_scalar_square()?))?;
+        names.register(
+            "Ric²",
+            "R_{ab}R^{ab}",
+            TensorExpression::from(ricci_square()?),
+        )?;
+        names.register(
+            "Kretschmann",
+            "R_{abcd}R^{abcd}",
+            TensorExpression::from(kretschmann()?),
+        )?;
+        let gauss_bonnet: TensorExpression = [
+            ricci_scalar_square()?,
+            ricci_square()?.scale(Rational::from(-4)),
+            kretschmann()?,
+        ]
+        .into_iter()
+        .collect();
+        names.register("Gauss–Bonnet", "\\mathcal{G}", gauss_bonnet)?;
+        Ok(names)
+    }
+
+    /// Adds a named invariant
+    ///
+    /// Entries registered later take precedence over earlier ones with the
+    /// same monomials.
+    ///
+    /// # Errors
+    /// Returns an `InvalidTensor` error if a term of `expression` is not a
+    /// fully contracted product of Riemann tensors and symmetrized derivative
+    /// towers, or if the expression vanishes.
+    pub fn register(
+        &mut self,
+        name: &str,
+        latex: &str,
+        expression: TensorExpression,
+    ) -> Result<()> {
+        let terms = decompose(&expression)?.ok_or_else(|| {
+            ButlerPortugalError::InvalidTensor(format!(
+                "{name} is not a combination of scalar Riemann monomials"
+            ))
+        })?;
+        if terms.is_empty() {
+            return Err(ButlerPortugalError::InvalidTensor(format!(
+                "{name} vanishes identically"
+            )));
+        }
+        self.entries.push(NamedInvariant {
+            name: name.to_string(),
+            latex: latex.to_string(),
+            expression,
+            terms,
+        });
+        Ok(())
+    }
+
+    /// Returns the registered invariants
+    pub fn entries(&self) -> &[NamedInvariant] {
+        &self.entries
+    }
+
+    /// Identifies an expression as a multiple of a registered invariant
+    ///
+    /// Returns the invariant and the factor `c` with `expression = c · invariant`,
+    /// or `None` if the expression is not proportional to any entry.
+    ///
+    /// # Errors
+    /// Returns an error if building the symmetry group of a monomial fails.
+    pub fn identify(
+        &self,
+        expression: &TensorExpression,
+    ) -> Result<Option<(&NamedInvariant, Rational)>> {
+        let Some(terms) = decompose(expression)? else {
+            return Ok(None);
+        };
+        Ok(self.find(&terms))
+    }
+
+    /// Renders an expression with registered invariants replaced by their names
+    ///
+    /// The expression as a whole is looked up first; otherwise each term is
+    /// named on its own, and terms without a name are printed as they are.
+    ///
+    /// # Errors
+    /// Returns an error if building the symmetry group of a monomial fails.
+    pub fn label(&self, expression: &TensorExpression) -> Result<String> {
+        self.render(expression, |entry| &entry.name)
+    }
+
+    /// Renders an expression like [`label`](Self::label), using the LaTeX names
+    ///
+    /// # Errors
+    /// Returns an error if building the symmetry group of a monomial fails.
+    pub fn label_latex(&self, expression: &TensorExpression) -> Result<String> {
+        self.render(expression, |entry| &entry.latex)
+    }
+
+    fn render(
+        &self,
+        expression: &TensorExpression,
+        name: impl Fn(&NamedInvariant) -> &String,
+    ) -> Result<String> {
+        if let Some((entry, factor)) = self.identify(expression)? {
+            return Ok(scaled(&factor, name(entry)));
+        }
+        let mut rendered = String::new();
+        for term in expression.terms().iter().filter(|t| !t.is_zero()) {
+            let single = TensorExpression::from(term.clone());
+            let (text, negative) = match self.identify(&single)? {
+                Some((entry, factor)) if factor < Rational::ZERO => {
+                    (scaled(&-factor, name(entry)), true)
+                }
+                Some((entry, factor)) => (scaled(&factor, name(entry)), false),
+                None if term.coefficient() < Rational::ZERO => {
+                    (term.scale(-Rational::ONE).to_string(), true)
+                }
+                None => (term.to_string(), false),
+            };
+            match (rendered.is_empty(), negative) {
+                (true, true) => rendered.push('-'),
+                (true, false) => {}
+                (false, true) => rendered.push_str(" - "),
+                (false, false) => rendered.push_str(" + "),
+            }
+            rendered.push_str(&text);
+        }
+        if rendered.is_empty() {
+            rendered.push('0');
+        }
+        Ok(rendered)
+    }
+
+    /// Finds the latest entry proportional to the decomposed terms
+    fn find(&self, terms: &[(MonomialKey, Rational)]) -> Option<(&NamedInvariant, Rational)> {
+        self.entries.iter().rev().find_map(|entry| {
+            if entry.terms.len() != terms.len() {
+                return None;
+            }
+            let factor = terms.first()?.1 / entry.terms.first()?.1;
+            let proportional = entry
+                .terms
+                .iter()
+                .zip(terms)
+                .all(|((key, c), (other, d))| key == other && *c * factor == *d);
+            proportional.then_some((entry, factor))
+        })
+    }
+}
+
+/// Writes `factor · name`, leaving out a unit factor
+fn scaled(factor: &Rational, name: &str) -> String {
+    if *factor == Rational::ONE {
+        name.to_string()
+    } else if *factor == -Rational::ONE {
+        format!("-{name}")
+    } else {
+        format!("{factor} {name}")
+    }
+}
+
+/// Builds a product of Riemann tensors, each label lowered at its first
+/// occurrence and raised at its second
+fn riemann_product(factors: &[[&str; 4]]) -> Result<TensorProduct> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let factors = factors
+        .iter()
+        .map(|names| {
+            riemann(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(position, &name)| {
+                        if seen.insert(name) {
+                            TensorIndex::covariant(name, position)
+                        } else {
+                            TensorIndex::contravariant(name, position)
+                        }
+                    })
+                    .collect(),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(TensorProduct::new(Rational::ONE, factors))
+}
+
+/// Writes an expression as a sorted list of monomial keys with their
+/// coefficients, or `None` if a term is not a scalar Riemann monomial
+fn decompose(expression: &TensorExpression) -> Result<Option<Vec<(MonomialKey, Rational)>>> {
+    let mut terms: Vec<(MonomialKey, Rational)> = Vec::new();
+    for term in expression.terms() {
+        let Some((key, sign)) = monomial_key(term) else {
+            return Ok(None);
+        };
+        let coefficient = term.coefficient() * Rational::from(sign);
+        match terms.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, total)) => *total += coefficient,
+            None => terms.push((key, coefficient)),
+        }
+    }
+    terms.retain(|(_, coefficient)| !coefficient.is_zero());
+    terms.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(Some(terms))
+}
+
+/// Returns the key of a fully contracted product of Riemann tensors and
+/// symmetrized derivative towers, with the sign relating the product to the
+/// representative contraction (0 if it vanishes by symmetry)
+fn monomial_key(product: &TensorProduct) -> Option<(MonomialKey, i32)> {
+    let symbol = DerivativeKind::Covariant.symbol();
+    let mut factors: Vec<(usize, &Tensor)> = Vec::with_capacity(product.factors().len());
+    for factor in product.factors() {
+        let k = symmetrized_order(factor, DerivativeKind::Covariant);
+        let curvature = factor.name() == format!("{}R", symbol.repeat(k));
+        if !curvature || factor.rank() != k + 4 {
+            return None;
+        }
+        factors.push((k, factor));
+    }
+    factors.sort_by_key(|(k, _)| *k);
+    let orders: Vec<usize> = factors.iter().map(|(k, _)| *k).collect();
+
+    let names: Vec<&str> = factors
+        .iter()
+        .flat_map(|(_, factor)| factor.indices().iter().map(TensorIndex::name))
+        .collect();
+    let mut matching = vec![0; names.len()];
+    for (slot, name) in names.iter().enumerate() {
+        let partners: Vec<usize> = (0..names.len())
+            .filter(|&other| other != slot && names[other] == *name)
+            .collect();
+        match partners[..] {
+            [partner] => matching[slot] = partner,
+            _ => return None,
+        }
+    }
+
+    let mut best: Option<(Vec<usize>, i32)> = None;
+    for (perm, sign) in slot_group(&orders) {
+        let mut image = vec![0; matching.len()];
+        for (slot, &partner) in matching.iter().enumerate() {
+            image[perm[slot]] = perm[partner];
+        }
+        best = match best {
+            Some((current, current_sign)) if current < image => Some((current, current_sign)),
+            Some((current, current_sign)) if current == image && current_sign != sign => {
+                Some((current, 0))
+            }
+            Some((current, current_sign)) if current == image => Some((current, current_sign)),
+            _ => Some((image, sign)),
+        };
+    }
+    let (representative, sign) = best?;
+    Some(((orders, representative), sign))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partitions() {
+        assert_eq!(derivative_partitions(2), vec![vec![0]]);
+        assert_eq!(derivative_partitions(4), vec![vec![0, 0], vec![2]]);
+        assert_eq!(
+            derivative_partitions(6),
+            vec![vec![0, 0, 0], vec![0, 2], vec![1, 1], vec![4]]
+        );
+        assert!(riemann_monomials(3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_slot_group_orders() {
+        assert_eq!(slot_group(&[0]).len(), 8);
+        assert_eq!(slot_group(&[0, 0]).len(), 128);
+        assert_eq!(slot_group(&[2]).len(), 16);
+    }
+
+    #[test]
+    fn test_dimension_four_basis() {
+        let monomials = riemann_monomials(4).unwrap();
+        let rendered: Vec<String> = monomials.iter().map(ToString::to_string).collect();
+        // R², Ric², Riem², R_abcd R^acbd, then ∇∇Ric and □R
+        assert_eq!(rendered.len(), 6, "{rendered:?}");
+        assert!(rendered.contains(&"R__a _b _c _d R_^a ^b ^c ^d".to_string()));
+        assert!(rendered.contains(&"R__a _b ^a ^b R__c _d ^c ^d".to_string()));
+        assert_eq!(
+            monomials.iter().filter(|m| m.factors().len() == 1).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_monomials_are_fully_contracted() {
+        for monomial in riemann_monomials(6).unwrap() {
+            let mut balance: HashMap<&str, i32> = HashMap::new();
+            for factor in monomial.factors() {
+                for index in factor.indices() {
+                    *balance.entry(index.name()).or_default() +=
+                        if index.is_contravariant() { 1 } else { -1 };
+                }
+            }
+            assert!(balance.values().all(|&b| b == 0), "{monomial}");
+        }
+    }
+
+    #[test]
+    fn test_invariant_names() {
+        let names = InvariantNames::standard().unwrap();
+        let generated = riemann_monomials(2).unwrap();
+        assert_eq!(
+            names
+                .label(&TensorExpression::from(generated[0].clone()))
+                .unwrap(),
+            "R"
+        );
+
+        // R_{abcd} R^{cdab} - 4 R^c_{acb} R_d^{adb} + R^{pq}_{pq} R^{rs}_{rs}, relabelled
+        let r = |names: [&str; 4], up: [bool; 4]| {
+            riemann(
+                (0..4)
+                    .map(|i| {
+                        if up[i] {
+                            TensorIndex::contravariant(names[i], i)
+                        } else {
+                            TensorIndex::covariant(names[i], i)
+                        }
+                    })
+                    .collect(),
+            )
+            .unwrap()
+        };
+        let down = [false; 4];
+        let up = [true; 4];
+        let kretschmann = TensorProduct::new(
+            Rational::ONE,
+            vec![r(["a", "b", "c", "d"], down), r(["c", "d", "a", "b"], up)],
+        );
+        let ricci_square = TensorProduct::new(
+            Rational::from(-4),
+            vec![
+                r(["c", "a", "c", "b"], [true, false, false, false]),
+                r(["d", "a", "d", "b"], [false, true, true, true]),
+            ],
+        );
+        let scalar_square = TensorProduct::new(
+            Rational::ONE,
+            vec![
+                r(["p", "q", "p", "q"], [true, true, false, false]),
+                r(["r", "s", "r", "s"], [true, true, false, false]),
+            ],
+        );
+        let gauss_bonnet: TensorExpression =
+            [kretschmann.clone(), ricci_square.clone(), scalar_square]
+                .into_iter()
+                .collect();
+        let (entry, factor) = names
+            .identify(&gauss_bonnet.scale(Rational::new(1, 2)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (entry.name.as_str(), factor),
+            ("Gauss–Bonnet", Rational::new(1, 2))
+        );
+        assert_eq!(names.label_latex(&gauss_bonnet).unwrap(), "\\mathcal{G}");
+
+        // Without the whole combination, terms are named one by one
+        let partial: TensorExpression = [ricci_square, kretschmann].into_iter().collect();
+        assert_eq!(names.label(&partial).unwrap(), "-4 Ric² + Kretschmann");
+        let other = TensorProduct::new(
+            Rational::ONE,
+            vec![r(["a", "b", "c", "d"], down), r(["a", "c", "b", "d"], up)],
+        );
+        let mixed: TensorExpression = [other.clone(), partial.terms()[1].clone()]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            names.label(&mixed).unwrap(),
+            format!("{other} + Kretschmann")
+        );
+
+        let vector = Tensor::new("V", vec![TensorIndex::new("a", 0)]);
+        let mut custom = InvariantNames::new();
+        assert!(custom
+            .register("V", "V", TensorExpression::from(vector))
+            .is_err());
+    }
+}
@@ -0,0 +1,343 @@
+//! Versioned JSON interchange format
+//!
+//! Callers without access to the Rust types, such as JavaScript running the
+//! WebAssembly build in a web worker, describe a tensor as a JSON document and
+//! get its canonical form back as JSON through [`canonicalize_json`]. Every
+//! document carries a `version` field; this module reads and writes
+//! [`FORMAT_VERSION`] and rejects any other.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "tensor": {
+//!     "name": "F",
+//!     "coefficient": 1,
+//!     "indices": [{ "name": "b" }, { "name": "a", "contravariant": true }],
+//!     "symmetries": [{ "type": "antisymmetric", "indices": [0, 1] }]
+//!   }
+//! }
+//! ```
+//!
+//! Index `class` is one of `spacetime` (the default), `tetrad`, `spatial` or
+//! `frame`. Symmetry `type` is one of `symmetric`, `antisymmetric`, `cyclic`
+//! and `anti_cyclic` (with `indices`), `symmetric_pairs` (with `pairs`),
+//! `young_shape` (with `columns`) or `custom` (with `permutations` and
+//! `signs`). An optional `ordering_version` selects the canonical ordering
+//! conventions (see [`crate::config`]).
+
+use crate::canonicalization::canonicalize_with_config;
+use crate::config::{CanonicalizationConfig, OrderingVersion};
+use crate::error::{ButlerPortugalError, Result};
+use crate::index::{IndexClass, TensorIndex};
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+use serde::{Deserialize, Serialize};
+
+/// The version of the JSON format read and written by this module
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A request or response document
+#[derive(Debug, Serialize, Deserialize)]
+struct Document {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ordering_version: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tensor: Option<TensorDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct TensorDoc {
+    name: String,
+    #[serde(default = "one")]
+    coefficient: i32,
+    #[serde(default)]
+    indices: Vec<IndexDoc>,
+    #[serde(default)]
+    symmetries: Vec<SymmetryDoc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexDoc {
+    name: String,
+    #[serde(default)]
+    contravariant: bool,
+    #[serde(default)]
+    class: ClassDoc,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dimension: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ClassDoc {
+    #[default]
+    Spacetime,
+    Tetrad,
+    Spatial,
+    Frame,
+    Undotted,
+    Dotted,
+    LieAlgebra,
+    Flavor,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SymmetryDoc {
+    Symmetric {
+        indices: Vec<usize>,
+    },
+    Antisymmetric {
+        indices: Vec<usize>,
+    },
+    SymmetricPairs {
+        pairs: Vec<(usize, usize)>,
+    },
+    Cyclic {
+        indices: Vec<usize>,
+    },
+    AntiCyclic {
+        indices: Vec<usize>,
+    },
+    YoungShape {
+        columns: Vec<Vec<usize>>,
+    },
+    Custom {
+        permutations: Vec<Vec<usize>>,
+        signs: Vec<i32>,
+    },
+}
+
+fn one() -> i32 {
+    1
+}
+
+/// Canonicalizes a tensor given as a JSON document, returning a JSON document
+///
+/// The response has the same version and carries either the canonical
+/// `tensor` or an `error` message; this function never panics on bad input.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::json::canonicalize_json;
+///
+/// let response = canonicalize_json(
+///     r#"{"version": 1, "tensor": {"name": "F", "indices": [{"name": "b"}, {"name": "a"}],
+///         "symmetries": [{"type": "antisymmetric", "indices": [0, 1]}]}}"#,
+/// );
+/// assert!(response.contains(r#""coefficient":-1"#));
+/// assert!(canonicalize_json("{}").contains("error"));
+/// ```
+pub fn canonicalize_json(input: &str) -> String {
+    let response = match canonicalize_document(input) {
+        Ok(tensor) => Document {
+            version: FORMAT_VERSION,
+            ordering_version: None,
+            tensor: Some(tensor),
+            error: None,
+        },
+        Err(e) => Document {
+            version: FORMAT_VERSION,
+            ordering_version: None,
+            tensor: None,
+            error: Some(e.to_string()),
+        },
+    };
+    serde_json::to_string(&response)
+        .unwrap_or_else(|e| format!(r#"{{"version":{FORMAT_VERSION},"error":"{e}"}}"#))
+}
+
+/// Parses a tensor from a JSON document
+///
+/// # Errors
+/// Returns an `InvalidTensor` error if the document is malformed, has an
+/// unsupported version or carries no tensor.
+pub fn tensor_from_json(input: &str) -> Result<Tensor> {
+    let (tensor, _) = parse_document(input)?;
+    Ok(tensor)
+}
+
+/// Writes a tensor as a JSON document
+pub fn tensor_to_json(tensor: &Tensor) -> String {
+    let document = Document {
+        version: FORMAT_VERSION,
+        ordering_version: None,
+        tensor: Some(TensorDoc::from(tensor)),
+        error: None,
+    };
+    serde_json::to_string(&document).unwrap_or_default()
+}
+
+fn canonicalize_document(input: &str) -> Result<TensorDoc> {
+    let (tensor, config) = parse_document(input)?;
+    Ok(TensorDoc::from(&canonicalize_with_config(
+        &tensor, &config,
+    )?))
+}
+
+fn parse_document(input: &str) -> Result<(Tensor, CanonicalizationConfig)> {
+    let document: Document = serde_json::from_str(input)
+        .map_err(|e| ButlerPortugalError::InvalidTensor(format!("Malformed JSON: {e}")))?;
+    if document.version != FORMAT_VERSION {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "Unsupported format version {}, expected {FORMAT_VERSION}",
+            document.version
+        )));
+    }
+    let ordering_version = match document.ordering_version {
+        None | Some(1) => OrderingVersion::V1,
+        Some(2) => OrderingVersion::V2,
+        Some(other) => {
+            return Err(ButlerPortugalError::InvalidTensor(format!(
+                "Unknown ordering version {other}"
+            )))
+        }
+    };
+    let tensor = document
+        .tensor
+        .ok_or_else(|| ButlerPortugalError::InvalidTensor("Document has no tensor".to_string()))?;
+    let config = CanonicalizationConfig::new().with_ordering_version(ordering_version);
+    Ok((Tensor::from(tensor), config))
+}
+
+impl From<TensorDoc> for Tensor {
+    fn from(doc: TensorDoc) -> Self {
+        let indices = doc
+            .indices
+            .into_iter()
+            .enumerate()
+            .map(|(position, index)| {
+                let class = match index.class {
+                    ClassDoc::Spacetime => IndexClass::Spacetime,
+                    ClassDoc::Tetrad => IndexClass::Tetrad,
+                    ClassDoc::Spatial => IndexClass::Spatial,
+                    ClassDoc::Frame => IndexClass::Frame,
+                    ClassDoc::Undotted => IndexClass::Undotted,
+                    ClassDoc::Dotted => IndexClass::Dotted,
+                    ClassDoc::LieAlgebra => IndexClass::LieAlgebra,
+                    ClassDoc::Flavor => IndexClass::Flavor,
+                };
+                let base = if index.contravariant {
+                    TensorIndex::contravariant(&index.name, position)
+                } else {
+                    TensorIndex::new(&index.name, position)
+                };
+                let index_with_class = base.with_class(class);
+                match index.dimension {
+                    Some(dimension) => index_with_class.with_dimension(dimension),
+                    None => index_with_class,
+                }
+            })
+            .collect();
+        let mut tensor = Tensor::with_coefficient(&doc.name, indices, doc.coefficient);
+        for symmetry in doc.symmetries {
+            tensor.add_symmetry(match symmetry {
+                SymmetryDoc::Symmetric { indices } => Symmetry::symmetric(indices),
+                SymmetryDoc::Antisymmetric { indices } => Symmetry::antisymmetric(indices),
+                SymmetryDoc::SymmetricPairs { pairs } => Symmetry::symmetric_pairs(pairs),
+                SymmetryDoc::Cyclic { indices } => Symmetry::cyclic(indices),
+                SymmetryDoc::AntiCyclic { indices } => Symmetry::anti_cyclic(indices),
+                SymmetryDoc::YoungShape { columns } => Symmetry::YoungShape { columns },
+                SymmetryDoc::Custom {
+                    permutations,
+                    signs,
+                } => Symmetry::custom(permutations, signs),
+            });
+        }
+        tensor
+    }
+}
+
+impl From<&Tensor> for TensorDoc {
+    fn from(tensor: &Tensor) -> Self {
+        let indices = tensor
+            .indices()
+            .iter()
+            .map(|index| IndexDoc {
+                name: index.name().to_string(),
+                contravariant: index.is_contravariant(),
+                class: match index.class() {
+                    IndexClass::Spacetime => ClassDoc::Spacetime,
+                    IndexClass::Tetrad => ClassDoc::Tetrad,
+                    IndexClass::Spatial => ClassDoc::Spatial,
+                    IndexClass::Frame => ClassDoc::Frame,
+                    IndexClass::Undotted => ClassDoc::Undotted,
+                    IndexClass::Dotted => ClassDoc::Dotted,
+                    IndexClass::LieAlgebra => ClassDoc::LieAlgebra,
+                    IndexClass::Flavor => ClassDoc::Flavor,
+                },
+                dimension: index.declared_dimension(),
+            })
+            .collect();
+        let symmetries = tensor
+            .symmetries()
+            .iter()
+            .map(|symmetry| match symmetry.clone() {
+                Symmetry::Symmetric { indices } => SymmetryDoc::Symmetric { indices },
+                Symmetry::Antisymmetric { indices } => SymmetryDoc::Antisymmetric { indices },
+                Symmetry::SymmetricPairs { pairs } => SymmetryDoc::SymmetricPairs { pairs },
+                Symmetry::Cyclic { indices } => SymmetryDoc::Cyclic { indices },
+                Symmetry::AntiCyclic { indices } => SymmetryDoc::AntiCyclic { indices },
+                Symmetry::YoungShape { columns } => SymmetryDoc::YoungShape { columns },
+                Symmetry::Custom {
+                    valid_permutations,
+                    signs,
+                } => SymmetryDoc::Custom {
+                    permutations: valid_permutations,
+                    signs,
+                },
+            })
+            .collect();
+        TensorDoc {
+            name: tensor.name().to_string(),
+            coefficient: tensor.coefficient(),
+            indices,
+            symmetries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip_and_errors() {
+        let r = crate::presets::riemann(
+            ["d", "c", "b", "a"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap();
+        let json = tensor_to_json(&r);
+        let parsed = tensor_from_json(&json).unwrap();
+        assert_eq!(parsed, r);
+
+        let form = Tensor::new("F", vec![TensorIndex::new("a", 0).with_dimension(3)]);
+        let form_json = tensor_to_json(&form);
+        assert!(form_json.contains(r#""dimension":3"#), "{form_json}");
+        assert_eq!(tensor_from_json(&form_json).unwrap(), form);
+
+        let response = canonicalize_json(&json);
+        let canonical = tensor_from_json(&response).unwrap();
+        // Slot positions are implied by order, so compare the rendered forms
+        assert_eq!(
+            canonical.to_string(),
+            crate::canonicalize(&r).unwrap().to_string()
+        );
+
+        for bad in [
+            "not json",
+            r#"{"version": 2, "tensor": {"name": "T"}}"#,
+            r#"{"version": 1, "ordering_version": 9, "tensor": {"name": "T"}}"#,
+            r#"{"version": 1}"#,
+        ] {
+            assert!(canonicalize_json(bad).contains(r#""error""#), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! LaTeX rendering of tensors and expressions
+//!
+//! Index and tensor names spelling a Greek letter (`mu`, `Gamma`) become the
+//! letter, a `bar` suffix becomes an overbar, dotted spinor indices carry a
+//! dot, derivative prefixes become `\nabla` and `\partial` operators, and
+//! consecutive indices of the same variance share one group:
+//! `R^a_{bcd}` is rendered as `R^{a}{}_{b c d}`.
+
+use crate::expression::{TensorExpression, TensorProduct};
+use crate::index::{IndexClass, TensorIndex};
+use crate::rational::Rational;
+use crate::tensor::Tensor;
+
+/// Values with a LaTeX rendering
+pub trait ToLatex {
+    /// Returns the value as LaTeX math, without surrounding delimiters
+    fn to_latex(&self) -> String;
+}
+
+/// Names with a LaTeX command of their own
+const GREEK: [&str; 34] = [
+    "alpha",
+    "beta",
+    "gamma",
+    "delta",
+    "epsilon",
+    "varepsilon",
+    "zeta",
+    "eta",
+    "theta",
+    "vartheta",
+    "iota",
+    "kappa",
+    "lambda",
+    "mu",
+    "nu",
+    "xi",
+    "pi",
+    "rho",
+    "sigma",
+    "tau",
+    "upsilon",
+    "phi",
+    "varphi",
+    "chi",
+    "psi",
+    "omega",
+    "Gamma",
+    "Delta",
+    "Theta",
+    "Lambda",
+    "Xi",
+    "Pi",
+    "Sigma",
+    "Omega",
+];
+
+/// Renders a tensor or index name
+fn name_latex(name: &str) -> String {
+    if let Some(base) = name.strip_suffix("bar").filter(|base| !base.is_empty()) {
+        format!("\\bar{{{}}}", name_latex(base))
+    } else if GREEK.contains(&name) {
+        format!("\\{name}")
+    } else if name.chars().count() > 1 && name.chars().all(char::is_alphabetic) {
+        format!("\\mathrm{{{name}}}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Renders an index label, dotting dotted spinor indices
+fn index_latex(index: &TensorIndex) -> String {
+    let name = name_latex(index.name());
+    if index.class() == IndexClass::Dotted {
+        format!("\\dot{{{name}}}")
+    } else {
+        name
+    }
+}
+
+/// Renders a rational prefactor, omitting a unit and keeping the sign in front
+fn coefficient_latex(coefficient: Rational) -> String {
+    let sign = if coefficient < Rational::ZERO {
+        "-"
+    } else {
+        ""
+    };
+    let magnitude = coefficient.abs();
+    if magnitude == Rational::ONE {
+        sign.to_string()
+    } else if magnitude.is_integer() {
+        format!("{sign}{}", magnitude.numerator())
+    } else {
+        format!(
+            "{sign}\\frac{{{}}}{{{}}}",
+            magnitude.numerator(),
+            magnitude.denominator()
+        )
+    }
+}
+
+impl ToLatex for Tensor {
+    fn to_latex(&self) -> String {
+        if self.coefficient() == 0 {
+            return "0".to_string();
+        }
+        let mut latex = coefficient_latex(Rational::from(self.coefficient()));
+        let mut name = self.name();
+        let mut indices = self.indices().iter();
+        // Each derivative prefix consumes the leading index
+        loop {
+            let operator = if let Some(rest) = name.strip_prefix('∇') {
+                name = rest;
+                "\\nabla"
+            } else if let Some(rest) = name.strip_prefix('∂') {
+                name = rest;
+                "\\partial"
+            } else {
+                break;
+            };
+            latex.push_str(operator);
+            if let Some(index) = indices.next() {
+                let marker = if index.is_contravariant() { '^' } else { '_' };
+                latex.push_str(&format!("{marker}{{{}}}", index_latex(index)));
+            }
+            latex.push(' ');
+        }
+        latex.push_str(&name_latex(name));
+
+        let mut group: Option<bool> = None;
+        for index in indices {
+            let contravariant = index.is_contravariant();
+            match group {
+                Some(current) if current == contravariant => latex.push(' '),
+                Some(_) => latex.push_str("}{}"),
+                None => {}
+            }
+            if group != Some(contravariant) {
+                latex.push(if contravariant { '^' } else { '_' });
+                latex.push('{');
+                group = Some(contravariant);
+            }
+            latex.push_str(&index_latex(index));
+        }
+        if group.is_some() {
+            latex.push('}');
+        }
+        latex
+    }
+}
+
+impl ToLatex for TensorProduct {
+    fn to_latex(&self) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let factors: Vec<String> = self.factors().iter().map(ToLatex::to_latex).collect();
+        let coefficient = coefficient_latex(self.coefficient());
+        if factors.is_empty() {
+            return match coefficient.as_str() {
+                "" => "1".to_string(),
+                "-" => "-1".to_string(),
+                _ => coefficient,
+            };
+        }
+        format!("{coefficient}{}", factors.join(" "))
+    }
+}
+
+impl ToLatex for TensorExpression {
+    fn to_latex(&self) -> String {
+        let mut latex = String::new();
+        for term in self.terms().iter().filter(|term| !term.is_zero()) {
+            let rendered = term.to_latex();
+            if latex.is_empty() {
+                latex = rendered;
+            } else if let Some(negated) = rendered.strip_prefix('-') {
+                latex.push_str(&format!(" - {negated}"));
+            } else {
+                latex.push_str(&format!(" + {rendered}"));
+            }
+        }
+        if latex.is_empty() {
+            latex.push('0');
+        }
+        latex
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_expression;
+
+    #[test]
+    fn test_latex_rendering() {
+        let r = Tensor::new(
+            "R",
+            vec![
+                TensorIndex::contravariant("a", 0),
+                TensorIndex::new("mu", 1),
+                TensorIndex::new("c", 2),
+                TensorIndex::contravariant("d", 3),
+            ],
+        );
+        assert_eq!(r.to_latex(), "R^{a}{}_{\\mu c}{}^{d}");
+
+        let expression =
+            parse_expression("3/2 R_{ab} g^{ab} - \\nabla_a \\nabla^a \\phi + 2 Ric").unwrap();
+        assert_eq!(
+            expression.to_latex(),
+            "\\frac{3}{2}R_{a b} g^{a b} - \\nabla_{a} \\nabla^{a} \\phi + 2\\mathrm{Ric}"
+        );
+        assert_eq!(TensorExpression::new().to_latex(), "0");
+        let constant = TensorExpression::from(TensorProduct::new(-Rational::ONE, Vec::new()));
+        assert_eq!(constant.to_latex(), "-1");
+
+        let spinor = Tensor::new(
+            "psibar",
+            vec![TensorIndex::dotted("A", 0), TensorIndex::undotted("B", 1)],
+        );
+        assert_eq!(spinor.to_latex(), "\\bar{\\psi}_{\\dot{A} B}");
+    }
+}
@@ -29,18 +29,59 @@
 //! # Ok::<(), butler_portugal::ButlerPortugalError>(())
 //! ```
 
+pub mod blocks;
+pub mod builtin;
+pub mod cache;
+pub mod canonical;
 pub mod canonicalization;
+pub mod codegen;
+pub mod coefficient;
+pub mod components;
+pub mod config;
+#[cfg(feature = "serde")]
+pub mod corpus;
+pub mod coset_enumeration;
+pub mod decomposition;
+pub mod derivatives;
 pub mod error;
+pub mod estimate;
+pub mod explain;
+pub mod expression;
+#[cfg(feature = "ffi")]
 pub mod ffi;
+pub mod fixed;
+pub mod forms;
 pub mod index;
+pub mod invariants;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod latex;
+#[cfg(feature = "evcxr")]
+pub mod notebook;
+#[cfg(feature = "oracle")]
+pub mod oracle;
+pub mod parser;
+pub mod permutation;
+pub mod prelude;
+pub mod presets;
+pub mod rational;
 pub mod schreier_sims;
+pub mod solver;
+pub mod spinors;
+pub mod stats;
 pub mod symmetry;
 pub mod tensor;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod workspace;
 pub mod young_tableaux;
 
-pub use canonicalization::{canonicalize, canonicalize_with_optimizations, CanonicalizationMethod};
+pub use canonicalization::{
+    canonical_key, canonicalize, canonicalize_double_coset, canonicalize_with_optimizations,
+    CanonicalKey, CanonicalizationMethod,
+};
 pub use error::{ButlerPortugalError, Result};
-pub use index::TensorIndex;
+pub use index::{IndexClass, TensorIndex};
 pub use symmetry::Symmetry;
 pub use tensor::Tensor;
 
@@ -0,0 +1,32 @@
+//! Rich display in Rust notebooks
+//!
+//! The [evcxr](https://github.com/evcxr/evcxr) Jupyter kernel renders a value
+//! through its `evcxr_display` method if it has one. With the `evcxr` feature,
+//! [`Tensor`] and [`TensorExpression`] gain that method and are shown as
+//! typeset LaTeX, as produced by [`ToLatex`], rather than as plain text.
+
+use crate::expression::TensorExpression;
+use crate::latex::ToLatex;
+use crate::tensor::Tensor;
+
+/// Prints a value in the form the evcxr kernel reads as rich output
+fn display_latex(value: &impl ToLatex) {
+    println!(
+        "EVCXR_BEGIN_CONTENT text/latex\n$${}$$\nEVCXR_END_CONTENT",
+        value.to_latex()
+    );
+}
+
+impl Tensor {
+    /// Displays the tensor as typeset math in an evcxr notebook
+    pub fn evcxr_display(&self) {
+        display_latex(self);
+    }
+}
+
+impl TensorExpression {
+    /// Displays the expression as typeset math in an evcxr notebook
+    pub fn evcxr_display(&self) {
+        display_latex(self);
+    }
+}
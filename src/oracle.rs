@@ -0,0 +1,268 @@
+//! Differential validation against a reference implementation
+//!
+//! When migrating a pipeline from another canonicalizer it is useful to run
+//! both side by side for a while. An [`Oracle`] produces the reference
+//! canonical form of a tensor; a [`DifferentialValidator`] canonicalizes with
+//! this crate, asks the oracle for its answer and records every disagreement
+//! as a [`Mismatch`].
+//!
+//! Any `Fn(&Tensor) -> Result<Tensor>` is an oracle. With the `serde` feature
+//! a [`CommandOracle`] talks to an external program, such as a SymPy script,
+//! through the JSON format of [`crate::json`].
+
+use crate::canonicalization::canonicalize;
+use crate::error::Result;
+use crate::tensor::Tensor;
+use std::fmt;
+
+/// A reference implementation of canonicalization
+pub trait Oracle {
+    /// Returns the reference canonical form of `tensor`
+    ///
+    /// # Errors
+    /// Returns an error if the reference implementation fails.
+    fn canonicalize(&self, tensor: &Tensor) -> Result<Tensor>;
+}
+
+impl<F> Oracle for F
+where
+    F: Fn(&Tensor) -> Result<Tensor>,
+{
+    fn canonicalize(&self, tensor: &Tensor) -> Result<Tensor> {
+        self(tensor)
+    }
+}
+
+/// A tensor on which this crate and the oracle disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// The tensor that was canonicalized
+    pub input: Tensor,
+    /// The canonical form computed by this crate
+    pub ours: Tensor,
+    /// The canonical form computed by the oracle
+    pub reference: Tensor,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: got {}, reference gives {}",
+            self.input, self.ours, self.reference
+        )
+    }
+}
+
+/// Canonicalizes tensors while cross-checking every result with an oracle
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::oracle::DifferentialValidator;
+/// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+///
+/// let mut f = Tensor::new("F", vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)]);
+/// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+///
+/// // A reference that forgets the antisymmetry sign
+/// let mut validator = DifferentialValidator::new(|t: &Tensor| {
+///     let mut r = butler_portugal::canonicalize(t)?;
+///     r.set_coefficient(r.coefficient().abs());
+///     Ok(r)
+/// });
+/// assert_eq!(validator.canonicalize(&f)?.to_string(), "-F__a _b");
+/// assert_eq!(validator.mismatches().len(), 1);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug)]
+pub struct DifferentialValidator<O> {
+    oracle: O,
+    checked: usize,
+    mismatches: Vec<Mismatch>,
+}
+
+impl<O: Oracle> DifferentialValidator<O> {
+    /// Creates a validator consulting `oracle`
+    pub fn new(oracle: O) -> Self {
+        Self {
+            oracle,
+            checked: 0,
+            mismatches: Vec::new(),
+        }
+    }
+
+    /// Canonicalizes a tensor, recording a mismatch if the oracle disagrees
+    ///
+    /// The result is always this crate's canonical form.
+    ///
+    /// # Errors
+    /// Returns an error if either canonicalization fails.
+    pub fn canonicalize(&mut self, tensor: &Tensor) -> Result<Tensor> {
+        let ours = canonicalize(tensor)?;
+        let reference = self.oracle.canonicalize(tensor)?;
+        self.checked += 1;
+        if !same_form(&ours, &reference) {
+            self.mismatches.push(Mismatch {
+                input: tensor.clone(),
+                ours: ours.clone(),
+                reference,
+            });
+        }
+        Ok(ours)
+    }
+
+    /// Returns the number of tensors cross-checked so far
+    pub fn checked(&self) -> usize {
+        self.checked
+    }
+
+    /// Returns the disagreements recorded so far
+    pub fn mismatches(&self) -> &[Mismatch] {
+        &self.mismatches
+    }
+
+    /// Consumes the validator, returning its recorded disagreements
+    pub fn into_mismatches(self) -> Vec<Mismatch> {
+        self.mismatches
+    }
+}
+
+/// Cross-checks the canonical forms of `tensors` against an oracle
+///
+/// # Errors
+/// Returns an error if either canonicalization fails for some tensor.
+pub fn cross_check<O: Oracle>(oracle: O, tensors: &[Tensor]) -> Result<Vec<Mismatch>> {
+    let mut validator = DifferentialValidator::new(oracle);
+    for tensor in tensors {
+        validator.canonicalize(tensor)?;
+    }
+    Ok(validator.into_mismatches())
+}
+
+/// Compares two canonical forms, ignoring the recorded index positions
+fn same_form(a: &Tensor, b: &Tensor) -> bool {
+    if a.coefficient() == 0 || b.coefficient() == 0 {
+        return a.coefficient() == b.coefficient();
+    }
+    a.name() == b.name()
+        && a.coefficient() == b.coefficient()
+        && a.rank() == b.rank()
+        && a.indices().iter().zip(b.indices()).all(|(x, y)| {
+            x.name() == y.name()
+                && x.is_contravariant() == y.is_contravariant()
+                && x.class() == y.class()
+        })
+}
+
+/// An oracle running an external program once per tensor
+///
+/// The program receives a JSON document describing the tensor on standard
+/// input and must write a JSON document with the canonical form to standard
+/// output, in the format read by [`crate::json::tensor_from_json`].
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+pub struct CommandOracle {
+    program: String,
+    args: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl CommandOracle {
+    /// Creates an oracle running `program` with `args`
+    pub fn new(program: &str, args: &[&str]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Oracle for CommandOracle {
+    fn canonicalize(&self, tensor: &Tensor) -> Result<Tensor> {
+        use crate::error::ButlerPortugalError;
+        use crate::json::{tensor_from_json, tensor_to_json};
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let failed = |e: std::io::Error| {
+            ButlerPortugalError::ComputationError(format!("Oracle {} failed: {e}", self.program))
+        };
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(failed)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(tensor_to_json(tensor).as_bytes())
+                .map_err(failed)?;
+        }
+        let output = child.wait_with_output().map_err(failed)?;
+        if !output.status.success() {
+            return Err(ButlerPortugalError::ComputationError(format!(
+                "Oracle {} exited with {}",
+                self.program, output.status
+            )));
+        }
+        tensor_from_json(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::riemann;
+    use crate::TensorIndex;
+
+    fn riemann_of(names: [&str; 4]) -> Tensor {
+        riemann(
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cross_check_reports_only_disagreements() {
+        let tensors = [
+            riemann_of(["d", "c", "b", "a"]),
+            riemann_of(["a", "b", "c", "d"]),
+            riemann_of(["a", "a", "c", "d"]),
+        ];
+        let agreeing = |t: &Tensor| crate::canonicalize(t);
+        assert!(cross_check(agreeing, &tensors).unwrap().is_empty());
+
+        // A reference that does nothing only agrees on already-canonical input
+        let identity = |t: &Tensor| Ok(t.clone());
+        let mismatches = cross_check(identity, &tensors).unwrap();
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].input, tensors[0]);
+        assert_eq!(mismatches[1].ours.coefficient(), 0);
+    }
+
+    #[cfg(all(feature = "serde", unix))]
+    #[test]
+    fn test_command_oracle_round_trips_json() {
+        // `cat` echoes the input document, acting as an identity reference
+        let oracle = CommandOracle::new("cat", &[]);
+        let mut validator = DifferentialValidator::new(oracle);
+        validator
+            .canonicalize(&riemann_of(["a", "b", "c", "d"]))
+            .unwrap();
+        validator
+            .canonicalize(&riemann_of(["b", "a", "c", "d"]))
+            .unwrap();
+        assert_eq!(validator.checked(), 2);
+        assert_eq!(validator.mismatches().len(), 1);
+
+        let missing = CommandOracle::new("/nonexistent/oracle", &[]);
+        assert!(missing
+            .canonicalize(&riemann_of(["a", "b", "c", "d"]))
+            .is_err());
+    }
+}
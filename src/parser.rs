@@ -0,0 +1,492 @@
+//! Parsing of tensor expressions from text
+//!
+//! The syntax follows LaTeX conventions:
+//!
+//! * A tensor is a name followed by index groups, `_` for covariant and `^`
+//!   for contravariant indices: `R^a_{bcd}`, `\phi`, `g^{\mu\nu}`. A name is
+//!   a run of letters and digits or a LaTeX command without its backslash.
+//! * Inside braces every character, or every `\command`, is one label;
+//!   labels separated by whitespace may be whole words (`T_{mu nu}`).
+//! * Factors are multiplied by juxtaposition or `*`; rational prefactors are
+//!   written `3/2`, sums with `+` and `-`, and parentheses group.
+//! * `\nabla_a` (`∇_a`) and `\partial_a` (`∂_a`) differentiate the factor
+//!   that follows, by the Leibniz rule if it is a parenthesized product or sum.
+//!
+//! An [`ExpressionParser`] knows the symmetries of declared tensors, so the
+//! parsed [`TensorExpression`] is ready for canonicalization. Errors carry the
+//! byte span of the offending input as a [`ParseError`].
+
+use crate::derivatives::{derivative, DerivativeKind};
+use crate::error::{ParseError, Result};
+use crate::expression::{TensorExpression, TensorProduct};
+use crate::index::TensorIndex;
+use crate::rational::Rational;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+use std::collections::HashMap;
+
+/// Parses tensor expressions, attaching the symmetries of declared tensors
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::parser::ExpressionParser;
+///
+/// let parser = ExpressionParser::standard();
+/// let expression = parser.parse("3/2 R_{ab} g^{ab} - \\nabla_a \\nabla^a \\phi")?;
+/// assert_eq!(expression.terms().len(), 2);
+/// assert_eq!(expression.to_string(), "3/2 R__a _b g_^a ^b - ∇∇phi__a ^a");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExpressionParser {
+    /// Symmetries of declared tensors, by name and rank
+    declared: HashMap<(String, usize), Vec<Symmetry>>,
+}
+
+impl ExpressionParser {
+    /// Creates a parser without declared tensors
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a parser knowing the metric `g`, the Riemann tensor `R` with
+    /// four indices, the Ricci tensor `R` with two and the Weyl tensor `C`
+    pub fn standard() -> Self {
+        let riemann = vec![
+            Symmetry::antisymmetric(vec![0, 1]),
+            Symmetry::antisymmetric(vec![2, 3]),
+            Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]),
+        ];
+        let mut parser = Self::new();
+        parser
+            .declare("g", 2, vec![Symmetry::symmetric(vec![0, 1])])
+            .declare("R", 4, riemann.clone())
+            .declare("R", 2, vec![Symmetry::symmetric(vec![0, 1])])
+            .declare("C", 4, riemann);
+        parser
+    }
+
+    /// Declares the symmetries of the tensor `name` with `rank` indices
+    pub fn declare(&mut self, name: &str, rank: usize, symmetries: Vec<Symmetry>) -> &mut Self {
+        self.declared.insert((name.to_string(), rank), symmetries);
+        self
+    }
+
+    /// Parses an expression
+    ///
+    /// # Errors
+    /// Returns a `Parse` error with the span of the offending input if the
+    /// text is malformed or a declared tensor appears with the wrong number
+    /// of indices.
+    pub fn parse(&self, input: &str) -> Result<TensorExpression> {
+        let mut cursor = Cursor {
+            input,
+            position: 0,
+            parser: self,
+        };
+        let expression = cursor.expression()?;
+        cursor.skip_whitespace();
+        if let Some(c) = cursor.peek() {
+            let end = cursor.position + c.len_utf8();
+            return Err(
+                ParseError::new("unexpected character", cursor.position..end)
+                    .expecting(&["+", "-", "factor"])
+                    .into(),
+            );
+        }
+        Ok(expression)
+    }
+
+    /// Parses a single tensor with an optional integer prefactor
+    ///
+    /// # Errors
+    /// Returns a `Parse` error if the text is malformed, or is a sum, a
+    /// product or has a fractional prefactor rather than a single tensor.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::parser::ExpressionParser;
+    ///
+    /// let r = ExpressionParser::standard().parse_tensor("-R_{mu nu}^{rho sigma}")?;
+    /// assert_eq!(r.to_string(), "-R__mu _nu ^rho ^sigma");
+    /// assert_eq!(r.symmetries().len(), 3);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn parse_tensor(&self, input: &str) -> Result<Tensor> {
+        let expression = self.parse(input)?;
+        let not_a_tensor = |message: &str| ParseError::new(message, 0..input.len()).into();
+        let [term] = expression.terms() else {
+            return Err(not_a_tensor("expected a single tensor, found a sum"));
+        };
+        let [factor] = term.factors() else {
+            return Err(not_a_tensor("expected a single tensor, found a product"));
+        };
+        let coefficient = term.coefficient();
+        let integer = (coefficient.denominator() == 1)
+            .then(|| i32::try_from(coefficient.numerator()).ok())
+            .flatten()
+            .ok_or_else(|| not_a_tensor("a tensor takes an integer prefactor"))?;
+        let mut tensor = factor.clone();
+        tensor.set_coefficient(integer);
+        Ok(tensor)
+    }
+
+    /// Builds a tensor, checking its rank against the declarations
+    fn tensor(
+        &self,
+        name: &str,
+        indices: Vec<TensorIndex>,
+        span: std::ops::Range<usize>,
+    ) -> Result<Tensor> {
+        let mut tensor = Tensor::new(name, indices);
+        if let Some(symmetries) = self.declared.get(&(name.to_string(), tensor.rank())) {
+            for symmetry in symmetries {
+                tensor.add_symmetry(symmetry.clone());
+            }
+            return Ok(tensor);
+        }
+        let mut ranks: Vec<usize> = self
+            .declared
+            .keys()
+            .filter(|(declared, _)| declared == name)
+            .map(|(_, rank)| *rank)
+            .collect();
+        if !ranks.is_empty() {
+            ranks.sort_unstable();
+            let ranks: Vec<String> = ranks.iter().map(ToString::to_string).collect();
+            return Err(ParseError::new(
+                &format!(
+                    "{name} has {} indices but is declared with {}",
+                    tensor.rank(),
+                    ranks.join(" or ")
+                ),
+                span,
+            )
+            .into());
+        }
+        Ok(tensor)
+    }
+}
+
+/// Parses an expression with [`ExpressionParser::standard`]
+///
+/// # Errors
+/// Returns a `Parse` error if the text is malformed.
+pub fn parse_expression(input: &str) -> Result<TensorExpression> {
+    ExpressionParser::standard().parse(input)
+}
+
+/// Tokens accepted where a factor starts
+const FACTOR_START: [&str; 5] = ["number", "tensor", "(", "\\nabla", "\\partial"];
+
+/// Recursive-descent state over the input text
+struct Cursor<'a> {
+    input: &'a str,
+    position: usize,
+    parser: &'a ExpressionParser,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Returns an error for the character at the cursor, or the end of input
+    fn unexpected(&self, expected: &[&str]) -> ParseError {
+        match self.peek() {
+            Some(c) => ParseError::new(
+                &format!("unexpected `{c}`"),
+                self.position..self.position + c.len_utf8(),
+            ),
+            None => ParseError::new("unexpected end of input", self.position..self.position),
+        }
+        .expecting(expected)
+    }
+
+    /// expression := [+|-] term { (+|-) term }
+    fn expression(&mut self) -> Result<TensorExpression> {
+        let mut sum = TensorExpression::new();
+        let mut sign = Rational::ONE;
+        self.skip_whitespace();
+        if let Some(c @ ('+' | '-')) = self.peek() {
+            self.bump();
+            if c == '-' {
+                sign = -Rational::ONE;
+            }
+        }
+        loop {
+            sum = sum.add(&self.term()?.scale(sign));
+            self.skip_whitespace();
+            match self.peek() {
+                Some('+') => sign = Rational::ONE,
+                Some('-') => sign = -Rational::ONE,
+                _ => return Ok(sum),
+            }
+            self.bump();
+        }
+    }
+
+    /// term := factor { [*] factor }
+    fn term(&mut self) -> Result<TensorExpression> {
+        let mut product = self.factor()?;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('*') => {
+                    self.bump();
+                }
+                None | Some('+' | '-' | ')') => return Ok(product),
+                _ => {}
+            }
+            product = product.multiply(&self.factor()?);
+        }
+    }
+
+    /// factor := number [/ number] | ( expression ) | derivative factor | tensor
+    fn factor(&mut self) -> Result<TensorExpression> {
+        self.skip_whitespace();
+        let start = self.position;
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() => {
+                let numerator = self.number()?;
+                self.skip_whitespace();
+                let value = if self.peek() == Some('/') {
+                    self.bump();
+                    self.skip_whitespace();
+                    let denominator_start = self.position;
+                    let denominator = self.number()?;
+                    if denominator == 0 {
+                        return Err(ParseError::new(
+                            "division by zero",
+                            denominator_start..self.position,
+                        )
+                        .into());
+                    }
+                    Rational::new(numerator, denominator)
+                } else {
+                    Rational::integer(numerator)
+                };
+                Ok(TensorProduct::new(value, Vec::new()).into())
+            }
+            Some('(') => {
+                self.bump();
+                let inner = self.expression()?;
+                self.skip_whitespace();
+                if self.peek() != Some(')') {
+                    return Err(self.unexpected(&[")", "+", "-"]).into());
+                }
+                self.bump();
+                Ok(inner)
+            }
+            Some(c) if c.is_alphabetic() || c == '\\' => {
+                let name = self.name()?;
+                let kind = match name.as_str() {
+                    "nabla" => Some(DerivativeKind::Covariant),
+                    "partial" => Some(DerivativeKind::Partial),
+                    _ => None,
+                };
+                let indices = self.indices()?;
+                if let Some(kind) = kind {
+                    let [index]: [TensorIndex; 1] = indices.try_into().map_err(|_| {
+                        ParseError::new(
+                            "a derivative takes exactly one index",
+                            start..self.position,
+                        )
+                    })?;
+                    let operand = self.factor()?;
+                    return Ok(differentiate(&operand, &index, kind));
+                }
+                let tensor = self.parser.tensor(&name, indices, start..self.position)?;
+                Ok(TensorProduct::from(tensor).into())
+            }
+            Some('∇' | '∂') => {
+                let kind = match self.bump() {
+                    Some('∇') => DerivativeKind::Covariant,
+                    _ => DerivativeKind::Partial,
+                };
+                let indices = self.indices()?;
+                let [index]: [TensorIndex; 1] = indices.try_into().map_err(|_| {
+                    ParseError::new("a derivative takes exactly one index", start..self.position)
+                })?;
+                let operand = self.factor()?;
+                Ok(differentiate(&operand, &index, kind))
+            }
+            _ => Err(self.unexpected(&FACTOR_START).into()),
+        }
+    }
+
+    /// Reads a non-negative integer
+    fn number(&mut self) -> Result<i64> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        if start == self.position {
+            return Err(self.unexpected(&["number"]).into());
+        }
+        self.input[start..self.position]
+            .parse()
+            .map_err(|_| ParseError::new("number too large", start..self.position).into())
+    }
+
+    /// Reads a tensor name: a LaTeX command or a run of letters and digits
+    fn name(&mut self) -> Result<String> {
+        let escaped = self.peek() == Some('\\');
+        if escaped {
+            self.bump();
+        }
+        let start = self.position;
+        while self.peek().is_some_and(char::is_alphanumeric) {
+            self.bump();
+        }
+        if start == self.position {
+            return Err(self.unexpected(&["name"]).into());
+        }
+        Ok(self.input[start..self.position].to_string())
+    }
+
+    /// Reads the index groups following a name
+    fn indices(&mut self) -> Result<Vec<TensorIndex>> {
+        let mut indices = Vec::new();
+        while let Some(marker @ ('_' | '^')) = self.peek() {
+            self.bump();
+            for label in self.labels()? {
+                let position = indices.len();
+                indices.push(if marker == '^' {
+                    TensorIndex::contravariant(&label, position)
+                } else {
+                    TensorIndex::new(&label, position)
+                });
+            }
+        }
+        Ok(indices)
+    }
+
+    /// Reads one label or a braced group of labels
+    fn labels(&mut self) -> Result<Vec<String>> {
+        match self.peek() {
+            Some('{') => {
+                self.bump();
+                let start = self.position;
+                let Some(length) = self.input[start..].find('}') else {
+                    self.position = self.input.len();
+                    return Err(self.unexpected(&["}"]).into());
+                };
+                let group = &self.input[start..start + length];
+                self.position = start + length + 1;
+                let labels = split_labels(group);
+                if labels.is_empty() {
+                    return Err(
+                        ParseError::new("empty index group", start - 1..self.position)
+                            .expecting(&["index"])
+                            .into(),
+                    );
+                }
+                Ok(labels)
+            }
+            Some('\\') => Ok(vec![self.name()?]),
+            Some(c) if c.is_alphanumeric() => {
+                self.bump();
+                Ok(vec![c.to_string()])
+            }
+            _ => Err(self.unexpected(&["index", "{"]).into()),
+        }
+    }
+}
+
+/// Splits a braced index group into labels
+///
+/// With whitespace in the group, the labels are the whitespace-separated
+/// words; otherwise every character or `\command` is a label.
+fn split_labels(group: &str) -> Vec<String> {
+    if group.trim().contains(char::is_whitespace) {
+        return group
+            .split_whitespace()
+            .map(|word| word.trim_start_matches('\\').to_string())
+            .collect();
+    }
+    let mut labels = Vec::new();
+    let mut chars = group.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let mut command = String::new();
+            while let Some(&next) = chars.peek() {
+                if !next.is_alphabetic() {
+                    break;
+                }
+                command.push(next);
+                chars.next();
+            }
+            labels.push(command);
+        } else {
+            labels.push(c.to_string());
+        }
+    }
+    labels
+}
+
+/// Differentiates every factor of every term in turn (Leibniz rule)
+fn differentiate(
+    expression: &TensorExpression,
+    index: &TensorIndex,
+    kind: DerivativeKind,
+) -> TensorExpression {
+    let mut result = TensorExpression::new();
+    for term in expression.terms() {
+        for (k, factor) in term.factors().iter().enumerate() {
+            let mut factors = term.factors().to_vec();
+            factors[k] = derivative(factor, index.clone(), kind);
+            result.push(TensorProduct::new(term.coefficient(), factors));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ButlerPortugalError;
+
+    #[test]
+    fn test_parse_sums_products_and_derivatives() {
+        let parser = ExpressionParser::standard();
+        let parsed = parser
+            .parse("-(A_a + 2 B_a) * V^a + 1/3 \\partial_b (A_a V^a)")
+            .unwrap();
+        assert_eq!(
+            parsed.to_string(),
+            "-A__a V_^a - 2 B__a V_^a + 1/3 ∂A__b _a V_^a + 1/3 A__a ∂V__b ^a"
+        );
+
+        // Declared symmetries make the expression ready for simplification
+        let vanishing = parser.parse("R_{abcd} + R_{bacd}").unwrap();
+        assert!(vanishing.simplify().unwrap().is_zero());
+        let words = parser.parse("g_{mu nu} g^{\\mu\\nu}").unwrap();
+        assert_eq!(words.to_string(), "g__mu _nu g_^mu ^nu");
+
+        let error = |input: &str| match parser.parse(input) {
+            Err(ButlerPortugalError::Parse(err)) => err,
+            other => panic!("expected a parse error, got {other:?}"),
+        };
+        let err = error("R_{ab} + * g_{ab}");
+        assert_eq!(err.span, 9..10);
+        assert!(err.expected.contains(&"tensor".to_string()));
+        assert_eq!(error("(A_a + B_a").span, 10..10);
+        assert_eq!(error("R_{abc} x_a").span, 0..7);
+        assert_eq!(error("1/0 A").span, 2..3);
+        assert_eq!(error("\\nabla_{ab} f").span, 0..11);
+        assert_eq!(error("A_{} B").span, 2..4);
+    }
+}
@@ -0,0 +1,446 @@
+//! Permutations in array form
+//!
+//! A [`Permutation`] of degree `n` holds the image `p[i]` of every point
+//! `i < n`, and points from `n` on are fixed. Composition applies the
+//! receiver first: `p.compose(&q)` maps `i` to `q[p[i]]`. Acting on the slots
+//! of a tensor, `p[k]` is the slot whose index moves into slot `k`, as for
+//! [`Tensor::permute`](crate::Tensor::permute).
+//!
+//! A [`SignedPermutation`] pairs a permutation with the sign a tensor picks up
+//! under it. Permutations print and parse in cycle notation, omitting fixed
+//! points:
+//!
+//! ```rust
+//! use butler_portugal::permutation::Permutation;
+//!
+//! let p: Permutation = "(0 2 1)(3 4)".parse()?;
+//! assert_eq!(p.images(), &[2, 0, 1, 4, 3]);
+//! assert_eq!(p.to_string(), "(0 2 1)(3 4)");
+//! assert_eq!(p.order(), 6);
+//! assert_eq!(p.parity(), -1);
+//! assert!(p.compose(&p.inverse()).is_identity());
+//! # Ok::<(), butler_portugal::ButlerPortugalError>(())
+//! ```
+
+use crate::error::{validate_permutation, ButlerPortugalError, Result};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+/// A permutation of the points `0..n` in array form
+///
+/// The images are read and rearranged through `Deref` to `[usize]`, so
+/// `p[i]`, `p.len()`, `p.iter()` and `p.swap(i, j)` work as on a slice; the
+/// degree cannot change this way. Conversions from `Vec<usize>` and
+/// collecting into a `Permutation` do not check the images; use
+/// [`Permutation::from_images`] for input from elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Permutation(Vec<usize>);
+
+impl Permutation {
+    /// Creates the identity permutation of the given degree
+    pub fn identity(degree: usize) -> Self {
+        Self((0..degree).collect())
+    }
+
+    /// Creates a permutation from its images, checking that they are one
+    ///
+    /// # Errors
+    /// Returns an `InvalidPermutation` error if an image is out of range or
+    /// repeated.
+    pub fn from_images(images: Vec<usize>) -> Result<Self> {
+        validate_permutation(&images, images.len())?;
+        Ok(Self(images))
+    }
+
+    /// Creates a permutation of the given degree from disjoint cycles
+    ///
+    /// # Errors
+    /// Returns an `InvalidPermutation` error if a point is out of range or
+    /// appears twice.
+    pub fn from_cycles(cycles: &[Vec<usize>], degree: usize) -> Result<Self> {
+        let mut images: Vec<usize> = (0..degree).collect();
+        let mut seen = vec![false; degree];
+        for cycle in cycles {
+            for (k, &point) in cycle.iter().enumerate() {
+                if point >= degree || seen[point] {
+                    return Err(ButlerPortugalError::InvalidPermutation(format!(
+                        "Point {point} is out of range or repeated in the cycles {cycles:?}"
+                    )));
+                }
+                seen[point] = true;
+                images[point] = cycle[(k + 1) % cycle.len()];
+            }
+        }
+        Ok(Self(images))
+    }
+
+    /// Returns the number of points the permutation acts on
+    pub fn degree(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the images of the points in order
+    pub fn images(&self) -> &[usize] {
+        &self.0
+    }
+
+    /// Returns the images, consuming the permutation
+    pub fn into_images(self) -> Vec<usize> {
+        self.0
+    }
+
+    /// Returns the image of a point; points beyond the degree are fixed
+    pub fn image(&self, point: usize) -> usize {
+        self.0.get(point).copied().unwrap_or(point)
+    }
+
+    /// Returns true if every point is fixed
+    pub fn is_identity(&self) -> bool {
+        self.0.iter().enumerate().all(|(i, &p)| i == p)
+    }
+
+    /// Returns the permutation applying `self` first and then `other`
+    ///
+    /// Permutations of different degrees are composed as if the shorter one
+    /// fixed the remaining points.
+    pub fn compose(&self, other: &Permutation) -> Permutation {
+        let degree = self.degree().max(other.degree());
+        (0..degree).map(|i| other.image(self.image(i))).collect()
+    }
+
+    /// Returns the inverse permutation
+    pub fn inverse(&self) -> Permutation {
+        let mut inverse = vec![0; self.degree()];
+        for (i, &p) in self.0.iter().enumerate() {
+            inverse[p] = i;
+        }
+        Self(inverse)
+    }
+
+    /// Returns the disjoint cycles of length at least two, each starting at
+    /// its smallest point
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.degree()];
+        let mut cycles = Vec::new();
+        for start in 0..self.degree() {
+            if seen[start] || self.0[start] == start {
+                continue;
+            }
+            let mut cycle = Vec::new();
+            let mut current = start;
+            while !seen[current] {
+                seen[current] = true;
+                cycle.push(current);
+                current = self.0[current];
+            }
+            cycles.push(cycle);
+        }
+        cycles
+    }
+
+    /// Returns the order, the least common multiple of the cycle lengths
+    pub fn order(&self) -> usize {
+        fn gcd(a: usize, b: usize) -> usize {
+            if b == 0 {
+                a
+            } else {
+                gcd(b, a % b)
+            }
+        }
+        self.cycles()
+            .iter()
+            .map(Vec::len)
+            .fold(1, |order, length| order / gcd(order, length) * length)
+    }
+
+    /// Returns the sign of the permutation: `1` if it is even, `-1` if odd
+    pub fn parity(&self) -> i32 {
+        let transpositions: usize = self.cycles().iter().map(|cycle| cycle.len() - 1).sum();
+        if transpositions % 2 == 0 {
+            1
+        } else {
+            -1
+        }
+    }
+
+    /// Rearranges a slice as the permutation rearranges tensor slots:
+    /// position `k` receives `items[p[k]]`
+    ///
+    /// # Errors
+    /// Returns an `InvalidPermutation` error if the slice length differs
+    /// from the degree.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::permutation::Permutation;
+    ///
+    /// let p = Permutation::from(vec![1, 2, 0]);
+    /// assert_eq!(p.apply_to_slice(&["a", "b", "c"])?, ["b", "c", "a"]);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn apply_to_slice<T: Clone>(&self, items: &[T]) -> Result<Vec<T>> {
+        if items.len() != self.degree() {
+            return Err(ButlerPortugalError::InvalidPermutation(format!(
+                "Permutation of degree {} applied to {} items",
+                self.degree(),
+                items.len()
+            )));
+        }
+        Ok(self.0.iter().map(|&source| items[source].clone()).collect())
+    }
+}
+
+impl Deref for Permutation {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl DerefMut for Permutation {
+    fn deref_mut(&mut self) -> &mut [usize] {
+        &mut self.0
+    }
+}
+
+impl AsRef<[usize]> for Permutation {
+    fn as_ref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl From<Vec<usize>> for Permutation {
+    fn from(images: Vec<usize>) -> Self {
+        Self(images)
+    }
+}
+
+impl From<&[usize]> for Permutation {
+    fn from(images: &[usize]) -> Self {
+        Self(images.to_vec())
+    }
+}
+
+impl From<Permutation> for Vec<usize> {
+    fn from(perm: Permutation) -> Self {
+        perm.0
+    }
+}
+
+impl FromIterator<usize> for Permutation {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Permutation {
+    type Item = usize;
+    type IntoIter = std::vec::IntoIter<usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Permutation {
+    type Item = &'a usize;
+    type IntoIter = std::slice::Iter<'a, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl PartialEq<Vec<usize>> for Permutation {
+    fn eq(&self, other: &Vec<usize>) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<[usize]> for Permutation {
+    fn eq(&self, other: &[usize]) -> bool {
+        self.0 == other
+    }
+}
+
+impl fmt::Display for Permutation {
+    /// Writes the permutation in cycle notation, `()` for the identity
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cycles = self.cycles();
+        if cycles.is_empty() {
+            return write!(f, "()");
+        }
+        for cycle in cycles {
+            let points: Vec<String> = cycle.iter().map(ToString::to_string).collect();
+            write!(f, "({})", points.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Permutation {
+    type Err = ButlerPortugalError;
+
+    /// Parses cycle notation such as `(0 2 1)(3 4)`, with points separated
+    /// by spaces or commas; the degree is one more than the largest point
+    fn from_str(input: &str) -> Result<Self> {
+        let malformed = || {
+            ButlerPortugalError::InvalidPermutation(format!("Malformed cycle notation {input:?}"))
+        };
+        let mut cycles = Vec::new();
+        let mut rest = input.trim();
+        while !rest.is_empty() {
+            let body = rest.strip_prefix('(').ok_or_else(malformed)?;
+            let end = body.find(')').ok_or_else(malformed)?;
+            let cycle = body[..end]
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|point| !point.is_empty())
+                .map(|point| point.parse::<usize>().map_err(|_| malformed()))
+                .collect::<Result<Vec<usize>>>()?;
+            if !cycle.is_empty() {
+                cycles.push(cycle);
+            }
+            rest = body[end + 1..].trim_start();
+        }
+        let degree = cycles.iter().flatten().max().map_or(0, |&max| max + 1);
+        Self::from_cycles(&cycles, degree)
+    }
+}
+
+/// A permutation together with a sign, `(p, ±1)`
+///
+/// Tensor symmetries are signed permutations: `T` rearranged by
+/// `permutation` equals `sign` times `T`. Composing signed permutations
+/// multiplies their signs, so the sign of any product of generators is
+/// exact, whichever symmetries the generators come from.
+///
+/// Inside a stabilizer chain a signed permutation of degree `n` is encoded as
+/// a permutation of `n + 2` points, swapping the two extra points when the
+/// sign is `-1`.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::permutation::SignedPermutation;
+///
+/// let swap = SignedPermutation::new("(0 1)".parse()?, -1);
+/// let square = swap.compose(&swap);
+/// assert!(square.permutation.is_identity());
+/// assert_eq!(square.sign, 1);
+/// assert_eq!(swap.to_string(), "-(0 1)");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SignedPermutation {
+    /// The rearrangement of the points
+    pub permutation: Permutation,
+    /// The sign, `1` or `-1`
+    pub sign: i32,
+}
+
+impl SignedPermutation {
+    /// Creates a signed permutation; any negative `sign` counts as `-1` and
+    /// any other as `1`
+    pub fn new(permutation: Permutation, sign: i32) -> Self {
+        Self {
+            permutation,
+            sign: if sign < 0 { -1 } else { 1 },
+        }
+    }
+
+    /// Creates the identity permutation of the given degree with sign `1`
+    pub fn identity(degree: usize) -> Self {
+        Self::new(Permutation::identity(degree), 1)
+    }
+
+    /// Returns the number of points the permutation acts on
+    pub fn degree(&self) -> usize {
+        self.permutation.degree()
+    }
+
+    /// Returns the signed permutation applying `self` first and then `other`
+    pub fn compose(&self, other: &SignedPermutation) -> SignedPermutation {
+        Self::new(
+            self.permutation.compose(&other.permutation),
+            self.sign * other.sign,
+        )
+    }
+
+    /// Returns the inverse, which has the same sign
+    pub fn inverse(&self) -> SignedPermutation {
+        Self::new(self.permutation.inverse(), self.sign)
+    }
+
+    /// Encodes the sign as a permutation of the two points following the
+    /// degree
+    pub(crate) fn to_extended(&self) -> Permutation {
+        let n = self.degree();
+        let mut extended = self.permutation.images().to_vec();
+        if self.sign < 0 {
+            extended.extend([n + 1, n]);
+        } else {
+            extended.extend([n, n + 1]);
+        }
+        Permutation(extended)
+    }
+
+    /// Decodes a permutation of `n + 2` points made by
+    /// [`to_extended`](Self::to_extended)
+    pub(crate) fn from_extended(extended: &[usize]) -> Self {
+        let n = extended.len().saturating_sub(2);
+        let sign = if extended.get(n) == Some(&n) { 1 } else { -1 };
+        Self::new(Permutation::from(&extended[..n]), sign)
+    }
+}
+
+impl fmt::Display for SignedPermutation {
+    /// Writes the permutation in cycle notation, preceded by `-` if the sign
+    /// is negative
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.sign < 0 {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.permutation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permutation_arithmetic() {
+        let p = Permutation::from(vec![1, 2, 0, 3]);
+        let q: Permutation = "(0 1)".parse().unwrap();
+        assert_eq!(p.compose(&q), vec![0, 2, 1, 3]);
+        assert_eq!(p.inverse(), vec![2, 0, 1, 3]);
+        assert!(p.compose(&p.inverse()).is_identity());
+        assert_eq!(p.cycles(), vec![vec![0, 1, 2]]);
+        assert_eq!((p.order(), p.parity()), (3, 1));
+        assert_eq!((q.order(), q.parity()), (2, -1));
+        assert_eq!(Permutation::identity(3).to_string(), "()");
+        assert_eq!("()".parse::<Permutation>().unwrap().degree(), 0);
+        assert_eq!(p.to_string().parse::<Permutation>().unwrap(), vec![1, 2, 0]);
+
+        assert!("(0 1".parse::<Permutation>().is_err());
+        assert!("(0 1)(1 2)".parse::<Permutation>().is_err());
+        assert!("(0 x)".parse::<Permutation>().is_err());
+        assert!(Permutation::from_images(vec![0, 0]).is_err());
+        assert!(p.apply_to_slice(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_signed_permutation_encoding() {
+        let cycle = SignedPermutation::new(vec![1, 2, 0].into(), -1);
+        let extended = cycle.to_extended();
+        assert_eq!(extended, vec![1, 2, 0, 4, 3]);
+        assert_eq!(SignedPermutation::from_extended(&extended), cycle);
+        assert_eq!(
+            cycle.inverse().compose(&cycle),
+            SignedPermutation::identity(3)
+        );
+        assert_eq!(cycle.compose(&cycle).sign, 1);
+        assert_eq!(SignedPermutation::identity(2).to_string(), "()");
+    }
+}
@@ -0,0 +1,31 @@
+//! Commonly used types and functions
+//!
+//! A glob import brings the core tensor types, canonicalization, the
+//! expression layer and the physics presets into scope at once.
+//!
+//! # Example
+//! ```rust
+//! use butler_portugal::prelude::*;
+//!
+//! let r = riemann(["a", "b", "c", "d"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+//! let term = TensorProduct::from(canonicalize(&r)?);
+//! assert_eq!(TensorExpression::from(term).simplify()?.terms().len(), 1);
+//! # Ok::<(), ButlerPortugalError>(())
+//! ```
+
+pub use crate::builtin::{epsilon, metric};
+pub use crate::canonical::Canonical;
+pub use crate::canonicalization::{
+    canonical_key, canonicalize, canonicalize_with_optimizations, CanonicalKey,
+};
+pub use crate::coefficient::Coefficient;
+pub use crate::derivatives::{covariant_derivative, partial_derivative, DerivativeKind};
+pub use crate::error::{ButlerPortugalError, Result};
+pub use crate::expression::{
+    contract, identity_residual, verify_identity, TensorExpression, TensorProduct, TensorSum,
+};
+pub use crate::index::{IndexClass, TensorIndex};
+pub use crate::presets::{self, riemann, weyl};
+pub use crate::rational::Rational;
+pub use crate::symmetry::Symmetry;
+pub use crate::tensor::Tensor;
@@ -0,0 +1,671 @@
+//! Ready-made tensor declarations for common physics calculations
+//!
+//! Each preset returns a `Tensor` with the standard symmetries already
+//! declared, so callers do not have to spell out the generators by hand.
+
+use crate::builtin::metric;
+use crate::canonicalization::{canonicalize, validate_symmetries};
+use crate::derivatives::covariant_derivative;
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::{TensorExpression, TensorProduct};
+use crate::index::{IndexClass, TensorIndex};
+use crate::rational::Rational;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+
+/// Name of the Weyl tensor, whose traces vanish
+const WEYL_NAME: &str = "C";
+
+/// Labels of the Newman-Penrose null tetrad `(l, n, m, m̄)`
+pub const NULL_TETRAD: [&str; 4] = ["l", "n", "m", "mbar"];
+
+/// Tetrad labels of the Weyl scalars `Ψ0…Ψ4`, as components `C_{abcd}`
+const WEYL_SCALAR_COMPONENTS: [[&str; 4]; 5] = [
+    ["l", "m", "l", "m"],
+    ["l", "n", "l", "m"],
+    ["l", "m", "mbar", "n"],
+    ["l", "n", "mbar", "n"],
+    ["n", "mbar", "n", "mbar"],
+];
+
+/// Adds the Riemann symmetries (pair antisymmetry and pair exchange) to a rank-4 tensor
+fn add_riemann_symmetries(tensor: &mut Tensor) {
+    tensor.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    tensor.add_symmetry(Symmetry::antisymmetric(vec![2, 3]));
+    tensor.add_symmetry(Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]));
+}
+
+/// Returns true if a rank-4 tensor carries the Riemann monoterm symmetries,
+/// and therefore also obeys the first Bianchi identity `R_{a[bcd]} = 0`
+///
+/// This holds for `riemann` and `weyl` presets and any tensor declared the same way.
+pub fn has_riemann_symmetries(tensor: &Tensor) -> bool {
+    let symmetries = tensor.symmetries();
+    tensor.rank() == 4
+        && symmetries.iter().any(|s| s.is_antisymmetric_pair(0, 1))
+        && symmetries.iter().any(|s| s.is_antisymmetric_pair(2, 3))
+        && symmetries.iter().any(|s| match s {
+            Symmetry::SymmetricPairs { pairs } => {
+                pairs.contains(&(0, 1)) && pairs.contains(&(2, 3))
+            }
+            _ => false,
+        })
+}
+
+/// Checks that a preset receives the expected number of indices
+fn check_rank(name: &str, indices: &[TensorIndex], rank: usize) -> Result<()> {
+    if indices.len() == rank {
+        Ok(())
+    } else {
+        Err(ButlerPortugalError::InvalidTensor(format!(
+            "{} needs {} indices, got {}",
+            name,
+            rank,
+            indices.len()
+        )))
+    }
+}
+
+/// Creates the Riemann tensor `R_{abcd}` with its monoterm symmetries
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::presets::riemann;
+/// use butler_portugal::TensorIndex;
+///
+/// let r = riemann(vec![
+///     TensorIndex::new("a", 0),
+///     TensorIndex::new("b", 1),
+///     TensorIndex::new("c", 2),
+///     TensorIndex::new("d", 3),
+/// ])?;
+/// assert_eq!(r.symmetries().len(), 3);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn riemann(indices: Vec<TensorIndex>) -> Result<Tensor> {
+    check_rank("Riemann tensor", &indices, 4)?;
+    let mut tensor = Tensor::new("R", indices);
+    add_riemann_symmetries(&mut tensor);
+    Ok(tensor)
+}
+
+/// Creates the Weyl tensor `C_{abcd}`, which has the Riemann symmetries
+pub fn weyl(indices: Vec<TensorIndex>) -> Result<Tensor> {
+    check_rank("Weyl tensor", &indices, 4)?;
+    let mut tensor = Tensor::new(WEYL_NAME, indices);
+    add_riemann_symmetries(&mut tensor);
+    Ok(tensor)
+}
+
+/// Creates the Ricci tensor `R_{ab} = R^c{}_{acb}`, which is symmetric
+pub fn ricci(indices: Vec<TensorIndex>) -> Result<Tensor> {
+    check_rank("Ricci tensor", &indices, 2)?;
+    let mut tensor = Tensor::new("R", indices);
+    tensor.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+    Ok(tensor)
+}
+
+/// Performs the contractions of a tensor with itself
+///
+/// A pair of slots carrying the same name with opposite variance is traced
+/// out when the trace is known from the declared symmetries:
+///
+/// * a Riemann-symmetric tensor becomes the Ricci tensor `R_{bd} = R^a{}_{bad}`,
+///   with the sign of the pair and antisymmetry relations, and a traced Weyl
+///   tensor vanishes;
+/// * a symmetric tensor of rank two becomes a scalar of the same name, as
+///   `R = R^a{}_a`;
+/// * a totally symmetric tensor keeps its name, loses the two slots and stays
+///   totally symmetric;
+/// * a contraction inside an antisymmetric pair of slots vanishes.
+///
+/// The coefficient carries over with the sign. Contractions of any other
+/// kind are left in place.
+///
+/// # Errors
+/// Returns an `InvalidSymmetry` error if a symmetry of the tensor is
+/// inconsistent with its rank.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::presets::{riemann, trace_self_contractions};
+/// use butler_portugal::TensorIndex;
+///
+/// // R^a_{bca} = -R^a_{bac} = -R_{bc}
+/// let r = riemann(vec![
+///     TensorIndex::contravariant("a", 0),
+///     TensorIndex::new("b", 1),
+///     TensorIndex::new("c", 2),
+///     TensorIndex::new("a", 3),
+/// ])?;
+/// let ricci = trace_self_contractions(&r)?;
+/// assert_eq!(ricci.to_string(), "-R__b _c");
+/// assert_eq!(ricci.symmetries().len(), 1);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn trace_self_contractions(tensor: &Tensor) -> Result<Tensor> {
+    validate_symmetries(tensor)?;
+    let mut traced = tensor.clone();
+    while let Some((first, second)) = self_contracted_pair(&traced) {
+        match trace_pair(&traced, first, second)? {
+            Some(next) => traced = next,
+            None => break,
+        }
+        if traced.coefficient() == 0 {
+            break;
+        }
+    }
+    Ok(traced)
+}
+
+/// Returns the first pair of slots contracted with each other
+fn self_contracted_pair(tensor: &Tensor) -> Option<(usize, usize)> {
+    let indices = tensor.indices();
+    (0..indices.len()).find_map(|first| {
+        (first + 1..indices.len())
+            .find(|&second| indices[first].can_contract_with(&indices[second]))
+            .map(|second| (first, second))
+    })
+}
+
+/// Traces out slots `first < second`, or returns `None` if the trace is not
+/// known from the symmetries
+fn trace_pair(tensor: &Tensor, first: usize, second: usize) -> Result<Option<Tensor>> {
+    let vanishing = |tensor: &Tensor| {
+        let mut zero = tensor.clone();
+        zero.set_coefficient(0);
+        zero
+    };
+    if tensor
+        .symmetries()
+        .iter()
+        .any(|s| s.is_antisymmetric_pair(first, second))
+    {
+        return Ok(Some(vanishing(tensor)));
+    }
+    let remaining: Vec<TensorIndex> = (0..tensor.rank())
+        .filter(|&slot| slot != first && slot != second)
+        .enumerate()
+        .map(|(position, slot)| tensor.indices()[slot].with_position(position))
+        .collect();
+
+    if has_riemann_symmetries(tensor) {
+        if tensor.name() == WEYL_NAME {
+            return Ok(Some(vanishing(tensor)));
+        }
+        // R_{xayb} = R_{axby} = R_{xy}; the other placements differ by one
+        // antisymmetric exchange
+        let sign = if second - first == 2 { 1 } else { -1 };
+        let mut traced = ricci(remaining)?;
+        traced.set_name(tensor.name());
+        traced.set_coefficient(sign * tensor.coefficient());
+        return Ok(Some(traced));
+    }
+    if tensor.rank() >= 2 && check_total_symmetry(tensor, false).is_ok() {
+        let mut traced = Tensor::with_coefficient(tensor.name(), remaining, tensor.coefficient());
+        if traced.rank() >= 2 {
+            traced.add_symmetry(Symmetry::symmetric((0..traced.rank()).collect()));
+        }
+        return Ok(Some(traced));
+    }
+    Ok(None)
+}
+
+/// Returns the Weyl scalar `Ψk` as a tetrad component of the Weyl tensor
+///
+/// With the null tetrad `(l, n, m, m̄)` the five scalars are
+/// `Ψ0 = C_{lmlm}`, `Ψ1 = C_{lnlm}`, `Ψ2 = C_{lm m̄ n}`,
+/// `Ψ3 = C_{ln m̄ n}` and `Ψ4 = C_{n m̄ n m̄}`.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::presets::weyl_scalar;
+///
+/// let psi0 = weyl_scalar(0)?;
+/// assert_eq!(psi0.to_string(), "C__l _m _l _m");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn weyl_scalar(k: usize) -> Result<Tensor> {
+    let labels = WEYL_SCALAR_COMPONENTS.get(k).ok_or_else(|| {
+        ButlerPortugalError::InvalidTensor(format!("There is no Weyl scalar Ψ{k}"))
+    })?;
+    weyl(
+        labels
+            .iter()
+            .enumerate()
+            .map(|(position, label)| TensorIndex::tetrad(label, position))
+            .collect(),
+    )
+}
+
+/// Identifies a tetrad component of the Weyl tensor with a Weyl scalar
+///
+/// Returns `Some((k, sign))` if the component equals `sign · Ψk` by the
+/// Riemann symmetries, and `None` if it is not one of the five scalars (for
+/// example a complex conjugate or a component vanishing by symmetry).
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::presets::{identify_weyl_scalar, weyl};
+/// use butler_portugal::TensorIndex;
+///
+/// // C_{m l l m} = -C_{l m l m} = -Ψ0
+/// let component = weyl(
+///     ["m", "l", "l", "m"]
+///         .iter()
+///         .enumerate()
+///         .map(|(i, label)| TensorIndex::tetrad(label, i))
+///         .collect(),
+/// )?;
+/// assert_eq!(identify_weyl_scalar(&component)?, Some((0, -1)));
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn identify_weyl_scalar(component: &Tensor) -> Result<Option<(usize, i32)>> {
+    if component.rank() != 4
+        || component
+            .indices()
+            .iter()
+            .any(|index| index.class() != IndexClass::Tetrad)
+    {
+        return Ok(None);
+    }
+    let canonical = canonicalize(component)?;
+    if canonical.coefficient() == 0 {
+        return Ok(None);
+    }
+    for k in 0..WEYL_SCALAR_COMPONENTS.len() {
+        let scalar = canonicalize(&weyl_scalar(k)?)?;
+        let same_slots = scalar
+            .indices()
+            .iter()
+            .zip(canonical.indices())
+            .all(|(a, b)| a.name() == b.name() && a.is_contravariant() == b.is_contravariant());
+        if same_slots {
+            return Ok(Some((k, canonical.coefficient() * scalar.coefficient())));
+        }
+    }
+    Ok(None)
+}
+
+/// Creates a Killing tensor `K_{a1…ap}`, totally symmetric
+///
+/// A Killing tensor satisfies [`killing_tensor_equation`]; along geodesics
+/// `K_{a1…ap} p^{a1}…p^{ap}` is conserved, as the Carter constant is for
+/// the rank-2 Killing tensor of the Kerr metric.
+///
+/// # Errors
+/// Returns an `InvalidTensor` error if no index is given.
+pub fn killing_tensor(indices: Vec<TensorIndex>) -> Result<Tensor> {
+    hidden_symmetry("K", "Killing tensor", indices, Symmetry::symmetric)
+}
+
+/// Creates a Killing–Yano form `Y_{a1…ap}`, totally antisymmetric
+///
+/// A Killing–Yano form satisfies [`killing_yano_equation`]; the square
+/// `Y_{a c…} Y_b{}^{c…}` of one is a Killing tensor.
+///
+/// # Errors
+/// Returns an `InvalidTensor` error if no index is given.
+pub fn killing_yano(indices: Vec<TensorIndex>) -> Result<Tensor> {
+    hidden_symmetry("Y", "Killing–Yano form", indices, Symmetry::antisymmetric)
+}
+
+/// Creates a conformal Killing tensor `Q_{a1…ap}`, totally symmetric
+///
+/// A conformal Killing tensor satisfies [`conformal_killing_equation`];
+/// along null geodesics `Q_{a1…ap} p^{a1}…p^{ap}` is conserved.
+///
+/// # Errors
+/// Returns an `InvalidTensor` error if no index is given.
+pub fn conformal_killing_tensor(indices: Vec<TensorIndex>) -> Result<Tensor> {
+    hidden_symmetry(
+        "Q",
+        "Conformal Killing tensor",
+        indices,
+        Symmetry::symmetric,
+    )
+}
+
+/// Declares a tensor of rank at least one with a total symmetry
+fn hidden_symmetry(
+    name: &str,
+    description: &str,
+    indices: Vec<TensorIndex>,
+    symmetry: fn(Vec<usize>) -> Symmetry,
+) -> Result<Tensor> {
+    if indices.is_empty() {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "{description} needs at least one index"
+        )));
+    }
+    let rank = indices.len();
+    let mut tensor = Tensor::new(name, indices);
+    if rank > 1 {
+        tensor.add_symmetry(symmetry((0..rank).collect()));
+    }
+    Ok(tensor)
+}
+
+/// Returns `∇_{(b} K_{a1…ap)}`, which vanishes for a Killing tensor `K`
+///
+/// `b` is the derivative index. As `K` is symmetric, the symmetrization has
+/// one term for each of the p + 1 indices taking the derivative slot.
+///
+/// # Errors
+/// Returns an `InvalidTensor` error unless `tensor` is totally symmetric.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::presets::{killing_tensor, killing_tensor_equation};
+/// use butler_portugal::TensorIndex;
+///
+/// let k = killing_tensor(vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)])?;
+/// let equation = killing_tensor_equation(&k, TensorIndex::new("c", 0))?;
+/// assert_eq!(equation.to_string(), "1/3 ∇K__c _a _b + 1/3 ∇K__a _c _b + 1/3 ∇K__b _a _c");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn killing_tensor_equation(tensor: &Tensor, index: TensorIndex) -> Result<TensorExpression> {
+    check_total_symmetry(tensor, false)?;
+    Ok(symmetrized_gradient(tensor, index))
+}
+
+/// Returns `∇_{(b} Y_{a1)a2…ap}`, which vanishes for a Killing–Yano form `Y`
+///
+/// `b` is the derivative index; the equation is
+/// `(∇_b Y_{a1 a2…} + ∇_{a1} Y_{b a2…}) / 2`.
+///
+/// # Errors
+/// Returns an `InvalidTensor` error unless `tensor` is totally antisymmetric.
+pub fn killing_yano_equation(tensor: &Tensor, index: TensorIndex) -> Result<TensorExpression> {
+    check_total_symmetry(tensor, true)?;
+    let first = tensor.indices()[0].clone();
+    let mut exchanged = tensor.clone();
+    exchanged.indices_mut()[0] = index.with_position(0);
+    let half = Rational::new(1, 2);
+    Ok([
+        TensorProduct::new(half, vec![covariant_derivative(tensor, index)]),
+        TensorProduct::new(half, vec![covariant_derivative(&exchanged, first)]),
+    ]
+    .into_iter()
+    .collect())
+}
+
+/// Returns `∇_{(b} Q_{a1…ap)} - g_{(b a1} λ_{a2…ap)}`, which vanishes for a
+/// conformal Killing tensor `Q`
+///
+/// `λ` is a totally symmetric tensor of rank p - 1, named `lambda`, fixed by
+/// the trace of the equation; for p = 1 it is a scalar and `Q` a conformal
+/// Killing vector.
+///
+/// # Errors
+/// Returns an `InvalidTensor` error unless `tensor` is totally symmetric.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::presets::{conformal_killing_equation, conformal_killing_tensor};
+/// use butler_portugal::TensorIndex;
+///
+/// let xi = conformal_killing_tensor(vec![TensorIndex::new("a", 0)])?;
+/// let equation = conformal_killing_equation(&xi, TensorIndex::new("b", 0))?;
+/// assert_eq!(equation.to_string(), "1/2 ∇Q__b _a + 1/2 ∇Q__a _b - g__b _a lambda");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn conformal_killing_equation(tensor: &Tensor, index: TensorIndex) -> Result<TensorExpression> {
+    check_total_symmetry(tensor, false)?;
+    let slots: Vec<TensorIndex> = std::iter::once(index.clone())
+        .chain(tensor.indices().iter().cloned())
+        .collect();
+    let pairs: Vec<(usize, usize)> = (0..slots.len())
+        .flat_map(|i| (i + 1..slots.len()).map(move |j| (i, j)))
+        .collect();
+    let weight = -Rational::new(1, pairs.len() as i64);
+    let trace_terms = pairs.iter().map(|&(i, j)| {
+        let rest: Vec<TensorIndex> = slots
+            .iter()
+            .enumerate()
+            .filter(|&(k, _)| k != i && k != j)
+            .enumerate()
+            .map(|(position, (_, index))| index.with_position(position))
+            .collect();
+        let rank = rest.len();
+        let mut lambda = Tensor::new("lambda", rest);
+        if rank > 1 {
+            lambda.add_symmetry(Symmetry::symmetric((0..rank).collect()));
+        }
+        let g = metric(slots[i].with_position(0), slots[j].with_position(1));
+        TensorProduct::new(weight, vec![g, lambda])
+    });
+    let trace: TensorExpression = trace_terms.collect();
+    Ok(symmetrized_gradient(tensor, index).add(&trace))
+}
+
+/// Returns `∇_{(b} T_{a1…ap)}` for a totally symmetric `T`
+fn symmetrized_gradient(tensor: &Tensor, index: TensorIndex) -> TensorExpression {
+    let weight = Rational::new(1, tensor.rank() as i64 + 1);
+    let mut terms: Vec<TensorProduct> = (0..tensor.rank())
+        .map(|slot| {
+            let mut exchanged = tensor.clone();
+            exchanged.indices_mut()[slot] = index.with_position(slot);
+            let derivative = tensor.indices()[slot].clone();
+            TensorProduct::new(weight, vec![covariant_derivative(&exchanged, derivative)])
+        })
+        .collect();
+    terms.insert(
+        0,
+        TensorProduct::new(weight, vec![covariant_derivative(tensor, index)]),
+    );
+    terms.into_iter().collect()
+}
+
+/// Checks that a tensor of rank at least one is totally symmetric, or
+/// totally antisymmetric
+fn check_total_symmetry(tensor: &Tensor, antisymmetric: bool) -> Result<()> {
+    let rank = tensor.rank();
+    let all: Vec<usize> = (0..rank).collect();
+    let total = rank == 1
+        || tensor.symmetries().iter().any(|symmetry| match symmetry {
+            Symmetry::Symmetric { indices } if !antisymmetric => {
+                let mut slots = indices.clone();
+                slots.sort_unstable();
+                slots == all
+            }
+            Symmetry::Antisymmetric { indices } if antisymmetric => {
+                let mut slots = indices.clone();
+                slots.sort_unstable();
+                slots == all
+            }
+            _ => false,
+        });
+    if total {
+        Ok(())
+    } else {
+        Err(ButlerPortugalError::InvalidTensor(format!(
+            "{} is not totally {}symmetric",
+            tensor.name(),
+            if antisymmetric { "anti" } else { "" }
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(labels: [&str; 4]) -> Tensor {
+        weyl(
+            labels
+                .iter()
+                .enumerate()
+                .map(|(i, label)| TensorIndex::tetrad(label, i))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_has_riemann_symmetries() {
+        assert!(has_riemann_symmetries(&weyl_scalar(2).unwrap()));
+        let mut partial = Tensor::new(
+            "R",
+            (0..4)
+                .map(|i| TensorIndex::new(&i.to_string(), i))
+                .collect(),
+        );
+        partial.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        partial.add_symmetry(Symmetry::antisymmetric(vec![2, 3]));
+        assert!(!has_riemann_symmetries(&partial));
+    }
+
+    #[test]
+    fn test_presets_check_rank() {
+        assert!(riemann(vec![TensorIndex::new("a", 0)]).is_err());
+        assert!(weyl_scalar(5).is_err());
+    }
+
+    #[test]
+    fn test_weyl_scalars_use_tetrad_indices() {
+        for k in 0..5 {
+            let psi = weyl_scalar(k).unwrap();
+            assert!(psi
+                .indices()
+                .iter()
+                .all(|i| i.class() == IndexClass::Tetrad && NULL_TETRAD.contains(&i.name())));
+            assert_eq!(identify_weyl_scalar(&psi).unwrap(), Some((k, 1)));
+        }
+    }
+
+    #[test]
+    fn test_identify_permuted_components() {
+        assert_eq!(
+            identify_weyl_scalar(&component(["l", "m", "m", "l"])).unwrap(),
+            Some((0, -1))
+        );
+        assert_eq!(
+            identify_weyl_scalar(&component(["n", "mbar", "m", "l"])).unwrap(),
+            Some((2, 1))
+        );
+        assert_eq!(
+            identify_weyl_scalar(&component(["n", "mbar", "n", "l"])).unwrap(),
+            Some((3, 1))
+        );
+    }
+
+    #[test]
+    fn test_unrecognized_components() {
+        // Vanishes by antisymmetry
+        assert_eq!(
+            identify_weyl_scalar(&component(["l", "l", "m", "n"])).unwrap(),
+            None
+        );
+        // Complex conjugate of Ψ0
+        assert_eq!(
+            identify_weyl_scalar(&component(["l", "mbar", "l", "mbar"])).unwrap(),
+            None
+        );
+        // Coordinate indices are not tetrad components
+        let r = riemann(
+            ["a", "b", "c", "d"]
+                .iter()
+                .enumerate()
+                .map(|(i, name)| TensorIndex::new(name, i))
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(identify_weyl_scalar(&r).unwrap(), None);
+    }
+
+    #[test]
+    fn test_hidden_symmetry_equations() {
+        use crate::expression::verify_identity;
+
+        let index = |name: &str| TensorIndex::new(name, 0);
+        let pair = |first: &str, second: &str| vec![index(first), index(second).with_position(1)];
+
+        // The equations are symmetric in the derivative and the first index
+        let k = |first, second| killing_tensor(pair(first, second)).unwrap();
+        let lhs = killing_tensor_equation(&k("a", "b"), index("c")).unwrap();
+        let rhs = killing_tensor_equation(&k("c", "b"), index("a")).unwrap();
+        assert!(verify_identity(&lhs, &rhs).unwrap());
+        let y = |first, second| killing_yano(pair(first, second)).unwrap();
+        let lhs = killing_yano_equation(&y("a", "c"), index("b")).unwrap();
+        let rhs = killing_yano_equation(&y("b", "c"), index("a")).unwrap();
+        assert!(verify_identity(&lhs, &rhs).unwrap());
+        let q = conformal_killing_tensor(pair("a", "b")).unwrap();
+        assert_eq!(
+            conformal_killing_equation(&q, index("c"))
+                .unwrap()
+                .terms()
+                .len(),
+            6
+        );
+
+        // For vectors all three reduce to the Killing equation
+        let xi = Tensor::new("xi", vec![index("a")]);
+        let killing = killing_tensor_equation(&xi, index("b")).unwrap();
+        assert!(
+            verify_identity(&killing, &killing_yano_equation(&xi, index("b")).unwrap()).unwrap()
+        );
+
+        assert!(killing_tensor(Vec::new()).is_err());
+        assert!(killing_tensor_equation(&y("a", "b"), index("c")).is_err());
+        assert!(killing_yano_equation(&k("a", "b"), index("c")).is_err());
+    }
+
+    #[test]
+    fn test_trace_self_contractions() {
+        let mixed = |names: [&str; 4], upper: usize| -> Vec<TensorIndex> {
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| {
+                    if i == upper {
+                        TensorIndex::contravariant(n, i)
+                    } else {
+                        TensorIndex::new(n, i)
+                    }
+                })
+                .collect()
+        };
+        let ricci_bc = |coefficient: i32| {
+            let mut expected =
+                ricci(vec![TensorIndex::new("b", 0), TensorIndex::new("c", 1)]).unwrap();
+            expected.set_coefficient(coefficient);
+            expected
+        };
+        for (names, upper, coefficient) in [
+            (["a", "b", "a", "c"], 0, 1),
+            (["b", "a", "c", "a"], 1, 1),
+            (["a", "b", "c", "a"], 0, -1),
+            (["b", "a", "a", "c"], 2, -1),
+        ] {
+            let r = riemann(mixed(names, upper)).unwrap();
+            assert_eq!(trace_self_contractions(&r).unwrap(), ricci_bc(coefficient));
+        }
+
+        // Traces inside an antisymmetric pair, and of the Weyl tensor, vanish
+        let r = riemann(mixed(["a", "a", "b", "c"], 0)).unwrap();
+        assert_eq!(trace_self_contractions(&r).unwrap().coefficient(), 0);
+        let c = weyl(mixed(["a", "b", "a", "c"], 0)).unwrap();
+        assert_eq!(trace_self_contractions(&c).unwrap().coefficient(), 0);
+
+        // R^{ab}_{ba} = -R
+        let mut r = riemann(mixed(["a", "b", "b", "a"], 0)).unwrap();
+        r.indices_mut()[1].set_contravariant(true);
+        r.set_coefficient(3);
+        let scalar = trace_self_contractions(&r).unwrap();
+        assert_eq!(scalar, Tensor::with_coefficient("R", Vec::new(), -3));
+
+        // A totally symmetric tensor stays totally symmetric
+        let k = killing_tensor(mixed(["a", "b", "a", "c"], 2)).unwrap();
+        let traced = trace_self_contractions(&k).unwrap();
+        assert_eq!(traced.to_string(), "K__b _c");
+        assert_eq!(traced.symmetries(), [Symmetry::symmetric(vec![0, 1])]);
+
+        // Other contractions are left in place
+        let t = Tensor::new(
+            "T",
+            vec![TensorIndex::contravariant("a", 0), TensorIndex::new("a", 1)],
+        );
+        assert_eq!(trace_self_contractions(&t).unwrap(), t);
+    }
+}
@@ -3,12 +3,72 @@
 //! Implements the Schreier-Sims algorithm for efficiently computing
 //! a base and strong generating set (BSGS) for a permutation group, as well as
 //! the so-called "sift algorithm" for group membership testing and order computation.
+//!
+//! The module works on permutations in array form and does not depend on
+//! [`Tensor`](crate::Tensor), so it can be used for group computations of
+//! any kind:
+//!
+//! * [`schreier_sims`] builds a [`BSGS`] from generators, and
+//!   [`random_schreier_sims`] does so by sifting random elements, which
+//!   scales to large degrees; both first thin out redundant generators
+//!   with [`reduce_generators`];
+//! * [`orbit`] and [`stabilizer`] compute the orbit of a point and a BSGS
+//!   of its stabilizer;
+//! * [`sift`] strips an element through the stabilizer chain and
+//!   [`is_member`] tests membership with it, also available as
+//!   [`BSGS::sift`] and [`BSGS::contains`];
+//! * [`BSGS::order`] and [`BSGS::transversals`] read off the group order and
+//!   the basic orbits, [`BSGS::elements`] streams the elements,
+//!   [`BSGS::change_base`] re-points the chain to another base, and
+//!   [`BSGS::verify`] checks BSGS data from elsewhere;
+//! * [`signed_schreier_sims`] builds a [`SignedBSGS`] of signed permutations,
+//!   whose elements carry the exact sign of every product of generators;
+//! * [`compose_permutations`] and [`inverse_permutation`] do the arithmetic.
+//!
+//! A permutation `p` of degree `n` is a [`Permutation`] holding the image
+//! `p[i]` of every point `i < n`. Composition applies the first argument
+//! first: `compose_permutations(p, q)[i] = q[p[i]]`.
+//!
+//! # Example
+//! ```rust
+//! use butler_portugal::schreier_sims::{
+//!     compose_permutations, is_member, schreier_sims, sift, Permutation,
+//! };
+//!
+//! // The dihedral group of the square, generated by a rotation and a reflection
+//! let rotation: Permutation = "(0 1 2 3)".parse()?;
+//! let reflection: Permutation = "(1 3)".parse()?;
+//! let bsgs = schreier_sims(&[rotation.clone(), reflection.clone()], 4);
+//! assert_eq!(bsgs.order(), 8);
+//!
+//! assert!(is_member(&compose_permutations(&rotation, &reflection), &bsgs));
+//! // A transposition of neighbouring corners is not a symmetry of the square
+//! let sifted = sift(&[1, 0, 2, 3], &bsgs);
+//! assert_ne!(sifted, (Permutation::identity(4), bsgs.base.len()));
+//! assert!(!is_member(&vec![1, 0, 2, 3].into(), &bsgs));
+//! # Ok::<(), butler_portugal::ButlerPortugalError>(())
+//! ```
 
-use crate::canonicalization::{Permutation, BSGS};
-use std::collections::{HashSet, VecDeque};
+pub use crate::canonicalization::{Permutation, BSGS};
+use crate::error::{validate_permutation, ButlerPortugalError, Result};
+use crate::permutation::SignedPermutation;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 
 /// Computes the orbit of a point under a set of generators
-fn orbit(point: usize, generators: &[Permutation]) -> HashSet<usize> {
+///
+/// Points beyond the degree of a generator are fixed by it.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::orbit;
+///
+/// // (0 1) and (2 3) never mix the two pairs
+/// let generators = [vec![1, 0, 2, 3].into(), vec![0, 1, 3, 2].into()];
+/// assert_eq!(orbit(0, &generators).len(), 2);
+/// assert!(orbit(2, &generators).contains(&3));
+/// ```
+pub fn orbit(point: usize, generators: &[Permutation]) -> HashSet<usize> {
     let mut orbit = HashSet::new();
     let mut queue = VecDeque::new();
     orbit.insert(point);
@@ -16,7 +76,7 @@ fn orbit(point: usize, generators: &[Permutation]) -> HashSet<usize> {
 
     while let Some(x) = queue.pop_front() {
         for g in generators {
-            let y = g[x];
+            let y = g.get(x).copied().unwrap_or(x);
             if orbit.insert(y) {
                 queue.push_back(y);
             }
@@ -25,54 +85,95 @@ fn orbit(point: usize, generators: &[Permutation]) -> HashSet<usize> {
     orbit
 }
 
+/// Computes a BSGS of the stabilizer of `point` in the group given by `bsgs`
+///
+/// The stabilizer is the subgroup of elements fixing `point`. Its order is
+/// the group order divided by the length of the orbit of `point`.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::{is_member, schreier_sims, stabilizer};
+///
+/// // S_4 fixing the point 3 is S_3
+/// let bsgs = schreier_sims(&[vec![1, 0, 2, 3].into(), vec![1, 2, 3, 0].into()], 4);
+/// let fixing = stabilizer(3, &bsgs);
+/// assert_eq!(fixing.order(), 6);
+/// assert!(is_member(&vec![2, 0, 1, 3].into(), &fixing));
+/// assert!(!is_member(&vec![0, 1, 3, 2].into(), &fixing));
+/// ```
+pub fn stabilizer(point: usize, bsgs: &BSGS) -> BSGS {
+    let degree = bsgs.generators.first().map_or(0, Permutation::degree);
+    if point >= degree || bsgs.base.is_empty() {
+        return bsgs.clone();
+    }
+    // A base starting at the point has the stabilizer as its first subgroup
+    let rebased = bsgs.rebase(&[point]);
+    let base: Vec<usize> = rebased.base.iter().copied().skip(1).collect();
+    let generators = stabilizer_generators(&rebased.base, &rebased.generators, 1);
+    if base.is_empty() || generators.is_empty() {
+        return BSGS::identity(degree);
+    }
+    BSGS { base, generators }
+}
+
 /// Schreier-Sims algorithm: computes a BSGS for the group generated by `generators`
+///
+/// The returned strong generating set has the defining property that, for every
+/// level `i`, the strong generators fixing `base[0..i]` pointwise generate the
+/// full pointwise stabilizer of those base points. Trivial groups are represented
+/// by an empty base and a single identity generator.
+///
+/// Generators of a length other than `degree` and identity generators are
+/// ignored.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::schreier_sims;
+///
+/// // S_3, generated by a transposition and a 3-cycle
+/// let bsgs = schreier_sims(&[vec![1, 0, 2].into(), vec![1, 2, 0].into()], 3);
+/// assert_eq!(bsgs.order(), 6);
+/// assert!(bsgs.verify().is_ok());
+/// ```
 pub fn schreier_sims(generators: &[Permutation], degree: usize) -> BSGS {
-    let mut base = Vec::new();
-    let mut strong_gens = generators.to_vec();
-    let mut used_points = HashSet::new();
-
-    // Find a base: pick points moved by the generators
-    for i in 0..degree {
-        if strong_gens.iter().any(|g| g[i] != i) {
-            base.push(i);
-            used_points.insert(i);
-        }
-    }
-    if base.is_empty() {
-        // Trivial group
-        return BSGS {
-            base: vec![],
-            generators: vec![(0..degree).collect()],
-        };
+    schreier_sims_with_base(generators, degree, &[])
+}
+
+/// Schreier-Sims algorithm with a base starting with the distinct points `prefix`
+///
+/// Further base points are chosen as by [`schreier_sims`]. Trivial groups
+/// still get an empty base.
+pub(crate) fn schreier_sims_with_base(
+    generators: &[Permutation],
+    degree: usize,
+    prefix: &[usize],
+) -> BSGS {
+    if degree >= RANDOMIZED_DEGREE {
+        return random_schreier_sims_with_base(
+            generators,
+            degree,
+            prefix,
+            &RandomSchreierSims::default(),
+        );
+    }
+    let mut strong_gens = reduce_generators(generators, degree);
+    if strong_gens.is_empty() {
+        return BSGS::identity(degree);
     }
+    let ranking = base_point_ranking(&strong_gens, degree);
+    let mut base = initial_base(&strong_gens, &ranking, prefix);
 
-    // Main Schreier-Sims loop
-    let mut i = 0;
-    while i < base.len() {
-        let b = base[i];
-        let orbit_set = orbit(b, &strong_gens);
-        let mut schreier_gens = Vec::new();
-
-        for &alpha in &orbit_set {
-            for g in &strong_gens {
-                let beta = g[alpha];
-                if !orbit_set.contains(&beta) {
-                    // Schreier generator
-                    let mut h = (0..degree).collect::<Vec<_>>();
-                    let tmp = 0..degree;
-                    for j in tmp {
-                        h[j] = g[j];
-                    }
-                    schreier_gens.push(h);
-                }
+    // Main Schreier-Sims loop, working from the deepest level upwards
+    let mut level = base.len();
+    while level > 0 {
+        let i = level - 1;
+        match find_non_sifting_schreier_generator(&base, &strong_gens, i) {
+            Some((residue, depth)) => {
+                level = depth + 1;
+                add_strong_generator(&mut base, &mut strong_gens, &ranking, residue, depth);
             }
+            None => level -= 1,
         }
-        for h in schreier_gens {
-            if !strong_gens.contains(&h) {
-                strong_gens.push(h);
-            }
-        }
-        i += 1;
     }
 
     BSGS {
@@ -81,94 +182,1209 @@ pub fn schreier_sims(generators: &[Permutation], degree: usize) -> BSGS {
     }
 }
 
-/// Sift algorithm for membership testing in a permutation group given by BSGS
-/// Returns true if perm is in the group, false otherwise
-pub fn is_member(perm: &Permutation, bsgs: &BSGS) -> bool {
-    let n = perm.len();
-    let mut h = perm.clone();
-    let id: Permutation = (0..n).collect();
-    let base = &bsgs.base;
-    let strong_gens = &bsgs.generators;
-
-    // For each base point, try to reduce h by right multiplying by a transversal
-    for &beta in base {
-        // Compute the orbit and Schreier tree for this base point
-        let (orbit, schreier_tree) = schreier_orbit_tree(beta, strong_gens, n);
-        let gamma = h[beta];
-        if !orbit.contains(&gamma) {
-            return false; // Not in the orbit, so not in the group
-        }
-        // Find the word (as a permutation) that maps beta to gamma in the Schreier tree
-        let u_gamma = schreier_coset_representative(beta, gamma, &schreier_tree);
-        // Right multiply h by the inverse of u_gamma
-        let u_gamma_inv = inverse_permutation(&u_gamma);
-        h = compose_permutations(&h, &u_gamma_inv);
-    }
-    h == id
-}
-
-/// Compute the orbit of a point and the Schreier tree (parent map)
-fn schreier_orbit_tree(
-    point: usize,
+/// Returns the generators of the given degree without identities and repeats
+fn distinct_generators(generators: &[Permutation], degree: usize) -> Vec<Permutation> {
+    let mut strong_gens: Vec<Permutation> = Vec::new();
+    for g in generators {
+        if g.len() == degree && !is_identity(g) && !strong_gens.contains(g) {
+            strong_gens.push(g.clone());
+        }
+    }
+    strong_gens
+}
+
+/// Reduces generators to at most `degree - 1` generators of the same group
+///
+/// Generators produced from tensor symmetries are highly redundant, with
+/// every adjacent transposition of every symmetric group of slots listed.
+/// Jerrum's filter keeps the generators as the edges of a forest on the
+/// points, joining the lowest point a generator moves to its image. A
+/// generator closing a cycle is traded for the product around the cycle,
+/// which fixes the lowest point of the cycle and is filtered in turn, so a
+/// forest, and with it at most `degree - 1` generators, remains.
+/// [`schreier_sims`] starts from the reduced generators.
+///
+/// Generators of a length other than `degree` and identity generators are
+/// dropped.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::{reduce_generators, schreier_sims, Permutation};
+///
+/// // S_4 from all six transpositions
+/// let mut transpositions = Vec::new();
+/// for i in 0..4 {
+///     for j in i + 1..4 {
+///         transpositions.push(Permutation::from_cycles(&[vec![i, j]], 4)?);
+///     }
+/// }
+/// let reduced = reduce_generators(&transpositions, 4);
+/// assert!(reduced.len() <= 3);
+/// assert_eq!(schreier_sims(&reduced, 4).order(), 24);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn reduce_generators(generators: &[Permutation], degree: usize) -> Vec<Permutation> {
+    let mut forest: Vec<Permutation> = Vec::new();
+    for generator in distinct_generators(generators, degree) {
+        let mut pending = Some(generator);
+        while let Some(element) = pending.take() {
+            let Some(low) = lowest_moved_point(&element) else {
+                continue;
+            };
+            let high = element[low];
+            let Some(path) = forest_path(&forest, high, low) else {
+                forest.push(element);
+                continue;
+            };
+            // The cycle low -> high -> ... -> low, as (edge, from, to) steps
+            // with `None` standing for the new element
+            let mut steps = vec![(None, low, high)];
+            let mut from = high;
+            for (edge, to) in path {
+                steps.push((Some(edge), from, to));
+                from = to;
+            }
+            let start = (0..steps.len())
+                .min_by_key(|&k| steps[k].1)
+                .unwrap_or_default();
+            let mut product = Permutation::identity(degree);
+            for k in 0..steps.len() {
+                let (edge, from, to) = steps[(start + k) % steps.len()];
+                let label = edge.map_or(&element, |e| &forest[e]);
+                product = if label[from] == to {
+                    product.compose(label)
+                } else {
+                    product.compose(&label.inverse())
+                };
+            }
+            // The product around the cycle replaces the edge leaving its
+            // lowest point
+            if let Some(edge) = steps[start].0 {
+                forest.remove(edge);
+                forest.push(element);
+            }
+            pending = Some(product);
+        }
+    }
+    forest
+}
+
+/// Returns the lowest point moved by a permutation
+fn lowest_moved_point(perm: &[usize]) -> Option<usize> {
+    (0..perm.len()).find(|&p| perm[p] != p)
+}
+
+/// Finds the path from `from` to `to` in the forest whose edges join the
+/// lowest point moved by each generator to its image, as (edge, next point)
+/// steps
+fn forest_path(forest: &[Permutation], from: usize, to: usize) -> Option<Vec<(usize, usize)>> {
+    let mut reached: HashMap<usize, (usize, usize)> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(point) = queue.pop_front() {
+        if point == to {
+            let mut path = Vec::new();
+            let mut current = to;
+            while current != from {
+                let (edge, previous) = reached[&current];
+                path.push((edge, current));
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for (edge, generator) in forest.iter().enumerate() {
+            let Some(low) = lowest_moved_point(generator) else {
+                continue;
+            };
+            let next = match (low == point, generator[low] == point) {
+                (true, _) => generator[low],
+                (_, true) => low,
+                _ => continue,
+            };
+            if next != from && !reached.contains_key(&next) {
+                reached.insert(next, (edge, point));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Starts a base with `prefix` and greedily covers every strong generator
+/// with a base point
+fn initial_base(strong_gens: &[Permutation], ranking: &[usize], prefix: &[usize]) -> Vec<usize> {
+    let mut base = prefix.to_vec();
+    loop {
+        let uncovered: Vec<&Permutation> = strong_gens
+            .iter()
+            .filter(|g| base.iter().all(|&b: &usize| g[b] == b))
+            .collect();
+        if uncovered.is_empty() {
+            break;
+        }
+        let best = ranking
+            .iter()
+            .copied()
+            .filter(|p| !base.contains(p))
+            .max_by_key(|&p| {
+                let moved_by = uncovered.iter().filter(|g| g[p] != p).count();
+                (moved_by, std::cmp::Reverse(rank_of(ranking, p)))
+            });
+        match best {
+            Some(point) => base.push(point),
+            None => break,
+        }
+    }
+    base
+}
+
+/// Adds the residue of an element that did not sift, which stopped at level
+/// `depth`, to the strong generators
+fn add_strong_generator(
+    base: &mut Vec<usize>,
+    strong_gens: &mut Vec<Permutation>,
+    ranking: &[usize],
+    residue: Permutation,
+    depth: usize,
+) {
+    if depth == base.len() {
+        // The residue fixes every base point, so the base must grow
+        if let Some(point) = ranking.iter().copied().find(|&p| residue[p] != p) {
+            base.push(point);
+        }
+    }
+    strong_gens.push(residue);
+}
+
+/// Degree from which [`schreier_sims`] builds the BSGS with
+/// [`random_schreier_sims`] and its default options
+///
+/// The verification pass keeps the result exact, so only the running time
+/// and the choice of strong generators depend on the algorithm used.
+pub const RANDOMIZED_DEGREE: usize = 30;
+
+/// Options for [`random_schreier_sims`]
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::RandomSchreierSims;
+///
+/// let options = RandomSchreierSims::new()
+///     .with_error_probability(1e-9)
+///     .with_seed(7)
+///     .with_verification(false);
+/// assert_eq!(options.seed, 7);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomSchreierSims {
+    /// The largest acceptable probability that the stabilizer chain is
+    /// incomplete before verification
+    pub error_probability: f64,
+    /// The seed of the random group elements; a fixed seed gives the same
+    /// BSGS on every run
+    pub seed: u64,
+    /// Whether the result is checked with [`BSGS::verify`] and completed
+    /// until it passes, which makes it exact
+    pub verify: bool,
+}
+
+impl Default for RandomSchreierSims {
+    fn default() -> Self {
+        Self {
+            error_probability: 1e-6,
+            seed: 0x5eed_5eed_5eed_5eed,
+            verify: true,
+        }
+    }
+}
+
+impl RandomSchreierSims {
+    /// Creates the default options: error probability `1e-6`, a fixed seed
+    /// and verification
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the options with the given error probability
+    pub fn with_error_probability(mut self, error_probability: f64) -> Self {
+        self.error_probability = error_probability;
+        self
+    }
+
+    /// Returns the options with the given seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns the options with the verification pass enabled or disabled
+    pub fn with_verification(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+}
+
+/// Randomized (Monte Carlo) Schreier-Sims algorithm
+///
+/// Instead of sifting every Schreier generator, random group elements are
+/// sifted through the partial stabilizer chain and the residues of those
+/// that fail are added as strong generators. An incomplete chain lets at
+/// most half of the group sift through, so the construction stops once
+/// `⌈log₂(1/ε)⌉` elements in a row have sifted, `ε` being the error
+/// probability. Random elements come from the product replacement
+/// algorithm, seeded for reproducibility.
+///
+/// With `verify` set the chain is then checked deterministically as by
+/// [`BSGS::verify`], and the residue of every Schreier generator that fails
+/// is added until none does, so the result is always exact; the random
+/// phase leaves little for this pass to do. Without it the result is a
+/// genuine BSGS with probability at least `1 - ε`, and its order is at
+/// worst too small.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::{random_schreier_sims, RandomSchreierSims};
+///
+/// // S_32 from a transposition and a 32-cycle
+/// let swap = (0..32).map(|i| [1, 0].get(i).copied().unwrap_or(i)).collect();
+/// let cycle = (0..32).map(|i| (i + 1) % 32).collect();
+/// let bsgs = random_schreier_sims(&[swap, cycle], 32, &RandomSchreierSims::new());
+/// assert_eq!(bsgs.order(), (1..=32u128).product());
+/// ```
+pub fn random_schreier_sims(
     generators: &[Permutation],
     degree: usize,
-) -> (Vec<usize>, Vec<Option<(usize, Permutation)>>) {
-    let mut orbit = Vec::new();
-    let mut schreier_tree = vec![None; degree];
-    let mut visited = vec![false; degree];
-    let mut queue = VecDeque::new();
-    orbit.push(point);
-    visited[point] = true;
-    queue.push_back(point);
-    while let Some(x) = queue.pop_front() {
-        for g in generators {
-            let y = g[x];
-            if !visited[y] {
-                visited[y] = true;
-                orbit.push(y);
-                // Store parent and generator used
-                schreier_tree[y] = Some((x, g.clone()));
-                queue.push_back(y);
+    options: &RandomSchreierSims,
+) -> BSGS {
+    random_schreier_sims_with_base(generators, degree, &[], options)
+}
+
+/// Randomized Schreier-Sims algorithm with a base starting with `prefix`
+fn random_schreier_sims_with_base(
+    generators: &[Permutation],
+    degree: usize,
+    prefix: &[usize],
+    options: &RandomSchreierSims,
+) -> BSGS {
+    let mut strong_gens = reduce_generators(generators, degree);
+    if strong_gens.is_empty() {
+        return BSGS::identity(degree);
+    }
+    let ranking = base_point_ranking(&strong_gens, degree);
+    let mut base = initial_base(&strong_gens, &ranking, prefix);
+
+    let probability = options.error_probability.clamp(f64::MIN_POSITIVE, 0.5);
+    let rounds = ((-probability.log2()).ceil() as usize).max(1);
+    let mut elements = ProductReplacement::new(&strong_gens, degree, options.seed);
+    let mut transversals = level_transversals(&base, &strong_gens, 0, degree);
+    let mut sifted = 0;
+    while sifted < rounds {
+        let element = elements.next_element();
+        let (residue, depth) = sift_through(&element, &base, &transversals, 0);
+        if depth == base.len() && is_identity(&residue) {
+            sifted += 1;
+        } else {
+            add_strong_generator(&mut base, &mut strong_gens, &ranking, residue, depth);
+            transversals = level_transversals(&base, &strong_gens, 0, degree);
+            sifted = 0;
+        }
+    }
+
+    let mut bsgs = BSGS {
+        base,
+        generators: strong_gens,
+    };
+    if options.verify {
+        while let Err(BsgsDefect::NonSiftingSchreierGenerator {
+            sift_level,
+            residue,
+            ..
+        }) = bsgs.verify()
+        {
+            add_strong_generator(
+                &mut bsgs.base,
+                &mut bsgs.generators,
+                &ranking,
+                residue,
+                sift_level,
+            );
+        }
+    }
+    bsgs
+}
+
+/// Random group elements by the product replacement algorithm
+///
+/// A list of at least ten group elements, starting with the generators, is
+/// repeatedly updated by multiplying one entry by another or its inverse;
+/// the elements returned are a running product of the updated entries.
+struct ProductReplacement {
+    slots: Vec<Permutation>,
+    accumulator: Permutation,
+    rng: SplitMix64,
+}
+
+impl ProductReplacement {
+    /// Number of products discarded before the elements are used
+    const WARM_UP: usize = 50;
+
+    /// Starts from non-empty generators of the group
+    fn new(generators: &[Permutation], degree: usize, seed: u64) -> Self {
+        let mut elements = Self {
+            slots: generators
+                .iter()
+                .cycle()
+                .take(generators.len().max(10))
+                .cloned()
+                .collect(),
+            accumulator: Permutation::identity(degree),
+            rng: SplitMix64(seed),
+        };
+        for _ in 0..Self::WARM_UP {
+            elements.next_element();
+        }
+        elements
+    }
+
+    fn next_element(&mut self) -> Permutation {
+        let n = self.slots.len();
+        let i = self.rng.below(n);
+        let j = (i + 1 + self.rng.below(n - 1)) % n;
+        let bits = self.rng.next();
+        let factor = if bits & 1 == 0 {
+            self.slots[j].clone()
+        } else {
+            self.slots[j].inverse()
+        };
+        self.slots[i] = if bits & 2 == 0 {
+            self.slots[i].compose(&factor)
+        } else {
+            factor.compose(&self.slots[i])
+        };
+        self.accumulator = self.accumulator.compose(&self.slots[i]);
+        self.accumulator.clone()
+    }
+}
+
+/// The SplitMix64 generator, enough to drive product replacement without a
+/// dependency
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number below `n`, which must be positive
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Orders the points of the domain by their suitability as base points
+///
+/// Points with large orbits come first, since fixing them early shrinks the
+/// stabilizers fastest; ties are broken by the number of generators moving the
+/// point and finally by the point itself, so the result is deterministic.
+fn base_point_ranking(generators: &[Permutation], degree: usize) -> Vec<usize> {
+    let mut orbit_size = vec![0; degree];
+    for point in 0..degree {
+        if orbit_size[point] == 0 {
+            let orb = orbit(point, generators);
+            for &q in &orb {
+                orbit_size[q] = orb.len();
             }
         }
     }
-    (orbit, schreier_tree)
+    let mut points: Vec<usize> = (0..degree).filter(|&p| orbit_size[p] > 1).collect();
+    points.sort_by_key(|&p| {
+        let moved_by = generators.iter().filter(|g| g[p] != p).count();
+        (
+            std::cmp::Reverse(orbit_size[p]),
+            std::cmp::Reverse(moved_by),
+            p,
+        )
+    });
+    points
 }
 
-/// Recover the coset representative (as a permutation) from Schreier tree
-fn schreier_coset_representative(
+/// Returns the position of `point` in a base point ranking
+fn rank_of(ranking: &[usize], point: usize) -> usize {
+    ranking
+        .iter()
+        .position(|&p| p == point)
+        .unwrap_or(ranking.len())
+}
+
+/// Searches level `i` of the stabilizer chain for a Schreier generator that does
+/// not sift through the deeper levels. Returns the residue and the level at which
+/// sifting stopped.
+fn find_non_sifting_schreier_generator(
+    base: &[usize],
+    strong_gens: &[Permutation],
+    i: usize,
+) -> Option<(Permutation, usize)> {
+    let level_gens = stabilizer_generators(base, strong_gens, i);
+    let degree = strong_gens.first().map_or(0, Permutation::degree);
+    let transversal = SchreierVector::new(base[i], &level_gens, degree);
+    let deeper = level_transversals(base, strong_gens, i + 1, degree);
+
+    for &beta in transversal.orbit() {
+        let Some(u_beta) = transversal.representative(beta) else {
+            continue;
+        };
+        for s in &level_gens {
+            let Some(u_image) = transversal.representative(s[beta]) else {
+                continue;
+            };
+            let h = compose_permutations(
+                &compose_permutations(&u_beta, s),
+                &inverse_permutation(&u_image),
+            );
+            if is_identity(&h) {
+                continue;
+            }
+            let (residue, depth) = sift_through(&h, base, &deeper, i + 1);
+            if depth < base.len() || !is_identity(&residue) {
+                return Some((residue, depth));
+            }
+        }
+    }
+    None
+}
+
+/// Sifts `perm` through the stabilizer chain starting at `start_level`.
+///
+/// Returns the residue together with the level at which sifting stopped
+/// (`base.len()` if it passed through every level).
+fn strip(
+    perm: &[usize],
+    base: &[usize],
+    strong_gens: &[Permutation],
+    start_level: usize,
+) -> (Permutation, usize) {
+    let transversals = level_transversals(base, strong_gens, start_level, perm.len());
+    sift_through(perm, base, &transversals, start_level)
+}
+
+/// Returns the transversals of the levels from `start_level` on
+fn level_transversals(
+    base: &[usize],
+    strong_gens: &[Permutation],
+    start_level: usize,
+    degree: usize,
+) -> Vec<SchreierVector> {
+    (start_level..base.len())
+        .map(|j| {
+            SchreierVector::new(
+                base[j],
+                &stabilizer_generators(base, strong_gens, j),
+                degree,
+            )
+        })
+        .collect()
+}
+
+/// Sifts `perm` through the transversals of the levels from `start_level`
+/// on, as [`strip`] does, so that several elements share the transversals
+fn sift_through(
+    perm: &[usize],
+    base: &[usize],
+    transversals: &[SchreierVector],
+    start_level: usize,
+) -> (Permutation, usize) {
+    let mut h = Permutation::from(perm);
+    for (k, transversal) in transversals.iter().enumerate() {
+        match transversal.representative(h[transversal.root()]) {
+            Some(u) => h = compose_permutations(&h, &inverse_permutation(&u)),
+            None => return (h, start_level + k),
+        }
+    }
+    (h, base.len())
+}
+
+/// Returns the strong generators fixing the first `level` base points pointwise
+fn stabilizer_generators(
+    base: &[usize],
+    strong_gens: &[Permutation],
+    level: usize,
+) -> Vec<Permutation> {
+    strong_gens
+        .iter()
+        .filter(|g| base[..level].iter().all(|&b| g[b] == b))
+        .cloned()
+        .collect()
+}
+
+/// Implicit transversal of a basic orbit, stored as a Schreier vector
+///
+/// Instead of keeping one full permutation per orbit point (which costs
+/// `|orbit| * degree` memory), only the generators and, for every orbit point,
+/// the index of the generator that first reached it are stored. Coset
+/// representatives are reconstructed on demand by walking back to the root.
+#[derive(Debug, Clone)]
+pub struct SchreierVector {
     root: usize,
-    target: usize,
-    schreier_tree: &[Option<(usize, Permutation)>],
-) -> Permutation {
-    let mut path = Vec::new();
-    let mut current = target;
-    while current != root {
-        if let Some((parent, gen)) = &schreier_tree[current] {
-            path.push(gen.clone());
-            current = *parent;
-        } else {
-            break; // Should not happen if target is in the orbit
+    generators: Vec<Permutation>,
+    inverses: Vec<Permutation>,
+    /// `Some(usize::MAX)` marks the root, `Some(k)` means "reached via generator k"
+    vector: Vec<Option<usize>>,
+    orbit: Vec<usize>,
+}
+
+impl SchreierVector {
+    /// Builds the Schreier vector for the orbit of `root` under `generators`
+    pub fn new(root: usize, generators: &[Permutation], degree: usize) -> Self {
+        let mut vector = vec![None; degree.max(root + 1)];
+        let mut orbit = vec![root];
+        vector[root] = Some(usize::MAX);
+        let mut queue = VecDeque::from([root]);
+        while let Some(x) = queue.pop_front() {
+            for (k, g) in generators.iter().enumerate() {
+                let y = g[x];
+                if vector[y].is_none() {
+                    vector[y] = Some(k);
+                    orbit.push(y);
+                    queue.push_back(y);
+                }
+            }
+        }
+        Self {
+            root,
+            generators: generators.to_vec(),
+            inverses: generators.iter().map(|g| inverse_permutation(g)).collect(),
+            vector,
+            orbit,
         }
     }
-    // Compose the path in reverse order
-    let mut rep: Permutation = (0..schreier_tree.len()).collect();
-    for g in path.iter().rev() {
-        rep = compose_permutations(&rep, g);
+
+    /// Returns the root point of the orbit
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Returns the orbit points in discovery order
+    pub fn orbit(&self) -> &[usize] {
+        &self.orbit
+    }
+
+    /// Returns true if `point` lies in the orbit
+    pub fn contains(&self, point: usize) -> bool {
+        self.vector.get(point).is_some_and(Option::is_some)
+    }
+
+    /// Reconstructs a coset representative mapping the root to `point`
+    ///
+    /// Returns `None` if `point` is not in the orbit.
+    pub fn representative(&self, point: usize) -> Option<Permutation> {
+        let mut path = Vec::new();
+        let mut current = point;
+        loop {
+            match *self.vector.get(current)? {
+                Some(usize::MAX) => break,
+                Some(k) => {
+                    path.push(k);
+                    current = self.inverses[k][current];
+                }
+                None => return None,
+            }
+        }
+        let mut rep: Permutation = (0..self.vector.len()).collect();
+        for &k in path.iter().rev() {
+            rep = compose_permutations(&rep, &self.generators[k]);
+        }
+        Some(rep)
     }
-    rep
 }
 
-/// Compute the inverse of a permutation
-fn inverse_permutation(perm: &Permutation) -> Permutation {
+/// Checks if a permutation is the identity
+fn is_identity(perm: &[usize]) -> bool {
+    perm.iter().enumerate().all(|(i, &p)| i == p)
+}
+
+impl BSGS {
+    /// Returns the order of the group described by this BSGS
+    ///
+    /// The order is the product of the basic orbit lengths along the stabilizer
+    /// chain, so it is exact and never enumerates group elements. The result
+    /// saturates at `u128::MAX` for astronomically large groups.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::schreier_sims::schreier_sims;
+    ///
+    /// // S_3 acting on three slots
+    /// let bsgs = schreier_sims(&[vec![1, 0, 2].into(), vec![1, 2, 0].into()], 3);
+    /// assert_eq!(bsgs.order(), 6);
+    /// ```
+    pub fn order(&self) -> u128 {
+        self.base
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                let level_gens = stabilizer_generators(&self.base, &self.generators, i);
+                orbit(b, &level_gens).len() as u128
+            })
+            .fold(1u128, u128::saturating_mul)
+    }
+
+    /// Returns the implicit transversals (one Schreier vector per base point)
+    ///
+    /// Level `i` describes the orbit of `base[i]` under the pointwise stabilizer
+    /// of `base[0..i]`. Representatives are reconstructed lazily, which keeps
+    /// memory proportional to the number of strong generators rather than to
+    /// the orbit sizes.
+    pub fn transversals(&self) -> Vec<SchreierVector> {
+        let degree = self.generators.first().map_or(0, Permutation::degree);
+        self.base
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                let level_gens = stabilizer_generators(&self.base, &self.generators, i);
+                SchreierVector::new(b, &level_gens, degree)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over every element of the group
+    ///
+    /// Elements are products `u_{k-1} ⋯ u_1 u_0` of one coset representative
+    /// per level, `u_{k-1}` applied first, and are produced in the order of an
+    /// odometer over the basic orbits, starting with the identity. Only the
+    /// representatives and one partial product per level are stored, so the
+    /// memory used is independent of the group order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::schreier_sims::schreier_sims;
+    ///
+    /// let bsgs = schreier_sims(&[vec![1, 2, 3, 0].into(), vec![0, 3, 2, 1].into()], 4);
+    /// let elements: Vec<_> = bsgs.elements().collect();
+    /// assert_eq!(elements.len(), 8);
+    /// assert_eq!(elements[0], vec![0, 1, 2, 3]);
+    /// ```
+    pub fn elements(&self) -> GroupElements {
+        let degree = self.generators.first().map_or(0, Permutation::degree);
+        let representatives: Vec<Vec<Permutation>> = self
+            .transversals()
+            .iter()
+            .map(|level| {
+                level
+                    .orbit()
+                    .iter()
+                    .filter_map(|&point| level.representative(point))
+                    .collect()
+            })
+            .collect();
+        let mut elements = GroupElements {
+            identity: (0..degree).collect(),
+            counters: vec![0; representatives.len()],
+            partial: Vec::with_capacity(representatives.len()),
+            representatives,
+            done: false,
+        };
+        elements.refresh_from(0);
+        elements
+    }
+
+    /// Returns a BSGS of the same group whose base starts with `new_base`
+    ///
+    /// The new stabilizer chain is filled by sifting random elements of the
+    /// group until the product of its basic orbit lengths reaches the known
+    /// order, which is far cheaper than running Schreier-Sims again and
+    /// still exact. Further base points are appended if `new_base` is not a
+    /// base on its own, and the trivial group keeps an empty base. `self`
+    /// must be a genuine BSGS, see [`verify`](Self::verify).
+    ///
+    /// # Errors
+    /// Returns an `InvalidPermutation` error if a point of `new_base` is out
+    /// of range or repeated.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::schreier_sims::schreier_sims;
+    ///
+    /// // The Riemann slot group, re-pointed to fix the last slots first
+    /// let bsgs = schreier_sims(&[vec![1, 0, 2, 3].into(), vec![2, 3, 0, 1].into()], 4);
+    /// let rebased = bsgs.change_base(&[3, 2])?;
+    /// assert!(rebased.base.starts_with(&[3, 2]));
+    /// assert_eq!(rebased.order(), bsgs.order());
+    /// assert!(rebased.verify().is_ok());
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn change_base(&self, new_base: &[usize]) -> Result<BSGS> {
+        let degree = self.generators.first().map_or(0, Permutation::degree);
+        let mut seen = HashSet::new();
+        if let Some(point) = new_base
+            .iter()
+            .find(|&&point| point >= degree || !seen.insert(point))
+        {
+            return Err(ButlerPortugalError::InvalidPermutation(format!(
+                "Base point {point} is out of range or repeated for degree {degree}"
+            )));
+        }
+        Ok(self.rebase(new_base))
+    }
+
+    /// Changes the base as [`change_base`](Self::change_base) does, for
+    /// distinct points below the degree
+    pub(crate) fn rebase(&self, prefix: &[usize]) -> BSGS {
+        let degree = self.generators.first().map_or(0, Permutation::degree);
+        if self.base.starts_with(prefix) {
+            return self.clone();
+        }
+        let strong_gens = reduce_generators(&self.generators, degree);
+        if strong_gens.is_empty() {
+            return BSGS::identity(degree);
+        }
+        let order = self.order();
+        if order == u128::MAX {
+            // A saturated order cannot tell when the chain is complete
+            return schreier_sims_with_base(&strong_gens, degree, prefix);
+        }
+        let ranking = base_point_ranking(&strong_gens, degree);
+        let mut base = initial_base(&strong_gens, &ranking, prefix);
+        let mut generators = strong_gens.clone();
+        let mut transversals = level_transversals(&base, &generators, 0, degree);
+        let mut elements =
+            ProductReplacement::new(&strong_gens, degree, RandomSchreierSims::default().seed);
+        let chain_order = |transversals: &[SchreierVector]| {
+            transversals
+                .iter()
+                .map(|level| level.orbit().len() as u128)
+                .fold(1u128, u128::saturating_mul)
+        };
+        while chain_order(&transversals) < order {
+            let element = elements.next_element();
+            let (residue, depth) = sift_through(&element, &base, &transversals, 0);
+            if depth < base.len() || !is_identity(&residue) {
+                add_strong_generator(&mut base, &mut generators, &ranking, residue, depth);
+                transversals = level_transversals(&base, &generators, 0, degree);
+            }
+        }
+        BSGS { base, generators }
+    }
+
+    /// Sifts a permutation through the stabilizer chain, see [`sift`]
+    pub fn sift(&self, perm: &[usize]) -> (Permutation, usize) {
+        sift(perm, self)
+    }
+
+    /// Tests whether a permutation lies in the group without enumerating it
+    ///
+    /// For the slot group of a tensor this tells whether a rearrangement of
+    /// its indices is a symmetry operation. Permutations of another degree
+    /// are never members.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::presets::riemann;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// let r = riemann(["a", "b", "c", "d"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+    /// let group = r.symmetry_group();
+    /// assert!(group.contains(&[1, 0, 3, 2]));
+    /// assert!(!group.contains(&[0, 2, 1, 3]));
+    /// assert!(!group.contains(&[1, 0]));
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn contains(&self, perm: &[usize]) -> bool {
+        if self.base.is_empty() {
+            return is_identity(perm);
+        }
+        if self.generators.iter().any(|g| g.len() != perm.len()) {
+            return false;
+        }
+        let (residue, depth) = self.sift(perm);
+        depth == self.base.len() && is_identity(&residue)
+    }
+}
+
+/// Lazy iterator over the elements of a group given by a BSGS, returned by
+/// [`BSGS::elements`]
+#[derive(Debug, Clone)]
+pub struct GroupElements {
+    /// Coset representatives of every level, in orbit order
+    representatives: Vec<Vec<Permutation>>,
+    /// The representative chosen at every level
+    counters: Vec<usize>,
+    /// `partial[i]` is the product of the chosen representatives of levels `0..=i`
+    partial: Vec<Permutation>,
+    /// The element of a group without levels
+    identity: Permutation,
+    done: bool,
+}
+
+impl GroupElements {
+    /// Recomputes the partial products from `level` on
+    fn refresh_from(&mut self, level: usize) {
+        self.partial.truncate(level);
+        for i in level..self.representatives.len() {
+            let chosen = &self.representatives[i][self.counters[i]];
+            let product = match self.partial.last() {
+                Some(previous) => compose_permutations(chosen, previous),
+                None => chosen.clone(),
+            };
+            self.partial.push(product);
+        }
+    }
+}
+
+impl Iterator for GroupElements {
+    type Item = Permutation;
+
+    fn next(&mut self) -> Option<Permutation> {
+        if self.done {
+            return None;
+        }
+        let element = self
+            .partial
+            .last()
+            .cloned()
+            .unwrap_or_else(|| self.identity.clone());
+        // Advance the odometer, deepest level first
+        let mut level = self.representatives.len();
+        loop {
+            if level == 0 {
+                self.done = true;
+                break;
+            }
+            level -= 1;
+            self.counters[level] += 1;
+            if self.counters[level] < self.representatives[level].len() {
+                self.refresh_from(level);
+                break;
+            }
+            self.counters[level] = 0;
+        }
+        Some(element)
+    }
+}
+
+/// A base and strong generating set of a group of signed permutations
+///
+/// Each signed permutation of degree `n` is encoded as a permutation of
+/// `n + 2` points whose last two points are swapped when the sign is `-1`,
+/// so the ordinary Schreier-Sims algorithm tracks signs through every
+/// product. If a product of generators is the identity with sign `-1`, the
+/// group holds both signs of every permutation and a tensor with these
+/// symmetries vanishes.
+#[derive(Debug, Clone)]
+pub struct SignedBSGS {
+    degree: usize,
+    bsgs: BSGS,
+}
+
+/// Schreier-Sims algorithm for signed permutations of `degree` points
+///
+/// Generators of another degree are ignored.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::permutation::SignedPermutation;
+/// use butler_portugal::schreier_sims::signed_schreier_sims;
+///
+/// // Antisymmetry in both pairs and a symmetric pair exchange
+/// let group = signed_schreier_sims(
+///     &[
+///         SignedPermutation::new("(0 1)".parse()?, -1),
+///         SignedPermutation::new(vec![0, 1, 3, 2].into(), -1),
+///         SignedPermutation::new(vec![2, 3, 0, 1].into(), 1),
+///     ],
+///     4,
+/// );
+/// assert_eq!(group.order(), 8);
+/// assert_eq!(group.sign(&[1, 0, 3, 2]), Some(1));
+/// assert_eq!(group.sign(&[3, 2, 0, 1]), Some(-1));
+/// assert_eq!(group.sign(&[0, 2, 1, 3]), None);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn signed_schreier_sims(generators: &[SignedPermutation], degree: usize) -> SignedBSGS {
+    signed_schreier_sims_with_base(generators, degree, &[])
+}
+
+/// Signed Schreier-Sims algorithm with a base starting with the points
+/// `prefix`, as [`schreier_sims_with_base`]
+pub(crate) fn signed_schreier_sims_with_base(
+    generators: &[SignedPermutation],
+    degree: usize,
+    prefix: &[usize],
+) -> SignedBSGS {
+    let extended: Vec<Permutation> = generators
+        .iter()
+        .filter(|g| g.degree() == degree)
+        .map(SignedPermutation::to_extended)
+        .collect();
+    SignedBSGS {
+        degree,
+        bsgs: schreier_sims_with_base(&extended, degree + 2, prefix),
+    }
+}
+
+impl SignedBSGS {
+    /// Returns the number of points the signed permutations act on
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Returns the BSGS of the encoded group, acting on `degree + 2` points
+    pub fn bsgs(&self) -> &BSGS {
+        &self.bsgs
+    }
+
+    /// Returns the order of the group of signed permutations
+    ///
+    /// This is twice the order of the underlying permutation group when the
+    /// group [vanishes](Self::is_vanishing), and equal to it otherwise.
+    pub fn order(&self) -> u128 {
+        self.bsgs.order()
+    }
+
+    /// Returns true if the group contains the identity with sign `-1`
+    pub fn is_vanishing(&self) -> bool {
+        let negated = SignedPermutation::new(Permutation::identity(self.degree), -1);
+        is_member(&negated.to_extended(), &self.bsgs)
+    }
+
+    /// Returns the sign of a permutation in the group
+    ///
+    /// Returns `None` if the permutation is not in the group, and `Some(0)`
+    /// if it is there with both signs because the group vanishes.
+    pub fn sign(&self, perm: &[usize]) -> Option<i32> {
+        if perm.len() != self.degree {
+            return None;
+        }
+        let positive = SignedPermutation::new(Permutation::from(perm), 1);
+        if is_member(&positive.to_extended(), &self.bsgs) {
+            return Some(if self.is_vanishing() { 0 } else { 1 });
+        }
+        let negative = SignedPermutation::new(positive.permutation, -1);
+        is_member(&negative.to_extended(), &self.bsgs).then_some(-1)
+    }
+
+    /// Returns an iterator over every signed element of the group, in the
+    /// order of [`BSGS::elements`]
+    pub fn elements(&self) -> SignedElements {
+        SignedElements {
+            elements: self.bsgs.elements(),
+        }
+    }
+}
+
+/// Lazy iterator over the elements of a group of signed permutations,
+/// returned by [`SignedBSGS::elements`]
+#[derive(Debug, Clone)]
+pub struct SignedElements {
+    elements: GroupElements,
+}
+
+impl Iterator for SignedElements {
+    type Item = SignedPermutation;
+
+    fn next(&mut self) -> Option<SignedPermutation> {
+        let extended = self.elements.next()?;
+        Some(SignedPermutation::from_extended(&extended))
+    }
+}
+
+/// Detailed reason why a BSGS failed verification
+#[derive(Debug, Clone, PartialEq)]
+pub enum BsgsDefect {
+    /// A strong generator is not a valid permutation of the group's degree
+    InvalidGenerator { index: usize, message: String },
+    /// A base point is out of range or repeated
+    InvalidBasePoint { level: usize, point: usize },
+    /// A non-identity strong generator fixes every base point
+    IncompleteBase { generator: usize },
+    /// A Schreier generator of `level` does not sift through the deeper levels,
+    /// so the strong generators of `sift_level` do not generate the stabilizer
+    NonSiftingSchreierGenerator {
+        level: usize,
+        sift_level: usize,
+        schreier_generator: Permutation,
+        residue: Permutation,
+    },
+}
+
+impl fmt::Display for BsgsDefect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BsgsDefect::InvalidGenerator { index, message } => {
+                write!(f, "strong generator {index} is invalid: {message}")
+            }
+            BsgsDefect::InvalidBasePoint { level, point } => {
+                write!(f, "base point {point} at level {level} is invalid")
+            }
+            BsgsDefect::IncompleteBase { generator } => {
+                write!(f, "strong generator {generator} fixes every base point")
+            }
+            BsgsDefect::NonSiftingSchreierGenerator {
+                level,
+                sift_level,
+                schreier_generator,
+                residue,
+            } => write!(
+                f,
+                "Schreier generator {schreier_generator:?} of level {level} leaves residue \
+                 {residue:?} at level {sift_level}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BsgsDefect {}
+
+impl From<BsgsDefect> for ButlerPortugalError {
+    fn from(defect: BsgsDefect) -> Self {
+        ButlerPortugalError::InvalidPermutation(format!("BSGS verification failed: {defect}"))
+    }
+}
+
+impl BSGS {
+    /// Verifies that this is a genuine base and strong generating set
+    ///
+    /// Checks that every generator is a permutation of a common degree, that
+    /// the base is a list of distinct points which no non-identity generator
+    /// fixes entirely, and that every Schreier generator of every level sifts
+    /// to the identity through the deeper levels. Together these guarantee that
+    /// the stabilizer chain (and hence `order`, membership testing and
+    /// canonicalization) is exact, which matters for BSGS data supplied from
+    /// outside this crate.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::canonicalization::BSGS;
+    ///
+    /// // The cyclic group C_3 is fully described by the base [0]
+    /// let bsgs = BSGS {
+    ///     base: vec![0],
+    ///     generators: vec![vec![1, 2, 0].into()],
+    /// };
+    /// assert!(bsgs.verify().is_ok());
+    ///
+    /// // S_3 with base [0] misses the stabilizer of 0
+    /// let broken = BSGS {
+    ///     base: vec![0],
+    ///     generators: vec![vec![1, 2, 0].into(), vec![1, 0, 2].into()],
+    /// };
+    /// assert!(broken.verify().is_err());
+    /// ```
+    pub fn verify(&self) -> std::result::Result<(), BsgsDefect> {
+        let degree = self.generators.first().map_or(0, Permutation::degree);
+        for (index, g) in self.generators.iter().enumerate() {
+            if let Err(err) = validate_permutation(g, degree) {
+                return Err(BsgsDefect::InvalidGenerator {
+                    index,
+                    message: err.to_string(),
+                });
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for (level, &point) in self.base.iter().enumerate() {
+            if point >= degree || !seen.insert(point) {
+                return Err(BsgsDefect::InvalidBasePoint { level, point });
+            }
+        }
+
+        if let Some(generator) = self
+            .generators
+            .iter()
+            .position(|g| !is_identity(g) && self.base.iter().all(|&b| g[b] == b))
+        {
+            return Err(BsgsDefect::IncompleteBase { generator });
+        }
+
+        let transversals = level_transversals(&self.base, &self.generators, 0, degree);
+        for (level, transversal) in transversals.iter().enumerate() {
+            let level_gens = stabilizer_generators(&self.base, &self.generators, level);
+            for &beta in transversal.orbit() {
+                let Some(u_beta) = transversal.representative(beta) else {
+                    continue;
+                };
+                for s in &level_gens {
+                    let Some(u_image) = transversal.representative(s[beta]) else {
+                        continue;
+                    };
+                    let schreier_generator = compose_permutations(
+                        &compose_permutations(&u_beta, s),
+                        &inverse_permutation(&u_image),
+                    );
+                    let (residue, sift_level) = sift_through(
+                        &schreier_generator,
+                        &self.base,
+                        &transversals[level + 1..],
+                        level + 1,
+                    );
+                    if sift_level < self.base.len() || !is_identity(&residue) {
+                        return Err(BsgsDefect::NonSiftingSchreierGenerator {
+                            level,
+                            sift_level,
+                            schreier_generator,
+                            residue,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sifts a permutation through the stabilizer chain of a BSGS
+///
+/// At each level the coset representative matching the image of the base
+/// point is divided off. Returns the residue and the level at which sifting
+/// stopped, `bsgs.base.len()` if it passed through every level; `perm` lies
+/// in the group exactly when it passes every level with the identity as
+/// residue.
+pub fn sift(perm: &[usize], bsgs: &BSGS) -> (Permutation, usize) {
+    strip(perm, &bsgs.base, &bsgs.generators, 0)
+}
+
+/// Sift algorithm for membership testing in a permutation group given by BSGS
+///
+/// Returns true if `perm` is in the group, false otherwise, including when
+/// its degree differs from the group's.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::{is_member, schreier_sims};
+///
+/// // The cyclic group C_3 contains the 3-cycles but no transposition
+/// let bsgs = schreier_sims(&[vec![1, 2, 0].into()], 3);
+/// assert!(is_member(&vec![2, 0, 1].into(), &bsgs));
+/// assert!(!is_member(&vec![1, 0, 2].into(), &bsgs));
+/// ```
+pub fn is_member(perm: &Permutation, bsgs: &BSGS) -> bool {
+    bsgs.contains(perm)
+}
+
+/// Computes the inverse of a permutation
+pub fn inverse_permutation(perm: &[usize]) -> Permutation {
     let mut inv = vec![0; perm.len()];
     for (i, &p) in perm.iter().enumerate() {
         inv[p] = i;
     }
-    inv
+    inv.into()
 }
 
-/// Composes two permutations (applies first, then second)
+/// Composes two permutations, applying `perm1` first and then `perm2`
+///
+/// The result maps `i` to `perm2[perm1[i]]`. Permutations of different
+/// lengths are composed as if the shorter one fixed the remaining points.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::compose_permutations;
+///
+/// assert_eq!(compose_permutations(&[1, 2, 0], &[1, 0, 2]), vec![0, 2, 1]);
+/// assert_eq!(compose_permutations(&[1, 0], &[0, 2, 1]), vec![2, 0, 1]);
+/// ```
 pub fn compose_permutations(perm1: &[usize], perm2: &[usize]) -> Permutation {
     let size = perm1.len().max(perm2.len());
     let mut result = vec![0; size];
@@ -180,7 +1396,7 @@ pub fn compose_permutations(perm1: &[usize], perm2: &[usize]) -> Permutation {
             intermediate
         };
     }
-    result
+    result.into()
 }
 
 #[cfg(test)]
@@ -189,7 +1405,7 @@ mod tests {
 
     #[test]
     fn test_orbit() {
-        let gens = vec![vec![1, 0, 2]]; // swap 0 and 1
+        let gens: Vec<Permutation> = vec![vec![1, 0, 2].into()]; // swap 0 and 1
         let orb = orbit(0, &gens);
         assert!(orb.contains(&0));
         assert!(orb.contains(&1));
@@ -206,7 +1422,7 @@ mod tests {
 
     #[test]
     fn test_schreier_sims_simple() {
-        let gens = vec![vec![1, 0, 2]]; // S_2 on 0,1
+        let gens: Vec<Permutation> = vec![vec![1, 0, 2].into()]; // S_2 on 0,1
         let bsgs = schreier_sims(&gens, 3);
         assert!(bsgs.generators.iter().any(|g| g == &vec![1, 0, 2]));
         assert!(bsgs.base.contains(&0));
@@ -233,13 +1449,386 @@ mod tests {
 
     #[test]
     fn test_is_member() {
-        let gens = vec![vec![1, 0, 2]];
+        let gens: Vec<Permutation> = vec![vec![1, 0, 2].into()];
         let bsgs = schreier_sims(&gens, 3);
-        let id = vec![0, 1, 2];
-        let swap = vec![1, 0, 2];
+        let id = Permutation::identity(3);
+        let swap = Permutation::from(vec![1, 0, 2]);
         assert!(is_member(&id, &bsgs));
         assert!(is_member(&swap, &bsgs));
-        let not_in_group = vec![2, 1, 0];
+        let not_in_group = Permutation::from(vec![2, 1, 0]);
         assert!(!is_member(&not_in_group, &bsgs));
     }
+
+    #[test]
+    fn test_sift_stops_at_missing_coset() {
+        let bsgs = schreier_sims(&[vec![1, 0, 2].into()], 3);
+        assert_eq!(
+            sift(&[1, 0, 2], &bsgs),
+            (Permutation::identity(3), bsgs.base.len())
+        );
+        assert_eq!(bsgs.base, vec![0]);
+        // Exchanging 0 and 2 sends the base point outside its orbit {0, 1},
+        // so sifting stops at the first level with nothing divided off
+        let (residue, level) = sift(&[2, 1, 0], &bsgs);
+        assert_eq!(level, 0);
+        assert_eq!(residue, vec![2, 1, 0]);
+        assert!(!is_identity(&residue));
+        // The 3-cycle passes the level but leaves a residue moving 2
+        let (residue, level) = sift(&[1, 2, 0], &bsgs);
+        assert_eq!(level, bsgs.base.len());
+        assert!(!is_identity(&residue));
+    }
+
+    #[test]
+    fn test_order_trivial_group() {
+        let bsgs = schreier_sims(&[], 4);
+        assert_eq!(bsgs.order(), 1);
+    }
+
+    #[test]
+    fn test_order_symmetric_group() {
+        // S_5 from a transposition and a 5-cycle
+        let gens: Vec<Permutation> = vec![vec![1, 0, 2, 3, 4].into(), vec![1, 2, 3, 4, 0].into()];
+        let bsgs = schreier_sims(&gens, 5);
+        assert_eq!(bsgs.order(), 120);
+    }
+
+    #[test]
+    fn test_order_riemann_slot_group() {
+        // Antisymmetry in (0,1), (2,3) and pair exchange generate a group of order 8
+        let gens: Vec<Permutation> = vec![
+            vec![1, 0, 2, 3].into(),
+            vec![0, 1, 3, 2].into(),
+            vec![2, 3, 0, 1].into(),
+        ];
+        let bsgs = schreier_sims(&gens, 4);
+        assert_eq!(bsgs.order(), 8);
+        assert!(is_member(&vec![3, 2, 1, 0].into(), &bsgs));
+        assert!(!is_member(&vec![0, 2, 1, 3].into(), &bsgs));
+    }
+
+    #[test]
+    fn test_schreier_vector_representatives() {
+        let gens: Vec<Permutation> = vec![vec![1, 2, 3, 0].into(), vec![1, 0, 2, 3].into()];
+        let sv = SchreierVector::new(0, &gens, 4);
+        assert_eq!(sv.orbit().len(), 4);
+        for point in 0..4 {
+            let rep = sv.representative(point).unwrap();
+            assert_eq!(rep[0], point);
+        }
+    }
+
+    #[test]
+    fn test_schreier_vector_outside_orbit() {
+        let gens: Vec<Permutation> = vec![vec![1, 0, 2].into()];
+        let sv = SchreierVector::new(0, &gens, 3);
+        assert!(sv.contains(1));
+        assert!(!sv.contains(2));
+        assert!(sv.representative(2).is_none());
+    }
+
+    #[test]
+    fn test_transversals_large_degree() {
+        // Full symmetric group on 24 slots: transversals stay implicit
+        let n = 24;
+        let mut swap: Permutation = (0..n).collect();
+        swap.swap(0, 1);
+        let cycle: Permutation = (0..n).map(|i| (i + 1) % n).collect();
+        let bsgs = schreier_sims(&[swap, cycle], n);
+        let transversals = bsgs.transversals();
+        assert_eq!(transversals.len(), bsgs.base.len());
+        let first = &transversals[0];
+        assert_eq!(first.orbit().len(), n);
+        let rep = first.representative(n - 1).unwrap();
+        assert_eq!(rep[first.root()], n - 1);
+        assert!(is_member(&rep, &bsgs));
+    }
+
+    #[test]
+    fn test_verify_computed_bsgs() {
+        let gens: Vec<Permutation> = vec![
+            vec![1, 0, 2, 3].into(),
+            vec![0, 1, 3, 2].into(),
+            vec![2, 3, 0, 1].into(),
+        ];
+        let bsgs = schreier_sims(&gens, 4);
+        assert!(bsgs.verify().is_ok());
+        assert!(schreier_sims(&[], 3).verify().is_ok());
+    }
+
+    #[test]
+    fn test_verify_detects_missing_stabilizer_generator() {
+        // S_3 = <(0 1), (0 1 2)> needs a second base point
+        let bsgs = BSGS {
+            base: vec![0],
+            generators: vec![vec![1, 0, 2].into(), vec![1, 2, 0].into()],
+        };
+        assert!(matches!(
+            bsgs.verify(),
+            Err(BsgsDefect::IncompleteBase { .. })
+                | Err(BsgsDefect::NonSiftingSchreierGenerator { .. })
+        ));
+
+        // With base [0, 1] but without a generator of Stab(0)
+        let bsgs = BSGS {
+            base: vec![0, 1],
+            generators: vec![vec![1, 2, 0].into(), vec![1, 0, 2].into()],
+        };
+        match bsgs.verify() {
+            Err(BsgsDefect::NonSiftingSchreierGenerator { level, residue, .. }) => {
+                assert_eq!(level, 0);
+                assert_eq!(residue[0], 0);
+            }
+            other => panic!("expected a non-sifting Schreier generator, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_data() {
+        let bad_gen = BSGS {
+            base: vec![0],
+            generators: vec![vec![1, 0, 2].into(), vec![0, 0, 1].into()],
+        };
+        assert!(matches!(
+            bad_gen.verify(),
+            Err(BsgsDefect::InvalidGenerator { index: 1, .. })
+        ));
+
+        let bad_base = BSGS {
+            base: vec![0, 0],
+            generators: vec![vec![1, 0, 2].into()],
+        };
+        assert!(matches!(
+            bad_base.verify(),
+            Err(BsgsDefect::InvalidBasePoint { level: 1, point: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_base_prefers_large_orbits() {
+        // (0 1)(2 3 4 5 6 7): starting at point 0 would need two base points,
+        // while the 6-cycle point 2 alone already has a trivial stabilizer
+        let gens: Vec<Permutation> = vec![vec![1, 0, 3, 4, 5, 6, 7, 2].into()];
+        let bsgs = schreier_sims(&gens, 8);
+        assert_eq!(bsgs.base, vec![2]);
+        assert_eq!(bsgs.order(), 6);
+        assert!(bsgs.verify().is_ok());
+    }
+
+    #[test]
+    fn test_base_for_riemann_squared_group() {
+        let mut gens = Vec::new();
+        for offset in [0, 4] {
+            let mut p: Permutation = (0..8).collect();
+            p.swap(offset, offset + 1);
+            gens.push(p);
+            let mut p: Permutation = (0..8).collect();
+            p.swap(offset + 2, offset + 3);
+            gens.push(p);
+            let mut p: Permutation = (0..8).collect();
+            p.swap(offset, offset + 2);
+            p.swap(offset + 1, offset + 3);
+            gens.push(p);
+        }
+        let bsgs = schreier_sims(&gens, 8);
+        assert_eq!(bsgs.base.len(), 4);
+        assert_eq!(bsgs.order(), 64);
+        assert!(bsgs.verify().is_ok());
+    }
+
+    #[test]
+    fn test_group_elements_are_streamed_once_each() {
+        // S_4 and the dihedral group of the square
+        for gens in [
+            vec![vec![1, 0, 2, 3], vec![1, 2, 3, 0]],
+            vec![vec![1, 2, 3, 0], vec![0, 3, 2, 1]],
+        ] {
+            let gens: Vec<Permutation> = gens.into_iter().map(Permutation::from).collect();
+            let bsgs = schreier_sims(&gens, 4);
+            let elements: Vec<Permutation> = bsgs.elements().collect();
+            assert_eq!(elements.len() as u128, bsgs.order());
+            let distinct: HashSet<&Permutation> = elements.iter().collect();
+            assert_eq!(distinct.len(), elements.len());
+            assert!(elements.iter().all(|g| is_member(g, &bsgs)));
+        }
+        let trivial = schreier_sims(&[], 3);
+        assert_eq!(trivial.elements().collect::<Vec<_>>(), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_orbit_stabilizer() {
+        // The Riemann slot group: |G| = |orbit| * |stabilizer| for every point
+        let gens: Vec<Permutation> = vec![
+            vec![1, 0, 2, 3].into(),
+            vec![0, 1, 3, 2].into(),
+            vec![2, 3, 0, 1].into(),
+        ];
+        let bsgs = schreier_sims(&gens, 4);
+        for point in 0..4 {
+            let fixing = stabilizer(point, &bsgs);
+            assert!(fixing.verify().is_ok());
+            assert_eq!(
+                orbit(point, &gens).len() as u128 * fixing.order(),
+                bsgs.order()
+            );
+            assert!(fixing.elements().all(|g| g[point] == point));
+        }
+        // A point the group fixes keeps the whole group
+        let partial = schreier_sims(&[vec![1, 0, 2].into()], 3);
+        assert_eq!(stabilizer(2, &partial).order(), 2);
+        assert_eq!(stabilizer(0, &partial).order(), 1);
+        assert_eq!(orbit(5, &gens).len(), 1);
+    }
+
+    #[test]
+    fn test_bsgs_contains() {
+        // Exactly the eight elements of the Riemann slot group are members
+        let riemann = schreier_sims(
+            &[
+                vec![1, 0, 2, 3].into(),
+                vec![0, 1, 3, 2].into(),
+                vec![2, 3, 0, 1].into(),
+            ],
+            4,
+        );
+        let symmetric = schreier_sims(&[vec![1, 0, 2, 3].into(), vec![1, 2, 3, 0].into()], 4);
+        let members: HashSet<Permutation> = riemann.elements().collect();
+        for perm in symmetric.elements() {
+            assert_eq!(riemann.contains(&perm), members.contains(&perm));
+            assert_eq!(riemann.sift(&perm), sift(&perm, &riemann));
+        }
+        assert_eq!(members.len(), 8);
+        assert!(!riemann.contains(&[0, 1, 2]));
+        assert!(BSGS::identity(3).contains(&[0, 1, 2]));
+        assert!(!BSGS::identity(3).contains(&[1, 0, 2]));
+    }
+
+    #[test]
+    fn test_random_schreier_sims() {
+        let unverified = RandomSchreierSims::new().with_verification(false);
+        let groups: Vec<(Vec<Permutation>, usize)> = vec![
+            (vec![vec![1, 0, 2, 3].into(), vec![1, 2, 3, 0].into()], 4),
+            (
+                vec![
+                    vec![1, 0, 2, 3].into(),
+                    vec![0, 1, 3, 2].into(),
+                    vec![2, 3, 0, 1].into(),
+                ],
+                4,
+            ),
+            (vec![vec![1, 0, 3, 4, 5, 6, 7, 2].into()], 8),
+            (vec![], 3),
+        ];
+        for (gens, degree) in &groups {
+            let exact = schreier_sims(gens, *degree);
+            for seed in 0..5 {
+                let random = random_schreier_sims(gens, *degree, &unverified.with_seed(seed));
+                assert_eq!(random.order(), exact.order());
+                assert!(random.verify().is_ok());
+            }
+        }
+
+        // Above the threshold schreier_sims itself is randomized and verified
+        let n = RANDOMIZED_DEGREE + 2;
+        let mut swap = Permutation::identity(n);
+        swap.swap(0, 1);
+        let cycle: Permutation = (0..n).map(|i| (i + 1) % n).collect();
+        let bsgs = schreier_sims(&[swap.clone(), cycle.clone()], n);
+        assert!(bsgs.verify().is_ok());
+        assert_eq!(bsgs.order(), (1..=n as u128).product());
+        // A chain cut short by a huge error probability is completed by verification
+        let sloppy = RandomSchreierSims::new().with_error_probability(0.9);
+        let verified = random_schreier_sims(&[swap, cycle], n, &sloppy);
+        assert_eq!(verified.order(), bsgs.order());
+    }
+
+    #[test]
+    fn test_reduce_generators() {
+        // S_6 from all transpositions and A_5 x S_2 from redundant 3-cycles
+        let mut transpositions = Vec::new();
+        for i in 0..6 {
+            for j in i + 1..6 {
+                let mut swap = Permutation::identity(6);
+                swap.swap(i, j);
+                transpositions.push(swap);
+            }
+        }
+        let mut mixed: Vec<Permutation> = (0..3)
+            .map(|k| Permutation::from_cycles(&[vec![k, k + 1, k + 2]], 7).unwrap())
+            .collect();
+        mixed.push(Permutation::from_cycles(&[vec![1, 2, 3]], 7).unwrap());
+        mixed.push(Permutation::from_cycles(&[vec![5, 6]], 7).unwrap());
+        mixed.push(Permutation::identity(7));
+        for (gens, degree) in [(transpositions, 6), (mixed, 7)] {
+            let reduced = reduce_generators(&gens, degree);
+            assert!(reduced.len() < degree);
+            let full = schreier_sims(&gens, degree);
+            assert_eq!(schreier_sims(&reduced, degree).order(), full.order());
+            assert!(gens
+                .iter()
+                .all(|g| is_member(g, &schreier_sims(&reduced, degree))));
+        }
+        assert!(reduce_generators(&[Permutation::identity(3)], 3).is_empty());
+    }
+
+    #[test]
+    fn test_change_base() {
+        let groups: Vec<(Vec<Permutation>, usize)> = vec![
+            (vec![vec![1, 0, 2, 3].into(), vec![1, 2, 3, 0].into()], 4),
+            (
+                vec![
+                    vec![1, 0, 2, 3, 4, 5, 6, 7].into(),
+                    vec![0, 1, 3, 2, 4, 5, 6, 7].into(),
+                    vec![2, 3, 0, 1, 4, 5, 6, 7].into(),
+                    vec![0, 1, 2, 3, 5, 4, 6, 7].into(),
+                    vec![0, 1, 2, 3, 6, 7, 4, 5].into(),
+                    vec![4, 5, 6, 7, 0, 1, 2, 3].into(),
+                ],
+                8,
+            ),
+            (vec![], 3),
+        ];
+        for (gens, degree) in &groups {
+            let bsgs = schreier_sims(gens, *degree);
+            let reversed: Vec<usize> = (0..*degree).rev().take(2).collect();
+            let rebased = bsgs.change_base(&reversed).unwrap();
+            assert_eq!(rebased.order(), bsgs.order());
+            assert!(rebased.verify().is_ok());
+            if bsgs.order() > 1 {
+                assert!(rebased.base.starts_with(&reversed));
+            }
+        }
+
+        let bsgs = schreier_sims(&groups[0].0, 4);
+        assert!(bsgs.change_base(&[1, 1]).is_err());
+        assert!(bsgs.change_base(&[4]).is_err());
+    }
+
+    #[test]
+    fn test_signed_schreier_sims() {
+        let signed = |images: Vec<usize>, sign| SignedPermutation::new(images.into(), sign);
+        // Antisymmetric pairs with a symmetric pair exchange: signs are consistent
+        let riemann = signed_schreier_sims(
+            &[
+                signed(vec![1, 0, 2, 3], -1),
+                signed(vec![0, 1, 3, 2], -1),
+                signed(vec![2, 3, 0, 1], 1),
+            ],
+            4,
+        );
+        assert_eq!(riemann.order(), 8);
+        assert!(!riemann.is_vanishing());
+        assert!(riemann.bsgs().verify().is_ok());
+        for element in riemann.elements() {
+            assert_eq!(riemann.sign(&element.permutation), Some(element.sign));
+        }
+        assert_eq!(riemann.sign(&[1, 0, 2]), None);
+
+        // Symmetric in (0 1) and antisymmetric in (1 2): (0 2) gets both signs
+        let clash = signed_schreier_sims(&[signed(vec![1, 0, 2], 1), signed(vec![0, 2, 1], -1)], 3);
+        assert!(clash.is_vanishing());
+        assert_eq!(clash.order(), 12);
+        assert_eq!(clash.sign(&[2, 1, 0]), Some(0));
+        assert_eq!(signed_schreier_sims(&[], 3).elements().count(), 1);
+    }
 }
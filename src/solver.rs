@@ -0,0 +1,221 @@
+//! Fixing unknown coefficients by matching canonical monomials
+//!
+//! An ansatz `Σ c_i E_i` with unknown scalars `c_i` is compared with a target
+//! expression: both sides are simplified (see `TensorExpression::simplify`),
+//! the coefficient of every canonical monomial is equated, and the resulting
+//! linear system is solved exactly over the rationals.
+
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::TensorExpression;
+use crate::rational::Rational;
+
+/// Values of the unknown coefficients that reproduce a target expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoefficientSolution {
+    values: Vec<(String, Rational)>,
+    free: Vec<String>,
+}
+
+impl CoefficientSolution {
+    /// Returns the value of every unknown, in ansatz order
+    ///
+    /// Unknowns the system does not determine are set to zero.
+    pub fn values(&self) -> &[(String, Rational)] {
+        &self.values
+    }
+
+    /// Returns the value of a named unknown
+    pub fn value(&self, name: &str) -> Option<Rational> {
+        self.values
+            .iter()
+            .find(|(unknown, _)| unknown == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Returns the unknowns left undetermined by the system
+    pub fn free_unknowns(&self) -> &[String] {
+        &self.free
+    }
+
+    /// Returns true if every unknown is fixed by the system
+    pub fn is_unique(&self) -> bool {
+        self.free.is_empty()
+    }
+}
+
+/// Solves `Σ c_i E_i = target` for the unknown coefficients `c_i`
+///
+/// # Arguments
+/// * `ansatz` - Pairs of unknown names and the expressions they multiply
+/// * `target` - The expression the ansatz has to reproduce
+///
+/// # Errors
+/// Returns a `MathematicalError` if no choice of coefficients matches the target.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::expression::TensorExpression;
+/// use butler_portugal::presets::riemann;
+/// use butler_portugal::rational::Rational;
+/// use butler_portugal::solver::solve_coefficients;
+/// use butler_portugal::TensorIndex;
+///
+/// let r = |names: [&str; 4]| {
+///     riemann(names.iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())
+///         .map(TensorExpression::from)
+/// };
+/// // R_acbd = x R_abcd + y R_adbc by the first Bianchi identity
+/// let ansatz = [("x", r(["a", "b", "c", "d"])?), ("y", r(["a", "d", "b", "c"])?)];
+/// let solution = solve_coefficients(&ansatz, &r(["a", "c", "b", "d"])?)?;
+/// assert_eq!(solution.value("x"), Some(Rational::ONE));
+/// assert_eq!(solution.value("y"), Some(Rational::ONE));
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn solve_coefficients(
+    ansatz: &[(&str, TensorExpression)],
+    target: &TensorExpression,
+) -> Result<CoefficientSolution> {
+    for (i, (name, _)) in ansatz.iter().enumerate() {
+        if ansatz[..i].iter().any(|(other, _)| other == name) {
+            return Err(ButlerPortugalError::ComputationError(format!(
+                "Unknown {name} appears twice in the ansatz"
+            )));
+        }
+    }
+
+    // One row per canonical monomial, one column per unknown plus the target
+    let unknowns = ansatz.len();
+    let mut monomials: Vec<String> = Vec::new();
+    let mut rows: Vec<Vec<Rational>> = Vec::new();
+    let columns = ansatz
+        .iter()
+        .map(|(_, expression)| expression)
+        .chain(std::iter::once(target));
+    for (column, expression) in columns.enumerate() {
+        for term in expression.simplify()?.terms() {
+            let key = term.structure_key();
+            let row = if let Some(row) = monomials.iter().position(|m| *m == key) {
+                row
+            } else {
+                monomials.push(key);
+                rows.push(vec![Rational::ZERO; unknowns + 1]);
+                rows.len() - 1
+            };
+            rows[row][column] += term.coefficient();
+        }
+    }
+
+    let pivots = row_reduce(&mut rows, unknowns);
+    if let Some(row) = rows[pivots.len()..]
+        .iter()
+        .find(|row| !row[unknowns].is_zero())
+    {
+        return Err(ButlerPortugalError::MathematicalError(format!(
+            "No choice of coefficients reproduces the target ({} ≠ 0)",
+            row[unknowns]
+        )));
+    }
+
+    let mut values: Vec<(String, Rational)> = ansatz
+        .iter()
+        .map(|(name, _)| (name.to_string(), Rational::ZERO))
+        .collect();
+    for (row, &column) in pivots.iter().enumerate() {
+        values[column].1 = rows[row][unknowns];
+    }
+    let free = (0..unknowns)
+        .filter(|column| !pivots.contains(column))
+        .map(|column| values[column].0.clone())
+        .collect();
+    Ok(CoefficientSolution { values, free })
+}
+
+/// Brings the first `unknowns` columns into reduced row echelon form and
+/// returns the pivot column of each leading row
+fn row_reduce(rows: &mut [Vec<Rational>], unknowns: usize) -> Vec<usize> {
+    let mut pivots = Vec::new();
+    for column in 0..unknowns {
+        let row = pivots.len();
+        let Some(found) = (row..rows.len()).find(|&r| !rows[r][column].is_zero()) else {
+            continue;
+        };
+        rows.swap(row, found);
+        let scale = Rational::ONE / rows[row][column];
+        for entry in rows[row].iter_mut() {
+            *entry *= scale;
+        }
+        for other in 0..rows.len() {
+            let factor = rows[other][column];
+            if other == row || factor.is_zero() {
+                continue;
+            }
+            for c in 0..rows[other].len() {
+                let delta = factor * rows[row][c];
+                rows[other][c] = rows[other][c] - delta;
+            }
+        }
+        pivots.push(column);
+    }
+    pivots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::TensorProduct;
+    use crate::symmetry::Symmetry;
+    use crate::tensor::Tensor;
+    use crate::TensorIndex;
+
+    fn symmetric(first: &str, second: &str) -> TensorExpression {
+        let mut s = Tensor::new(
+            "S",
+            vec![TensorIndex::new(first, 0), TensorIndex::new(second, 1)],
+        );
+        s.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        TensorExpression::from(s)
+    }
+
+    fn vector(name: &str, index: &str) -> TensorExpression {
+        TensorExpression::from(Tensor::new(name, vec![TensorIndex::new(index, 0)]))
+    }
+
+    #[test]
+    fn test_underdetermined_unknowns_are_free() {
+        // S_ab and S_ba are the same monomial, so only x + y is fixed
+        let ansatz = [("x", symmetric("a", "b")), ("y", symmetric("b", "a"))];
+        let target = symmetric("a", "b").scale(Rational::integer(2));
+        let solution = solve_coefficients(&ansatz, &target).unwrap();
+        assert_eq!(solution.value("x"), Some(Rational::integer(2)));
+        assert_eq!(solution.free_unknowns(), ["y".to_string()]);
+        assert!(!solution.is_unique());
+    }
+
+    #[test]
+    fn test_mixed_system() {
+        // x (u_a v_b) + y (u_a v_b - 2 S_ab) = 3 u_a v_b + S_ab
+        let uv = TensorExpression::from(vector("u", "a").terms()[0].multiply(
+            &TensorProduct::from(Tensor::new("v", vec![TensorIndex::new("b", 0)])),
+        ));
+        let ansatz = [
+            ("x", uv.clone()),
+            (
+                "y",
+                uv.add(&symmetric("a", "b").scale(Rational::integer(-2))),
+            ),
+        ];
+        let target = uv.scale(Rational::integer(3)).add(&symmetric("a", "b"));
+        let solution = solve_coefficients(&ansatz, &target).unwrap();
+        assert!(solution.is_unique());
+        assert_eq!(solution.value("x"), Some(Rational::new(7, 2)));
+        assert_eq!(solution.value("y"), Some(Rational::new(-1, 2)));
+    }
+
+    #[test]
+    fn test_inconsistent_and_duplicate_unknowns() {
+        let ansatz = [("x", vector("u", "a"))];
+        assert!(solve_coefficients(&ansatz, &vector("v", "a")).is_err());
+        let ansatz = [("x", vector("u", "a")), ("x", vector("v", "a"))];
+        assert!(solve_coefficients(&ansatz, &vector("u", "a")).is_err());
+    }
+}
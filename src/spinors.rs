@@ -0,0 +1,246 @@
+//! Two-component SL(2,C) spinors in van der Waerden conventions
+//!
+//! Spinor indices come in two classes, [`IndexClass::Undotted`] (`ψ_A`) and
+//! [`IndexClass::Dotted`] (`χ̄_{Ȧ}`), which never contract with each other.
+//! Both are raised and lowered with the antisymmetric `ε` of their class,
+//! contracting on the second index of `ε` when raising and on the first when
+//! lowering:
+//!
+//! ```text
+//! ψ^A = ε^{AB} ψ_B        ψ_A = ψ^B ε_{BA}
+//! ```
+//!
+//! so that `ψ^A χ_A = -ψ_A χ^A`. Complex conjugation exchanges the two classes
+//! and toggles a `bar` suffix on tensor names, as for the tetrad `m` and
+//! `mbar`; `ε` is real and keeps its name.
+
+use crate::builtin::EPSILON_NAME;
+use crate::error::{ButlerPortugalError, Result};
+use crate::expression::{TensorExpression, TensorProduct};
+use crate::index::{IndexClass, TensorIndex};
+use crate::rational::Rational;
+use crate::symmetry::Symmetry;
+use crate::tensor::Tensor;
+
+/// Suffix marking the complex conjugate of a tensor name
+const CONJUGATE_SUFFIX: &str = "bar";
+
+/// Returns true for the two spinor index classes
+pub fn is_spinor_class(class: IndexClass) -> bool {
+    matches!(class, IndexClass::Undotted | IndexClass::Dotted)
+}
+
+/// Creates the spinor metric `ε_{AB}` on two indices of the same spinor class
+///
+/// # Errors
+/// Returns an `InvalidTensor` error unless both indices are undotted or both
+/// are dotted.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::spinors::spinor_epsilon;
+/// use butler_portugal::TensorIndex;
+///
+/// let eps = spinor_epsilon(TensorIndex::dotted("A", 0), TensorIndex::dotted("B", 1))?;
+/// assert_eq!(eps.name(), "epsilon");
+/// assert!(spinor_epsilon(TensorIndex::undotted("A", 0), TensorIndex::dotted("B", 1)).is_err());
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn spinor_epsilon(mut first: TensorIndex, mut second: TensorIndex) -> Result<Tensor> {
+    if !is_spinor_class(first.class()) || first.class() != second.class() {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "The spinor epsilon needs two indices of one spinor class, got {:?} and {:?}",
+            first.class(),
+            second.class()
+        )));
+    }
+    first.set_position(0);
+    second.set_position(1);
+    let mut tensor = Tensor::new(EPSILON_NAME, vec![first, second]);
+    tensor.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    Ok(tensor)
+}
+
+/// Raises the spinor index in `slot` as `ψ^A = ε^{AB} ψ_B`
+///
+/// The raised index keeps its name on `ε`; the contracted one is primed.
+///
+/// # Errors
+/// Returns an error if the slot is out of range or does not hold a covariant
+/// spinor index.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::spinors::raise_spinor;
+/// use butler_portugal::{Tensor, TensorIndex};
+///
+/// let psi = Tensor::new("psi", vec![TensorIndex::undotted("A", 0)]);
+/// assert_eq!(raise_spinor(&psi, 0)?.to_string(), "epsilon_^A ^A' psi__A'");
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn raise_spinor(tensor: &Tensor, slot: usize) -> Result<TensorProduct> {
+    let (index, dummy) = spinor_slot(tensor, slot, false)?;
+    let mut free = index.clone();
+    free.set_contravariant(true);
+    let mut upper_dummy = dummy.clone();
+    upper_dummy.set_contravariant(true);
+    let epsilon = spinor_epsilon(free, upper_dummy)?;
+    let mut contracted = tensor.clone();
+    contracted.indices_mut()[slot] = dummy;
+    Ok(TensorProduct::new(Rational::ONE, vec![epsilon, contracted]))
+}
+
+/// Lowers the spinor index in `slot` as `ψ_A = ψ^B ε_{BA}`
+///
+/// The lowered index keeps its name on `ε`; the contracted one is primed.
+///
+/// # Errors
+/// Returns an error if the slot is out of range or does not hold a
+/// contravariant spinor index.
+pub fn lower_spinor(tensor: &Tensor, slot: usize) -> Result<TensorProduct> {
+    let (index, dummy) = spinor_slot(tensor, slot, true)?;
+    let mut free = index.clone();
+    free.set_contravariant(false);
+    let mut lower_dummy = dummy.clone();
+    lower_dummy.set_contravariant(false);
+    let epsilon = spinor_epsilon(lower_dummy, free)?;
+    let mut contracted = tensor.clone();
+    contracted.indices_mut()[slot] = dummy;
+    Ok(TensorProduct::new(Rational::ONE, vec![contracted, epsilon]))
+}
+
+/// Returns the spinor index in `slot` and its primed dummy, checking its variance
+fn spinor_slot(
+    tensor: &Tensor,
+    slot: usize,
+    contravariant: bool,
+) -> Result<(TensorIndex, TensorIndex)> {
+    let index = tensor
+        .indices()
+        .get(slot)
+        .ok_or(ButlerPortugalError::IndexOutOfBounds {
+            index: slot,
+            max: tensor.rank(),
+        })?;
+    if !is_spinor_class(index.class()) || index.is_contravariant() != contravariant {
+        return Err(ButlerPortugalError::InvalidTensor(format!(
+            "Slot {slot} of {} does not hold a {} spinor index",
+            tensor.name(),
+            if contravariant {
+                "contravariant"
+            } else {
+                "covariant"
+            }
+        )));
+    }
+    let dummy = index.with_name(&format!("{}'", index.name()));
+    Ok((index.clone(), dummy))
+}
+
+/// Returns the complex conjugate of a tensor
+///
+/// Undotted and dotted indices are exchanged and the `bar` suffix of the
+/// name is toggled; `ε` keeps its name. Indices of other classes are real and
+/// stay as they are.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::spinors::conjugate;
+/// use butler_portugal::{IndexClass, Tensor, TensorIndex};
+///
+/// let psi = Tensor::new("psi", vec![TensorIndex::undotted("A", 0)]);
+/// let psibar = conjugate(&psi);
+/// assert_eq!(psibar.name(), "psibar");
+/// assert_eq!(psibar.indices()[0].class(), IndexClass::Dotted);
+/// assert_eq!(conjugate(&psibar), psi);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+pub fn conjugate(tensor: &Tensor) -> Tensor {
+    let name = if tensor.name() == EPSILON_NAME {
+        tensor.name().to_string()
+    } else if let Some(base) = tensor.name().strip_suffix(CONJUGATE_SUFFIX) {
+        base.to_string()
+    } else {
+        format!("{}{CONJUGATE_SUFFIX}", tensor.name())
+    };
+    let mut conjugated = tensor.clone();
+    conjugated.set_name(&name);
+    for index in conjugated.indices_mut() {
+        let class = match index.class() {
+            IndexClass::Undotted => IndexClass::Dotted,
+            IndexClass::Dotted => IndexClass::Undotted,
+            other => other,
+        };
+        *index = index.with_class(class);
+    }
+    conjugated
+}
+
+/// Returns the complex conjugate of an expression with real coefficients
+pub fn conjugate_expression(expression: &TensorExpression) -> TensorExpression {
+    expression
+        .terms()
+        .iter()
+        .map(|term| {
+            let mut conjugated = TensorProduct::new(
+                term.coefficient(),
+                term.factors().iter().map(conjugate).collect(),
+            );
+            for symmetry in term.symmetries() {
+                conjugated.add_symmetry(symmetry.clone());
+            }
+            conjugated
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::verify_identity;
+
+    #[test]
+    fn test_van_der_waerden_conventions() {
+        let psi = Tensor::new("psi", vec![TensorIndex::undotted("A", 0)]);
+        let chi = Tensor::new("chi", vec![TensorIndex::undotted("A", 0)]);
+        let raised = raise_spinor(&psi, 0).unwrap();
+        assert!(raise_spinor(&raised.factors()[0], 0).is_err());
+        assert!(raise_spinor(&Tensor::new("v", vec![TensorIndex::new("a", 0)]), 0).is_err());
+
+        let mut upper = psi.clone();
+        upper.indices_mut()[0].set_contravariant(true);
+        assert_eq!(
+            lower_spinor(&upper, 0).unwrap().to_string(),
+            "psi_^A' epsilon__A' _A"
+        );
+
+        // ψ^A χ_A = -ψ_A χ^A, once the dummies of the second are exchanged
+        let up_down = raised.multiply(&TensorProduct::from(chi.clone()));
+        let down_up = TensorProduct::from(psi.clone()).multiply(&raise_spinor(&chi, 0).unwrap());
+        let swap_dummies = |tensor: &Tensor| {
+            let mut swapped = tensor.clone();
+            for index in swapped.indices_mut() {
+                let name = if index.name() == "A" { "A'" } else { "A" };
+                *index = index.with_name(name);
+            }
+            swapped
+        };
+        let down_up = TensorProduct::new(
+            -Rational::ONE,
+            down_up.factors().iter().map(swap_dummies).collect(),
+        );
+        assert!(verify_identity(
+            &TensorExpression::from(up_down),
+            &TensorExpression::from(down_up)
+        )
+        .unwrap());
+
+        let conjugated = conjugate_expression(&TensorExpression::from(raised));
+        let factors = conjugated.terms()[0].factors();
+        assert_eq!(factors[0].name(), "epsilon");
+        assert_eq!(factors[1].name(), "psibar");
+        assert!(factors
+            .iter()
+            .all(|factor| factor.indices()[0].class() == IndexClass::Dotted));
+    }
+}
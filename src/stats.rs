@@ -0,0 +1,227 @@
+//! Timing and size statistics of canonicalization runs
+//!
+//! `canonicalize_with_stats` records how long each phase took, so users can
+//! tell whether their bottleneck is building the symmetry group or searching
+//! it. Statistics of many runs can be summed with `+=`. Runs too long to wait
+//! for can be followed as they go through the [`ProgressEvent`]s passed to a
+//! [`ProgressCallback`](crate::config::ProgressCallback).
+
+use crate::index::TensorIndex;
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::Duration;
+
+/// Wall time per phase and search sizes of one or more canonicalizations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CanonicalizationStats {
+    /// Trivial-case checks (zero tensors, rank at most one)
+    pub validation: Duration,
+    /// Construction of the stabilizer chain of the slot symmetry group
+    pub bsgs: Duration,
+    /// Enumeration of the slot symmetry group, which only happens for groups
+    /// moving indices between slots of different classes
+    pub enumeration: Duration,
+    /// Evaluation of the candidate forms and selection of the minimum
+    pub evaluation: Duration,
+    /// Order of the slot symmetry group
+    pub group_order: usize,
+    /// Number of candidate forms compared
+    pub candidates: usize,
+}
+
+impl CanonicalizationStats {
+    /// Returns the total wall time over all phases
+    pub fn total(&self) -> Duration {
+        self.validation + self.bsgs + self.enumeration + self.evaluation
+    }
+}
+
+impl AddAssign for CanonicalizationStats {
+    fn add_assign(&mut self, other: Self) {
+        self.validation += other.validation;
+        self.bsgs += other.bsgs;
+        self.enumeration += other.enumeration;
+        self.evaluation += other.evaluation;
+        self.group_order += other.group_order;
+        self.candidates += other.candidates;
+    }
+}
+
+impl fmt::Display for CanonicalizationStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "validation   {:>12?}", self.validation)?;
+        writeln!(f, "bsgs         {:>12?}", self.bsgs)?;
+        writeln!(
+            f,
+            "enumeration  {:>12?}  ({} elements)",
+            self.enumeration, self.group_order
+        )?;
+        writeln!(
+            f,
+            "evaluation   {:>12?}  ({} candidates)",
+            self.evaluation, self.candidates
+        )?;
+        write!(f, "total        {:>12?}", self.total())
+    }
+}
+
+/// A step of a canonicalization, reported to a progress callback
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// The stabilizer chain of the slot symmetry group was built
+    GroupBuilt {
+        /// Order of the group, saturating at `usize::MAX`
+        order: usize,
+        /// Number of levels of the stabilizer chain
+        base_length: usize,
+        /// Whether the group is searched by enumerating its elements rather
+        /// than by descending the chain
+        enumerated: bool,
+    },
+    /// A level of the stabilizer chain was descended, fixing one more slot
+    LevelDescended {
+        /// The slot filled, counting from zero
+        level: usize,
+        /// Number of levels in all
+        levels: usize,
+        /// Number of partial forms tied for the minimum and kept
+        frontier: usize,
+        /// Candidate slot fillings compared so far
+        explored: usize,
+        /// The minimal indices of the slots filled so far
+        best: Vec<TensorIndex>,
+    },
+    /// Elements of an enumerated group were evaluated
+    CandidatesExplored {
+        /// Candidates evaluated so far
+        explored: usize,
+        /// Order of the group, the number of candidates in all
+        total: usize,
+        /// The minimal arrangement of the indices found so far
+        best: Option<Vec<TensorIndex>>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canonicalization::canonicalize_with_stats;
+    use crate::symmetry::Symmetry;
+    use crate::{Tensor, TensorIndex};
+
+    #[test]
+    fn test_stats_accumulate_and_report() {
+        let mut s = Tensor::new(
+            "S",
+            vec![
+                TensorIndex::new("c", 0),
+                TensorIndex::new("b", 1),
+                TensorIndex::new("a", 2),
+            ],
+        );
+        s.add_symmetry(Symmetry::symmetric(vec![0, 1, 2]));
+        let (_, first) = canonicalize_with_stats(&s).unwrap();
+        assert_eq!(first.group_order, 6);
+        assert_eq!(first.candidates, 6);
+
+        // Trivial tensors stop after validation
+        let v = Tensor::new("v", vec![TensorIndex::new("a", 0)]);
+        let (_, trivial) = canonicalize_with_stats(&v).unwrap();
+        assert_eq!(trivial.group_order, 0);
+
+        let mut total = first;
+        total += trivial;
+        assert_eq!(total.group_order, 6);
+        assert!(total.total() >= first.total());
+        let report = total.to_string();
+        assert!(report.contains("(6 elements)") && report.lines().count() == 5);
+    }
+
+    #[test]
+    fn test_progress_events() {
+        use crate::canonicalization::canonicalize_with_config;
+        use crate::config::CanonicalizationConfig;
+        use crate::stats::ProgressEvent;
+        use crate::IndexClass;
+        use std::sync::{Arc, Mutex};
+
+        let record = || {
+            let events = Arc::new(Mutex::new(Vec::new()));
+            let seen = events.clone();
+            let config = CanonicalizationConfig::new().with_progress(move |event| {
+                seen.lock().unwrap().push(event.clone());
+            });
+            (events, config)
+        };
+
+        // Indices of two classes make the 7! elements be enumerated
+        let mut t = Tensor::new(
+            "T",
+            ["g", "f", "e", "d", "c", "b", "a"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| {
+                    let index = TensorIndex::new(n, i);
+                    if i < 3 {
+                        index.with_class(IndexClass::Frame)
+                    } else {
+                        index
+                    }
+                })
+                .collect(),
+        );
+        t.add_symmetry(Symmetry::symmetric((0..7).collect()));
+        let (events, config) = record();
+        let canonical = canonicalize_with_config(&t, &config).unwrap();
+        let events = events.lock().unwrap();
+        assert!(matches!(
+            events[0],
+            ProgressEvent::GroupBuilt {
+                order: 5040,
+                enumerated: true,
+                ..
+            }
+        ));
+        let explored: Vec<usize> = events[1..]
+            .iter()
+            .filter_map(|event| match event {
+                ProgressEvent::CandidatesExplored { explored, .. } => Some(*explored),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(explored, vec![1024, 2048, 3072, 4096, 5040]);
+        assert_eq!(
+            events.last(),
+            Some(&ProgressEvent::CandidatesExplored {
+                explored: 5040,
+                total: 5040,
+                best: Some(canonical.indices().to_vec()),
+            })
+        );
+
+        // The chain is descended one slot per event
+        let mut s = Tensor::new(
+            "S",
+            ["c", "b", "a"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        );
+        s.add_symmetry(Symmetry::symmetric(vec![0, 1, 2]));
+        let (events, config) = record();
+        let canonical = canonicalize_with_config(&s, &config).unwrap();
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[3],
+            ProgressEvent::LevelDescended {
+                level: 2,
+                levels: 3,
+                frontier: 1,
+                explored: 6,
+                best: canonical.indices().to_vec(),
+            }
+        );
+    }
+}
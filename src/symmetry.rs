@@ -3,11 +3,23 @@
 //! This module defines various types of tensor symmetries and provides
 //! methods for checking permutation validity and calculating sign changes.
 
+use crate::canonicalization::{Permutation, PermutationAction};
+use crate::error::{validate_permutation, ButlerPortugalError, Result};
 use crate::index::TensorIndex;
-use std::collections::HashSet;
+use crate::presets::has_riemann_symmetries;
+use crate::schreier_sims::compose_permutations;
+use crate::tensor::Tensor;
+use crate::young_tableaux::{tableau_columns, Shape, StandardTableau};
+use std::collections::{HashMap, HashSet};
+
+/// A permutation of slots together with the sign it picks up
+type SignedPermutation = (Vec<usize>, i32);
+
+/// Upper bound on the number of elements of a closed `Custom` symmetry
+const MAX_CUSTOM_ELEMENTS: usize = 100_000;
 
 /// Represents different types of tensor symmetries
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Symmetry {
     /// Symmetric in a group of indices
     Symmetric { indices: Vec<usize> },
@@ -17,6 +29,13 @@ pub enum Symmetry {
     SymmetricPairs { pairs: Vec<(usize, usize)> },
     /// Cyclic symmetry in a group of indices
     Cyclic { indices: Vec<usize> },
+    /// Cyclic symmetry where a shift by `k` places picks up the sign `(-1)^k`
+    AntiCyclic { indices: Vec<usize> },
+    /// Mixed symmetry of a Young diagram: antisymmetric in each column, with
+    /// columns of equal height exchangeable as blocks
+    ///
+    /// Each column lists its slots from top to bottom.
+    YoungShape { columns: Vec<Vec<usize>> },
     /// Custom symmetry with explicit permutation rules
     Custom {
         valid_permutations: Vec<Vec<usize>>,
@@ -86,6 +105,93 @@ impl Symmetry {
         Self::Cyclic { indices }
     }
 
+    /// Creates anti-cyclic symmetry, where every cyclic shift by one place flips the sign
+    ///
+    /// Shifting an odd number of indices all the way round gives `T = -T`, so
+    /// an anti-cyclic tensor with an odd number of indices vanishes.
+    ///
+    /// # Arguments
+    /// * `indices` - Vector of indices with anti-cyclic symmetry
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::Symmetry;
+    ///
+    /// let acyc = Symmetry::anti_cyclic(vec![0, 1, 2, 3]); // T_abcd = -T_bcda = T_cdab
+    /// assert_eq!(acyc.permutation_sign(&[1, 2, 3, 0]), -1);
+    /// assert_eq!(acyc.permutation_sign(&[2, 3, 0, 1]), 1);
+    /// ```
+    pub fn anti_cyclic(indices: Vec<usize>) -> Self {
+        Self::AntiCyclic { indices }
+    }
+
+    /// Creates the mixed symmetry of a Young diagram, filling its columns with
+    /// consecutive slots from left to right
+    ///
+    /// The shape lists row lengths. Shape `[2, 2]` fills its columns with
+    /// slots `[0, 1]` and `[2, 3]`, which are exactly the monoterm symmetries
+    /// of the Riemann tensor.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::young_tableaux::Shape;
+    /// use butler_portugal::Symmetry;
+    ///
+    /// let sym = Symmetry::young_shape(&Shape(vec![2, 2]));
+    /// assert_eq!(sym.permutation_sign(&[1, 0, 2, 3]), -1); // R_abcd = -R_bacd
+    /// assert_eq!(sym.permutation_sign(&[2, 3, 0, 1]), 1); // R_abcd = R_cdab
+    /// assert_eq!(sym.permutation_sign(&[0, 2, 1, 3]), 0);
+    /// ```
+    pub fn young_shape(shape: &Shape) -> Self {
+        let mut next = 0;
+        let columns = (0..shape.cols())
+            .map(|j| {
+                let height = shape.0.iter().filter(|&&len| len > j).count();
+                next += height;
+                (next - height..next).collect()
+            })
+            .collect();
+        Self::YoungShape { columns }
+    }
+
+    /// Creates the mixed symmetry of a tableau's shape, placing entry `k` on slot `k - 1`
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::young_tableaux::{Shape, StandardTableau};
+    /// use butler_portugal::Symmetry;
+    ///
+    /// let tableau = StandardTableau::new(Shape(vec![2, 1]), vec![vec![1, 2], vec![3]]).unwrap();
+    /// let sym = Symmetry::from_tableau(&tableau);
+    /// assert_eq!(sym, Symmetry::YoungShape { columns: vec![vec![0, 2], vec![1]] });
+    /// assert_eq!(sym.tableau(), Some(tableau));
+    /// ```
+    pub fn from_tableau(tableau: &StandardTableau) -> Self {
+        Self::YoungShape {
+            columns: tableau_columns(tableau),
+        }
+    }
+
+    /// Returns the standard tableau of a `YoungShape` symmetry, if its columns
+    /// fill slots `0..n` in a standard way
+    ///
+    /// This is the tableau whose Young symmetrizer projects onto the symmetry.
+    pub fn tableau(&self) -> Option<StandardTableau> {
+        let Self::YoungShape { columns } = self else {
+            return None;
+        };
+        let rows: Vec<Vec<usize>> = (0..columns.first().map_or(0, Vec::len))
+            .map(|i| {
+                columns
+                    .iter()
+                    .filter_map(|column| column.get(i))
+                    .map(|&slot| slot + 1)
+                    .collect()
+            })
+            .collect();
+        StandardTableau::new(Shape(rows.iter().map(Vec::len).collect()), rows)
+    }
+
     /// Creates custom symmetry with explicit rules
     ///
     /// # Arguments
@@ -98,6 +204,213 @@ impl Symmetry {
         }
     }
 
+    /// Creates custom symmetry from permutations written in the given convention
+    ///
+    /// The permutations are stored in the crate's
+    /// [`Slots`](PermutationAction::Slots) convention.
+    ///
+    /// # Errors
+    /// Returns an `InvalidPermutation` error if an entry is not a permutation.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::canonicalization::PermutationAction;
+    /// use butler_portugal::Symmetry;
+    ///
+    /// let sym = Symmetry::custom_with(vec![vec![1, 2, 0]], vec![1], PermutationAction::Labels)?;
+    /// assert_eq!(sym, Symmetry::custom(vec![vec![2, 0, 1]], vec![1]));
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn custom_with(
+        valid_permutations: Vec<Vec<usize>>,
+        signs: Vec<i32>,
+        action: PermutationAction,
+    ) -> Result<Self> {
+        let valid_permutations = valid_permutations
+            .into_iter()
+            .map(|perm| action.to_slots(&perm).map(Vec::from))
+            .collect::<Result<_>>()?;
+        Ok(Self::custom(valid_permutations, signs))
+    }
+
+    /// Infers a symmetry from a chain of equalities between index orderings
+    ///
+    /// The statement lists forms of one tensor separated by `=`, each with an
+    /// optional sign and an optional name, e.g. `T_{abc} = T_{bca} = -T_{acb}`.
+    /// Labels are single characters, or whitespace-separated words if a form
+    /// contains spaces (`T_{mu nu}`). Every form is compared with the first
+    /// one, and the signed permutations so obtained are closed into the full
+    /// group, returned as a `Custom` symmetry.
+    ///
+    /// # Errors
+    /// Returns an `InvalidSymmetry` error if the forms do not rearrange the same
+    /// distinct labels, and a `MathematicalError` if the equalities force the
+    /// tensor to vanish.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::{canonicalize, Symmetry, Tensor, TensorIndex};
+    ///
+    /// // Cyclic and antisymmetric in the last two slots: totally antisymmetric
+    /// let sym = Symmetry::from_equalities("T_{abc} = T_{bca} = -T_{acb}")?;
+    /// let mut t = Tensor::new(
+    ///     "T",
+    ///     vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1), TensorIndex::new("c", 2)],
+    /// );
+    /// t.add_symmetry(sym);
+    /// assert_eq!(canonicalize(&t)?.to_string(), "-T__a _b _c");
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn from_equalities(statement: &str) -> Result<Self> {
+        let forms: Vec<(i32, Vec<&str>)> = statement.split('=').map(parse_form).collect();
+        let Some((reference_sign, reference)) = forms.first() else {
+            return Err(ButlerPortugalError::InvalidSymmetry(
+                "No forms in equality statement".to_string(),
+            ));
+        };
+        if reference.is_empty() {
+            return Err(ButlerPortugalError::InvalidSymmetry(format!(
+                "No index labels in {statement:?}"
+            )));
+        }
+        if reference.iter().collect::<HashSet<_>>().len() != reference.len() {
+            return Err(ButlerPortugalError::InvalidSymmetry(format!(
+                "Repeated label in {}",
+                reference.concat()
+            )));
+        }
+        let mut generators = Vec::with_capacity(forms.len() - 1);
+        for (sign, labels) in &forms[1..] {
+            // new[k] = old[perm[k]]: slot k of this form holds the label of reference slot perm[k]
+            let perm: Option<Vec<usize>> = labels
+                .iter()
+                .map(|label| reference.iter().position(|r| r == label))
+                .collect();
+            let perm = perm
+                .filter(|perm| perm.len() == reference.len())
+                .filter(|perm| perm.iter().collect::<HashSet<_>>().len() == perm.len())
+                .ok_or_else(|| {
+                    ButlerPortugalError::InvalidSymmetry(format!(
+                        "{} does not rearrange the labels {}",
+                        labels.concat(),
+                        reference.concat()
+                    ))
+                })?;
+            generators.push((perm, sign * reference_sign));
+        }
+        let elements = signed_closure(&generators, reference.len())?;
+        let (valid_permutations, signs) = elements.into_iter().unzip();
+        Ok(Self::custom(valid_permutations, signs))
+    }
+
+    /// Checks that the symmetry describes a consistent signed group on `rank` slots
+    ///
+    /// Only `Custom` symmetries carry explicit data that can be inconsistent:
+    /// every entry must be a permutation of `rank` slots with sign `±1`, and
+    /// the listed pairs must extend to a sign homomorphism on the group they
+    /// generate, i.e. no permutation may be reachable with both signs. The
+    /// list does not need to be closed already.
+    ///
+    /// # Errors
+    /// Returns an `InvalidSymmetry` error describing the first inconsistency.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::Symmetry;
+    ///
+    /// // A transposition squares to the identity, so it cannot carry the sign -1 twice over
+    /// assert!(Symmetry::custom(vec![vec![1, 0]], vec![-1]).validate(2).is_ok());
+    /// assert!(Symmetry::custom(vec![vec![1, 0], vec![1, 0]], vec![1, -1]).validate(2).is_err());
+    /// assert!(Symmetry::custom(vec![vec![1, 2, 0]], vec![-1]).validate(3).is_err());
+    /// ```
+    pub fn validate(&self, rank: usize) -> Result<()> {
+        if let Self::YoungShape { columns } = self {
+            return validate_columns(columns, rank);
+        }
+        let Some(generators) = self.signed_permutations(rank)? else {
+            return Ok(());
+        };
+        signed_closure(&generators, rank)
+            .map(|_| ())
+            .map_err(|e| match e {
+                ButlerPortugalError::MathematicalError(msg) => {
+                    ButlerPortugalError::InvalidSymmetry(format!("Signs are inconsistent: {msg}"))
+                }
+                other => other,
+            })
+    }
+
+    /// Returns the `Custom` symmetry listing every element of the group its
+    /// permutations generate, with their signs
+    ///
+    /// This lets a custom symmetry be specified by a minimal set of signed
+    /// generators. Other variants are returned unchanged.
+    ///
+    /// # Errors
+    /// Returns a `MathematicalError` if a permutation is reachable with both
+    /// signs, which means every tensor with this symmetry vanishes, and an
+    /// `InvalidSymmetry` error for malformed data (see [`validate`](Self::validate)).
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::Symmetry;
+    ///
+    /// // An antisymmetric 3-cycle and transposition generate all of S_3 with the parity sign
+    /// let generators = Symmetry::custom(vec![vec![1, 0, 2], vec![1, 2, 0]], vec![-1, 1]);
+    /// let group = generators.closure()?;
+    /// assert_eq!(group.permutation_sign(&[2, 1, 0]), -1);
+    /// assert_eq!(group.permutation_sign(&[2, 0, 1]), 1);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn closure(&self) -> Result<Self> {
+        let degree = match self {
+            Self::Custom {
+                valid_permutations, ..
+            } => valid_permutations.first().map_or(0, Vec::len),
+            _ => return Ok(self.clone()),
+        };
+        let Some(generators) = self.signed_permutations(degree)? else {
+            return Ok(self.clone());
+        };
+        let (valid_permutations, signs) = signed_closure(&generators, degree)?.into_iter().unzip();
+        Ok(Self::custom(valid_permutations, signs))
+    }
+
+    /// Returns the checked (permutation, sign) pairs of a `Custom` symmetry on
+    /// `rank` slots, or `None` for the other variants
+    fn signed_permutations(&self, rank: usize) -> Result<Option<Vec<SignedPermutation>>> {
+        let Self::Custom {
+            valid_permutations,
+            signs,
+        } = self
+        else {
+            return Ok(None);
+        };
+        if valid_permutations.len() != signs.len() {
+            return Err(ButlerPortugalError::InvalidSymmetry(format!(
+                "{} permutations but {} signs",
+                valid_permutations.len(),
+                signs.len()
+            )));
+        }
+        for (perm, &sign) in valid_permutations.iter().zip(signs) {
+            validate_permutation(perm, rank)
+                .map_err(|e| ButlerPortugalError::InvalidSymmetry(e.to_string()))?;
+            if sign != 1 && sign != -1 {
+                return Err(ButlerPortugalError::InvalidSymmetry(format!(
+                    "Sign {sign} of {perm:?} is not ±1"
+                )));
+            }
+        }
+        Ok(Some(
+            valid_permutations
+                .iter()
+                .cloned()
+                .zip(signs.iter().copied())
+                .collect(),
+        ))
+    }
+
     /// Returns the sign change when swapping two specific indices
     ///
     /// # Arguments
@@ -107,7 +420,8 @@ impl Symmetry {
     /// # Returns
     /// * `1` if the swap preserves sign or indices are not covered by this symmetry
     /// * `-1` if the swap changes sign
-    /// * `0` if the swap makes the tensor zero
+    /// * `0` if the swap is not in the group of the symmetry, as for two
+    ///   slots of a cyclic symmetry on three or more slots
     pub fn sign_change_for_swap(&self, i: usize, j: usize) -> i32 {
         if i == j {
             return 1;
@@ -134,30 +448,11 @@ impl Symmetry {
                 }
                 1 // Not a pair exchange
             }
-            Self::Cyclic { indices } => {
-                if indices.contains(&i) && indices.contains(&j) {
-                    // For cyclic symmetry, adjacent swaps have sign +1
-                    // Non-adjacent swaps need to be computed based on cycle structure
-                    let Some(pos_i) = indices.iter().position(|&x| x == i) else {
-                        return 1;
-                    };
-                    let Some(pos_j) = indices.iter().position(|&x| x == j) else {
-                        return 1;
-                    };
-                    let distance = (pos_i as i32 - pos_j as i32).abs();
-                    if distance == 1 || distance == indices.len() as i32 - 1 {
-                        1 // Adjacent in cycle
-                    } else {
-                        // For general cyclic permutations, compute based on parity
-                        if (pos_i as i32 - pos_j as i32).abs() % 2 == 1 {
-                            -1
-                        } else {
-                            1
-                        }
-                    }
-                } else {
-                    1
-                }
+            Self::Cyclic { .. } | Self::AntiCyclic { .. } | Self::YoungShape { .. } => {
+                // A swap is a shift only for two indices, and a column move only within a column
+                let mut perm = (0..std::cmp::max(i, j) + 1).collect::<Vec<_>>();
+                perm.swap(i, j);
+                self.permutation_sign(&perm)
             }
             Self::Custom {
                 valid_permutations,
@@ -178,86 +473,130 @@ impl Symmetry {
 
     /// Returns the sign of a complete permutation
     ///
+    /// The permutation is reduced to the rearrangement it induces on this
+    /// symmetry's own slots: the slots that receive their indices, taken in
+    /// slot order, stand in for the symmetry's slots in sorted order. The
+    /// result is the exact sign of that rearrangement in the symmetry's group,
+    /// or `0` if it is not a member. Slots beyond the end of the permutation
+    /// are fixed. `Custom` symmetries are looked up in their explicit list,
+    /// which should be closed (see [`closure`](Self::closure)).
+    ///
     /// # Arguments
     /// * `permutation` - The permutation to check
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::Symmetry;
+    ///
+    /// let cyc = Symmetry::cyclic(vec![0, 1, 2, 3]);
+    /// assert_eq!(cyc.permutation_sign(&[2, 3, 0, 1]), 1);
+    /// assert_eq!(cyc.permutation_sign(&[1, 0, 2, 3]), 0);
+    /// // Rearranging other slots leaves the sign alone
+    /// let pairs = Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]);
+    /// assert_eq!(pairs.permutation_sign(&[0, 1, 2, 3, 5, 4]), 1);
+    /// assert_eq!(pairs.permutation_sign(&[0, 2, 1, 3]), 0);
+    /// ```
     pub fn permutation_sign(&self, permutation: &[usize]) -> i32 {
-        match self {
-            // merge symmetric and symmetric pairs into one case
-            Self::Symmetric { indices: _ } | Self::SymmetricPairs { pairs: _ } => {
-                // Symmetric groups always have sign +1
-                1
-            }
-            Self::Antisymmetric { indices } => {
-                // Calculate sign based on parity of permutation within the antisymmetric group
-                self.antisymmetric_permutation_sign(permutation, indices)
-            }
-            Self::Cyclic { indices } => {
-                // Calculate sign for cyclic permutation
-                self.cyclic_permutation_sign(permutation, indices)
-            }
-            Self::Custom {
-                valid_permutations,
-                signs,
-            } => {
-                if let Some(pos) = valid_permutations.iter().position(|p| *p == permutation) {
-                    signs[pos]
-                } else {
-                    0 // Invalid permutation
-                }
-            }
+        if let Self::Custom {
+            valid_permutations,
+            signs,
+        } = self
+        {
+            return valid_permutations
+                .iter()
+                .position(|p| *p == permutation)
+                .map_or(0, |pos| signs[pos]);
         }
+        induced_permutation(permutation, &self.slots()).map_or(0, |q| self.induced_sign(&q))
     }
 
-    /// Calculates sign for antisymmetric permutation
-    fn antisymmetric_permutation_sign(&self, permutation: &[usize], indices: &[usize]) -> i32 {
-        // Extract the sub-permutation for the antisymmetric indices
-        let mut sub_perm = Vec::new();
-        let mut index_map = std::collections::HashMap::new();
-
-        for (new_pos, &orig_pos) in indices.iter().enumerate() {
-            index_map.insert(orig_pos, new_pos);
-        }
-
-        for &perm_val in permutation {
-            if let Some(&mapped) = index_map.get(&perm_val) {
-                sub_perm.push(mapped);
-            }
+    /// Returns the slots this symmetry acts on, in the order it lists them
+    pub(crate) fn slots(&self) -> Vec<usize> {
+        match self {
+            Self::Symmetric { indices }
+            | Self::Antisymmetric { indices }
+            | Self::Cyclic { indices }
+            | Self::AntiCyclic { indices } => indices.clone(),
+            Self::SymmetricPairs { pairs } => pairs.iter().flat_map(|&(a, b)| [a, b]).collect(),
+            Self::YoungShape { columns } => columns.concat(),
+            Self::Custom {
+                valid_permutations, ..
+            } => (0..valid_permutations.first().map_or(0, Vec::len)).collect(),
         }
-
-        // Calculate parity of the sub-permutation
-        permutation_parity(&sub_perm)
     }
 
-    /// Calculates sign for cyclic permutation
-    fn cyclic_permutation_sign(&self, permutation: &[usize], indices: &[usize]) -> i32 {
-        // For cyclic symmetry, only cyclic permutations of the specified indices are allowed
-        let n = indices.len();
-        if n <= 1 {
-            return 1;
-        }
-
-        // Extract the sub-permutation: for each cyclic index, where does it go?
-        let mut sub_perm = vec![0; n];
-        for (i, &idx_pos) in indices.iter().enumerate() {
-            if idx_pos >= permutation.len() {
-                return 0; // Invalid
-            }
-            let target = permutation[idx_pos];
-
-            // Find where this target appears in the cyclic indices
-            if let Some(target_pos) = indices.iter().position(|&x| x == target) {
-                sub_perm[i] = target_pos;
-            } else {
-                return 0; // Not a permutation within the cyclic group
-            }
+    /// Returns the sign of a rearrangement of this symmetry's slots, given by
+    /// positions in [`slots`](Self::slots), or `0` if it is not in the group
+    fn induced_sign(&self, q: &[usize]) -> i32 {
+        match self {
+            Self::Symmetric { .. } => 1,
+            Self::Antisymmetric { .. } => permutation_parity(q),
+            Self::Cyclic { .. } => i32::from(rotation_shift(q).is_some()),
+            Self::AntiCyclic { .. } => match rotation_shift(q) {
+                Some(shift) if shift % 2 == 1 => -1,
+                Some(_) => 1,
+                None => 0,
+            },
+            // Pairs must land on pairs, in either order
+            Self::SymmetricPairs { .. } => i32::from(
+                q.chunks(2)
+                    .all(|pair| pair.len() == 2 && pair[0] / 2 == pair[1] / 2),
+            ),
+            Self::YoungShape { columns } => young_permutation_sign(q, columns),
+            Self::Custom { .. } => self.permutation_sign(q),
         }
+    }
 
-        // Check if it's a valid cyclic permutation and return sign +1
-        // Cyclic permutations in our implementation always have sign +1
-        if is_cyclic_permutation(&sub_perm) {
-            1
-        } else {
-            0 // Invalid
+    /// Returns the same symmetry acting on slots moved `offset` places to the right
+    ///
+    /// Used when new slots are prepended to a tensor, e.g. by a derivative.
+    /// Explicit permutations of `Custom` symmetries fix the new slots.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::Symmetry;
+    ///
+    /// let sym = Symmetry::antisymmetric(vec![0, 1]).shifted(1);
+    /// assert_eq!(sym, Symmetry::antisymmetric(vec![1, 2]));
+    /// ```
+    pub fn shifted(&self, offset: usize) -> Self {
+        let shift = |indices: &[usize]| indices.iter().map(|&i| i + offset).collect();
+        match self {
+            Self::Symmetric { indices } => Self::Symmetric {
+                indices: shift(indices),
+            },
+            Self::Antisymmetric { indices } => Self::Antisymmetric {
+                indices: shift(indices),
+            },
+            Self::SymmetricPairs { pairs } => Self::SymmetricPairs {
+                pairs: pairs
+                    .iter()
+                    .map(|&(a, b)| (a + offset, b + offset))
+                    .collect(),
+            },
+            Self::Cyclic { indices } => Self::Cyclic {
+                indices: shift(indices),
+            },
+            Self::AntiCyclic { indices } => Self::AntiCyclic {
+                indices: shift(indices),
+            },
+            Self::YoungShape { columns } => Self::YoungShape {
+                columns: columns.iter().map(|column| shift(column)).collect(),
+            },
+            Self::Custom {
+                valid_permutations,
+                signs,
+            } => Self::Custom {
+                valid_permutations: valid_permutations
+                    .iter()
+                    .map(|perm| {
+                        (0..offset)
+                            .chain(perm.iter().map(|&i| i + offset))
+                            .collect()
+                    })
+                    .collect(),
+                signs: signs.clone(),
+            },
         }
     }
 
@@ -266,22 +605,47 @@ impl Symmetry {
         self.permutation_sign(permutation) != 0
     }
 
-    /// Checks if swapping two indices makes the tensor zero
+    /// Checks if the symmetry alone makes the tensor zero
+    ///
+    /// An antisymmetric set of slots vanishes if two of them carry the same
+    /// index, or if it holds more indices of one class than their dimension.
+    /// An anticyclic set vanishes if it has an odd number of slots, or if one
+    /// index fills all of them.
     pub fn makes_tensor_zero(&self, indices: &[TensorIndex]) -> bool {
         match self {
             Self::Antisymmetric {
                 indices: sym_indices,
             } => {
                 // Check if any two indices in the antisymmetric group are equal
-                let names: Vec<&str> = sym_indices
-                    .iter()
-                    .filter_map(|&i| indices.get(i))
-                    .map(|idx| idx.name())
-                    .collect();
+                let slots: Vec<&TensorIndex> =
+                    sym_indices.iter().filter_map(|&i| indices.get(i)).collect();
+                let names: Vec<&str> = slots.iter().map(|idx| idx.name()).collect();
 
                 let unique_names: HashSet<&str> = names.iter().cloned().collect();
-                names.len() != unique_names.len()
+                names.len() != unique_names.len() || exceeds_dimension(&slots)
+            }
+            Self::AntiCyclic {
+                indices: sym_indices,
+            } => {
+                if sym_indices.len() % 2 == 1 {
+                    return sym_indices.len() > 1;
+                }
+                // A single shift flips the sign, so a name filling every
+                // slot makes the tensor its own negative
+                let mut names = sym_indices
+                    .iter()
+                    .filter_map(|&i| indices.get(i).map(TensorIndex::name));
+                names
+                    .next()
+                    .is_some_and(|first| names.all(|name| name == first))
             }
+            Self::YoungShape { columns } => columns.iter().any(|column| {
+                let slots: Vec<&TensorIndex> =
+                    column.iter().filter_map(|&i| indices.get(i)).collect();
+                let names: Vec<&str> = slots.iter().map(|idx| idx.name()).collect();
+                names.iter().collect::<HashSet<_>>().len() != names.len()
+                    || exceeds_dimension(&slots)
+            }),
             _ => false,
         }
     }
@@ -305,6 +669,187 @@ impl Symmetry {
     }
 }
 
+/// Checks whether antisymmetric slots hold more indices of one class than
+/// the dimension those indices range over
+fn exceeds_dimension(slots: &[&TensorIndex]) -> bool {
+    slots.iter().any(|slot| {
+        slot.dimension().is_some_and(|dimension| {
+            slots
+                .iter()
+                .filter(|other| other.class() == slot.class())
+                .count()
+                > dimension
+        })
+    })
+}
+
+/// Splits one side of an equality into its sign and index labels
+///
+/// A name is everything before the first `_` or `^`; braces are ignored.
+fn parse_form(form: &str) -> (i32, Vec<&str>) {
+    let form = form.trim();
+    let (sign, form) = match form.strip_prefix('-') {
+        Some(rest) => (-1, rest.trim_start()),
+        None => (1, form.strip_prefix('+').unwrap_or(form).trim_start()),
+    };
+    let labels = form
+        .find(['_', '^'])
+        .map_or(form, |start| &form[start + 1..])
+        .trim_matches(|c: char| c == '{' || c == '}' || c.is_whitespace());
+    let labels = if labels.contains(char::is_whitespace) {
+        labels.split_whitespace().collect()
+    } else {
+        labels
+            .char_indices()
+            .map(|(i, c)| &labels[i..i + c.len_utf8()])
+            .collect()
+    };
+    (sign, labels)
+}
+
+/// Closes signed permutations of `degree` slots into the group they generate
+///
+/// Returns a `MathematicalError` if some permutation is reached with both
+/// signs, which forces the tensor to vanish, and a `ComputationError` if the
+/// group exceeds [`MAX_CUSTOM_ELEMENTS`] elements.
+fn signed_closure(
+    generators: &[SignedPermutation],
+    degree: usize,
+) -> Result<Vec<SignedPermutation>> {
+    let identity: Vec<usize> = (0..degree).collect();
+    let mut signs: HashMap<Vec<usize>, i32> = HashMap::new();
+    signs.insert(identity.clone(), 1);
+    let mut elements = vec![(identity.clone(), 1)];
+    let mut queue = vec![identity];
+    while let Some(current) = queue.pop() {
+        let sign = signs[&current];
+        for (generator, generator_sign) in generators {
+            let next = compose_permutations(&current, generator).into_images();
+            let next_sign = sign * generator_sign;
+            match signs.get(&next) {
+                Some(&existing) if existing != next_sign => {
+                    return Err(ButlerPortugalError::MathematicalError(format!(
+                        "Permutation {next:?} is reached with both signs, so the tensor vanishes"
+                    )));
+                }
+                Some(_) => {}
+                None => {
+                    if elements.len() >= MAX_CUSTOM_ELEMENTS {
+                        return Err(ButlerPortugalError::ComputationError(format!(
+                            "Symmetry group has more than {MAX_CUSTOM_ELEMENTS} elements"
+                        )));
+                    }
+                    signs.insert(next.clone(), next_sign);
+                    elements.push((next.clone(), next_sign));
+                    queue.push(next);
+                }
+            }
+        }
+    }
+    Ok(elements)
+}
+
+/// Returns `k` if the rearrangement rotates its positions by `k` places,
+/// i.e. position `i` receives the index of position `(i + k) % n`, and
+/// `None` if it is not a rotation
+fn rotation_shift(q: &[usize]) -> Option<usize> {
+    let n = q.len();
+    let shift = q.first().copied().unwrap_or(0);
+    q.iter()
+        .enumerate()
+        .all(|(i, &source)| source == (i + shift) % n)
+        .then_some(shift)
+}
+
+/// Returns the rearrangement a permutation induces on the given slots
+///
+/// The slots receiving indices from `slots`, in increasing order, are matched
+/// with `slots` in increasing order. Entry `i` of the result is the position
+/// in `slots` of the index that arrives in the slot matched with `slots[i]`.
+/// Returns `None` if the permutation is not a bijection on these slots.
+fn induced_permutation(permutation: &[usize], slots: &[usize]) -> Option<Vec<usize>> {
+    let position: HashMap<usize, usize> = slots.iter().enumerate().map(|(i, &s)| (s, i)).collect();
+    let len = slots
+        .iter()
+        .map(|&s| s + 1)
+        .max()
+        .map_or(permutation.len(), |end| end.max(permutation.len()));
+    let source = |slot: usize| permutation.get(slot).copied().unwrap_or(slot);
+    let targets: Vec<usize> = (0..len)
+        .filter(|&k| position.contains_key(&source(k)))
+        .collect();
+    if targets.len() != slots.len() {
+        return None;
+    }
+    let mut sorted = slots.to_vec();
+    sorted.sort_unstable();
+    let induced: Vec<usize> = slots
+        .iter()
+        .map(|slot| {
+            let rank = sorted.binary_search(slot).unwrap_or_default();
+            position[&source(targets[rank])]
+        })
+        .collect();
+    let distinct = induced.iter().collect::<HashSet<_>>().len() == induced.len();
+    distinct.then_some(induced)
+}
+
+/// Checks that Young diagram columns are non-empty, weakly decreasing in
+/// height and fill distinct slots below `rank`
+fn validate_columns(columns: &[Vec<usize>], rank: usize) -> Result<()> {
+    if columns.iter().any(Vec::is_empty) {
+        return Err(ButlerPortugalError::InvalidSymmetry(
+            "Young diagram has an empty column".to_string(),
+        ));
+    }
+    if columns.windows(2).any(|pair| pair[0].len() < pair[1].len()) {
+        return Err(ButlerPortugalError::InvalidSymmetry(format!(
+            "Column heights of {columns:?} are not weakly decreasing"
+        )));
+    }
+    let slots: Vec<usize> = columns.iter().flatten().copied().collect();
+    if let Some(&slot) = slots.iter().find(|&&slot| slot >= rank) {
+        return Err(ButlerPortugalError::InvalidSymmetry(format!(
+            "Slot {slot} is out of range for rank {rank}"
+        )));
+    }
+    if slots.iter().collect::<HashSet<_>>().len() != slots.len() {
+        return Err(ButlerPortugalError::InvalidSymmetry(format!(
+            "Repeated slot in {columns:?}"
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the sign of a rearrangement of Young diagram columns, given by
+/// positions in the concatenated columns, or 0 if it is not in the group
+///
+/// Every column must be carried onto a column of the same height; the sign
+/// is the product of the parities of the induced row orders.
+fn young_permutation_sign(q: &[usize], columns: &[Vec<usize>]) -> i32 {
+    let place: Vec<(usize, usize)> = columns
+        .iter()
+        .enumerate()
+        .flat_map(|(c, column)| (0..column.len()).map(move |r| (c, r)))
+        .collect();
+    let mut sign = 1;
+    let mut start = 0;
+    for column in columns {
+        let mut target = None;
+        let mut rows = Vec::with_capacity(column.len());
+        for &source in &q[start..start + column.len()] {
+            let (c, r) = place[source];
+            if *target.get_or_insert(c) != c || columns[c].len() != column.len() {
+                return 0;
+            }
+            rows.push(r);
+        }
+        sign *= permutation_parity(&rows);
+        start += column.len();
+    }
+    sign
+}
+
 /// Calculates the parity (sign) of a permutation
 /// Returns 1 for even permutations, -1 for odd permutations
 fn permutation_parity(permutation: &[usize]) -> i32 {
@@ -335,6 +880,7 @@ fn permutation_parity(permutation: &[usize]) -> i32 {
 }
 
 /// Checks if a permutation is cyclic
+#[allow(dead_code)]
 fn is_cyclic_permutation(permutation: &[usize]) -> bool {
     let n = permutation.len();
     if n <= 1 {
@@ -364,6 +910,72 @@ fn is_cyclic_permutation(permutation: &[usize]) -> bool {
     cycle_count == 1
 }
 
+/// A linear relation among several index arrangements of a tensor
+///
+/// A monoterm [`Symmetry`] relates two arrangements by a sign. A multi-term
+/// symmetry states that a sum of arrangements vanishes, as the first Bianchi
+/// identity `R_{a[bcd]} = 0` does; it is not a group of slot permutations, so
+/// canonicalization cannot apply it.
+/// [`TensorProduct::reduce_multi_term`](crate::expression::TensorProduct::reduce_multi_term)
+/// instead rewrites monomials onto a basis by eliminating one arrangement of
+/// every relation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MultiTermSymmetry {
+    /// The arrangements related by the cyclic shifts of a group of slots sum
+    /// to zero
+    CyclicSum { indices: Vec<usize> },
+}
+
+impl MultiTermSymmetry {
+    /// Creates a vanishing sum over the cyclic shifts of a group of slots
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::symmetry::MultiTermSymmetry;
+    ///
+    /// // T_abc + T_bca + T_cab = 0
+    /// let sum = MultiTermSymmetry::cyclic_sum(vec![0, 1, 2]);
+    /// assert_eq!(sum.terms(3), [vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1]]);
+    /// ```
+    pub fn cyclic_sum(indices: Vec<usize>) -> Self {
+        Self::CyclicSum { indices }
+    }
+
+    /// The first Bianchi identity `R_{abcd} + R_{acdb} + R_{adbc} = 0`
+    pub fn first_bianchi() -> Self {
+        Self::cyclic_sum(vec![1, 2, 3])
+    }
+
+    /// Returns the arrangements summing to zero, as permutations of `rank`
+    /// slots acting as in [`Tensor::permute`], starting with the identity
+    pub fn terms(&self, rank: usize) -> Vec<Permutation> {
+        match self {
+            Self::CyclicSum { indices } => (0..indices.len().max(1))
+                .map(|shift| {
+                    let mut perm: Permutation = (0..rank).collect();
+                    for (k, &slot) in indices.iter().enumerate() {
+                        perm[slot] = indices[(k + shift) % indices.len()];
+                    }
+                    perm
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the multi-term symmetries that follow from the monoterm
+    /// symmetries of a tensor
+    ///
+    /// A rank-4 tensor with the Riemann symmetries (see
+    /// [`has_riemann_symmetries`]) obeys the first Bianchi identity.
+    pub fn implied_by(tensor: &Tensor) -> Vec<Self> {
+        if has_riemann_symmetries(tensor) {
+            vec![Self::first_bianchi()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -414,4 +1026,275 @@ mod tests {
         assert!(is_cyclic_permutation(&[0])); // Single element
         assert!(!is_cyclic_permutation(&[1, 0, 2])); // Two cycles: 0<->1, 2->2
     }
+
+    #[test]
+    fn test_shifted_symmetries() {
+        let pairs = Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]).shifted(2);
+        assert_eq!(pairs, Symmetry::symmetric_pairs(vec![(2, 3), (4, 5)]));
+
+        let custom = Symmetry::custom(vec![vec![1, 0]], vec![-1]).shifted(1);
+        assert_eq!(custom, Symmetry::custom(vec![vec![0, 2, 1]], vec![-1]));
+    }
+
+    #[test]
+    fn test_symmetry_from_equalities() {
+        // Riemann monoterm symmetries stated as equalities
+        let sym = Symmetry::from_equalities("R_{abcd} = -R_{bacd} = -R_{abdc} = R_{cdab}").unwrap();
+        let Symmetry::Custom { signs, .. } = &sym else {
+            panic!("expected a custom symmetry");
+        };
+        assert_eq!(signs.len(), 8);
+        assert_eq!(sym.permutation_sign(&[1, 0, 3, 2]), 1);
+        assert_eq!(sym.permutation_sign(&[2, 3, 1, 0]), -1);
+
+        // Multi-character labels and a vanishing statement
+        let sym = Symmetry::from_equalities("g^{mu nu} = g^{nu mu}").unwrap();
+        assert_eq!(sym.permutation_sign(&[1, 0]), 1);
+        assert!(matches!(
+            Symmetry::from_equalities("S_{ab} = S_{ba} = -S_{ba}"),
+            Err(ButlerPortugalError::MathematicalError(_))
+        ));
+        assert!(Symmetry::from_equalities("T_{abc} = T_{abd}").is_err());
+        assert!(Symmetry::from_equalities("T_{aab} = T_{aba}").is_err());
+    }
+
+    #[test]
+    fn test_custom_closure() {
+        // The pair exchange and one antisymmetric pair generate the Riemann monoterm group
+        let generators = Symmetry::custom(vec![vec![1, 0, 2, 3], vec![2, 3, 0, 1]], vec![-1, 1]);
+        let Symmetry::Custom { signs, .. } = generators.closure().unwrap() else {
+            panic!("expected a custom symmetry");
+        };
+        assert_eq!(signs.len(), 8);
+
+        // Reaching the identity with sign -1 means the tensor vanishes
+        let conflicting = Symmetry::custom(vec![vec![1, 2, 0]], vec![-1]);
+        assert!(matches!(
+            conflicting.closure(),
+            Err(ButlerPortugalError::MathematicalError(_))
+        ));
+        let plain = Symmetry::symmetric(vec![0, 1]);
+        assert_eq!(plain.closure().unwrap(), plain);
+    }
+
+    #[test]
+    fn test_cyclic_swap_outside_group() {
+        let sym = Symmetry::cyclic(vec![0, 1, 2, 3]);
+        // No transposition is a rotation of four slots
+        assert_eq!(sym.sign_change_for_swap(0, 2), 0);
+        assert_eq!(sym.sign_change_for_swap(1, 2), 0);
+        assert_eq!(sym.sign_change_for_swap(4, 5), 1);
+        // Two slots rotate into each other by a swap
+        assert_eq!(Symmetry::cyclic(vec![0, 1]).sign_change_for_swap(0, 1), 1);
+    }
+
+    #[test]
+    fn test_anti_cyclic_signs() {
+        let sym = Symmetry::anti_cyclic(vec![1, 2]);
+        assert_eq!(sym.permutation_sign(&[0, 2, 1]), -1);
+        assert_eq!(sym.sign_change_for_swap(1, 2), -1);
+        // Moving the block without rearranging it keeps the sign
+        assert_eq!(sym.permutation_sign(&[1, 0, 2]), 1);
+        assert_eq!(
+            Symmetry::anti_cyclic(vec![0, 1, 2]).permutation_sign(&[1, 0, 2]),
+            0
+        );
+
+        // Odd numbers of anti-cyclic indices force the tensor to vanish
+        let indices: Vec<TensorIndex> = ["a", "b", "c"]
+            .iter()
+            .enumerate()
+            .map(|(i, n)| TensorIndex::new(n, i))
+            .collect();
+        assert!(Symmetry::anti_cyclic(vec![0, 1, 2]).makes_tensor_zero(&indices));
+        assert!(!Symmetry::anti_cyclic(vec![0, 1]).makes_tensor_zero(&indices));
+
+        // T_aa = -T_aa, while T_abab = -T_baba does not vanish
+        let repeated = |names: &[&str]| -> Vec<TensorIndex> {
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect()
+        };
+        assert!(Symmetry::anti_cyclic(vec![0, 1]).makes_tensor_zero(&repeated(&["a", "a"])));
+        assert!(!Symmetry::anti_cyclic(vec![0, 1, 2, 3])
+            .makes_tensor_zero(&repeated(&["a", "b", "a", "b"])));
+        assert!(Symmetry::anti_cyclic(vec![0, 1, 2, 3])
+            .makes_tensor_zero(&repeated(&["a", "a", "a", "a"])));
+        let mut t = crate::Tensor::new("T", repeated(&["a", "a"]));
+        t.add_symmetry(Symmetry::anti_cyclic(vec![0, 1]));
+        assert!(t.is_zero());
+        assert_eq!(crate::canonicalize(&t).unwrap().coefficient(), 0);
+        let tensor = |names: [&str; 5]| {
+            let mut t = crate::Tensor::new(
+                "T",
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| TensorIndex::new(n, i))
+                    .collect(),
+            );
+            t.add_symmetry(Symmetry::anti_cyclic(vec![0, 1, 2, 3]));
+            crate::canonicalize(&t).unwrap()
+        };
+        // Two shifts keep the sign, one shift flips it
+        assert_eq!(
+            tensor(["c", "d", "a", "b", "e"]).to_string(),
+            "T__a _b _c _d _e"
+        );
+        assert_eq!(
+            tensor(["b", "c", "d", "a", "e"]).to_string(),
+            "-T__a _b _c _d _e"
+        );
+        assert_eq!(
+            Symmetry::anti_cyclic(vec![0, 1]).shifted(2),
+            Symmetry::anti_cyclic(vec![2, 3])
+        );
+    }
+
+    #[test]
+    fn test_young_shape_matches_riemann() {
+        let names = ["d", "c", "b", "a"];
+        let indices: Vec<TensorIndex> = names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| TensorIndex::new(n, i))
+            .collect();
+        let mut young = crate::Tensor::new("R", indices.clone());
+        young.add_symmetry(Symmetry::young_shape(&Shape(vec![2, 2])));
+        let riemann = crate::presets::riemann(indices).unwrap();
+        assert_eq!(
+            crate::canonicalize(&young).unwrap().to_string(),
+            crate::canonicalize(&riemann).unwrap().to_string()
+        );
+
+        let sym = Symmetry::young_shape(&Shape(vec![2, 1]));
+        assert_eq!(
+            sym,
+            Symmetry::YoungShape {
+                columns: vec![vec![0, 1], vec![2]]
+            }
+        );
+        assert!(sym.validate(3).is_ok());
+        assert!(sym.validate(2).is_err());
+        assert!(Symmetry::YoungShape {
+            columns: vec![vec![0], vec![1, 2]]
+        }
+        .validate(3)
+        .is_err());
+        let repeated: Vec<TensorIndex> = ["a", "a", "b"]
+            .iter()
+            .enumerate()
+            .map(|(i, n)| TensorIndex::new(n, i))
+            .collect();
+        assert!(sym.makes_tensor_zero(&repeated));
+        assert_eq!(
+            sym.shifted(1),
+            Symmetry::YoungShape {
+                columns: vec![vec![1, 2], vec![3]]
+            }
+        );
+        // Columns of different heights cannot be exchanged
+        assert_eq!(sym.permutation_sign(&[2, 1, 0]), 0);
+        assert_eq!(sym.sign_change_for_swap(0, 1), -1);
+    }
+
+    /// Checks that the permutations of a symmetry's own slots with a nonzero
+    /// sign form a group on which the sign is a homomorphism, returning its order
+    fn check_sign_homomorphism(symmetry: &Symmetry, degree: usize) -> usize {
+        use itertools::Itertools;
+        let slots = symmetry.slots();
+        let members: HashMap<Vec<usize>, i32> = (0..degree)
+            .permutations(degree)
+            .filter(|p| (0..degree).all(|k| p[k] == k || slots.contains(&k)))
+            .map(|p| {
+                let sign = symmetry.permutation_sign(&p);
+                (p, sign)
+            })
+            .filter(|(_, sign)| *sign != 0)
+            .collect();
+        assert_eq!(members.get(&(0..degree).collect::<Vec<_>>()), Some(&1));
+        for (a, sign_a) in &members {
+            for (b, sign_b) in &members {
+                let product = compose_permutations(a, b);
+                assert_eq!(
+                    members.get(product.images()),
+                    Some(&(sign_a * sign_b)),
+                    "{symmetry:?}: {a:?} * {b:?}"
+                );
+            }
+        }
+        members.len()
+    }
+
+    #[test]
+    fn test_signs_are_homomorphisms() {
+        let cases = [
+            (Symmetry::symmetric(vec![0, 2, 3]), 6),
+            (Symmetry::antisymmetric(vec![3, 1, 0]), 6),
+            (Symmetry::symmetric_pairs(vec![(0, 1), (2, 3)]), 8),
+            (Symmetry::cyclic(vec![0, 1, 2, 3]), 4),
+            (Symmetry::cyclic(vec![4, 0, 2]), 3),
+            (Symmetry::anti_cyclic(vec![0, 1, 2, 3]), 4),
+            (Symmetry::young_shape(&Shape(vec![2, 2, 1])), 12),
+        ];
+        for (symmetry, order) in cases {
+            assert_eq!(check_sign_homomorphism(&symmetry, 5), order, "{symmetry:?}");
+        }
+        // Custom symmetries act on exactly as many slots as their permutations
+        let custom = Symmetry::from_equalities("T_{abc} = T_{bca} = -T_{acb}").unwrap();
+        assert_eq!(check_sign_homomorphism(&custom, 3), 6);
+    }
+
+    #[test]
+    fn test_tensor_signs_are_homomorphisms() {
+        let indices = |n: usize| {
+            (0..n)
+                .map(|i| TensorIndex::new(&format!("i{i}"), i))
+                .collect::<Vec<_>>()
+        };
+        let mut cyclic_blocks = crate::Tensor::new("C", indices(6));
+        cyclic_blocks.add_symmetry(Symmetry::cyclic(vec![0, 1, 2]));
+        cyclic_blocks.add_symmetry(Symmetry::cyclic(vec![3, 4, 5]));
+        let mut riemann_squared = crate::Tensor::new("R", indices(8));
+        for offset in [0, 4] {
+            riemann_squared.add_symmetry(Symmetry::antisymmetric(vec![offset, offset + 1]));
+            riemann_squared.add_symmetry(Symmetry::antisymmetric(vec![offset + 2, offset + 3]));
+            riemann_squared.add_symmetry(Symmetry::symmetric_pairs(vec![
+                (offset, offset + 1),
+                (offset + 2, offset + 3),
+            ]));
+        }
+        for (tensor, order) in [(cyclic_blocks, 9), (riemann_squared, 64)] {
+            let signed = tensor.signed_symmetry_group();
+            let group: Vec<_> = signed.elements().collect();
+            assert_eq!(group.len(), order);
+            for a in &group {
+                for b in &group {
+                    let product = compose_permutations(&a.permutation, &b.permutation);
+                    assert_eq!(signed.sign(&product), Some(a.sign * b.sign));
+                }
+                // The declared symmetries each contain every element here
+                assert_eq!(tensor.permutation_sign(&a.permutation), a.sign);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_term_symmetries() {
+        let bianchi = MultiTermSymmetry::first_bianchi();
+        assert_eq!(
+            bianchi.terms(4),
+            [vec![0, 1, 2, 3], vec![0, 2, 3, 1], vec![0, 3, 1, 2]]
+        );
+        let indices: Vec<TensorIndex> = ["a", "b", "c", "d"]
+            .iter()
+            .enumerate()
+            .map(|(i, n)| TensorIndex::new(n, i))
+            .collect();
+        let r = crate::presets::riemann(indices.clone()).unwrap();
+        assert_eq!(MultiTermSymmetry::implied_by(&r), [bianchi]);
+        assert!(MultiTermSymmetry::implied_by(&Tensor::new("T", indices)).is_empty());
+    }
 }
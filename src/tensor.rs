@@ -3,22 +3,42 @@
 //! This module provides the core `Tensor` struct and associated methods
 //! for representing tensors with indices and symmetry properties.
 
-use crate::index::TensorIndex;
+use crate::canonicalization::{
+    enumerate_group, tensor_signed_generators, tensor_symmetry_generators, Permutation,
+    PermutationAction, BSGS,
+};
+use crate::index::{IndexClass, TensorIndex};
+use crate::parser::ExpressionParser;
+use crate::permutation::SignedPermutation;
+use crate::schreier_sims::{is_member, schreier_sims, signed_schreier_sims, SignedBSGS};
 use crate::symmetry::Symmetry;
+#[cfg(feature = "tableaux")]
 use crate::young_tableaux::{young_symmetrizer_permutations, StandardTableau};
+use std::collections::{HashSet, VecDeque};
 use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
 
 /// Represents a tensor with indices and symmetry properties
-#[derive(Debug, Clone, PartialEq)]
+///
+/// The name, the index list and the symmetry list are shared behind `Arc`s,
+/// so cloning a tensor only bumps reference counts; mutation copies the data
+/// on write. Candidates produced by `permute` share the symmetry list with
+/// the original tensor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Tensor {
     /// The name/symbol of the tensor (e.g., "R" for Riemann tensor)
-    name: String,
-    /// The indices of the tensor
-    indices: Vec<TensorIndex>,
+    name: Arc<str>,
+    /// The indices of the tensor (a `Vec` so `indices_mut` can copy on write)
+    #[allow(clippy::rc_buffer)]
+    indices: Arc<Vec<TensorIndex>>,
     /// Symmetry properties of the tensor
-    symmetries: Vec<Symmetry>,
+    #[allow(clippy::rc_buffer)]
+    symmetries: Arc<Vec<Symmetry>>,
     /// Optional coefficient (default is 1)
     coefficient: i32,
+    /// Perturbative order, e.g. 1 for a metric perturbation `h_{ab} = O(ε)` (default is 0)
+    perturbative_order: u32,
 }
 
 impl Tensor {
@@ -39,36 +59,167 @@ impl Tensor {
     /// ```
     pub fn new(name: &str, indices: Vec<TensorIndex>) -> Self {
         Self {
-            name: name.to_string(),
-            indices,
-            symmetries: Vec::new(),
+            name: Arc::from(name),
+            indices: Arc::new(indices),
+            symmetries: Arc::default(),
             coefficient: 1,
+            perturbative_order: 0,
         }
     }
 
     /// Creates a new tensor with a coefficient
     pub fn with_coefficient(name: &str, indices: Vec<TensorIndex>, coefficient: i32) -> Self {
         Self {
-            name: name.to_string(),
-            indices,
-            symmetries: Vec::new(),
+            name: Arc::from(name),
+            indices: Arc::new(indices),
+            symmetries: Arc::default(),
             coefficient,
+            perturbative_order: 0,
         }
     }
 
+    /// Parses a tensor written in LaTeX index notation
+    ///
+    /// The syntax is that of [`crate::parser`]; the metric `g`, the Riemann
+    /// and Ricci tensors `R` and the Weyl tensor `C` get their symmetries, as
+    /// with [`ExpressionParser::standard`].
+    ///
+    /// # Errors
+    /// Returns a `Parse` error if the text is not a single tensor.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::Tensor;
+    ///
+    /// let r = Tensor::parse("R_{mu nu}^{rho sigma}")?;
+    /// assert_eq!(r.rank(), 4);
+    /// assert!(r.indices()[2].is_contravariant());
+    /// let t: Tensor = "2 T_a^b".parse()?;
+    /// assert_eq!(t.to_string(), "2T__a ^b");
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn parse(input: &str) -> crate::Result<Self> {
+        ExpressionParser::standard().parse_tensor(input)
+    }
+
     /// Returns the name of the tensor
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Returns the perturbative order of the tensor
+    pub fn perturbative_order(&self) -> u32 {
+        self.perturbative_order
+    }
+
+    /// Sets the perturbative order, e.g. 1 for a quantity of order `O(ε)`
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::{Tensor, TensorIndex};
+    ///
+    /// let mut h = Tensor::new("h", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+    /// h.set_perturbative_order(1);
+    /// assert_eq!(h.perturbative_order(), 1);
+    /// ```
+    pub fn set_perturbative_order(&mut self, order: u32) {
+        self.perturbative_order = order;
+    }
+
+    /// Sets the name of the tensor
+    pub fn set_name(&mut self, name: &str) {
+        self.name = Arc::from(name);
+    }
+
     /// Returns a reference to the tensor indices
     pub fn indices(&self) -> &[TensorIndex] {
         &self.indices
     }
 
+    /// Returns the indices whose name occurs only once in the tensor
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::prelude::*;
+    ///
+    /// // The trace T^a_{ab} has the single free index b
+    /// let t = Tensor::new(
+    ///     "T",
+    ///     vec![TensorIndex::contravariant("a", 0), TensorIndex::new("a", 1), TensorIndex::new("b", 2)],
+    /// );
+    /// let free: Vec<&str> = t.free_indices().map(|i| i.name()).collect();
+    /// assert_eq!(free, ["b"]);
+    /// assert_eq!(t.dummy_indices().count(), 2);
+    /// ```
+    pub fn free_indices(&self) -> impl Iterator<Item = &TensorIndex> {
+        self.indices
+            .iter()
+            .filter(move |index| self.name_count(index.name()) == 1)
+    }
+
+    /// Returns the indices whose name is shared with another slot, i.e. the contracted ones
+    pub fn dummy_indices(&self) -> impl Iterator<Item = &TensorIndex> {
+        self.indices
+            .iter()
+            .filter(move |index| self.name_count(index.name()) > 1)
+    }
+
+    /// Returns the contracted index pairs as `(upper slot, lower slot)`, in
+    /// order of their upper slots
+    ///
+    /// A pair is a name carried by exactly two slots of one class with
+    /// opposite variance; repeated names of equal variance are not contracted.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::prelude::*;
+    ///
+    /// // R_{a b}^{a c} contracts slots 0 and 2
+    /// let r = Tensor::new(
+    ///     "R",
+    ///     vec![
+    ///         TensorIndex::new("a", 0),
+    ///         TensorIndex::new("b", 1),
+    ///         TensorIndex::contravariant("a", 2),
+    ///         TensorIndex::contravariant("c", 3),
+    ///     ],
+    /// );
+    /// assert_eq!(r.contracted_pairs(), [(2, 0)]);
+    /// ```
+    pub fn contracted_pairs(&self) -> Vec<(usize, usize)> {
+        self.indices
+            .iter()
+            .enumerate()
+            .filter(|(_, index)| index.is_contravariant())
+            .filter_map(|(upper, index)| {
+                let mut slots = self
+                    .indices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, other)| other.name() == index.name());
+                match (slots.next(), slots.next(), slots.next()) {
+                    (Some((first, a)), Some((second, b)), None)
+                        if a.class() == b.class()
+                            && a.is_contravariant() != b.is_contravariant() =>
+                    {
+                        Some((upper, if first == upper { second } else { first }))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Counts the slots carrying an index with the given name
+    fn name_count(&self, name: &str) -> usize {
+        self.indices.iter().filter(|i| i.name() == name).count()
+    }
+
     /// Returns a mutable reference to the tensor indices
+    ///
+    /// The index list is copied first if it is shared with another tensor.
     pub fn indices_mut(&mut self) -> &mut Vec<TensorIndex> {
-        &mut self.indices
+        Arc::make_mut(&mut self.indices)
     }
 
     /// Returns a reference to the symmetries
@@ -91,12 +242,12 @@ impl Tensor {
     /// # Arguments
     /// * `symmetry` - The symmetry to add
     pub fn add_symmetry(&mut self, symmetry: Symmetry) {
-        self.symmetries.push(symmetry);
+        Arc::make_mut(&mut self.symmetries).push(symmetry);
     }
 
     /// Removes all symmetries
     pub fn clear_symmetries(&mut self) {
-        self.symmetries.clear();
+        self.symmetries = Arc::default();
     }
 
     /// Returns the rank (number of indices) of the tensor
@@ -116,11 +267,11 @@ impl Tensor {
             return 1;
         }
 
-        self.indices.swap(i, j);
+        Arc::make_mut(&mut self.indices).swap(i, j);
 
         // Calculate sign change based on symmetries
         let mut sign = 1;
-        for symmetry in &self.symmetries {
+        for symmetry in self.symmetries.iter() {
             sign *= symmetry.sign_change_for_swap(i, j);
         }
 
@@ -153,9 +304,10 @@ impl Tensor {
 
         let mut new_tensor = Self {
             name: self.name.clone(),
-            indices: new_indices,
+            indices: Arc::new(new_indices),
             symmetries: self.symmetries.clone(),
             coefficient: self.coefficient,
+            perturbative_order: self.perturbative_order,
         };
 
         // Calculate sign change for this permutation
@@ -165,18 +317,169 @@ impl Tensor {
         Ok(new_tensor)
     }
 
-    /// Calculates the sign of a permutation based on tensor symmetries
-    fn permutation_sign(&self, permutation: &[usize]) -> i32 {
+    /// Creates a copy of the tensor with indices permuted in the given convention
+    ///
+    /// `permute_with(perm, PermutationAction::Slots)` is `permute(perm)`; with
+    /// `PermutationAction::Labels` the inverse permutation is applied.
+    ///
+    /// # Arguments
+    /// * `permutation` - Array representing the permutation
+    /// * `action` - How the permutation acts on the tensor
+    pub fn permute_with(
+        &self,
+        permutation: &[usize],
+        action: PermutationAction,
+    ) -> crate::Result<Self> {
+        self.permute(&action.to_slots(permutation)?)
+    }
+
+    /// Multiplies the signs each declared symmetry gives a permutation
+    ///
+    /// The result is zero unless every symmetry contains the permutation on
+    /// its own, so a permutation built from generators of several symmetries
+    /// can get zero or a wrong sign; its sign in the generated group comes
+    /// from [`signed_symmetry_group`](Self::signed_symmetry_group).
+    pub(crate) fn permutation_sign(&self, permutation: &[usize]) -> i32 {
         let mut sign = 1;
 
         // Count inversions for each symmetry group
-        for symmetry in &self.symmetries {
+        for symmetry in self.symmetries.iter() {
             sign *= symmetry.permutation_sign(permutation);
         }
 
         sign
     }
 
+    /// Returns the slot symmetry group generated by all of the tensor's symmetries
+    ///
+    /// This is the group the canonicalizer searches, as a base and strong
+    /// generating set, so its order, membership and basic orbits can be
+    /// queried without enumerating it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::presets::riemann;
+    /// use butler_portugal::schreier_sims::is_member;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// let r = riemann(["a", "b", "c", "d"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+    /// let group = r.symmetry_group();
+    /// assert_eq!(group.order(), 8);
+    /// assert!(is_member(&vec![2, 3, 0, 1].into(), &group));
+    /// assert!(!is_member(&vec![0, 2, 1, 3].into(), &group));
+    /// // Slot 0 can receive the index of any slot
+    /// assert_eq!(group.transversals()[0].orbit().len(), 4);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn symmetry_group(&self) -> BSGS {
+        schreier_sims(&tensor_symmetry_generators(self), self.rank())
+    }
+
+    /// Returns the slot symmetry group with the sign of every element
+    ///
+    /// Generators take the sign all symmetries of the tensor give them, and
+    /// products take the product of the signs, so elements combining several
+    /// symmetries get exact signs. Inconsistent symmetries show up as the
+    /// identity with sign `-1`, see [`SignedBSGS::is_vanishing`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::presets::riemann;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// let r = riemann(["a", "b", "c", "d"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+    /// let group = r.signed_symmetry_group();
+    /// assert_eq!(group.order(), 8);
+    /// assert_eq!(group.sign(&[3, 2, 0, 1]), Some(-1));
+    /// assert!(!group.is_vanishing());
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn signed_symmetry_group(&self) -> SignedBSGS {
+        signed_schreier_sims(&tensor_signed_generators(self), self.rank())
+    }
+
+    /// Returns an iterator over every distinct form of the tensor reachable through its symmetries
+    ///
+    /// Each form is yielded once, with the symmetry sign folded into its
+    /// coefficient; the tensor itself comes first. Forms are produced lazily by
+    /// a breadth-first search over the signed generators of the symmetries,
+    /// so taking only a few of them never materializes the whole orbit. A
+    /// tensor that vanishes by symmetry has an empty orbit.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::{Symmetry, Tensor, TensorIndex};
+    ///
+    /// let mut f = Tensor::new("F", vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)]);
+    /// f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+    /// let forms: Vec<String> = f.orbit().map(|t| t.to_string()).collect();
+    /// assert_eq!(forms, ["F__a _b", "-F__b _a"]);
+    /// ```
+    pub fn orbit(&self) -> Orbit {
+        let mut orbit = Orbit {
+            generators: tensor_signed_generators(self),
+            queue: VecDeque::new(),
+            seen: HashSet::new(),
+        };
+        if self.orbit_size() > 0 {
+            orbit.seen.insert(arrangement_key(self));
+            orbit.queue.push_back(self.clone());
+        }
+        orbit
+    }
+
+    /// Returns the number of forms yielded by [`orbit`](Self::orbit), without enumerating them
+    ///
+    /// By the orbit-stabilizer theorem the orbit size is `|G| / |G ∩ Y|`, where
+    /// `G` is the slot symmetry group and `Y` the group of slot rearrangements
+    /// that leave the index arrangement unchanged (the permutations among
+    /// slots carrying identical indices). `|G|` is read off the BSGS and
+    /// `G ∩ Y` is found by sifting the elements of `Y`, which is trivial when
+    /// all indices are distinct and small for the usual dummy pairs. An
+    /// element of `G ∩ Y` with sign -1 makes the tensor vanish, and the orbit
+    /// empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use butler_portugal::presets::riemann;
+    /// use butler_portugal::TensorIndex;
+    ///
+    /// let r = riemann(["a", "b", "c", "d"].iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+    /// assert_eq!(r.orbit_size(), 8);
+    /// assert_eq!(r.orbit_size(), r.orbit().count() as u128);
+    /// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+    /// ```
+    pub fn orbit_size(&self) -> u128 {
+        if self.is_zero() {
+            return 0;
+        }
+        let degree = self.rank();
+        let group = self.symmetry_group();
+        let signed = self.signed_symmetry_group();
+
+        // Y is generated by transpositions of slots with identical indices
+        let keys = arrangement_key(self);
+        let mut transpositions = Vec::new();
+        for (j, key) in keys.iter().enumerate() {
+            if let Some(i) = keys[..j].iter().rposition(|other| other == key) {
+                let mut transposition: Permutation = (0..degree).collect();
+                transposition.swap(i, j);
+                transpositions.push(transposition);
+            }
+        }
+        let arrangement_group = schreier_sims(&transpositions, degree);
+        let mut stabilizer: u128 = 0;
+        for perm in enumerate_group(&arrangement_group, degree) {
+            if is_member(&perm, &group) {
+                if signed.sign(&perm) != Some(1) {
+                    return 0;
+                }
+                stabilizer += 1;
+            }
+        }
+        group.order() / stabilizer.max(1)
+    }
+
     /// Checks if the tensor is zero due to symmetry constraints
     pub fn is_zero(&self) -> bool {
         self.coefficient == 0
@@ -194,6 +497,7 @@ impl Tensor {
     ///
     /// # Returns
     /// The projected tensor (or an error if permutation fails)
+    #[cfg(feature = "tableaux")]
     pub fn project_with_tableau(&self, tableau: &StandardTableau) -> crate::Result<Tensor> {
         let degree = self.rank();
         let perms = young_symmetrizer_permutations(tableau, degree);
@@ -228,7 +532,54 @@ impl Tensor {
     }
 }
 
+/// Iterator over the equivalent forms of a tensor, created by [`Tensor::orbit`]
+#[derive(Debug, Clone)]
+pub struct Orbit {
+    generators: Vec<SignedPermutation>,
+    queue: VecDeque<Tensor>,
+    seen: HashSet<Vec<(String, bool, IndexClass)>>,
+}
+
+impl Iterator for Orbit {
+    type Item = Tensor;
+
+    fn next(&mut self) -> Option<Tensor> {
+        let current = self.queue.pop_front()?;
+        for generator in &self.generators {
+            let mut next = current.clone();
+            next.indices = Arc::new(
+                generator
+                    .permutation
+                    .iter()
+                    .map(|&slot| current.indices[slot].clone())
+                    .collect(),
+            );
+            next.coefficient = current.coefficient * generator.sign;
+            if self.seen.insert(arrangement_key(&next)) {
+                self.queue.push_back(next);
+            }
+        }
+        Some(current)
+    }
+}
+
+/// Identifies the arrangement of a tensor's indices, ignoring their recorded positions
+fn arrangement_key(tensor: &Tensor) -> Vec<(String, bool, IndexClass)> {
+    tensor
+        .indices()
+        .iter()
+        .map(|index| {
+            (
+                index.name().to_string(),
+                index.is_contravariant(),
+                index.class(),
+            )
+        })
+        .collect()
+}
+
 /// Helper: add two tensors if their names and indices (by name/variance) match, summing coefficients
+#[cfg(feature = "tableaux")]
 fn add_tensors(a: &Tensor, b: &Tensor) -> crate::Result<Tensor> {
     if a.name() != b.name() {
         return Err(crate::ButlerPortugalError::IncompatibleTensors(
@@ -249,9 +600,7 @@ fn add_tensors(a: &Tensor, b: &Tensor) -> crate::Result<Tensor> {
         let mut result = a.clone();
         result.set_coefficient(a.coefficient() + b.coefficient());
         // Sort indices to canonical order
-        let mut indices: Vec<_> = result.indices.clone();
-        indices.sort_by(|x, y| x.canonical_cmp(y));
-        result.indices = indices;
+        result.indices_mut().sort_by(|x, y| x.canonical_cmp(y));
         Ok(result)
     } else {
         Err(crate::ButlerPortugalError::IncompatibleTensors(
@@ -260,6 +609,14 @@ fn add_tensors(a: &Tensor, b: &Tensor) -> crate::Result<Tensor> {
     }
 }
 
+impl FromStr for Tensor {
+    type Err = crate::ButlerPortugalError;
+
+    fn from_str(input: &str) -> crate::Result<Self> {
+        Self::parse(input)
+    }
+}
+
 impl fmt::Display for Tensor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.coefficient == 0 {
@@ -339,4 +696,160 @@ mod tests {
         assert!(display.contains("mu"));
         assert!(display.contains("nu"));
     }
+
+    #[test]
+    fn test_perturbative_order_survives_permutation() {
+        let mut h = Tensor::new(
+            "h",
+            vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)],
+        );
+        h.add_symmetry(Symmetry::symmetric(vec![0, 1]));
+        h.set_perturbative_order(1);
+        assert_eq!(h.permute(&[1, 0]).unwrap().perturbative_order(), 1);
+        assert_eq!(crate::canonicalize(&h).unwrap().perturbative_order(), 1);
+    }
+
+    #[test]
+    fn test_free_and_dummy_indices() {
+        let t = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::new("a", 0),
+                TensorIndex::new("b", 1),
+                TensorIndex::contravariant("a", 2),
+                TensorIndex::new("c", 3),
+            ],
+        );
+        let free: Vec<&str> = t.free_indices().map(|i| i.name()).collect();
+        let dummies: Vec<usize> = t.dummy_indices().map(|i| i.position()).collect();
+        assert_eq!(free, ["b", "c"]);
+        assert_eq!(dummies, [0, 2]);
+        assert_eq!(t.contracted_pairs(), [(2, 0)]);
+
+        // Three slots named alike, or two of equal variance, are no pair
+        let repeated = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::contravariant("a", 0),
+                TensorIndex::new("a", 1),
+                TensorIndex::new("a", 2),
+                TensorIndex::new("b", 3),
+                TensorIndex::new("b", 4),
+            ],
+        );
+        assert!(repeated.contracted_pairs().is_empty());
+    }
+
+    #[test]
+    fn test_clones_share_indices_until_written() {
+        let t = Tensor::new(
+            "T",
+            vec![TensorIndex::new("a", 0), TensorIndex::new("b", 1)],
+        );
+        let mut copy = t.clone();
+        assert_eq!(copy.indices().as_ptr(), t.indices().as_ptr());
+        copy.swap_indices(0, 1);
+        assert_ne!(copy.indices().as_ptr(), t.indices().as_ptr());
+        assert_eq!(t.indices()[0].name(), "a");
+        assert_eq!(copy.indices()[0].name(), "b");
+    }
+
+    #[test]
+    fn test_permuted_candidates_share_symmetries() {
+        let mut t = Tensor::new(
+            "F",
+            vec![TensorIndex::new("b", 0), TensorIndex::new("a", 1)],
+        );
+        t.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        let candidate = t.permute(&[1, 0]).unwrap();
+        assert_eq!(candidate.symmetries().as_ptr(), t.symmetries().as_ptr());
+
+        let mut extended = candidate.clone();
+        extended.add_symmetry(Symmetry::symmetric(vec![0]));
+        assert_eq!(candidate.symmetries().len(), 1);
+        assert_eq!(extended.symmetries().len(), 2);
+    }
+
+    #[test]
+    fn test_orbit_of_riemann_symmetries() {
+        let r = crate::presets::riemann(
+            ["a", "b", "c", "d"]
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        )
+        .unwrap();
+        let forms: Vec<Tensor> = r.orbit().collect();
+        assert_eq!(forms.len(), 8);
+        assert_eq!(r.orbit_size(), 8);
+        assert_eq!(forms[0], r);
+        // Every form canonicalizes back to the tensor itself
+        for form in &forms {
+            assert_eq!(crate::canonicalize(form).unwrap(), r);
+        }
+
+        // Repeated names collapse forms; vanishing tensors have none
+        let mut s = Tensor::new(
+            "S",
+            vec![
+                TensorIndex::new("a", 0),
+                TensorIndex::new("a", 1),
+                TensorIndex::new("b", 2),
+            ],
+        );
+        s.add_symmetry(Symmetry::symmetric(vec![0, 1, 2]));
+        assert_eq!(s.orbit().count(), 3);
+        assert_eq!(s.orbit_size(), 3);
+        let mut f = Tensor::new(
+            "F",
+            vec![TensorIndex::new("a", 0), TensorIndex::new("a", 1)],
+        );
+        f.add_symmetry(Symmetry::antisymmetric(vec![0, 1]));
+        assert_eq!(f.orbit().next(), None);
+        assert_eq!(f.orbit_size(), 0);
+
+        // Cyclic and symmetric declarations generate S3 together; the
+        // transposition of slots 0 and 1 is not cyclic
+        let mut t = Tensor::new(
+            "T",
+            vec![
+                TensorIndex::new("b", 0),
+                TensorIndex::contravariant("d", 1),
+                TensorIndex::contravariant("d", 2),
+            ],
+        );
+        t.add_symmetry(Symmetry::cyclic(vec![0, 1, 2]));
+        t.add_symmetry(Symmetry::symmetric(vec![1, 0]));
+        let forms: Vec<Tensor> = t.orbit().collect();
+        assert_eq!(forms.len() as u128, t.orbit_size());
+        assert_eq!(forms.len(), 3);
+        assert!(forms.iter().all(|form| form.coefficient() == 1));
+        for form in &forms {
+            assert_eq!(
+                crate::canonicalize(form).unwrap().to_string(),
+                crate::canonicalize(&t).unwrap().to_string()
+            );
+        }
+        let mut u = Tensor::new(
+            "U",
+            vec![
+                TensorIndex::new("a", 0),
+                TensorIndex::new("b", 1),
+                TensorIndex::new("c", 2),
+            ],
+        );
+        u.add_symmetry(Symmetry::cyclic(vec![0, 1, 2]));
+        u.add_symmetry(Symmetry::anti_cyclic(vec![0, 1]));
+        let forms: Vec<Tensor> = u.orbit().collect();
+        assert_eq!(forms.len() as u128, u.orbit_size());
+        assert_eq!(forms.len(), 6);
+        assert!(forms.iter().all(|form| form.coefficient().abs() == 1));
+        for form in &forms {
+            assert_eq!(
+                crate::canonicalize(form).unwrap().to_string(),
+                crate::canonicalize(&u).unwrap().to_string()
+            );
+        }
+    }
 }
@@ -0,0 +1,140 @@
+//! WebAssembly bindings for browser-based notebooks
+//!
+//! With the `wasm` feature, built for `wasm32-unknown-unknown` and processed
+//! by `wasm-bindgen`, the canonicalizer is callable from JavaScript. Tensors
+//! are built from the JSON documents of [`crate::json`] or from the text
+//! notation of [`crate::parser`]:
+//!
+//! ```js
+//! import { WasmTensor, canonicalize } from "butler_portugal";
+//!
+//! const r = WasmTensor.parse("R_{bacd}");
+//! console.log(r.canonicalize().toString());
+//! console.log(canonicalize(JSON.stringify({ version: 1, tensor: { name: "g", indices: [] } })));
+//! ```
+//!
+//! Errors reach JavaScript as thrown `Error` objects carrying the message of
+//! the underlying [`ButlerPortugalError`](crate::error::ButlerPortugalError).
+
+use crate::canonicalization::canonicalize as canonicalize_tensor;
+use crate::error::Result;
+use crate::json::{canonicalize_json, tensor_from_json, tensor_to_json};
+use crate::latex::ToLatex;
+use crate::tensor::Tensor;
+use wasm_bindgen::prelude::*;
+
+/// A tensor owned by JavaScript
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WasmTensor {
+    inner: Tensor,
+}
+
+#[wasm_bindgen]
+impl WasmTensor {
+    /// Reads a tensor from a JSON document of [`crate::json`]
+    ///
+    /// # Errors
+    /// Throws if the document is malformed or has an unsupported version.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(input: &str) -> std::result::Result<WasmTensor, JsError> {
+        to_js(tensor_from_json(input).map(WasmTensor::from))
+    }
+
+    /// Parses a tensor written as in [`Tensor::parse`]
+    ///
+    /// # Errors
+    /// Throws if the input is not a single tensor.
+    pub fn parse(input: &str) -> std::result::Result<WasmTensor, JsError> {
+        to_js(Tensor::parse(input).map(WasmTensor::from))
+    }
+
+    /// Returns the canonical form of the tensor
+    ///
+    /// # Errors
+    /// Throws if a symmetry of the tensor is inconsistent with its rank.
+    pub fn canonicalize(&self) -> std::result::Result<WasmTensor, JsError> {
+        to_js(canonicalize_tensor(&self.inner).map(WasmTensor::from))
+    }
+
+    /// Returns the name of the tensor
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.inner.name().to_string()
+    }
+
+    /// Returns the coefficient of the tensor
+    #[wasm_bindgen(getter)]
+    pub fn coefficient(&self) -> i32 {
+        self.inner.coefficient()
+    }
+
+    /// Returns true if the tensor vanishes
+    #[wasm_bindgen(js_name = isZero)]
+    pub fn is_zero(&self) -> bool {
+        self.inner.coefficient() == 0
+    }
+
+    /// Writes the tensor as a JSON document of [`crate::json`]
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        tensor_to_json(&self.inner)
+    }
+
+    /// Writes the tensor as LaTeX
+    #[wasm_bindgen(js_name = toLatex)]
+    pub fn to_latex(&self) -> String {
+        self.inner.to_latex()
+    }
+
+    /// Writes the tensor in the notation of its `Display` implementation
+    #[wasm_bindgen(js_name = toString)]
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+impl From<Tensor> for WasmTensor {
+    fn from(inner: Tensor) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<WasmTensor> for Tensor {
+    fn from(tensor: WasmTensor) -> Self {
+        tensor.inner
+    }
+}
+
+/// Canonicalizes a tensor given as a JSON document, as [`canonicalize_json`]
+///
+/// Never throws; failures are reported in the `error` field of the response.
+#[wasm_bindgen]
+pub fn canonicalize(input: &str) -> String {
+    canonicalize_json(input)
+}
+
+/// Converts a crate error into a JavaScript `Error`
+fn to_js<T>(result: Result<T>) -> std::result::Result<T, JsError> {
+    result.map_err(|e| JsError::new(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Error paths construct JavaScript values and only run on wasm targets
+    #[test]
+    fn test_wasm_tensor_round_trip() {
+        let f = WasmTensor::parse("F_{ba}").unwrap();
+        assert_eq!(f.name(), "F");
+        let from_json = WasmTensor::from_json(&f.to_json()).unwrap();
+        assert_eq!(from_json, f);
+        let canonical = f.canonicalize().unwrap();
+        assert_eq!(canonical.coefficient(), 1);
+        assert!(!canonical.is_zero());
+        assert_eq!(Tensor::from(canonical).to_string(), "F__b _a");
+        assert!(canonicalize("{}").contains("error"));
+    }
+}
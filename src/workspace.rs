@@ -0,0 +1,164 @@
+//! Reusable scratch space for repeated canonicalization
+//!
+//! Every call to [`canonicalize`](crate::canonicalize) builds the signed
+//! stabilizer chain of its tensor's slot group and allocates the frontier
+//! of the chain descent. A [`Workspace`] owns these buffers instead and
+//! keeps them between calls, and it keeps the slot group for as long as
+//! consecutive tensors share their symmetry signature. Batch jobs
+//! canonicalizing millions of terms of a few tensor types then run almost
+//! allocation-free.
+
+use crate::cache::SymmetryKey;
+use crate::canonicalization::{
+    apply_minimal_in, settle_trivial, slot_group, validate_symmetries, ChainScratch, SlotGroup,
+};
+use crate::config::CanonicalizationConfig;
+use crate::error::Result;
+use crate::tensor::Tensor;
+
+/// Buffers owned across canonicalization calls
+///
+/// Results agree with [`canonicalize`](crate::canonicalize) under the
+/// default configuration. The global cache is not consulted.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::presets::riemann;
+/// use butler_portugal::workspace::Workspace;
+/// use butler_portugal::{canonicalize, TensorIndex};
+///
+/// let mut workspace = Workspace::new();
+/// for names in [["d", "c", "b", "a"], ["b", "a", "c", "d"], ["c", "d", "b", "a"]] {
+///     let r = riemann(names.iter().enumerate().map(|(i, n)| TensorIndex::new(n, i)).collect())?;
+///     assert_eq!(workspace.canonicalize(&r)?, canonicalize(&r)?);
+/// }
+/// assert_eq!(workspace.group_order(), 8);
+/// # Ok::<(), butler_portugal::ButlerPortugalError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Workspace {
+    /// Symmetry signature and slot group of the last tensor canonicalized
+    group: Option<(SymmetryKey, SlotGroup)>,
+    /// Buffers of the chain descent
+    scratch: ChainScratch,
+}
+
+impl Workspace {
+    /// Creates an empty workspace
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the order of the group currently held, saturating at
+    /// `usize::MAX`, or zero if none is
+    pub fn group_order(&self) -> usize {
+        self.group.as_ref().map_or(0, |(_, group)| group.order())
+    }
+
+    /// Releases the held group, keeping the other buffers
+    pub fn clear(&mut self) {
+        self.group = None;
+    }
+
+    /// Canonicalizes a tensor, returning the canonical form
+    ///
+    /// # Errors
+    /// Returns the same errors as [`canonicalize`](crate::canonicalize).
+    pub fn canonicalize(&mut self, tensor: &Tensor) -> Result<Tensor> {
+        let mut canonical = tensor.clone();
+        self.canonicalize_in_place(&mut canonical)?;
+        Ok(canonical)
+    }
+
+    /// Canonicalizes a tensor in place, like
+    /// [`canonicalize_in_place`](crate::canonicalization::canonicalize_in_place)
+    ///
+    /// # Errors
+    /// Returns the same errors as [`canonicalize`](crate::canonicalize).
+    pub fn canonicalize_in_place(&mut self, tensor: &mut Tensor) -> Result<()> {
+        validate_symmetries(tensor)?;
+        if settle_trivial(tensor) {
+            return Ok(());
+        }
+        let key = SymmetryKey::of(tensor);
+        let group = match self.group.take() {
+            Some((held, group)) if held == key => group,
+            _ => slot_group(tensor),
+        };
+        let config = CanonicalizationConfig::new();
+        let applied = apply_minimal_in(tensor, &group, &config, &mut self.scratch);
+        self.group = Some((key, group));
+        applied.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalize;
+    use crate::index::TensorIndex;
+    use crate::symmetry::Symmetry;
+
+    #[test]
+    fn test_workspace_agrees_with_canonicalize() {
+        let tensor = |names: &[&str], upper: &[usize], symmetries: &[Symmetry]| {
+            let mut t = Tensor::new(
+                "T",
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| {
+                        if upper.contains(&i) {
+                            TensorIndex::contravariant(n, i)
+                        } else {
+                            TensorIndex::new(n, i)
+                        }
+                    })
+                    .collect(),
+            );
+            for symmetry in symmetries {
+                t.add_symmetry(symmetry.clone());
+            }
+            t
+        };
+        let antisymmetric = [Symmetry::antisymmetric(vec![0, 1, 2])];
+        let mixed = [
+            Symmetry::symmetric(vec![0, 1]),
+            Symmetry::anti_cyclic(vec![1, 2]),
+        ];
+        let cases = [
+            tensor(&["c", "b", "a"], &[], &antisymmetric),
+            tensor(&["c", "a", "a"], &[], &antisymmetric),
+            tensor(&["a", "c", "b"], &[1], &antisymmetric),
+            tensor(&["b", "a", "a"], &[], &mixed),
+            tensor(&["c", "b", "a"], &[0], &mixed),
+            tensor(&["b", "a"], &[], &[]),
+        ];
+        let mut workspace = Workspace::new();
+        for t in &cases {
+            assert_eq!(workspace.canonicalize(t).unwrap(), canonicalize(t).unwrap());
+        }
+        assert_eq!(workspace.group_order(), 1);
+        workspace.clear();
+        assert_eq!(workspace.group_order(), 0);
+    }
+
+    #[test]
+    fn test_workspace_descends_the_chain() {
+        // A symmetric group on 12 slots is far too large to enumerate
+        let names = ["l", "k", "j", "i", "h", "g", "f", "e", "d", "c", "b", "a"];
+        let mut t = Tensor::new(
+            "S",
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| TensorIndex::new(n, i))
+                .collect(),
+        );
+        t.add_symmetry(Symmetry::symmetric((0..12).collect()));
+        let mut workspace = Workspace::new();
+        let canonical = workspace.canonicalize(&t).unwrap();
+        assert_eq!(canonical, canonicalize(&t).unwrap());
+        assert_eq!(workspace.group_order(), 479_001_600);
+    }
+}
@@ -1,5 +1,7 @@
 //! Young tableaux and related combinatorics for tensor canonicalization
 
+use crate::canonicalization::Permutation;
+#[cfg(feature = "tableaux")]
 use itertools::Itertools;
 use std::fmt;
 
@@ -147,8 +149,7 @@ pub fn rsk(word: &[usize]) -> (SemistandardTableau, StandardTableau) {
     // Simple RSK implementation (not optimized)
     let mut p_rows: Vec<Vec<usize>> = Vec::new();
     let mut q_rows: Vec<Vec<usize>> = Vec::new();
-    let mut next_label = 1;
-    for &x in word {
+    for (next_label, &x) in (1..).zip(word) {
         let mut i = 0;
         let mut to_insert = x;
         loop {
@@ -168,7 +169,6 @@ pub fn rsk(word: &[usize]) -> (SemistandardTableau, StandardTableau) {
                 break;
             }
         }
-        next_label += 1;
     }
     let shape = Shape(p_rows.iter().map(|r| r.len()).collect());
     let p = SemistandardTableau {
@@ -193,6 +193,7 @@ pub fn rsk(word: &[usize]) -> (SemistandardTableau, StandardTableau) {
 ///
 /// # Returns
 /// A vector of (permutation, sign) pairs representing the Young symmetrizer action
+#[cfg(feature = "tableaux")]
 pub fn young_symmetrizer_permutations(
     tableau: &StandardTableau,
     degree: usize,
@@ -266,6 +267,68 @@ pub fn young_symmetrizer_permutations(
     result
 }
 
+/// Returns generators of the slot symmetry group a tableau describes
+///
+/// These are the transpositions of neighbouring slots within each column,
+/// from the column group, and the exchanges of neighbouring columns of equal
+/// height, which are the row group elements commuting with the column
+/// antisymmetrizer. Entry `k` sits on slot `k - 1`. The generators are the
+/// ones `Symmetry::from_tableau` contributes to canonicalization, so the
+/// Schreier–Sims path and [`young_symmetrizer_permutations`] agree on the
+/// symmetry of a tableau.
+///
+/// # Example
+/// ```rust
+/// use butler_portugal::schreier_sims::schreier_sims;
+/// use butler_portugal::young_tableaux::{slot_group_from_tableau, Shape, StandardTableau};
+///
+/// let tableau = StandardTableau::new(Shape(vec![2, 2]), vec![vec![1, 3], vec![2, 4]]).unwrap();
+/// let generators = slot_group_from_tableau(&tableau);
+/// assert_eq!(generators, vec![vec![1, 0, 2, 3], vec![0, 1, 3, 2], vec![2, 3, 0, 1]]);
+/// assert_eq!(schreier_sims(&generators, 4).order(), 8);
+/// ```
+pub fn slot_group_from_tableau(tableau: &StandardTableau) -> Vec<Permutation> {
+    column_generators(&tableau_columns(tableau), tableau.size())
+}
+
+/// Returns the slots of each column of a tableau, from top to bottom
+pub(crate) fn tableau_columns(tableau: &StandardTableau) -> Vec<Vec<usize>> {
+    (0..tableau.shape.cols())
+        .map(|j| {
+            tableau
+                .entries
+                .iter()
+                .filter_map(|row| row.get(j))
+                .map(|&entry| entry - 1)
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns generators of the group of Young diagram columns on `degree` slots
+///
+/// Columns list slots from top to bottom; none may be `degree` or larger.
+pub(crate) fn column_generators(columns: &[Vec<usize>], degree: usize) -> Vec<Permutation> {
+    let mut generators = Vec::new();
+    for column in columns {
+        for pair in column.windows(2) {
+            let mut perm: Vec<usize> = (0..degree).collect();
+            perm.swap(pair[0], pair[1]);
+            generators.push(perm.into());
+        }
+    }
+    for pair in columns.windows(2) {
+        if pair[0].len() == pair[1].len() {
+            let mut perm: Vec<usize> = (0..degree).collect();
+            for (&a, &b) in pair[0].iter().zip(&pair[1]) {
+                perm.swap(a, b);
+            }
+            generators.push(perm.into());
+        }
+    }
+    generators
+}
+
 /// Helper: parity of a permutation (usize version)
 pub fn permutation_parity_usize(perm: &[usize]) -> i32 {
     let n = perm.len();
@@ -297,6 +360,7 @@ pub fn compose_permutations(p1: &[usize], p2: &[usize]) -> Vec<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
 
     #[test]
     fn test_standard_tableau_valid() {
@@ -337,4 +401,26 @@ mod tests {
         assert_eq!(p.shape.size(), 4);
         assert_eq!(q.shape.size(), 4);
     }
+
+    #[test]
+    fn test_slot_group_matches_tableau_symmetry() {
+        let tableaux = [
+            (vec![2, 1], vec![vec![1, 2], vec![3]]),
+            (vec![2, 2], vec![vec![1, 2], vec![3, 4]]),
+            (vec![3, 3], vec![vec![1, 3, 5], vec![2, 4, 6]]),
+        ];
+        for (shape, entries) in tableaux {
+            let tableau = StandardTableau::new(Shape(shape), entries).unwrap();
+            let symmetry = crate::Symmetry::from_tableau(&tableau);
+            let degree = tableau.size();
+            let generators = slot_group_from_tableau(&tableau);
+            assert!(generators.iter().all(|g| symmetry.permutation_sign(g) != 0));
+            let members = (0..degree)
+                .permutations(degree)
+                .filter(|p| symmetry.permutation_sign(p) != 0)
+                .count();
+            let order = crate::schreier_sims::schreier_sims(&generators, degree).order();
+            assert_eq!(order, members as u128);
+        }
+    }
 }
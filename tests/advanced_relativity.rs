@@ -216,7 +216,7 @@ fn test_gauge_field_strength_nonabelian() {
     let mut nonabelian_field = Tensor::new(
         "F",
         vec![
-            TensorIndex::contravariant("a", 0), // Lie algebra index
+            TensorIndex::contravariant("a", 0).with_class(IndexClass::LieAlgebra),
             TensorIndex::new("nu", 1),
             TensorIndex::new("mu", 2),
         ],
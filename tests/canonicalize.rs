@@ -1,5 +1,6 @@
 //! Integration tests for canonicalization methods
 
+#[cfg(feature = "tableaux")]
 use butler_portugal::young_tableaux::{Shape, StandardTableau};
 use butler_portugal::{
     canonicalize_with_optimizations, CanonicalizationMethod, Symmetry, Tensor, TensorIndex,
@@ -22,16 +23,19 @@ fn test_symmetric_tensor_canonicalization_methods() {
     assert_eq!(canonical_schreier.indices()[1].name(), "b");
 
     // Young symmetrizer method
-    let shape = Shape(vec![2]);
-    let tableau = StandardTableau::new(shape, vec![vec![1, 2]]).unwrap();
-    let canonical_young = canonicalize_with_optimizations(
-        &tensor,
-        Some(&tableau),
-        &CanonicalizationMethod::YoungSymmetrizer,
-    )
-    .unwrap();
-    assert_eq!(canonical_young.indices()[0].name(), "a");
-    assert_eq!(canonical_young.indices()[1].name(), "b");
+    #[cfg(feature = "tableaux")]
+    {
+        let shape = Shape(vec![2]);
+        let tableau = StandardTableau::new(shape, vec![vec![1, 2]]).unwrap();
+        let canonical_young = canonicalize_with_optimizations(
+            &tensor,
+            Some(&tableau),
+            &CanonicalizationMethod::YoungSymmetrizer,
+        )
+        .unwrap();
+        assert_eq!(canonical_young.indices()[0].name(), "a");
+        assert_eq!(canonical_young.indices()[1].name(), "b");
+    }
 }
 
 #[test]
@@ -52,26 +56,29 @@ fn test_antisymmetric_tensor_canonicalization_methods() {
     assert_eq!(canonical_schreier.coefficient(), -1);
 
     // Young symmetrizer method
-    let shape = Shape(vec![1, 1]);
-    let tableau = StandardTableau::new(shape, vec![vec![1], vec![2]]).unwrap();
-    let canonical_young = canonicalize_with_optimizations(
-        &tensor,
-        Some(&tableau),
-        &CanonicalizationMethod::YoungSymmetrizer,
-    )
-    .unwrap();
-    println!(
-        "Young result: indices = {:?}, coefficient = {}",
-        canonical_young
-            .indices()
-            .iter()
-            .map(|i| i.name())
-            .collect::<Vec<_>>(),
-        canonical_young.coefficient()
-    );
-    assert_eq!(canonical_young.indices()[0].name(), "a");
-    assert_eq!(canonical_young.indices()[1].name(), "b");
-    assert_eq!(canonical_young.coefficient(), -1);
+    #[cfg(feature = "tableaux")]
+    {
+        let shape = Shape(vec![1, 1]);
+        let tableau = StandardTableau::new(shape, vec![vec![1], vec![2]]).unwrap();
+        let canonical_young = canonicalize_with_optimizations(
+            &tensor,
+            Some(&tableau),
+            &CanonicalizationMethod::YoungSymmetrizer,
+        )
+        .unwrap();
+        println!(
+            "Young result: indices = {:?}, coefficient = {}",
+            canonical_young
+                .indices()
+                .iter()
+                .map(|i| i.name())
+                .collect::<Vec<_>>(),
+            canonical_young.coefficient()
+        );
+        assert_eq!(canonical_young.indices()[0].name(), "a");
+        assert_eq!(canonical_young.indices()[1].name(), "b");
+        assert_eq!(canonical_young.coefficient(), -1);
+    }
 }
 
 #[test]
@@ -90,15 +97,18 @@ fn test_mixed_symmetry_tensor_canonicalization_methods() {
     assert_eq!(canonical_schreier.indices()[1].name(), "b");
 
     // Young symmetrizer method with shape [2] (symmetric)
-    let shape = Shape(vec![2]);
-    let tableau = StandardTableau::new(shape, vec![vec![1, 2]]).unwrap();
-    let canonical_young = canonicalize_with_optimizations(
-        &tensor,
-        Some(&tableau),
-        &CanonicalizationMethod::YoungSymmetrizer,
-    )
-    .unwrap();
-    // Should be symmetric, so indices sorted
-    assert_eq!(canonical_young.indices()[0].name(), "a");
-    assert_eq!(canonical_young.indices()[1].name(), "b");
+    #[cfg(feature = "tableaux")]
+    {
+        let shape = Shape(vec![2]);
+        let tableau = StandardTableau::new(shape, vec![vec![1, 2]]).unwrap();
+        let canonical_young = canonicalize_with_optimizations(
+            &tensor,
+            Some(&tableau),
+            &CanonicalizationMethod::YoungSymmetrizer,
+        )
+        .unwrap();
+        // Should be symmetric, so indices sorted
+        assert_eq!(canonical_young.indices()[0].name(), "a");
+        assert_eq!(canonical_young.indices()[1].name(), "b");
+    }
 }
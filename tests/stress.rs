@@ -1,5 +1,6 @@
 //! Stress tests for tensor canonicalization at scale
 
+#[cfg(feature = "tableaux")]
 use butler_portugal::young_tableaux::{Shape, StandardTableau};
 use butler_portugal::{
     canonicalize, canonicalize_with_optimizations, CanonicalizationMethod, Symmetry, Tensor,
@@ -206,36 +207,37 @@ fn test_canonicalization_methods_comparison() {
             .expect("Schreier-Sims canonicalization failed");
     let schreier_duration = start.elapsed();
 
-    // Test Young symmetrizer method with symmetric tableau
-    let shape = Shape(vec![rank]);
-    let tableau = StandardTableau::new(shape, vec![(1..=rank).collect()]).unwrap();
-    let start = Instant::now();
-    let young_result = canonicalize_with_optimizations(
-        &tensor,
-        Some(&tableau),
-        &CanonicalizationMethod::YoungSymmetrizer,
-    )
-    .expect("Young symmetrizer canonicalization failed");
-    let young_duration = start.elapsed();
-
-    println!("Schreier-Sims took: {schreier_duration:?}");
-    println!("Young symmetrizer took: {young_duration:?}");
-
-    // Both methods should produce canonical results
     let schreier_indices: Vec<_> = schreier_result.indices().iter().map(|i| i.name()).collect();
-    let young_indices: Vec<_> = young_result.indices().iter().map(|i| i.name()).collect();
-
     let mut sorted_indices = schreier_indices.clone();
     sorted_indices.sort();
-
     assert_eq!(
         schreier_indices, sorted_indices,
         "Schreier-Sims result should be canonical"
     );
-    assert_eq!(
-        young_indices, sorted_indices,
-        "Young symmetrizer result should be canonical"
-    );
+    println!("Schreier-Sims took: {schreier_duration:?}");
+
+    // Test Young symmetrizer method with symmetric tableau
+    #[cfg(feature = "tableaux")]
+    {
+        let shape = Shape(vec![rank]);
+        let tableau = StandardTableau::new(shape, vec![(1..=rank).collect()]).unwrap();
+        let start = Instant::now();
+        let young_result = canonicalize_with_optimizations(
+            &tensor,
+            Some(&tableau),
+            &CanonicalizationMethod::YoungSymmetrizer,
+        )
+        .expect("Young symmetrizer canonicalization failed");
+        let young_duration = start.elapsed();
+
+        println!("Young symmetrizer took: {young_duration:?}");
+
+        let young_indices: Vec<_> = young_result.indices().iter().map(|i| i.name()).collect();
+        assert_eq!(
+            young_indices, sorted_indices,
+            "Young symmetrizer result should be canonical"
+        );
+    }
 }
 
 /// Stress test for very large tensor (if system can handle it)
@@ -4,7 +4,6 @@
 //! including complex tensor canonicalization scenarios.
 
 use butler_portugal::canonicalize_with_optimizations;
-use butler_portugal::young_tableaux::{Shape, StandardTableau};
 use butler_portugal::*;
 
 #[test]
@@ -301,8 +300,10 @@ fn test_large_tensor_performance() {
     assert!(!canonical.is_zero());
 }
 
+#[cfg(feature = "tableaux")]
 #[test]
 fn test_tensor_projection_with_tableau() {
+    use butler_portugal::young_tableaux::{Shape, StandardTableau};
     use butler_portugal::{Tensor, TensorIndex};
     // Symmetric tableau shape for 2 indices
     let shape = Shape(vec![2]);